@@ -0,0 +1,94 @@
+//! Benchmarks of the image operations that run over the whole screen every
+//! frame, on a 300x100 grid.
+//!
+//! Run with `cargo bench --bench image`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use mage_core::{
+    animation::AnimationPhase,
+    image::{BlitOptions, Char, Image, Point, Rect},
+    Colour, PresentInput,
+};
+
+const WIDTH: u32 = 300;
+const HEIGHT: u32 = 100;
+
+/// Runs a function repeatedly for about a second and prints the average time
+/// it took.
+fn bench(name: &str, mut f: impl FnMut()) {
+    // Warm up the caches before timing.
+    for _ in 0..100 {
+        f();
+    }
+
+    let mut iterations = 0u32;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(1) {
+        for _ in 0..100 {
+            f();
+        }
+        iterations += 100;
+    }
+    let per_iteration = start.elapsed() / iterations;
+    println!("{:<32} {:>10.2?}", name, per_iteration);
+}
+
+/// Returns a screen-sized image with some transparent chars in it, as a
+/// sprite with holes would have.
+fn sprite() -> Image {
+    let mut image = Image::new(WIDTH, HEIGHT);
+    image.clear(Colour::White, Colour::Blue);
+    for y in 0..HEIGHT as i32 {
+        for x in (y % 7..WIDTH as i32).step_by(7) {
+            image.draw_char(Point::new(x, y), Char::transparent());
+        }
+    }
+    image
+}
+
+fn main() {
+    let mut screen = Image::new(WIDTH, HEIGHT);
+    let solid = {
+        let mut image = Image::new(WIDTH, HEIGHT);
+        image.clear(Colour::White, Colour::Blue);
+        image
+    };
+    let sprite = sprite();
+    let tile = Image::new(8, 4);
+    let rect = screen.rect();
+
+    bench("Image::clear", || {
+        black_box(&mut screen).clear(Colour::White, Colour::Black)
+    });
+    bench("Image::draw_filled_rect", || {
+        black_box(&mut screen).draw_filled_rect(rect, Char::new(b'#', Colour::Red, Colour::Black))
+    });
+    bench("Image::blit (solid)", || {
+        black_box(&mut screen).blit(Point::new(0, 0), &solid, rect, BlitOptions::default())
+    });
+    bench("Image::blit (transparent)", || {
+        black_box(&mut screen).blit(Point::new(0, 0), &sprite, rect, BlitOptions::default())
+    });
+    bench("Image::blit_glyphs", || {
+        black_box(&mut screen).blit_glyphs(Point::new(0, 0), &sprite, rect, BlitOptions::default())
+    });
+    bench("Image::blit_tiled", || {
+        black_box(&mut screen).blit_tiled(rect, &tile, tile.rect(), BlitOptions::default())
+    });
+    bench("PresentInput::blit", || {
+        let mut present_input = PresentInput {
+            screen: &mut screen,
+            animation: AnimationPhase::default(),
+        };
+        black_box(&mut present_input).blit(
+            rect,
+            Rect::new(-10, -5, WIDTH, HEIGHT),
+            &sprite,
+            Colour::Black,
+        )
+    });
+}
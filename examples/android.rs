@@ -0,0 +1,97 @@
+//! A game that runs as an Android app, showing where the screen is touched.
+//! Tapping the button at the top shows or hides the soft keyboard, and text
+//! typed on it is shown below the button.
+//!
+//! Build it with `cargo-apk` or `xbuild`, for example:
+//!
+//! ```bash
+//! $ cargo apk run --example android
+//! ```
+//!
+//! On other platforms this example builds to an empty library.
+
+#[cfg(target_os = "android")]
+use mage_core::{
+    image::{Point, Rect},
+    run, App, Colour, Config, PresentInput, PresentResult, TickInput, TickResult,
+};
+#[cfg(target_os = "android")]
+use winit::platform::android::activity::AndroidApp;
+
+#[cfg(target_os = "android")]
+const KEYBOARD_BUTTON: &str = "[ Keyboard ]";
+
+#[cfg(target_os = "android")]
+#[no_mangle]
+fn android_main(android_app: AndroidApp) {
+    let app = TouchApp::new(android_app.clone());
+    let config = Config {
+        android_app: Some(android_app),
+        ..Default::default()
+    };
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let _ = runtime.block_on(run(app, config));
+}
+
+#[cfg(target_os = "android")]
+struct TouchApp {
+    android_app: AndroidApp,
+    touch: Option<Point>,
+    keyboard_shown: bool,
+    typed: String,
+}
+
+#[cfg(target_os = "android")]
+impl TouchApp {
+    fn new(android_app: AndroidApp) -> Self {
+        Self {
+            android_app,
+            touch: None,
+            keyboard_shown: false,
+            typed: String::new(),
+        }
+    }
+}
+
+#[cfg(target_os = "android")]
+impl App for TouchApp {
+    fn tick(&mut self, tick_input: TickInput) -> TickResult {
+        self.typed.push_str(tick_input.text);
+        let mouse = tick_input.mouse;
+        if mouse.left.down {
+            self.touch = mouse.position;
+        }
+        let button = Rect::new(0, 0, KEYBOARD_BUTTON.len() as u32, 1);
+        let on_button = mouse.position.is_some_and(|p| {
+            p.x >= button.x && p.x < button.x + button.width as i32 && p.y == button.y
+        });
+        if mouse.left.released && on_button {
+            self.keyboard_shown = !self.keyboard_shown;
+            if self.keyboard_shown {
+                self.android_app.show_soft_input(true);
+            } else {
+                self.android_app.hide_soft_input(true);
+            }
+        }
+        TickResult::Continue
+    }
+
+    fn present(&mut self, mut present_input: PresentInput) -> PresentResult {
+        let mut image = present_input.new_image();
+        image.clear(Colour::White, Colour::Black);
+        image.draw_string(
+            Point::new(0, 0),
+            KEYBOARD_BUTTON,
+            Colour::Black,
+            Colour::LightCyan,
+        );
+        image.draw_string(Point::new(0, 1), &self.typed, Colour::White, Colour::Black);
+        if let Some(p) = self.touch {
+            image.draw_string(p, "X", Colour::Yellow, Colour::Red);
+        }
+        present_input.blit(present_input.rect(), image.rect(), &image, Colour::Black);
+
+        PresentResult::Changed
+    }
+}
@@ -61,19 +61,9 @@ impl App for TestApp {
 
         let fps = (1.0 / self.dt) as u32;
         let message = format!("FPS: {} ", fps);
-        image.draw_string(
-            Point::default(),
-            &message,
-            Colour::White.into(),
-            Colour::LightRed.into(),
-        );
+        image.draw_string(Point::default(), &message, Colour::White, Colour::LightRed);
 
-        present_input.blit(
-            present_input.rect(),
-            image.rect(),
-            &image,
-            Colour::Black.into(),
-        );
+        present_input.blit(present_input.rect(), image.rect(), &image, Colour::Black);
 
         PresentResult::Changed
     }
@@ -40,25 +40,20 @@ impl App for HelloApp {
 
     fn present(&mut self, mut present_input: PresentInput) -> PresentResult {
         let mut image = present_input.new_image();
-        image.clear(Colour::White.into(), Colour::Black.into());
+        image.clear(Colour::White, Colour::Black);
 
         image.draw_string(
             Point::new(0, 0),
             "Hello, World!",
-            Colour::Black.into(),
-            Colour::Yellow.into(),
+            Colour::Black,
+            Colour::Yellow,
         );
         image.draw_char(
             Point::new(image.width as i32 - 1, 0),
-            Char::new_char('A', Colour::LightRed.into(), Colour::Black.into()),
+            Char::new_char('A', Colour::LightRed, Colour::Black),
         );
 
-        present_input.blit(
-            present_input.rect(),
-            image.rect(),
-            &image,
-            Colour::Black.into(),
-        );
+        present_input.blit(present_input.rect(), image.rect(), &image, Colour::Black);
 
         PresentResult::Changed
     }
@@ -0,0 +1,60 @@
+//! A game that runs as an iOS app, showing where the screen is touched and
+//! how big the screen is in characters.
+//!
+//! Build it into an app bundle with `cargo-bundle`, then install the bundle
+//! on the simulator with `xcrun simctl install` or on a device with Xcode:
+//!
+//! ```bash
+//! $ cargo bundle --example ios --target aarch64-apple-ios-sim
+//! ```
+//!
+//! On other platforms this example runs in a window, with the mouse standing
+//! in for touches.
+
+use mage_core::{
+    image::Point, run, App, Colour, Config, PresentInput, PresentResult, TickInput, TickResult,
+};
+
+#[tokio::main]
+async fn main() {
+    let config = Config {
+        title: Some("Mage Touch".to_string()),
+        ..Default::default()
+    };
+
+    let _ = run(TouchApp::default(), config).await;
+}
+
+#[derive(Default)]
+struct TouchApp {
+    touch: Option<Point>,
+    size: (u32, u32),
+}
+
+impl App for TouchApp {
+    fn tick(&mut self, tick_input: TickInput) -> TickResult {
+        let mouse = tick_input.mouse;
+        if mouse.left.down {
+            self.touch = mouse.position;
+        }
+        self.size = (tick_input.width, tick_input.height);
+        TickResult::Continue
+    }
+
+    fn present(&mut self, mut present_input: PresentInput) -> PresentResult {
+        let mut image = present_input.new_image();
+        image.clear(Colour::White, Colour::Black);
+        image.draw_string(
+            Point::new(0, 0),
+            &format!("{}x{} chars", self.size.0, self.size.1),
+            Colour::Black,
+            Colour::LightCyan,
+        );
+        if let Some(p) = self.touch {
+            image.draw_string(p, "X", Colour::Yellow, Colour::Red);
+        }
+        present_input.blit(present_input.rect(), image.rect(), &image, Colour::Black);
+
+        PresentResult::Changed
+    }
+}
@@ -0,0 +1,71 @@
+//! A game that runs natively or in a web page.
+//!
+//! To run it in a web page, build it for WebAssembly and generate the
+//! JavaScript bindings with `wasm-bindgen`:
+//!
+//! ```bash
+//! $ cargo build --release --example web --target wasm32-unknown-unknown --features webgl
+//! $ wasm-bindgen --target web --out-dir examples/pkg \
+//!     target/wasm32-unknown-unknown/release/examples/web.wasm
+//! ```
+//!
+//! Then serve the `examples` folder with any web server and open `web.html`.
+
+use mage_core::{
+    image::Point, run, App, Colour, Config, PresentInput, PresentResult, TickInput, TickResult,
+};
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    console_error_panic_hook::set_once();
+    wasm_bindgen_futures::spawn_local(async {
+        let _ = run(WebApp::new(), config()).await;
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::main]
+async fn main() {
+    let _ = run(WebApp::new(), config()).await;
+}
+
+fn config() -> Config {
+    Config {
+        title: Some("Mage on the web".to_string()),
+        canvas_id: Some("mage".to_string()),
+        ..Default::default()
+    }
+}
+
+struct WebApp {
+    /// How far the message has moved across the screen, in characters.
+    x: f32,
+}
+
+impl WebApp {
+    fn new() -> Self {
+        Self { x: 0.0 }
+    }
+}
+
+impl App for WebApp {
+    fn tick(&mut self, tick_input: TickInput) -> TickResult {
+        let dt = tick_input.dt.num_microseconds().unwrap_or(0) as f32 / 1_000_000.0;
+        self.x = (self.x + dt * 10.0) % tick_input.width.max(1) as f32;
+        TickResult::Continue
+    }
+
+    fn present(&mut self, mut present_input: PresentInput) -> PresentResult {
+        let mut image = present_input.new_image();
+        image.clear(Colour::White, Colour::Black);
+        image.draw_string(
+            Point::new(self.x as i32, image.height as i32 / 2),
+            "Hello, Web!",
+            Colour::Yellow,
+            Colour::Black,
+        );
+        present_input.blit(present_input.rect(), image.rect(), &image, Colour::Black);
+
+        PresentResult::Changed
+    }
+}
@@ -0,0 +1,278 @@
+//! Describing the screen as structured text, for screen readers and other
+//! assistive tools.
+//!
+//! An ASCII game's screen is already text, but read row by row it mixes
+//! panels, borders and maps together.  Apps tag areas of the screen with a
+//! [`Role`] and a label, in reading order, and the text in each area can then
+//! be read out on its own:
+//!
+//! ```
+//! use mage_core::{
+//!     accessibility::{Role, ScreenRegions},
+//!     image::{Image, Point, Rect},
+//!     Colour,
+//! };
+//!
+//! let mut screen = Image::new(40, 10);
+//! screen.draw_string(Point::new(2, 0), "Inventory", Colour::White, Colour::Black);
+//! screen.draw_string(Point::new(2, 2), "Sword", Colour::White, Colour::Black);
+//! screen.draw_string(Point::new(2, 3), "Shield", Colour::White, Colour::Black);
+//!
+//! let mut regions = ScreenRegions::new();
+//! regions.add(Rect::new(0, 0, 20, 1), Role::Heading, "");
+//! regions.add(Rect::new(0, 1, 20, 5), Role::List, "Items carried");
+//!
+//! assert_eq!(
+//!     regions.to_text(&screen),
+//!     "heading\nInventory\n\nlist: Items carried\nSword\nShield\n",
+//! );
+//! ```
+//!
+//! Apps give their regions to the engine by implementing
+//! [`App::screen_regions`].
+//!
+//! [`Role`]: enum.Role.html
+//! [`App::screen_regions`]: ../trait.App.html#method.screen_regions
+
+use std::fmt;
+
+use crate::{
+    codepage::cp437_to_unicode,
+    image::{Image, Point, Rect},
+};
+
+/// What an area of the screen is for, which tells assistive tools how to
+/// present it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// Ordinary text, such as a description or dialogue.
+    #[default]
+    Text,
+
+    /// A title for the regions that follow it.
+    Heading,
+
+    /// Something that can be clicked or chosen to do something.
+    Button,
+
+    /// A list of items, one per line.
+    List,
+
+    /// A menu of choices, one per line.
+    Menu,
+
+    /// Information that changes as the game is played, such as health.
+    Status,
+
+    /// Messages that are added to over time, newest last.
+    Log,
+
+    /// A picture of the game world, such as a dungeon map.
+    Map,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Role::Text => "text",
+            Role::Heading => "heading",
+            Role::Button => "button",
+            Role::List => "list",
+            Role::Menu => "menu",
+            Role::Status => "status",
+            Role::Log => "log",
+            Role::Map => "map",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An area of the screen tagged with a role and label.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    /// The area of the screen, in chars.
+    pub rect: Rect,
+
+    /// What the area is for.
+    pub role: Role,
+
+    /// A short description of the area, or an empty string if the text in it
+    /// is enough.
+    pub label: String,
+
+    /// Where the area comes in the reading order.  Areas with lower orders
+    /// are read first, and those with the same order are read in the order
+    /// they were added.
+    pub order: i32,
+}
+
+/// The text in a [`Region`] of the screen, as given by
+/// [`ScreenRegions::describe`].
+///
+/// [`Region`]: struct.Region.html
+/// [`ScreenRegions::describe`]: struct.ScreenRegions.html#method.describe
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionText {
+    /// What the area is for.
+    pub role: Role,
+
+    /// The label of the area.
+    pub label: String,
+
+    /// The text in the area, one line per row with blank rows left out.
+    pub text: String,
+}
+
+/// The tagged areas of the screen, in reading order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScreenRegions {
+    regions: Vec<Region>,
+}
+
+impl ScreenRegions {
+    /// Creates a new, empty set of regions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tags an area of the screen, to be read after the areas already added.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area of the screen, in chars.
+    /// * `role` - What the area is for.
+    /// * `label` - A short description of the area, or an empty string if
+    ///   the text in it is enough.
+    ///
+    pub fn add(&mut self, rect: Rect, role: Role, label: impl Into<String>) -> &mut Self {
+        let order = self.regions.last().map_or(0, |region| region.order);
+        self.add_region(Region {
+            rect,
+            role,
+            label: label.into(),
+            order,
+        })
+    }
+
+    /// Tags an area of the screen with a region, which sets its own reading
+    /// order.
+    pub fn add_region(&mut self, region: Region) -> &mut Self {
+        // Keep the regions sorted, after any with the same order.
+        let i = self
+            .regions
+            .partition_point(|other| other.order <= region.order);
+        self.regions.insert(i, region);
+        self
+    }
+
+    /// Removes all the regions, such as when the screen changes completely.
+    pub fn clear(&mut self) {
+        self.regions.clear();
+    }
+
+    /// Returns the regions in reading order.
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// Returns true if no areas of the screen have been tagged.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Returns the text in each region of a screen, in reading order.
+    ///
+    /// # Arguments
+    ///
+    /// * `screen` - The screen the regions are on.
+    ///
+    /// # Returns
+    ///
+    /// The text of each region.  If no regions have been tagged, the whole
+    /// screen is given as a single region of text.
+    ///
+    /// # Notes
+    ///
+    /// Box drawing, shading and block glyphs are read as spaces, as they are
+    /// used for borders and are noise when read out.  Runs of spaces are
+    /// read as a single space, and spaces at the start and end of each row
+    /// are removed.
+    ///
+    pub fn describe(&self, screen: &Image) -> Vec<RegionText> {
+        if self.regions.is_empty() {
+            return vec![RegionText {
+                role: Role::Text,
+                label: String::new(),
+                text: region_text(screen, screen.rect()),
+            }];
+        }
+        self.regions
+            .iter()
+            .map(|region| RegionText {
+                role: region.role,
+                label: region.label.clone(),
+                text: region_text(screen, region.rect),
+            })
+            .collect()
+    }
+
+    /// Returns the text of a screen as a single string, such as to show in a
+    /// text box that a screen reader can read, or to send to a braille
+    /// display.
+    ///
+    /// Each region starts with a line giving its role and label, followed by
+    /// its text, and regions are separated by blank lines.
+    ///
+    pub fn to_text(&self, screen: &Image) -> String {
+        let mut text = String::new();
+        for (i, region) in self.describe(screen).iter().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            text.push_str(&region.role.to_string());
+            if !region.label.is_empty() {
+                text.push_str(": ");
+                text.push_str(&region.label);
+            }
+            text.push('\n');
+            for line in region.text.lines() {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+/// Returns the text in an area of an image, with borders and extra spaces
+/// removed and one line per row that isn't blank.
+fn region_text(image: &Image, rect: Rect) -> String {
+    // Clip to the image, so huge areas don't take long to read.
+    let (x0, y0) = (rect.x.max(0), rect.y.max(0));
+    let x1 = rect
+        .x
+        .saturating_add(rect.width as i32)
+        .min(image.width as i32);
+    let y1 = rect
+        .y
+        .saturating_add(rect.height as i32)
+        .min(image.height as i32);
+
+    let mut lines = Vec::new();
+    for y in y0..y1 {
+        let row = (x0..x1)
+            .filter_map(|x| image.get_glyph(Point::new(x, y)))
+            .map(|glyph| match glyph as u8 {
+                // Shades, box drawing and blocks.
+                0xb0..=0xdf => ' ',
+                glyph => cp437_to_unicode(glyph),
+            })
+            .collect::<String>();
+        let line = row.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,59 @@
+//! Support for running as an Android app.
+
+use winit::{
+    event::{Event, KeyEvent},
+    event_loop::{EventLoop, EventLoopBuilder, EventLoopWindowTarget},
+    keyboard::SmolStr,
+    platform::{
+        android::{activity::AndroidApp, EventLoopBuilderExtAndroid},
+        pump_events::{EventLoopExtPumpEvents, PumpStatus},
+    },
+};
+
+use crate::error::MageError;
+
+/// Creates an event loop attached to the app's activity.
+pub(crate) fn event_loop(app: Option<AndroidApp>) -> Result<EventLoop<()>, MageError> {
+    let app = app.ok_or(MageError::NoAndroidApp)?;
+    Ok(EventLoopBuilder::new().with_android_app(app).build()?)
+}
+
+/// Waits until the app is resumed, when its window can first be drawn to.
+///
+/// # Returns
+///
+/// False if the app quit before it was resumed.
+///
+pub(crate) fn wait_for_resume(event_loop: &mut EventLoop<()>) -> bool {
+    let mut resumed = false;
+    while !resumed {
+        let status = event_loop.pump_events(None, |event, _| {
+            if let Event::Resumed = event {
+                resumed = true;
+            }
+        });
+        if let PumpStatus::Exit(_) = status {
+            return false;
+        }
+    }
+    true
+}
+
+/// Runs the event loop until the app quits.  Once events have been pumped,
+/// Android event loops can only carry on being pumped.
+pub(crate) fn run(
+    mut event_loop: EventLoop<()>,
+    mut event_handler: impl FnMut(Event<()>, &EventLoopWindowTarget<()>),
+) {
+    while let PumpStatus::Continue = event_loop.pump_events(None, &mut event_handler) {}
+}
+
+/// Returns the text typed with a key.  Android never fills in the key event's
+/// text, and gives the character typed, including on the soft keyboard, only
+/// as the logical key.
+pub(crate) fn key_text(event: &KeyEvent) -> Option<SmolStr> {
+    event
+        .text
+        .clone()
+        .or_else(|| event.logical_key.to_text().map(SmolStr::new))
+}
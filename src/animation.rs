@@ -0,0 +1,116 @@
+//! A clock shared by everything that animates in time with the engine, such
+//! as blinking cursors and pulsing highlights.
+//!
+//! The engine keeps an [`AnimationPhase`] that goes round once every
+//! [`Config::blink_period`], and gives it to each tick and present in
+//! [`TickInput::animation`] and [`PresentInput::animation`].  Anything drawn
+//! from it stays in step with everything else that is, without each part of
+//! the app keeping its own timer.
+//!
+//! ```
+//! use mage_core::{
+//!     image::{Char, Point},
+//!     Colour, PresentInput, PresentResult,
+//! };
+//!
+//! fn draw_cursor(present_input: PresentInput, cursor: Point) -> PresentResult {
+//!     let glyph = if present_input.animation.blink() { b'_' } else { b' ' };
+//!     present_input
+//!         .screen
+//!         .draw_char(cursor, Char::new(glyph, Colour::White, Colour::Black));
+//!     PresentResult::Changed
+//! }
+//! ```
+//!
+//! [`AnimationPhase`]: struct.AnimationPhase.html
+//! [`Config::blink_period`]: ../struct.Config.html#structfield.blink_period
+//! [`TickInput::animation`]: ../struct.TickInput.html#structfield.animation
+//! [`PresentInput::animation`]: ../struct.PresentInput.html#structfield.animation
+
+use chrono::Duration;
+
+/// How far the engine's animation clock is through its current period.
+///
+/// The clock only moves on as the app is ticked, by the time passed to each
+/// tick, so it stops while the app is paused by the frame policy and is
+/// repeatable in headless runs.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnimationPhase {
+    /// How far through the period the clock is in microseconds.
+    elapsed: i64,
+
+    /// How long the clock takes to go round once in microseconds, or 0 if
+    /// it never moves.
+    period: i64,
+}
+
+impl AnimationPhase {
+    /// Creates a clock at the start of its period.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - How long the clock takes to go round once.  A period of
+    ///   zero or less stops the clock at the start.
+    ///
+    pub fn new(period: Duration) -> Self {
+        Self {
+            elapsed: 0,
+            period: period.num_microseconds().unwrap_or(i64::MAX).max(0),
+        }
+    }
+
+    /// Returns how long the clock takes to go round once.
+    pub fn period(&self) -> Duration {
+        Duration::microseconds(self.period)
+    }
+
+    /// Returns how far through the period the clock is, from 0 at the start
+    /// up to but not including 1 at the end.
+    pub fn phase(&self) -> f32 {
+        if self.period == 0 {
+            0.0
+        } else {
+            (self.elapsed as f64 / self.period as f64) as f32
+        }
+    }
+
+    /// Returns true for the first half of each period and false for the
+    /// second, for things that blink on and off.  A stopped clock is always
+    /// on.
+    pub fn blink(&self) -> bool {
+        self.period == 0 || self.elapsed < self.period - self.period / 2
+    }
+
+    /// Returns a value that rises from 0 to 1 over the first half of each
+    /// period and falls back to 0 over the second, for things that pulse
+    /// smoothly.
+    pub fn pulse(&self) -> f32 {
+        1.0 - (self.phase() * 2.0 - 1.0).abs()
+    }
+
+    /// Moves the clock on by the time passed to a tick, going round as many
+    /// times as that takes.
+    pub(crate) fn advance(&mut self, dt: Duration) {
+        if self.period == 0 {
+            return;
+        }
+        let dt = dt.num_microseconds().unwrap_or(0).rem_euclid(self.period);
+        self.elapsed = (self.elapsed + dt) % self.period;
+    }
+}
+
+impl Default for AnimationPhase {
+    /// Returns a clock with the period that [`Config::blink_period`] has by
+    /// default.
+    ///
+    /// [`Config::blink_period`]: ../struct.Config.html#structfield.blink_period
+    ///
+    fn default() -> Self {
+        Self::new(Duration::milliseconds(DEFAULT_BLINK_PERIOD_MS))
+    }
+}
+
+/// The default time the animation clock takes to go round once, which blinks
+/// about as fast as a text cursor.
+pub(crate) const DEFAULT_BLINK_PERIOD_MS: i64 = 1000;
@@ -0,0 +1,345 @@
+//! Loading of ANSI art, such as `.ans` files drawn for BBSes.
+//!
+//! ANSI art is text with escape codes that set colours and move the cursor.
+//! Classic art is code page 437 text that wraps at 80 columns, drawn with the
+//! 16 CGA colours, and may end with a SAUCE record that gives its width.
+//! Files written by [`Image::save_ansi`] are read too: they are UTF-8 text
+//! with 24-bit colours, and don't wrap.
+//!
+//! These escape codes are understood:
+//!
+//! * `ESC[...m` - Colours and attributes, including bold for bright ink,
+//!   blink for bright paper, reverse video, and 256 and 24-bit colours.
+//! * `ESC[nA`, `ESC[nB`, `ESC[nC` and `ESC[nD` - Moving the cursor.
+//! * `ESC[y;xH` and `ESC[y;xf` - Moving the cursor to a position.
+//! * `ESC[s` and `ESC[u` - Saving and restoring the cursor position.
+//! * `ESC[2J` - Clearing the screen.
+//!
+//! Others are skipped.
+//!
+//! [`Image::save_ansi`]: ../image/struct.Image.html#method.save_ansi
+
+use std::{fs, path::Path};
+
+use crate::{
+    codepage::unicode_to_cp437,
+    colour::Colour,
+    error::MageError,
+    image::{Char, Image, Point},
+    palette::{CGA, XTERM_256},
+};
+
+/// The width classic ANSI art wraps at, unless its SAUCE record says
+/// otherwise.
+const DEFAULT_WIDTH: usize = 80;
+
+/// The size of a SAUCE record at the end of a file.
+const SAUCE_SIZE: usize = 128;
+
+/// The end of file marker that comes before a SAUCE record.
+const EOF_MARKER: u8 = 0x1a;
+
+/// The furthest the cursor can be moved by an escape code, so that a bad
+/// code can't make a huge image.
+const MAX_CURSOR: usize = 4096;
+
+/// The CGA palette index of each of the 8 ANSI colours, which are in a
+/// different order.
+const ANSI_TO_CGA: [u8; 8] = [0, 4, 2, 6, 1, 5, 3, 7];
+
+impl Image {
+    /// Loads an image from a file of ANSI art.
+    pub fn load_ans(path: impl AsRef<Path>) -> Result<Image, MageError> {
+        Ok(Image::from_ans_bytes(&fs::read(path)?))
+    }
+
+    /// Decodes an image from ANSI art, such as data embedded with
+    /// `include_bytes!`.
+    ///
+    /// # Returns
+    ///
+    /// The image, which is as tall as the rows drawn.  Classic art is as wide
+    /// as it wraps, and UTF-8 art is as wide as its longest row.  Cells that
+    /// aren't drawn are spaces in light grey on black.
+    ///
+    pub fn from_ans_bytes(data: &[u8]) -> Image {
+        let (data, sauce_width) = strip_sauce(data);
+        let mut canvas = Canvas::default();
+
+        match std::str::from_utf8(data) {
+            Ok(text) if !text.is_ascii() => {
+                let mut chars = text.chars().peekable();
+                while let Some(ch) = chars.next() {
+                    if ch == '\x1b' && chars.next_if_eq(&'[').is_some() {
+                        let mut sequence = String::new();
+                        while let Some(ch) = chars.next_if(|ch| !('@'..='~').contains(ch)) {
+                            sequence.push(ch);
+                        }
+                        let command = chars.next().unwrap_or('\0');
+                        canvas.escape(&sequence, command);
+                    } else if ch < ' ' {
+                        canvas.text(ch as u8, None);
+                    } else {
+                        canvas.text(unicode_to_cp437(ch).unwrap_or(b'?'), None);
+                    }
+                }
+            }
+            _ => {
+                let width = sauce_width.unwrap_or(DEFAULT_WIDTH);
+                let mut bytes = data.iter().copied().peekable();
+                while let Some(byte) = bytes.next() {
+                    if byte == 0x1b && bytes.next_if_eq(&b'[').is_some() {
+                        let mut sequence = String::new();
+                        while let Some(byte) = bytes.next_if(|byte| !(0x40..=0x7e).contains(byte)) {
+                            sequence.push(byte as char);
+                        }
+                        let command = bytes.next().unwrap_or(0) as char;
+                        canvas.escape(&sequence, command);
+                    } else {
+                        canvas.text(byte, Some(width));
+                    }
+                }
+                canvas.width = canvas.width.max(width);
+            }
+        }
+
+        canvas.into_image()
+    }
+}
+
+/// Removes the SAUCE record and end of file marker from ANSI art.
+///
+/// # Returns
+///
+/// The art, and the width given in the SAUCE record if there is one.
+///
+fn strip_sauce(data: &[u8]) -> (&[u8], Option<usize>) {
+    let mut width = None;
+    let mut art = data;
+    if data.len() >= SAUCE_SIZE {
+        let (rest, sauce) = data.split_at(data.len() - SAUCE_SIZE);
+        if sauce.starts_with(b"SAUCE00") {
+            // Character art gives its width in the first info field.
+            let data_type = sauce[94];
+            let info = u16::from_le_bytes([sauce[96], sauce[97]]) as usize;
+            if data_type == 1 && info > 0 {
+                width = Some(info);
+            }
+            art = rest;
+        }
+    }
+    if let Some(end) = art.iter().position(|&byte| byte == EOF_MARKER) {
+        art = &art[..end];
+    }
+    (art, width)
+}
+
+/// A colour set by an escape code.
+#[derive(Clone, Copy)]
+enum AnsiColour {
+    /// One of the 8 ANSI colours, made bright by bold or blink.
+    Base(u8),
+
+    /// A colour that isn't changed by bold or blink.
+    Exact(Colour),
+}
+
+/// The cells drawn so far and the state of the escape codes.
+struct Canvas {
+    /// The rows drawn, which grow as they are drawn to.
+    rows: Vec<Vec<Char>>,
+
+    /// The width of the widest row.
+    width: usize,
+
+    /// The cursor.
+    x: usize,
+    y: usize,
+
+    /// The cursor position saved by `ESC[s`.
+    saved: (usize, usize),
+
+    ink: AnsiColour,
+    paper: AnsiColour,
+    bold: bool,
+    blink: bool,
+    reverse: bool,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self {
+            rows: Vec::new(),
+            width: 0,
+            x: 0,
+            y: 0,
+            saved: (0, 0),
+            ink: AnsiColour::Base(7),
+            paper: AnsiColour::Base(0),
+            bold: false,
+            blink: false,
+            reverse: false,
+        }
+    }
+}
+
+impl Canvas {
+    /// Draws a glyph or handles a control char.
+    ///
+    /// # Arguments
+    ///
+    /// * `glyph` - The glyph, in code page 437.
+    /// * `wrap` - The width to wrap at, or `None` to not wrap.
+    ///
+    fn text(&mut self, glyph: u8, wrap: Option<usize>) {
+        match glyph {
+            b'\r' => self.x = 0,
+            b'\n' => {
+                self.x = 0;
+                self.y += 1;
+            }
+            b'\t' => self.x = (self.x / 8 + 1) * 8,
+            glyph => {
+                if wrap.is_some_and(|width| self.x >= width) {
+                    self.x = 0;
+                    self.y += 1;
+                }
+                let (mut ink, mut paper) = (
+                    resolve(self.ink, self.bold),
+                    resolve(self.paper, self.blink),
+                );
+                if self.reverse {
+                    (ink, paper) = (paper, ink);
+                }
+                if self.rows.len() <= self.y {
+                    self.rows.resize(self.y + 1, Vec::new());
+                }
+                let row = &mut self.rows[self.y];
+                if row.len() <= self.x {
+                    row.resize(self.x + 1, blank());
+                }
+                row[self.x] = Char::new(glyph, ink, paper);
+                self.x += 1;
+                self.width = self.width.max(self.x);
+            }
+        }
+    }
+
+    /// Handles an escape code.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The text between `ESC[` and the command.
+    /// * `command` - The char that ends the code.
+    ///
+    fn escape(&mut self, params: &str, command: char) {
+        let numbers = params
+            .split(';')
+            .map(|param| param.parse::<usize>().ok())
+            .collect::<Vec<_>>();
+        let count = numbers.first().copied().flatten().unwrap_or(1).max(1);
+        match command {
+            'A' => self.y = self.y.saturating_sub(count),
+            'B' => self.y += count,
+            'C' => self.x += count,
+            'D' => self.x = self.x.saturating_sub(count),
+            'H' | 'f' => {
+                let at = |i: usize| numbers.get(i).copied().flatten().unwrap_or(1).max(1) - 1;
+                self.y = at(0);
+                self.x = at(1);
+            }
+            's' => self.saved = (self.x, self.y),
+            'u' => (self.x, self.y) = self.saved,
+            'J' if numbers.first() == Some(&Some(2)) => {
+                self.rows.clear();
+                (self.x, self.y) = (0, 0);
+            }
+            'm' => self.attributes(&numbers),
+            _ => {}
+        }
+        self.x = self.x.min(MAX_CURSOR);
+        self.y = self.y.min(MAX_CURSOR);
+    }
+
+    /// Handles the parameters of an `ESC[...m` code.
+    fn attributes(&mut self, numbers: &[Option<usize>]) {
+        let mut numbers = numbers.iter().map(|n| n.unwrap_or(0));
+        while let Some(n) = numbers.next() {
+            match n {
+                0 => {
+                    self.ink = AnsiColour::Base(7);
+                    self.paper = AnsiColour::Base(0);
+                    self.bold = false;
+                    self.blink = false;
+                    self.reverse = false;
+                }
+                1 => self.bold = true,
+                5 => self.blink = true,
+                7 => self.reverse = true,
+                22 => self.bold = false,
+                25 => self.blink = false,
+                27 => self.reverse = false,
+                30..=37 => self.ink = AnsiColour::Base((n - 30) as u8),
+                39 => self.ink = AnsiColour::Base(7),
+                40..=47 => self.paper = AnsiColour::Base((n - 40) as u8),
+                49 => self.paper = AnsiColour::Base(0),
+                90..=97 => self.ink = AnsiColour::Exact(bright((n - 90) as u8)),
+                100..=107 => self.paper = AnsiColour::Exact(bright((n - 100) as u8)),
+                38 | 48 => {
+                    let colour = match numbers.next() {
+                        Some(5) => numbers.next().map(|i| XTERM_256[i.min(255)]),
+                        Some(2) => {
+                            let mut channel = || numbers.next().unwrap_or(0).min(255) as u8;
+                            Some(Colour::Rgb(channel(), channel(), channel()))
+                        }
+                        _ => None,
+                    };
+                    if let Some(colour) = colour {
+                        if n == 38 {
+                            self.ink = AnsiColour::Exact(colour);
+                        } else {
+                            self.paper = AnsiColour::Exact(colour);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Converts the rows drawn to an image.
+    fn into_image(self) -> Image {
+        let mut image = Image::new(self.width as u32, self.rows.len() as u32);
+        image.draw_filled_rect(image.rect(), blank());
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, &ch) in row.iter().enumerate() {
+                image.draw_char(Point::new(x as i32, y as i32), ch);
+            }
+        }
+        image
+    }
+}
+
+/// Returns the colour of an escape code colour.
+///
+/// # Arguments
+///
+/// * `colour` - The colour set by the escape code.
+/// * `bright` - True if bold is set for the ink, or blink for the paper.
+///
+fn resolve(colour: AnsiColour, bright: bool) -> Colour {
+    match colour {
+        AnsiColour::Base(i) => CGA[ANSI_TO_CGA[i as usize & 7] as usize + 8 * bright as usize],
+        AnsiColour::Exact(colour) => colour,
+    }
+}
+
+/// Returns the bright version of one of the 8 ANSI colours.
+fn bright(i: u8) -> Colour {
+    CGA[ANSI_TO_CGA[i as usize & 7] as usize + 8]
+}
+
+/// Returns an undrawn cell.
+fn blank() -> Char {
+    Char::new(b' ', CGA[7], CGA[0])
+}
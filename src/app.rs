@@ -1,4 +1,21 @@
 use chrono::Duration;
+#[cfg(feature = "raw-window-handle")]
+use raw_window_handle::{
+    DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, WindowHandle,
+};
+#[cfg(feature = "raw-window-handle")]
+use winit::window::Window;
+
+use crate::{
+    accessibility::ScreenRegions,
+    animation::AnimationPhase,
+    context::MageContext,
+    error::MageError,
+    gamepad::Gamepad,
+    image::Image,
+    input::{Composition, KeyEvent, MouseState},
+    stats::MemoryStats,
+};
 
 /// The [`App`] trait is the main interface for the game. It is called by the
 /// framework to update the game state and render the game.
@@ -81,6 +98,108 @@ pub trait App {
     /// [`PresentResult`]: enum.PresentResult.html
     ///
     fn present(&mut self, present_input: PresentInput) -> PresentResult;
+
+    /// Called when the window has been created, and again whenever it is
+    /// recreated, such as when an Android app is resumed.  The handles can be
+    /// given to other libraries that draw to or change the window.
+    ///
+    /// # Parameters
+    ///
+    /// * `window` - The raw window and display handles of the window.  They
+    ///   may change when the window is recreated, so shouldn't be kept.
+    ///
+    /// Returns the areas of the screen tagged with what they are for, so the
+    /// screen can be described as structured text to assistive tools, such
+    /// as by [`Headless::screen_text`].
+    ///
+    /// # Returns
+    ///
+    /// The tagged areas of the screen as last presented, or `None` if the
+    /// app doesn't tag them.  By default, none are.
+    ///
+    /// [`Headless::screen_text`]: headless/struct.Headless.html#method.screen_text
+    ///
+    fn screen_regions(&self) -> Option<&ScreenRegions> {
+        None
+    }
+
+    /// Called when drawing to the window has failed for several frames in a
+    /// row, or has failed in a way that can't be recovered from, so that the
+    /// game can save and shut down gracefully.
+    ///
+    /// # Parameters
+    ///
+    /// * `error` - The last error, which is a [`MageError::RenderError`].
+    /// * `failures` - The number of frames in a row that have failed to draw.
+    ///
+    /// # Returns
+    ///
+    /// [`TickResult::Quit`] to stop the game, or [`TickResult::Continue`] to
+    /// keep trying to draw.  If the GPU has run out of memory, the game stops
+    /// whatever is returned and `run` returns the error.  By default, the
+    /// game keeps trying.
+    ///
+    /// [`MageError::RenderError`]: error/enum.MageError.html#variant.RenderError
+    /// [`TickResult::Quit`]: enum.TickResult.html#variant.Quit
+    /// [`TickResult::Continue`]: enum.TickResult.html#variant.Continue
+    ///
+    fn render_failed(&mut self, error: &MageError, failures: u32) -> TickResult {
+        let _ = (error, failures);
+        TickResult::Continue
+    }
+
+    /// Called when the window stops having an area to draw to, such as when
+    /// it is minimised, and again when it can be drawn to.  The game is still
+    /// ticked and presented while hidden, but nothing is drawn, and the size
+    /// in characters stays as it was before the window was hidden.
+    ///
+    /// # Parameters
+    ///
+    /// * `hidden` - True if the window has just been hidden, or false if it
+    ///   has just been shown again.
+    ///
+    fn window_hidden(&mut self, hidden: bool) {
+        let _ = hidden;
+    }
+
+    /// Called when the player has selected an area of the screen with the
+    /// mouse to copy its text.  See [`Config::selection_key`].
+    ///
+    /// The text has already been put on the clipboard, in web pages and on
+    /// desktops with the `clipboard` feature, which is on by default.  This
+    /// lets the app tell the player, or copy the text itself on other
+    /// platforms.  By default, nothing is done.
+    ///
+    /// # Parameters
+    ///
+    /// * `text` - The text of the area selected, one line per row.
+    ///
+    /// [`Config::selection_key`]: struct.Config.html#structfield.selection_key
+    ///
+    fn selection_copied(&mut self, text: String) {
+        let _ = text;
+    }
+
+    /// Called when the font has been zoomed, by the player or with
+    /// [`zoom::set_zoom`].  The grid has already been recomputed for the new
+    /// zoom, and the next tick is given its size.  By default, nothing is
+    /// done.
+    ///
+    /// # Parameters
+    ///
+    /// * `zoom` - How many screen pixels wide and high each pixel of the
+    ///   font is now drawn.
+    ///
+    /// [`zoom::set_zoom`]: zoom/fn.set_zoom.html
+    ///
+    fn zoom_changed(&mut self, zoom: u32) {
+        let _ = zoom;
+    }
+
+    #[cfg(feature = "raw-window-handle")]
+    fn window_created(&mut self, window: WindowHandles) {
+        let _ = window;
+    }
 }
 
 /// The [`TickResult`] is returned by the [`tick`] method of the [`App`] trait
@@ -123,7 +242,7 @@ pub enum PresentResult {
 /// [`App`]: trait.App.html
 ///
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct TickInput {
+pub struct TickInput<'input> {
     /// The time since the last frame.
     pub dt: Duration,
 
@@ -132,35 +251,121 @@ pub struct TickInput {
 
     /// The height of the window in characters.
     pub height: u32,
+
+    /// The state of the mouse.
+    pub mouse: MouseState,
+
+    /// The keys pressed and released since the last tick, in the order they
+    /// were.  Keys the engine uses itself, such as [`Config::quit_key`], are
+    /// kept from the app when pressed.
+    ///
+    /// [`Config::quit_key`]: struct.Config.html#structfield.quit_key
+    pub keys: &'input [KeyEvent],
+
+    /// The text typed since the last tick, in the layout the player uses,
+    /// for name entry and chat boxes.  This includes text committed with an
+    /// input method, but not control chars such as from Enter and Backspace,
+    /// which are only in [`keys`].
+    ///
+    /// [`keys`]: struct.TickInput.html#structfield.keys
+    pub text: &'input str,
+
+    /// The text being composed with an input method, which the app can show
+    /// where the text will go, or `None` if none is.  Input methods are only
+    /// used with [`Config::ime`].
+    ///
+    /// [`Config::ime`]: struct.Config.html#structfield.ime
+    pub composition: Option<&'input Composition>,
+
+    /// The game controllers that are connected, in the order they were
+    /// connected.  See the [`gamepad`] module.
+    ///
+    /// [`gamepad`]: gamepad/index.html
+    pub gamepads: &'input [Gamepad],
+
+    /// How much memory the engine is using to draw the window.  Headless
+    /// runs have no textures, so only count the screen.
+    pub memory: MemoryStats,
+
+    /// How many screen pixels wide and high each pixel of the font is drawn.
+    /// See the [`zoom`] module.  Headless runs are always at a zoom of 1.
+    ///
+    /// [`zoom`]: zoom/index.html
+    pub zoom: u32,
+
+    /// How far the engine's animation clock is through its period, after
+    /// moving on by `dt`.
+    pub animation: AnimationPhase,
+
+    /// Changes the window, such as its title or size, once the tick has
+    /// finished.  See the [`context`] module.
+    ///
+    /// [`context`]: context/index.html
+    pub context: &'input MageContext,
 }
 
 /// The [`PresentInput`] struct is passed to the [`present`] method of the
-/// [`App`] trait to provide information about the current frame.
+/// [`App`] trait to give access to the screen for the current frame.
+///
+/// The screen is an [`Image`] holding the chars that are uploaded to the GPU,
+/// so the app can draw on it directly with the image drawing API, or within
+/// an area of it with a [`Canvas`].  The screen keeps its contents between
+/// frames, unless the window changes size, when it is cleared.
+///
+/// [`PresentInput`]: struct.PresentInput.html
+/// [`present`]: trait.App.html#tymethod.present
+/// [`App`]: trait.App.html
+/// [`Image`]: image/struct.Image.html
+/// [`Canvas`]: canvas/struct.Canvas.html
+///
 #[derive(Debug, Eq, PartialEq)]
-pub struct PresentInput<'textures> {
-    /// The width of the window in characters.
-    pub width: u32,
+pub struct PresentInput<'screen> {
+    /// The chars drawn on the window, which is the size of the window in
+    /// characters.  Each char's ink and paper colours are 32-bit RGBA values,
+    /// with red in the least significant 8 bits and alpha, which is currently
+    /// unused, in the most significant.  The least significant 8 bits of each
+    /// glyph choose the tile in the font, and the other 24 bits are unused by
+    /// the engine but are available for use by the game.
+    pub screen: &'screen mut Image,
 
-    /// The height of the window in characters.
-    pub height: u32,
+    /// How far the engine's animation clock is through its period, as it was
+    /// for the last tick.
+    pub animation: AnimationPhase,
+}
+
+/// The [`WindowHandles`] struct is passed to the [`window_created`] method of
+/// the [`App`] trait to give access to the raw handles of the window, for use
+/// with other libraries such as video players and native overlays.
+///
+/// This is only available with the `raw-window-handle` feature.
+///
+/// [`WindowHandles`]: struct.WindowHandles.html
+/// [`window_created`]: trait.App.html#method.window_created
+/// [`App`]: trait.App.html
+///
+#[cfg(feature = "raw-window-handle")]
+#[derive(Clone, Copy)]
+pub struct WindowHandles<'window> {
+    window: &'window Window,
+}
+
+#[cfg(feature = "raw-window-handle")]
+impl<'window> WindowHandles<'window> {
+    pub(crate) fn new(window: &'window Window) -> Self {
+        Self { window }
+    }
+}
+
+#[cfg(feature = "raw-window-handle")]
+impl HasWindowHandle for WindowHandles<'_> {
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        self.window.window_handle()
+    }
+}
 
-    /// The foreground color of each character.  Each 32-bit RGBA value
-    /// represents a single character.  The least significant 8 bits are the
-    /// red value, the next 8 bits are the green value, the next 8 bits are the
-    /// blue value, and the most significant 8 bits are the alpha value. The
-    /// alpha value is currently unused.
-    pub fore_image: &'textures mut [u32],
-
-    /// The background color of each character.  Each 32-bit RGBA value
-    /// represents a single character.  The least significant 8 bits are the
-    /// red value, the next 8 bits are the green value, the next 8 bits are the
-    /// blue value, and the most significant 8 bits are the alpha value. The
-    /// alpha value is currently unused.
-    pub back_image: &'textures mut [u32],
-
-    /// The character buffer.  Each 32-bit RGBA value represents a single
-    /// character. The least significant 8 bits are the ASCII value of the
-    /// character, and the most significant 24 bits are unused by the engine but
-    /// are available for use by the game.
-    pub text_image: &'textures mut [u32],
+#[cfg(feature = "raw-window-handle")]
+impl HasDisplayHandle for WindowHandles<'_> {
+    fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        self.window.display_handle()
+    }
 }
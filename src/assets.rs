@@ -0,0 +1,588 @@
+//! Loading and caching of fonts, images, palettes and themes from files.
+//!
+//! An [`Assets`] manager loads files relative to a root folder.  Loading an
+//! asset returns a [`Handle`] straight away, and the file is read and decoded
+//! on a background thread so that the game can show progress while it waits.
+//! Each file is only loaded once; loading it again returns the same handle.
+//!
+//! ```no_run
+//! use mage_core::{assets::Assets, image::Image, palette::Palette};
+//!
+//! let mut assets = Assets::new("assets");
+//! let title = assets.load::<Image>("title.xp");
+//! let palette = assets.load::<Palette>("pico-8.hex");
+//!
+//! // Each tick:
+//! assets.update();
+//! let progress = assets.progress();
+//! if progress.is_done() {
+//!     let title = assets.get(title).unwrap();
+//! }
+//! ```
+//!
+//! During development, [`Assets::with_hot_reload`] checks loaded files for
+//! changes and loads them again when they are saved, so art can be tweaked
+//! while the game runs.  Files are checked by polling their modification
+//! times in [`Assets::update`].
+//!
+//! These types are assets, and apps can add their own by implementing
+//! [`Asset`]:
+//!
+//! * [`Image`] - REXPaint `.xp` files, ANSI art in `.ans` files, pictures
+//!   such as `.png` files drawn with half block glyphs, and Mage's own image
+//!   files for anything else.
+//! * [`FontData`] - Font images with 16 by 16 glyphs, such as `.png` files.
+//! * [`Palette`] - GIMP `.gpl` files and lists of hex colours, or JSON with
+//!   the `serde` feature.
+//! * [`Theme`] - JSON, with the `serde` feature.
+//!
+//! [`Assets`]: struct.Assets.html
+//! [`Handle`]: struct.Handle.html
+//! [`Assets::with_hot_reload`]: struct.Assets.html#method.with_hot_reload
+//! [`Assets::update`]: struct.Assets.html#method.update
+//! [`Asset`]: trait.Asset.html
+//! [`Image`]: ../image/struct.Image.html
+//! [`FontData`]: ../config/struct.FontData.html
+//! [`Palette`]: ../palette/struct.Palette.html
+//! [`Theme`]: ../ui/struct.Theme.html
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt, fs,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use image::{load_from_memory, GenericImageView};
+use tracing::warn;
+
+use crate::{
+    colour::Colour,
+    config::{load_font_image, FontData},
+    error::MageError,
+    image::{Char, Image, Point, UPPER_HALF_GLYPH},
+    palette::Palette,
+};
+
+/// A type that can be loaded from a file by an [`Assets`] manager.
+///
+/// [`Assets`]: struct.Assets.html
+///
+pub trait Asset: Sized + Send + Sync + 'static {
+    /// Decodes the asset from the contents of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The contents of the file.
+    /// * `path` - The path of the file, whose extension can be used to tell
+    ///   formats apart.
+    ///
+    fn from_file_bytes(data: &[u8], path: &Path) -> Result<Self, MageError>;
+}
+
+impl Asset for Image {
+    fn from_file_bytes(data: &[u8], path: &Path) -> Result<Self, MageError> {
+        match extension(path).as_str() {
+            "xp" => Image::from_xp_bytes(data),
+            "ans" | "ansi" => Ok(Image::from_ans_bytes(data)),
+            "png" | "gif" | "bmp" | "jpg" | "jpeg" => picture_to_image(data),
+            _ => Image::from_bytes(data),
+        }
+    }
+}
+
+impl Asset for FontData {
+    fn from_file_bytes(data: &[u8], _path: &Path) -> Result<Self, MageError> {
+        load_font_image(data)
+    }
+}
+
+impl Asset for Palette {
+    fn from_file_bytes(data: &[u8], path: &Path) -> Result<Self, MageError> {
+        #[cfg(feature = "serde")]
+        if extension(path) == "json" {
+            return from_json(data);
+        }
+        #[cfg(not(feature = "serde"))]
+        let _ = path;
+
+        let text = std::str::from_utf8(data).map_err(|e| MageError::InvalidAsset(e.to_string()))?;
+        Palette::parse(text)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Asset for crate::ui::Theme {
+    fn from_file_bytes(data: &[u8], _path: &Path) -> Result<Self, MageError> {
+        from_json(data)
+    }
+}
+
+/// Refers to an asset loaded by an [`Assets`] manager.  Handles are cheap to
+/// copy and compare.
+///
+/// [`Assets`]: struct.Assets.html
+///
+pub struct Handle<T> {
+    id: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    fn new(id: usize) -> Self {
+        Self {
+            id,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Handle").field(&self.id).finish()
+    }
+}
+
+/// Whether an asset has loaded yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoadState {
+    /// The file is being read and decoded.
+    Loading,
+
+    /// The asset is ready to use.
+    Loaded,
+
+    /// The file couldn't be read or decoded, for the reason given.
+    Failed(String),
+}
+
+/// How many of the assets asked for have finished loading.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of assets that have loaded.
+    pub loaded: usize,
+
+    /// The number of assets that failed to load.
+    pub failed: usize,
+
+    /// The number of assets asked for.
+    pub total: usize,
+}
+
+impl Progress {
+    /// Returns the share of assets that have finished loading, whether they
+    /// loaded or failed, from 0 to 1.  With no assets, this is 1.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.loaded + self.failed) as f32 / self.total as f32
+        }
+    }
+
+    /// Returns true if no assets are still loading.
+    pub fn is_done(&self) -> bool {
+        self.loaded + self.failed >= self.total
+    }
+}
+
+/// A decoded asset, of any type.
+type AnyAsset = Arc<dyn Any + Send + Sync>;
+
+/// Decodes an asset of a particular type.
+type Decoder = fn(&[u8], &Path) -> Result<AnyAsset, MageError>;
+
+/// An asset asked for, whether it has loaded or not.
+struct Entry {
+    /// The full path of the file.
+    path: PathBuf,
+
+    state: LoadState,
+
+    /// The asset, once loaded.  This is kept when reloading it fails.
+    value: Option<AnyAsset>,
+
+    /// The number of times the asset has been loaded.
+    version: u64,
+
+    /// The modification time of the file when it was last read.
+    modified: Option<SystemTime>,
+
+    /// True while the file is being read on the background thread.
+    pending: bool,
+
+    decode: Decoder,
+}
+
+/// A file to read and decode on the background thread.
+struct Job {
+    id: usize,
+    path: PathBuf,
+    decode: Decoder,
+}
+
+/// The result of a job.
+struct Loaded {
+    id: usize,
+    result: Result<(AnyAsset, Option<SystemTime>), String>,
+}
+
+/// Loads and caches assets, reading files on a background thread.
+pub struct Assets {
+    /// The folder paths are relative to.
+    root: PathBuf,
+
+    /// The assets asked for, indexed by their handles' ids.
+    entries: Vec<Entry>,
+
+    /// The id of each file loaded as each type.
+    ids: HashMap<(PathBuf, TypeId), usize>,
+
+    /// Sends jobs to the background thread, once it has started.
+    jobs: Option<Sender<Job>>,
+
+    /// Receives finished jobs from the background thread.
+    loaded_receiver: Receiver<Loaded>,
+    loaded_sender: Sender<Loaded>,
+
+    /// How often to check files for changes, if at all.
+    hot_reload: Option<Duration>,
+
+    /// When files were last checked for changes.
+    last_check: Instant,
+}
+
+impl Assets {
+    /// Creates a new asset manager that loads files relative to a folder.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let (loaded_sender, loaded_receiver) = mpsc::channel();
+        Self {
+            root: root.into(),
+            entries: Vec::new(),
+            ids: HashMap::new(),
+            jobs: None,
+            loaded_receiver,
+            loaded_sender,
+            hot_reload: None,
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Checks loaded files for changes every so often in [`update`], loading
+    /// them again when they change.  This is meant for development.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The time between checks.
+    ///
+    /// [`update`]: struct.Assets.html#method.update
+    ///
+    pub fn with_hot_reload(mut self, interval: Duration) -> Self {
+        self.hot_reload = Some(interval);
+        self
+    }
+
+    /// Starts loading an asset in the background, unless it has already been
+    /// asked for.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file, relative to the root folder.
+    ///
+    /// # Returns
+    ///
+    /// A handle to the asset, which can be used once it has loaded.
+    ///
+    pub fn load<T: Asset>(&mut self, path: impl AsRef<Path>) -> Handle<T> {
+        let (id, is_new) = self.entry::<T>(path.as_ref());
+        if is_new {
+            self.start_job(id);
+        }
+        Handle::new(id)
+    }
+
+    /// Loads an asset straight away, unless it has already loaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file, relative to the root folder.
+    ///
+    /// # Returns
+    ///
+    /// A handle to the loaded asset, or the error if it couldn't be loaded.
+    ///
+    pub fn load_now<T: Asset>(&mut self, path: impl AsRef<Path>) -> Result<Handle<T>, MageError> {
+        let (id, _) = self.entry::<T>(path.as_ref());
+        let entry = &mut self.entries[id];
+        if entry.value.is_none() {
+            match read(&entry.path, entry.decode) {
+                Ok((value, modified)) => {
+                    entry.value = Some(value);
+                    entry.modified = modified;
+                    entry.version += 1;
+                    entry.state = LoadState::Loaded;
+                }
+                Err(e) => {
+                    entry.state = LoadState::Failed(e.to_string());
+                    return Err(e);
+                }
+            }
+        }
+        Ok(Handle::new(id))
+    }
+
+    /// Returns an asset, or `None` if it hasn't loaded.
+    pub fn get<T: Asset>(&self, handle: Handle<T>) -> Option<Arc<T>> {
+        self.entries
+            .get(handle.id)?
+            .value
+            .clone()?
+            .downcast::<T>()
+            .ok()
+    }
+
+    /// Returns whether an asset has loaded yet.
+    pub fn state<T>(&self, handle: Handle<T>) -> &LoadState {
+        &self.entries[handle.id].state
+    }
+
+    /// Returns the number of times an asset has been loaded, which goes up
+    /// each time it is reloaded.  Apps can compare versions to know when to
+    /// rebuild anything made from the asset.
+    pub fn version<T>(&self, handle: Handle<T>) -> u64 {
+        self.entries[handle.id].version
+    }
+
+    /// Returns the full path of an asset's file.
+    pub fn path<T>(&self, handle: Handle<T>) -> &Path {
+        &self.entries[handle.id].path
+    }
+
+    /// Returns how many of the assets asked for have finished loading.
+    pub fn progress(&self) -> Progress {
+        let mut progress = Progress {
+            total: self.entries.len(),
+            ..Progress::default()
+        };
+        for entry in &self.entries {
+            match entry.state {
+                LoadState::Loading => {}
+                LoadState::Loaded => progress.loaded += 1,
+                LoadState::Failed(_) => progress.failed += 1,
+            }
+        }
+        progress
+    }
+
+    /// Takes in the assets loaded on the background thread, and checks for
+    /// changed files if hot reloading.  Call this once per tick.
+    ///
+    /// # Returns
+    ///
+    /// True if any assets have loaded or reloaded since the last update.
+    ///
+    pub fn update(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(loaded) = self.loaded_receiver.try_recv() {
+            changed |= self.finish_job(loaded);
+        }
+
+        if let Some(interval) = self.hot_reload {
+            if self.last_check.elapsed() >= interval {
+                self.last_check = Instant::now();
+                for id in 0..self.entries.len() {
+                    let entry = &self.entries[id];
+                    if entry.pending || entry.modified.is_none() {
+                        continue;
+                    }
+                    let modified = fs::metadata(&entry.path).and_then(|m| m.modified()).ok();
+                    if modified.is_some() && modified != entry.modified {
+                        self.start_job(id);
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+
+    /// Waits until all the assets asked for have finished loading.
+    pub fn wait(&mut self) {
+        while self.entries.iter().any(|entry| entry.pending) {
+            match self.loaded_receiver.recv() {
+                Ok(loaded) => {
+                    self.finish_job(loaded);
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Returns the id of a file loaded as a type, adding an entry for it if
+    /// it hasn't been asked for.
+    ///
+    /// # Returns
+    ///
+    /// The id, and true if the entry is new.
+    ///
+    fn entry<T: Asset>(&mut self, path: &Path) -> (usize, bool) {
+        let path = self.root.join(path);
+        let key = (path.clone(), TypeId::of::<T>());
+        if let Some(&id) = self.ids.get(&key) {
+            return (id, false);
+        }
+
+        let id = self.entries.len();
+        self.entries.push(Entry {
+            path,
+            state: LoadState::Loading,
+            value: None,
+            version: 0,
+            modified: None,
+            pending: false,
+            decode: |data, path| Ok(Arc::new(T::from_file_bytes(data, path)?)),
+        });
+        self.ids.insert(key, id);
+        (id, true)
+    }
+
+    /// Sends an entry's file to the background thread to be loaded, starting
+    /// the thread if needed.
+    fn start_job(&mut self, id: usize) {
+        let jobs = self.jobs.get_or_insert_with(|| {
+            let (jobs, job_receiver) = mpsc::channel::<Job>();
+            let loaded = self.loaded_sender.clone();
+            thread::spawn(move || {
+                for job in job_receiver {
+                    let result = read(&job.path, job.decode).map_err(|e| e.to_string());
+                    if loaded.send(Loaded { id: job.id, result }).is_err() {
+                        break;
+                    }
+                }
+            });
+            jobs
+        });
+
+        let entry = &mut self.entries[id];
+        let job = Job {
+            id,
+            path: entry.path.clone(),
+            decode: entry.decode,
+        };
+        entry.pending = jobs.send(job).is_ok();
+    }
+
+    /// Stores the result of a job.
+    ///
+    /// # Returns
+    ///
+    /// True if the asset loaded.
+    ///
+    fn finish_job(&mut self, loaded: Loaded) -> bool {
+        let entry = &mut self.entries[loaded.id];
+        entry.pending = false;
+        match loaded.result {
+            Ok((value, modified)) => {
+                entry.value = Some(value);
+                entry.modified = modified;
+                entry.version += 1;
+                entry.state = LoadState::Loaded;
+                true
+            }
+            Err(e) if entry.value.is_some() => {
+                // Keep the old version, as files are often read while they are
+                // half written.
+                warn!("Unable to reload {}: {}", entry.path.display(), e);
+                entry.modified = fs::metadata(&entry.path).and_then(|m| m.modified()).ok();
+                false
+            }
+            Err(e) => {
+                warn!("Unable to load {}: {}", entry.path.display(), e);
+                entry.state = LoadState::Failed(e);
+                false
+            }
+        }
+    }
+}
+
+/// Reads and decodes a file.
+///
+/// # Returns
+///
+/// The asset and the modification time of the file.
+///
+fn read(path: &Path, decode: Decoder) -> Result<(AnyAsset, Option<SystemTime>), MageError> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+    let data = fs::read(path)?;
+    Ok((decode(&data, path)?, modified))
+}
+
+/// Returns the extension of a path in lower case.
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Decodes an asset from JSON.
+#[cfg(feature = "serde")]
+fn from_json<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T, MageError> {
+    serde_json::from_slice(data).map_err(|e| MageError::InvalidAsset(e.to_string()))
+}
+
+/// Decodes a picture, such as a PNG file, into an image with two pixels in
+/// each cell: the top one as the ink of an upper half block and the bottom one
+/// as the paper.
+fn picture_to_image(data: &[u8]) -> Result<Image, MageError> {
+    let picture = load_from_memory(data).map_err(|_| MageError::InvalidImageData)?;
+    let (width, height) = picture.dimensions();
+    let pixels = picture.to_rgba8();
+    let pixel = |x: u32, y: u32| {
+        if y < height {
+            let [r, g, b, a] = pixels.get_pixel(x, y).0;
+            Colour::Rgba(r, g, b, a)
+        } else {
+            Colour::transparent()
+        }
+    };
+
+    let mut image = Image::new(width, height.div_ceil(2));
+    for y in 0..image.height {
+        for x in 0..width {
+            let ch = Char::new(UPPER_HALF_GLYPH, pixel(x, y * 2), pixel(x, y * 2 + 1));
+            image.draw_char(Point::new(x as i32, y as i32), ch);
+        }
+    }
+    Ok(image)
+}
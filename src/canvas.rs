@@ -0,0 +1,204 @@
+//! Drawing within an area of an image, with coordinates relative to it.
+//!
+//! A [`Canvas`] borrows an image and limits drawing to a rectangle of it, so
+//! that a panel, a status bar or a map view can be drawn with its own
+//! coordinates without the code doing the drawing knowing where it is on the
+//! screen.  Anything drawn outside the rectangle is clipped.
+//!
+//! ```
+//! use mage_core::{
+//!     image::{Image, Point, Rect},
+//!     Colour,
+//! };
+//!
+//! let mut screen = Image::new(20, 5);
+//! let mut panel = screen.canvas(Rect::new(10, 1, 6, 2));
+//! panel.clear(Colour::White, Colour::Blue);
+//! panel.draw_string(Point::new(0, 0), "Health", Colour::Red, Colour::Blue);
+//! panel.draw_string(Point::new(0, 1), "100 HP left", Colour::White, Colour::Blue);
+//!
+//! assert_eq!(screen.to_plain_text().lines().nth(2).unwrap().trim(), "100 HP");
+//! ```
+//!
+//! [`Canvas`]: struct.Canvas.html
+
+use crate::{
+    codepage::{char_to_glyph, unicode_to_cp437},
+    colour::Colour,
+    image::{BlitOptions, Char, Image, Point, Rect, DEFAULT_FALLBACK_GLYPH},
+};
+
+/// An area of an image to draw within.
+///
+/// Points given to a canvas are relative to the top-left corner of its area,
+/// and drawing is clipped to the area.  Canvases are created with
+/// [`Image::canvas`] or [`PresentInput::canvas`], and can be split further
+/// with [`canvas`].
+///
+/// [`Image::canvas`]: ../image/struct.Image.html#method.canvas
+/// [`PresentInput::canvas`]: ../struct.PresentInput.html#method.canvas
+/// [`canvas`]: struct.Canvas.html#method.canvas
+///
+#[derive(Debug)]
+pub struct Canvas<'a> {
+    /// The image being drawn on.
+    image: &'a mut Image,
+
+    /// The area of the image that is drawn within, clipped to the image.
+    area: Rect,
+}
+
+impl<'a> Canvas<'a> {
+    /// Creates a canvas for an area of an image, clipped to the image.
+    pub(crate) fn new(image: &'a mut Image, rect: Rect) -> Self {
+        let area = rect
+            .intersect(image.rect())
+            .unwrap_or(Rect::new(rect.x, rect.y, 0, 0));
+        Self { image, area }
+    }
+
+    /// Returns the width of the canvas in chars.
+    pub fn width(&self) -> u32 {
+        self.area.width
+    }
+
+    /// Returns the height of the canvas in chars.
+    pub fn height(&self) -> u32 {
+        self.area.height
+    }
+
+    /// Returns the area of the canvas in its own coordinates, which starts
+    /// at (0, 0).
+    pub fn rect(&self) -> Rect {
+        Rect::new(0, 0, self.area.width, self.area.height)
+    }
+
+    /// Returns the area of the image that the canvas draws within.
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    /// Returns a canvas for an area of this canvas, given in this canvas's
+    /// coordinates.  The area is clipped to this canvas.
+    pub fn canvas(&mut self, rect: Rect) -> Canvas<'_> {
+        let area = self
+            .rect()
+            .intersect(rect)
+            .map(|rect| rect.translate(self.area.x, self.area.y))
+            .unwrap_or(Rect::new(self.area.x, self.area.y, 0, 0));
+        Canvas::new(self.image, area)
+    }
+
+    /// Returns the char at the given coordinates, or `None` if they are
+    /// outside the canvas.
+    pub fn get_char(&self, p: Point) -> Option<Char> {
+        self.to_image(p).and_then(|p| self.image.get_char(p))
+    }
+
+    /// Draws a char at the given coordinates, unless they are outside the
+    /// canvas.
+    pub fn draw_char(&mut self, p: Point, ch: Char) {
+        if let Some(p) = self.to_image(p) {
+            self.image.draw_char(p, ch);
+        }
+    }
+
+    /// Draws a string at the given coordinates, clipped to the canvas.  See
+    /// [`Image::draw_string`].
+    ///
+    /// [`Image::draw_string`]: ../image/struct.Image.html#method.draw_string
+    ///
+    pub fn draw_string(&mut self, p: Point, text: &str, ink: Colour, paper: Colour) {
+        let glyphs = text
+            .chars()
+            .map(|ch| char_to_glyph(ch, DEFAULT_FALLBACK_GLYPH));
+        self.draw_glyphs(p, glyphs, ink, paper);
+    }
+
+    /// Draws a string of Unicode text at the given coordinates, clipped to
+    /// the canvas.  See [`Image::draw_string_unicode`].
+    ///
+    /// [`Image::draw_string_unicode`]: ../image/struct.Image.html#method.draw_string_unicode
+    ///
+    pub fn draw_string_unicode(&mut self, p: Point, text: &str, ink: Colour, paper: Colour) {
+        let glyphs = text
+            .chars()
+            .map(|ch| unicode_to_cp437(ch).unwrap_or(DEFAULT_FALLBACK_GLYPH));
+        self.draw_glyphs(p, glyphs, ink, paper);
+    }
+
+    /// Fills a rectangle with a char, clipped to the canvas.
+    pub fn draw_filled_rect(&mut self, rect: Rect, ch: Char) {
+        if let Some(rect) = self.rect().intersect(rect) {
+            let rect = rect.translate(self.area.x, self.area.y);
+            self.image.draw_filled_rect(rect, ch);
+        }
+    }
+
+    /// Clears the canvas with a given ink and paper colour.
+    pub fn clear(&mut self, ink: Colour, paper: Colour) {
+        self.image
+            .draw_filled_rect(self.area, Char::new_u32(0, ink, paper));
+    }
+
+    /// Blits an area of another image on to the canvas, clipped to the
+    /// canvas.  See [`Image::blit`].
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - The coordinates on the canvas to blit the top-left corner of
+    ///   the source area to.
+    /// * `src_image` - The image to blit from.
+    /// * `src_rect` - The area of the source image to blit.
+    /// * `options` - Controls which source chars are considered transparent.
+    ///
+    /// [`Image::blit`]: ../image/struct.Image.html#method.blit
+    ///
+    pub fn blit(&mut self, dst: Point, src_image: &Image, src_rect: Rect, options: BlitOptions) {
+        let dst_rect = Rect::from_point_and_size(dst, src_rect.width, src_rect.height);
+        let Some(clipped) = self.rect().intersect(dst_rect) else {
+            return;
+        };
+        // The source is moved by as much as the destination was clipped.
+        let shift = |src: i32, clipped: i32, dst: i32| {
+            (src as i64 + clipped as i64 - dst as i64).clamp(i32::MIN as i64, i32::MAX as i64)
+                as i32
+        };
+        let src_rect = Rect::new(
+            shift(src_rect.x, clipped.x, dst.x),
+            shift(src_rect.y, clipped.y, dst.y),
+            clipped.width,
+            clipped.height,
+        );
+        let dst = clipped.translate(self.area.x, self.area.y).top_left();
+        self.image.blit(dst, src_image, src_rect, options);
+    }
+
+    /// Draws glyphs in a row from the given coordinates, clipped to the
+    /// canvas.
+    fn draw_glyphs(
+        &mut self,
+        p: Point,
+        glyphs: impl Iterator<Item = u8>,
+        ink: Colour,
+        paper: Colour,
+    ) {
+        for (i, glyph) in glyphs.enumerate() {
+            let Ok(i) = i32::try_from(i) else {
+                break;
+            };
+            self.draw_char(
+                Point::new(p.x.saturating_add(i), p.y),
+                Char::new(glyph, ink, paper),
+            );
+        }
+    }
+
+    /// Converts a point on the canvas to a point on the image, or `None` if
+    /// it is outside the canvas.
+    fn to_image(&self, p: Point) -> Option<Point> {
+        self.rect()
+            .contains(p)
+            .then(|| Point::new(p.x + self.area.x, p.y + self.area.y))
+    }
+}
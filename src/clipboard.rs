@@ -0,0 +1,35 @@
+//! Putting text on the clipboard on desktops, with the `clipboard` feature.
+
+use tracing::warn;
+
+/// The desktop's clipboard, opened the first time text is copied.
+///
+/// It is kept open afterwards, as on Linux the text copied is only
+/// available while it is.
+pub(crate) struct Clipboard {
+    clipboard: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    pub(crate) fn new() -> Self {
+        Self { clipboard: None }
+    }
+
+    /// Puts text on the clipboard.  If the clipboard can't be used, a warning
+    /// is logged and nothing is copied.
+    pub(crate) fn copy(&mut self, text: &str) {
+        let clipboard = match &mut self.clipboard {
+            Some(clipboard) => clipboard,
+            None => match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard.insert(clipboard),
+                Err(e) => {
+                    warn!("The clipboard can't be opened: {}", e);
+                    return;
+                }
+            },
+        };
+        if let Err(e) = clipboard.set_text(text) {
+            warn!("The text can't be copied to the clipboard: {}", e);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Mapping between code page 437 glyphs and Unicode.
+//!
+//! The default font follows the layout of code page 437, the character set of
+//! the original IBM PC.  These helpers allow Unicode text (such as box drawing
+//! characters written directly in source code) to be drawn with the classic
+//! 256 glyphs, and drawn glyphs to be converted back to Unicode for export.
+
+/// Maps each code page 437 glyph to its Unicode equivalent.
+///
+/// Glyph 0 maps to a space and glyphs 1 to 31 and 127 map to the graphical
+/// symbols shown by the original IBM PC rather than control codes.
+///
+pub const CP437_TO_UNICODE: [char; 256] = [
+    ' ', '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼', '►', '◄', '↕',
+    '‼', '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼', ' ', '!', '"', '#', '$', '%',
+    '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/', '0', '1', '2', '3', '4', '5', '6', '7', '8',
+    '9', ':', ';', '<', '=', '>', '?', '@', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K',
+    'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '[', '\\', ']', '^',
+    '_', '`', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q',
+    'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '{', '|', '}', '~', '⌂', 'Ç', 'ü', 'é', 'â', 'ä',
+    'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', 'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù',
+    'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬',
+    '½', '¼', '¡', '«', '»', '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜',
+    '╛', '┐', '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧', '╨',
+    '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀', 'α', 'ß', 'Γ', 'π',
+    'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', '≡', '±', '≥', '≤', '⌠', '⌡', '÷',
+    '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// Additional Unicode characters that are not in the table above but have a
+/// close equivalent in code page 437.
+const UNICODE_ALIASES: [(char, u8); 10] = [
+    ('\0', 0x00),
+    ('β', 0xe1),
+    ('Π', 0xe3),
+    ('∑', 0xe4),
+    ('μ', 0xe6),
+    ('Ω', 0xea),
+    ('∅', 0xed),
+    ('ϕ', 0xed),
+    ('∈', 0xee),
+    ('▪', 0xfe),
+];
+
+/// Converts a code page 437 glyph to its Unicode equivalent.
+pub fn cp437_to_unicode(glyph: u8) -> char {
+    CP437_TO_UNICODE[glyph as usize]
+}
+
+/// Converts a Unicode character to its code page 437 glyph.
+///
+/// # Returns
+///
+/// The glyph, or `None` if the character has no equivalent in code page 437.
+///
+pub fn unicode_to_cp437(ch: char) -> Option<u8> {
+    if (' '..='~').contains(&ch) {
+        return Some(ch as u8);
+    }
+
+    CP437_TO_UNICODE
+        .iter()
+        .position(|&c| c == ch)
+        .map(|glyph| glyph as u8)
+        .or_else(|| {
+            UNICODE_ALIASES
+                .iter()
+                .find(|&&(c, _)| c == ch)
+                .map(|&(_, glyph)| glyph)
+        })
+}
+
+/// Converts a character to the glyph drawn for it by [`Image::draw_string`].
+///
+/// ASCII characters, including control codes, are used directly as the glyph.
+/// Anything else is mapped to its code page 437 glyph, or `fallback` if there
+/// is none.
+///
+/// [`Image::draw_string`]: ../image/struct.Image.html#method.draw_string
+///
+pub(crate) fn char_to_glyph(ch: char, fallback: u8) -> u8 {
+    if ch.is_ascii() {
+        ch as u8
+    } else {
+        unicode_to_cp437(ch).unwrap_or(fallback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_latin1_through_the_table() {
+        assert_eq!(char_to_glyph('a', b'?'), b'a');
+        assert_eq!(char_to_glyph('\u{1}', b'?'), 1);
+        assert_eq!(char_to_glyph('é', b'?'), 0x82);
+        assert_eq!(char_to_glyph('£', b'?'), 0x9c);
+        assert_eq!(char_to_glyph('╔', b'?'), 0xc9);
+    }
+
+    #[test]
+    fn uses_fallback_for_unmapped_chars() {
+        assert_eq!(char_to_glyph('\u{80}', b'?'), b'?');
+        assert_eq!(char_to_glyph('®', b'#'), b'#');
+        assert_eq!(char_to_glyph('😀', b'?'), b'?');
+    }
+}
@@ -1,3 +1,12 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use crate::error::MageError;
+
+#[derive(Clone, Copy, Debug, Eq)]
 pub enum Colour {
     Black,
     Blue,
@@ -16,9 +25,12 @@ pub enum Colour {
     Yellow,
     White,
     Rgb(u8, u8, u8),
+    Rgba(u8, u8, u8, u8),
 }
 
 impl Colour {
+    /// Returns the colour packed into a `u32` as stored in an image, with red
+    /// in the lowest byte, then green, blue and alpha in the highest byte.
     pub fn colour(&self) -> u32 {
         match self {
             Colour::Black => 0xff000000,
@@ -41,14 +53,941 @@ impl Colour {
                 let r = *r as u32;
                 let g = *g as u32;
                 let b = *b as u32;
-                0xff000000 | (r << 16) | (g << 8) | b
+                0xff000000 | (b << 16) | (g << 8) | r
+            }
+            Colour::Rgba(r, g, b, a) => {
+                let r = *r as u32;
+                let g = *g as u32;
+                let b = *b as u32;
+                let a = *a as u32;
+                (a << 24) | (b << 16) | (g << 8) | r
+            }
+        }
+    }
+
+    /// Returns the red component of the colour.
+    pub fn r(&self) -> u8 {
+        self.colour() as u8
+    }
+
+    /// Returns the green component of the colour.
+    pub fn g(&self) -> u8 {
+        (self.colour() >> 8) as u8
+    }
+
+    /// Returns the blue component of the colour.
+    pub fn b(&self) -> u8 {
+        (self.colour() >> 16) as u8
+    }
+
+    /// Returns the alpha component of the colour.
+    pub fn a(&self) -> u8 {
+        (self.colour() >> 24) as u8
+    }
+
+    /// Returns a fully transparent colour.
+    ///
+    /// Paper with this colour is skipped by blits with
+    /// [`BlitOptions::transparent_paper`] set.
+    ///
+    /// [`BlitOptions::transparent_paper`]: ../image/struct.BlitOptions.html#structfield.transparent_paper
+    ///
+    pub fn transparent() -> Colour {
+        Colour::Rgba(0, 0, 0, 0)
+    }
+
+    /// Returns the colour with its alpha replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The new alpha, from 0 (fully transparent) to 255 (opaque).
+    ///
+    pub fn with_alpha(&self, a: u8) -> Colour {
+        Colour::Rgba(self.r(), self.g(), self.b(), a)
+    }
+
+    /// Returns the colour with an alpha of 255, making it opaque.
+    pub fn opaque(&self) -> Colour {
+        self.with_alpha(0xff)
+    }
+
+    /// Returns true if the colour has an alpha of 255.
+    pub fn is_opaque(&self) -> bool {
+        self.a() == 0xff
+    }
+
+    /// Returns true if the colour has an alpha of 0.
+    pub fn is_transparent(&self) -> bool {
+        self.a() == 0
+    }
+
+    /// Returns the red, green and blue components of the colour.
+    pub fn to_rgb(&self) -> (u8, u8, u8) {
+        (self.r(), self.g(), self.b())
+    }
+
+    /// Returns the red, green, blue and alpha components of the colour.
+    pub fn to_rgba(&self) -> (u8, u8, u8, u8) {
+        (self.r(), self.g(), self.b(), self.a())
+    }
+
+    /// Linearly interpolates between two colours, component by component.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The colour to start from.
+    /// * `b` - The colour to end at.
+    /// * `t` - How far to move from `a` to `b`.  0.0 gives `a` and 1.0 gives
+    ///   `b`.  Values outside this range are clamped.
+    ///
+    /// # Returns
+    ///
+    /// The interpolated colour.
+    ///
+    pub fn lerp(a: Colour, b: Colour, t: f32) -> Colour {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Colour::Rgba(
+            mix(a.r(), b.r()),
+            mix(a.g(), b.g()),
+            mix(a.b(), b.b()),
+            mix(a.a(), b.a()),
+        )
+    }
+
+    /// Interpolates between two colours in the Oklab perceptual colour space.
+    ///
+    /// Compared to [`lerp`], the intermediate colours have a more even
+    /// brightness and avoid the muddy greys produced when mixing
+    /// complementary colours.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The colour to start from.
+    /// * `b` - The colour to end at.
+    /// * `t` - How far to move from `a` to `b`.  0.0 gives `a` and 1.0 gives
+    ///   `b`.  Values outside this range are clamped.
+    ///
+    /// # Returns
+    ///
+    /// The interpolated colour.
+    ///
+    /// [`lerp`]: enum.Colour.html#method.lerp
+    ///
+    pub fn lerp_perceptual(a: Colour, b: Colour, t: f32) -> Colour {
+        let t = t.clamp(0.0, 1.0) as f64;
+        let alpha = (a.a() as f64 + (b.a() as f64 - a.a() as f64) * t).round() as u8;
+        let a = oklab::from_rgb(a.to_rgb());
+        let b = oklab::from_rgb(b.to_rgb());
+        let mix = |a: f64, b: f64| a + (b - a) * t;
+        oklab::to_colour([mix(a[0], b[0]), mix(a[1], b[1]), mix(a[2], b[2])], alpha)
+    }
+
+    /// Returns how different two colours look, ignoring alpha.
+    ///
+    /// The distance is measured in the Oklab perceptual colour space, so
+    /// colours that look alike are close even if their components differ.  0.0
+    /// means the colours are identical and black and white are about 1.0
+    /// apart.
+    ///
+    pub fn distance(&self, other: Colour) -> f32 {
+        let a = oklab::from_rgb(self.to_rgb());
+        let b = oklab::from_rgb(other.to_rgb());
+        oklab::distance(a, b) as f32
+    }
+
+    /// Returns a darker version of the colour.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - How much darker to make the colour, from 0.0 (unchanged)
+    ///   to 1.0 (black).
+    ///
+    pub fn darken(&self, amount: f32) -> Colour {
+        Colour::lerp(*self, Colour::Black.with_alpha(self.a()), amount)
+    }
+
+    /// Returns a lighter version of the colour.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - How much lighter to make the colour, from 0.0 (unchanged)
+    ///   to 1.0 (white).
+    ///
+    pub fn lighten(&self, amount: f32) -> Colour {
+        Colour::lerp(*self, Colour::White.with_alpha(self.a()), amount)
+    }
+
+    /// Returns a more saturated version of the colour.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - How much to increase the saturation, from 0.0 (unchanged)
+    ///   to 1.0 (fully saturated).
+    ///
+    pub fn saturate(&self, amount: f32) -> Colour {
+        let (h, s, l) = hsl::from_rgb(self.to_rgb());
+        hsl::to_colour(h, s + (1.0 - s) * amount.clamp(0.0, 1.0), l, self.a())
+    }
+
+    /// Returns a less saturated version of the colour.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - How much to decrease the saturation, from 0.0 (unchanged)
+    ///   to 1.0 (grey).
+    ///
+    pub fn desaturate(&self, amount: f32) -> Colour {
+        let (h, s, l) = hsl::from_rgb(self.to_rgb());
+        hsl::to_colour(h, s * (1.0 - amount.clamp(0.0, 1.0)), l, self.a())
+    }
+
+    /// Returns the opposite colour, with each of the red, green and blue
+    /// components subtracted from 255, so black becomes white.
+    ///
+    /// The alpha of this colour is kept.
+    ///
+    pub fn invert(&self) -> Colour {
+        Colour::Rgba(255 - self.r(), 255 - self.g(), 255 - self.b(), self.a())
+    }
+
+    /// Returns the colour with its hue rotated around the colour wheel,
+    /// keeping its saturation and lightness.
+    ///
+    /// # Arguments
+    ///
+    /// * `degrees` - How far to rotate the hue.  120 turns red into green and
+    ///   240 turns red into blue.  Negative values rotate the other way.
+    ///
+    pub fn rotate_hue(&self, degrees: f32) -> Colour {
+        let (h, s, l) = hsl::from_rgb(self.to_rgb());
+        hsl::to_colour(h + degrees, s, l, self.a())
+    }
+
+    /// Multiplies each component of this colour by the other colour, so white
+    /// leaves this colour unchanged and black makes it black.  Useful for
+    /// lighting and shadows.
+    ///
+    /// The alpha of this colour is kept.
+    ///
+    pub fn multiply(&self, other: Colour) -> Colour {
+        self.blend_channels(other, |a, b| a * b)
+    }
+
+    /// The inverse of [`multiply`]: black leaves this colour unchanged and
+    /// white makes it white.  Useful for glows and highlights.
+    ///
+    /// The alpha of this colour is kept.
+    ///
+    /// [`multiply`]: enum.Colour.html#method.multiply
+    ///
+    pub fn screen(&self, other: Colour) -> Colour {
+        self.blend_channels(other, |a, b| 1.0 - (1.0 - a) * (1.0 - b))
+    }
+
+    /// Multiplies the dark components of this colour and screens the light
+    /// ones, increasing contrast while keeping this colour's highlights and
+    /// shadows.
+    ///
+    /// The alpha of this colour is kept.
+    ///
+    pub fn overlay(&self, other: Colour) -> Colour {
+        self.blend_channels(other, |a, b| {
+            if a < 0.5 {
+                2.0 * a * b
+            } else {
+                1.0 - 2.0 * (1.0 - a) * (1.0 - b)
             }
+        })
+    }
+
+    /// Adds the components of the other colour to this colour, saturating at
+    /// white.  Useful for light sources and fire.
+    ///
+    /// The alpha of this colour is kept.
+    ///
+    pub fn additive(&self, other: Colour) -> Colour {
+        self.blend_channels(other, |a, b| a + b)
+    }
+
+    /// Composites this colour over a background colour according to this
+    /// colour's alpha.
+    ///
+    /// An opaque colour hides the background completely, a fully transparent
+    /// colour leaves the background unchanged and anything in between mixes
+    /// the two.
+    ///
+    /// # Arguments
+    ///
+    /// * `background` - The colour behind this one.
+    ///
+    /// # Returns
+    ///
+    /// The combined colour, with an alpha covering both colours.
+    ///
+    pub fn over(&self, background: Colour) -> Colour {
+        let sa = self.a() as f32 / 255.0;
+        let da = background.a() as f32 / 255.0 * (1.0 - sa);
+        let alpha = sa + da;
+        if alpha <= 0.0 {
+            return Colour::transparent();
+        }
+
+        let mix = |s: u8, d: u8| ((s as f32 * sa + d as f32 * da) / alpha).round() as u8;
+        Colour::Rgba(
+            mix(self.r(), background.r()),
+            mix(self.g(), background.g()),
+            mix(self.b(), background.b()),
+            (alpha * 255.0).round() as u8,
+        )
+    }
+
+    /// Combines the red, green and blue components of this colour with those
+    /// of another, each scaled from 0.0 to 1.0, keeping this colour's alpha.
+    fn blend_channels(&self, other: Colour, f: impl Fn(f32, f32) -> f32) -> Colour {
+        let channel = |a: u8, b: u8| {
+            let c = f(a as f32 / 255.0, b as f32 / 255.0);
+            (c.clamp(0.0, 1.0) * 255.0).round() as u8
+        };
+        Colour::Rgba(
+            channel(self.r(), other.r()),
+            channel(self.g(), other.g()),
+            channel(self.b(), other.b()),
+            self.a(),
+        )
+    }
+
+    /// Returns the relative luminance of the colour, from 0.0 for black to 1.0
+    /// for white, as perceived by the human eye.
+    pub fn luminance(&self) -> f32 {
+        let (r, g, b) = self.to_rgb();
+        (0.2126 * oklab::to_linear(r) + 0.7152 * oklab::to_linear(g) + 0.0722 * oklab::to_linear(b))
+            as f32
+    }
+
+    /// Returns the grey with the same luminance as this colour.
+    ///
+    /// The alpha of this colour is kept.
+    ///
+    pub fn grayscale(&self) -> Colour {
+        let grey = oklab::from_linear(self.luminance() as f64);
+        Colour::Rgba(grey, grey, grey, self.a())
+    }
+
+    /// Returns the colour tinted brown like an old photograph.
+    ///
+    /// The alpha of this colour is kept.
+    ///
+    pub fn sepia(&self) -> Colour {
+        let (r, g, b) = self.to_rgb();
+        let (r, g, b) = (r as f32, g as f32, b as f32);
+        let channel = |c: f32| c.min(255.0).round() as u8;
+        Colour::Rgba(
+            channel(0.393 * r + 0.769 * g + 0.189 * b),
+            channel(0.349 * r + 0.686 * g + 0.168 * b),
+            channel(0.272 * r + 0.534 * g + 0.131 * b),
+            self.a(),
+        )
+    }
+
+    /// Returns how this colour looks to someone with the given type of colour
+    /// vision.
+    ///
+    /// The alpha of this colour is kept.
+    ///
+    pub fn simulate_vision(&self, vision: ColourVision) -> Colour {
+        let Some(m) = vision.matrix() else {
+            return *self;
+        };
+
+        let (r, g, b) = self.to_rgb();
+        let rgb = [
+            oklab::to_linear(r) as f32,
+            oklab::to_linear(g) as f32,
+            oklab::to_linear(b) as f32,
+        ];
+        let channel = |row: [f32; 3]| {
+            let c = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2];
+            oklab::from_linear(c as f64)
+        };
+        Colour::Rgba(channel(m[0]), channel(m[1]), channel(m[2]), self.a())
+    }
+
+    /// Creates a bright colour from a hash of any value.
+    ///
+    /// The same value always gives the same colour in the same build of the
+    /// game on the same platform, so this is useful for giving entities,
+    /// players or message sources a colour of their own.  The value is hashed
+    /// through the `Hash` trait, whose output can differ between platforms
+    /// and Rust versions, so use [`from_hash_bytes`] for colours that must
+    /// match everywhere, such as ones saved to files or sent between players.
+    ///
+    /// [`from_hash_bytes`]: enum.Colour.html#method.from_hash_bytes
+    ///
+    pub fn from_hash(seed: impl Hash) -> Colour {
+        let mut hasher = Fnv1a::default();
+        seed.hash(&mut hasher);
+        hashed_colour(hasher.finish())
+    }
+
+    /// Creates a bright colour from a hash of some bytes, as [`from_hash`]
+    /// does.
+    ///
+    /// The bytes are hashed directly, so the same bytes give the same colour
+    /// on every platform, in every build and in every run of the game.
+    ///
+    /// [`from_hash`]: enum.Colour.html#method.from_hash
+    ///
+    pub fn from_hash_bytes(bytes: &[u8]) -> Colour {
+        let mut hasher = Fnv1a::default();
+        hasher.write(bytes);
+        hashed_colour(hasher.finish())
+    }
+}
+
+/// Returns the bright colour for a hash, for [`Colour::from_hash`] and
+/// [`Colour::from_hash_bytes`].
+///
+/// [`Colour::from_hash`]: enum.Colour.html#method.from_hash
+/// [`Colour::from_hash_bytes`]: enum.Colour.html#method.from_hash_bytes
+///
+fn hashed_colour(hash: u64) -> Colour {
+    let hue = (hash & 0xffff) as f32 / 65536.0 * 360.0;
+    let saturation = 0.6 + ((hash >> 16) & 0xff) as f32 / 255.0 * 0.4;
+    let lightness = 0.5 + ((hash >> 24) & 0xff) as f32 / 255.0 * 0.15;
+    hsl::to_colour(hue, saturation, lightness, 0xff)
+}
+
+/// Random colours, available with the `rand` feature.
+#[cfg(feature = "rand")]
+impl Colour {
+    /// Returns a random opaque colour.
+    pub fn random(rng: &mut impl rand::Rng) -> Colour {
+        Colour::Rgb(rng.gen(), rng.gen(), rng.gen())
+    }
+
+    /// Returns a fully saturated colour with a random hue, such as a bright
+    /// red, green or purple.
+    pub fn random_hue(rng: &mut impl rand::Rng) -> Colour {
+        hsl::to_colour(rng.gen_range(0.0..360.0), 1.0, 0.5, 0xff)
+    }
+
+    /// Returns the colour with each of its red, green and blue components
+    /// moved by a random amount, for natural looking variation in textures
+    /// such as grass and stone.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator to use.
+    /// * `amount` - The largest change to each component, from 0.0 (no change)
+    ///   to 1.0 (any value).
+    ///
+    pub fn jitter(&self, rng: &mut impl rand::Rng, amount: f32) -> Colour {
+        let range = (amount.clamp(0.0, 1.0) * 255.0).round() as i32;
+        let mut channel = |c: u8| (c as i32 + rng.gen_range(-range..=range)).clamp(0, 255) as u8;
+        Colour::Rgba(
+            channel(self.r()),
+            channel(self.g()),
+            channel(self.b()),
+            self.a(),
+        )
+    }
+}
+
+/// The 64-bit FNV-1a hash function, used by [`Colour::from_hash`] and
+/// [`Colour::from_hash_bytes`] because, unlike the standard library's hasher,
+/// the hash of the same bytes never changes.
+///
+/// [`Colour::from_hash`]: enum.Colour.html#method.from_hash
+/// [`Colour::from_hash_bytes`]: enum.Colour.html#method.from_hash_bytes
+///
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
         }
     }
 }
 
+impl PartialEq for Colour {
+    /// Colours are equal if they produce the same packed value, so named
+    /// colours compare equal to their RGB equivalents.
+    fn eq(&self, other: &Self) -> bool {
+        self.colour() == other.colour()
+    }
+}
+
 impl From<Colour> for u32 {
     fn from(colour: Colour) -> Self {
         colour.colour()
     }
 }
+
+impl From<u32> for Colour {
+    /// Unpacks a colour from the format used by the image and screen buffers,
+    /// with red in the least significant byte and alpha in the most.
+    fn from(colour: u32) -> Self {
+        Colour::Rgba(
+            colour as u8,
+            (colour >> 8) as u8,
+            (colour >> 16) as u8,
+            (colour >> 24) as u8,
+        )
+    }
+}
+
+impl From<(u8, u8, u8)> for Colour {
+    fn from((r, g, b): (u8, u8, u8)) -> Self {
+        Colour::Rgb(r, g, b)
+    }
+}
+
+impl From<(u8, u8, u8, u8)> for Colour {
+    fn from((r, g, b, a): (u8, u8, u8, u8)) -> Self {
+        Colour::Rgba(r, g, b, a)
+    }
+}
+
+impl From<[u8; 3]> for Colour {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Colour::Rgb(r, g, b)
+    }
+}
+
+impl From<[u8; 4]> for Colour {
+    fn from([r, g, b, a]: [u8; 4]) -> Self {
+        Colour::Rgba(r, g, b, a)
+    }
+}
+
+/// A type of colour vision, used to simulate colour blindness.
+///
+/// Simulating colour blindness with [`Colour::simulate_vision`] or
+/// [`Config::colour_vision`] shows how a game looks to players with colour
+/// vision deficiencies, to check that important information is not shown by
+/// colour alone.
+///
+/// [`Colour::simulate_vision`]: enum.Colour.html#method.simulate_vision
+/// [`Config::colour_vision`]: ../config/struct.Config.html#structfield.colour_vision
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColourVision {
+    /// Normal colour vision.  Colours are unchanged.
+    #[default]
+    Normal,
+
+    /// No red cones, making reds look dark and confusing red with green.
+    Protanopia,
+
+    /// No green cones, the most common form, confusing red with green.
+    Deuteranopia,
+
+    /// No blue cones, confusing blue with green and yellow with violet.
+    Tritanopia,
+}
+
+impl ColourVision {
+    /// Returns the matrix, in rows, that transforms a linear RGB colour as
+    /// seen with normal vision to how it is seen with this type of vision, or
+    /// `None` for normal vision.
+    ///
+    /// The matrices are from Machado, Oliveira and Fernandes (2009) at full
+    /// severity.
+    ///
+    pub(crate) fn matrix(self) -> Option<[[f32; 3]; 3]> {
+        match self {
+            ColourVision::Normal => None,
+            ColourVision::Protanopia => Some([
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ]),
+            ColourVision::Deuteranopia => Some([
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ]),
+            ColourVision::Tritanopia => Some([
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ]),
+        }
+    }
+}
+
+/// The names of the named colours, as written by [`Display`] and read by
+/// [`FromStr`].
+///
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+/// [`FromStr`]: https://doc.rust-lang.org/std/str/trait.FromStr.html
+///
+const COLOUR_NAMES: [(Colour, &str); 16] = [
+    (Colour::Black, "black"),
+    (Colour::Blue, "blue"),
+    (Colour::Green, "green"),
+    (Colour::Cyan, "cyan"),
+    (Colour::Red, "red"),
+    (Colour::Magenta, "magenta"),
+    (Colour::Brown, "brown"),
+    (Colour::LightGray, "light_gray"),
+    (Colour::DarkGray, "dark_gray"),
+    (Colour::LightBlue, "light_blue"),
+    (Colour::LightGreen, "light_green"),
+    (Colour::LightCyan, "light_cyan"),
+    (Colour::LightRed, "light_red"),
+    (Colour::LightMagenta, "light_magenta"),
+    (Colour::Yellow, "yellow"),
+    (Colour::White, "white"),
+];
+
+/// The prefix for the engine's named colours when written as strings, which
+/// keeps names such as `blue` free for the CSS colours.
+const ENGINE_COLOUR_PREFIX: &str = "mage:";
+
+impl fmt::Display for Colour {
+    /// Named colours are written as their name with the `mage:` prefix, e.g.
+    /// `mage:light_red`, and other colours as a hex string, e.g. `#ff8000`,
+    /// with the alpha added if the colour is not opaque, e.g. `#ff800080`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Colour::Rgb(..) | Colour::Rgba(..) => {
+                let (r, g, b, a) = self.to_rgba();
+                write!(f, "#{:02x}{:02x}{:02x}", r, g, b)?;
+                if a != 0xff {
+                    write!(f, "{:02x}", a)?;
+                }
+                Ok(())
+            }
+            named => {
+                let (_, name) = COLOUR_NAMES
+                    .iter()
+                    .find(|(colour, _)| colour == named)
+                    .expect("every named colour has a name");
+                write!(f, "{ENGINE_COLOUR_PREFIX}{name}")
+            }
+        }
+    }
+}
+
+impl FromStr for Colour {
+    type Err = MageError;
+
+    /// Parses a CSS colour name, such as `cornflowerblue` or `blue`, or a hex
+    /// string in the form `#rgb`, `#rrggbb` or `#rrggbbaa`.
+    ///
+    /// The engine's own named colours are written with a `mage:` prefix, such
+    /// as `mage:light_red`, `mage:LightRed` or `mage:light grey`.  Names that
+    /// are not also CSS names, such as `light_red`, are accepted without the
+    /// prefix, but names such as `blue` and `light_gray` always give the CSS
+    /// colour.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || MageError::InvalidColour(s.to_string());
+        let s = s.trim();
+
+        if let Some(hex) = s.strip_prefix('#') {
+            if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+            let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+            return match hex.len() {
+                3 => {
+                    let digit = |i: usize| {
+                        u8::from_str_radix(&hex[i..i + 1], 16)
+                            .map(|d| d * 0x11)
+                            .map_err(|_| invalid())
+                    };
+                    Ok(Colour::Rgb(digit(0)?, digit(1)?, digit(2)?))
+                }
+                6 => Ok(Colour::Rgb(byte(0)?, byte(2)?, byte(4)?)),
+                8 => Ok(Colour::Rgba(byte(0)?, byte(2)?, byte(4)?, byte(6)?)),
+                _ => Err(invalid()),
+            };
+        }
+
+        let (s, engine_only) = match s.strip_prefix(ENGINE_COLOUR_PREFIX) {
+            Some(name) => (name, true),
+            None => (s, false),
+        };
+        if !engine_only {
+            if let Some(colour) = Colour::from_css_name(s) {
+                return Ok(colour);
+            }
+        }
+
+        let normalised = s
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .collect::<String>()
+            .to_ascii_lowercase()
+            .replace("grey", "gray");
+        COLOUR_NAMES
+            .iter()
+            .find(|(_, name)| name.replace('_', "") == normalised)
+            .map(|(colour, _)| *colour)
+            .ok_or_else(invalid)
+    }
+}
+
+/// Conversions between sRGB and the Oklab perceptual colour space.
+mod oklab {
+    use super::Colour;
+
+    /// Converts an sRGB component to linear light.
+    pub(super) fn to_linear(c: u8) -> f64 {
+        let c = c as f64 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    /// Converts a linear light component to sRGB.
+    pub(super) fn from_linear(c: f64) -> u8 {
+        let c = if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        (c.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+
+    /// Converts sRGB components to Oklab `[L, a, b]`.
+    pub(super) fn from_rgb((r, g, b): (u8, u8, u8)) -> [f64; 3] {
+        let (r, g, b) = (to_linear(r), to_linear(g), to_linear(b));
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+        [
+            0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        ]
+    }
+
+    /// Returns the Euclidean distance between two Oklab colours.
+    pub(super) fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+        let d = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+        (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt()
+    }
+
+    /// Converts Oklab `[L, a, b]` and an alpha component to a colour.
+    pub(super) fn to_colour([l, a, b]: [f64; 3], alpha: u8) -> Colour {
+        let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+        let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+        let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+        Colour::Rgba(
+            from_linear(4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_),
+            from_linear(-1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_),
+            from_linear(-0.0041960771 * l_ - 0.7034186147 * m_ + 1.7076642316 * s_),
+            alpha,
+        )
+    }
+}
+
+/// Conversions between RGB and hue, saturation and lightness.
+mod hsl {
+    use super::Colour;
+
+    /// Converts RGB components to hue (in degrees), saturation and lightness
+    /// (both from 0.0 to 1.0).
+    pub(super) fn from_rgb((r, g, b): (u8, u8, u8)) -> (f32, f32, f32) {
+        let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let d = max - min;
+        if d == 0.0 {
+            return (0.0, 0.0, l);
+        }
+
+        let s = d / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == r {
+            ((g - b) / d).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / d + 2.0
+        } else {
+            (r - g) / d + 4.0
+        };
+        (h * 60.0, s, l)
+    }
+
+    /// Converts hue (in degrees), saturation, lightness and an alpha component
+    /// to a colour.
+    pub(super) fn to_colour(h: f32, s: f32, l: f32, a: u8) -> Colour {
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let h = h.rem_euclid(360.0) / 60.0;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = l - c / 2.0;
+        let channel = |v: f32| ((v + m).clamp(0.0, 1.0) * 255.0).round() as u8;
+        Colour::Rgba(channel(r), channel(g), channel(b), a)
+    }
+}
+
+/// Serialisation of colours as names or hex strings.  Colours can also be read
+/// from an array of 3 or 4 components, e.g. `[255, 128, 0]`.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use std::fmt;
+
+    use serde::{
+        de::{self, SeqAccess, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    use super::Colour;
+
+    impl Serialize for Colour {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Colour {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(ColourVisitor)
+        }
+    }
+
+    struct ColourVisitor;
+
+    impl<'de> Visitor<'de> for ColourVisitor {
+        type Value = Colour;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a colour name, a hex string or an array of 3 or 4 components")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Colour, E> {
+            v.parse().map_err(E::custom)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Colour, A::Error> {
+            let mut component = |i: usize| {
+                seq.next_element::<u8>()?
+                    .ok_or_else(|| de::Error::invalid_length(i, &self))
+            };
+            let r = component(0)?;
+            let g = component(1)?;
+            let b = component(2)?;
+            let a = seq.next_element::<u8>()?;
+            if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                return Err(de::Error::invalid_length(5, &self));
+            }
+            Ok(match a {
+                Some(a) => Colour::Rgba(r, g, b, a),
+                None => Colour::Rgb(r, g, b),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_bytes_give_fixed_colours() {
+        // FNV-1a test vectors, so the hash can't change unnoticed.
+        let fnv = |bytes: &[u8]| {
+            let mut hasher = Fnv1a::default();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+        assert_eq!(fnv(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv(b"foobar"), 0x85944171f73967e8);
+
+        assert_eq!(
+            Colour::from_hash_bytes(b"a"),
+            hashed_colour(0xaf63dc4c8601ec8c)
+        );
+        assert_ne!(Colour::from_hash_bytes(b"a"), Colour::from_hash_bytes(b"b"));
+        assert!(Colour::from_hash_bytes(b"player").is_opaque());
+    }
+
+    #[test]
+    fn rgb_packs_like_the_named_colours() {
+        assert_eq!(Colour::Rgb(0x80, 0, 0).colour(), Colour::Red.colour());
+        assert_eq!(Colour::Rgb(0, 0, 0x80).colour(), Colour::Blue.colour());
+        assert_eq!(Colour::Rgb(0xff, 0xff, 0).colour(), Colour::Yellow.colour());
+        assert_eq!(Colour::Rgb(1, 2, 3).colour(), 0xff030201);
+        assert_eq!(Colour::Rgba(1, 2, 3, 4).colour(), 0x04030201);
+    }
+
+    #[test]
+    fn parses_css_names_before_engine_names() {
+        for (name, colour) in [
+            ("blue", Colour::Rgb(0, 0, 0xff)),
+            ("red", Colour::Rgb(0xff, 0, 0)),
+            ("green", Colour::Rgb(0, 0x80, 0)),
+            ("darkgray", Colour::Rgb(0xa9, 0xa9, 0xa9)),
+            ("light_gray", Colour::Rgb(0xd3, 0xd3, 0xd3)),
+            ("LightBlue", Colour::Rgb(0xad, 0xd8, 0xe6)),
+        ] {
+            assert_eq!(name.parse::<Colour>().unwrap(), colour, "{name}");
+        }
+    }
+
+    #[test]
+    fn parses_engine_names() {
+        assert_eq!("mage:blue".parse::<Colour>().unwrap(), Colour::Blue);
+        assert_eq!(
+            "mage:LightGray".parse::<Colour>().unwrap(),
+            Colour::LightGray
+        );
+        assert_eq!(
+            "mage:light grey".parse::<Colour>().unwrap(),
+            Colour::LightGray
+        );
+        assert_eq!("light_red".parse::<Colour>().unwrap(), Colour::LightRed);
+        assert!("mage:cornflowerblue".parse::<Colour>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for colour in COLOUR_NAMES
+            .iter()
+            .map(|(colour, _)| *colour)
+            .chain([Colour::Rgb(1, 2, 3), Colour::Rgba(1, 2, 3, 4)])
+        {
+            let text = colour.to_string();
+            let parsed = text.parse::<Colour>().unwrap();
+            assert_eq!(parsed, colour, "{text}");
+            assert_eq!(parsed.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn rgb_components_round_trip() {
+        let colour = Colour::Rgb(1, 2, 3);
+        assert_eq!(
+            (colour.r(), colour.g(), colour.b(), colour.a()),
+            (1, 2, 3, 0xff)
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Compact images storing 16-colour palette indices instead of full colours.
+//!
+//! An [`Image`] uses 12 bytes per char.  A [`CompactImage`] packs the glyph
+//! and 4-bit ink and paper palette indices into 2 bytes, in the style of the
+//! attribute bytes of the original IBM PC text mode.  This makes it suitable
+//! for very large buffers such as scrollback logs and map caches, which are
+//! converted to full colour only when drawn.
+//!
+//! [`Image`]: ../image/struct.Image.html
+//! [`CompactImage`]: struct.CompactImage.html
+
+use crate::{
+    codepage::char_to_glyph,
+    colour::Colour,
+    image::{Char, Image, Point, Rect, DEFAULT_FALLBACK_GLYPH},
+    palette::Palette,
+};
+
+/// A single char of a [`CompactImage`]: an 8-bit glyph and 4-bit ink and
+/// paper palette indices packed into 16 bits.
+///
+/// [`CompactImage`]: struct.CompactImage.html
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CompactChar(u16);
+
+impl CompactChar {
+    /// Creates a new compact char.
+    ///
+    /// # Arguments
+    ///
+    /// * `glyph` - The glyph to draw.
+    /// * `ink` - The palette index of the foreground colour.  Only the lower 4
+    ///   bits are used.
+    /// * `paper` - The palette index of the background colour.  Only the lower
+    ///   4 bits are used.
+    ///
+    pub fn new(glyph: u8, ink: u8, paper: u8) -> Self {
+        Self(glyph as u16 | ((ink as u16 & 0xf) << 8) | ((paper as u16 & 0xf) << 12))
+    }
+
+    /// Returns the glyph.
+    pub fn glyph(&self) -> u8 {
+        self.0 as u8
+    }
+
+    /// Returns the palette index of the foreground colour.
+    pub fn ink(&self) -> u8 {
+        ((self.0 >> 8) & 0xf) as u8
+    }
+
+    /// Returns the palette index of the background colour.
+    pub fn paper(&self) -> u8 {
+        (self.0 >> 12) as u8
+    }
+
+    /// Converts the compact char to a full colour char using a palette.
+    /// Indices beyond the end of the palette are black.
+    pub fn to_char(&self, palette: &[Colour]) -> Char {
+        let colour = |index: u8| palette.get(index as usize).copied();
+        Char::new(
+            self.glyph(),
+            colour(self.ink()).unwrap_or(Colour::Black),
+            colour(self.paper()).unwrap_or(Colour::Black),
+        )
+    }
+}
+
+/// An image of glyphs with 16-colour palette indices for their colours, using
+/// 2 bytes per char.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactImage {
+    pub width: u32,
+    pub height: u32,
+    pub cells: Vec<CompactChar>,
+}
+
+impl CompactImage {
+    /// Creates a new compact image with the given dimensions, filled with
+    /// glyph zero in palette colour zero.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![CompactChar::default(); (width * height) as usize],
+        }
+    }
+
+    /// Creates a compact image from an image, converting every colour to the
+    /// index of the nearest colour in a palette.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image to convert.
+    /// * `palette` - The colours to use.  Only the first 16 colours are
+    ///   considered.
+    ///
+    /// # Notes
+    ///
+    /// Glyphs above 255, including transparent glyphs, are stored as glyph
+    /// zero.
+    ///
+    pub fn from_image(image: &Image, palette: &[Colour]) -> Self {
+        let palette: Palette = palette.iter().copied().take(16).collect();
+        let index = |colour: u32| palette.nearest_index(colour.into()).unwrap_or(0) as u8;
+
+        let cells = (0..image.text_image.len())
+            .map(|i| {
+                CompactChar::new(
+                    u8::try_from(image.text_image[i]).unwrap_or(0),
+                    index(image.fore_image[i]),
+                    index(image.back_image[i]),
+                )
+            })
+            .collect();
+        Self {
+            width: image.width,
+            height: image.height,
+            cells,
+        }
+    }
+
+    /// Returns the index of the char at the given coordinates, or `None` if
+    /// the coordinates are out of bounds.
+    fn point_to_index(&self, p: Point) -> Option<usize> {
+        (p.x >= 0 && p.y >= 0 && (p.x as u32) < self.width && (p.y as u32) < self.height)
+            .then(|| (p.y as u32 * self.width + p.x as u32) as usize)
+    }
+
+    /// Returns the char at the given coordinates, or `None` if the coordinates
+    /// are out of bounds.
+    pub fn get(&self, p: Point) -> Option<CompactChar> {
+        self.point_to_index(p).map(|i| self.cells[i])
+    }
+
+    /// Draws a char at the given coordinates.  If the coordinates are out of
+    /// bounds, the char is not drawn.
+    pub fn draw_char(&mut self, p: Point, ch: CompactChar) {
+        if let Some(i) = self.point_to_index(p) {
+            self.cells[i] = ch;
+        }
+    }
+
+    /// Draws a string at the given coordinates with the given palette indices.
+    ///
+    /// Characters are mapped to glyphs as with [`Image::draw_string`], with
+    /// [`DEFAULT_FALLBACK_GLYPH`] drawn for characters that have no glyph.  If
+    /// the coordinates are out of bounds, the string is clipped.
+    ///
+    /// [`Image::draw_string`]: ../image/struct.Image.html#method.draw_string
+    /// [`DEFAULT_FALLBACK_GLYPH`]: ../image/constant.DEFAULT_FALLBACK_GLYPH.html
+    ///
+    pub fn draw_string(&mut self, p: Point, text: &str, ink: u8, paper: u8) {
+        for (ch, x) in text.chars().zip(p.x..) {
+            let glyph = char_to_glyph(ch, DEFAULT_FALLBACK_GLYPH);
+            self.draw_char(Point::new(x, p.y), CompactChar::new(glyph, ink, paper));
+        }
+    }
+
+    /// Fills the image with glyph zero in the given palette indices.
+    pub fn clear(&mut self, ink: u8, paper: u8) {
+        self.cells.fill(CompactChar::new(0, ink, paper));
+    }
+
+    /// Converts the compact image to a full colour image using a palette.
+    /// Indices beyond the end of the palette are black.
+    pub fn to_image(&self, palette: &[Colour]) -> Image {
+        let mut image = Image::new(self.width, self.height);
+        self.draw_to(&mut image, Point::default(), self.rect(), palette);
+        image
+    }
+
+    /// Draws an area of the compact image on to a full colour image.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image to draw on to.
+    /// * `dst` - The coordinates on the image to draw the top-left corner of
+    ///   the area to.
+    /// * `src_rect` - The area of the compact image to draw.
+    /// * `palette` - The colours of the palette indices.  Indices beyond the
+    ///   end of the palette are black.
+    ///
+    /// # Notes
+    ///
+    /// Only the visible part of the area is converted, so this is cheap for
+    /// drawing a window on to a large buffer.  The area is clipped to both
+    /// images.
+    ///
+    pub fn draw_to(&self, image: &mut Image, dst: Point, src_rect: Rect, palette: &[Colour]) {
+        let palette = palette
+            .iter()
+            .map(|colour| colour.colour())
+            .chain(std::iter::repeat(Colour::Black.colour()))
+            .take(16)
+            .collect::<Vec<_>>();
+
+        // The range of offsets into the area that are inside both images.
+        let range = |src: i32, dst: i32, len: u32, src_len: u32, dst_len: u32| {
+            let (src, dst) = (src as i64, dst as i64);
+            let start = 0.max(-src).max(-dst);
+            let end = (len as i64)
+                .min(src_len as i64 - src)
+                .min(dst_len as i64 - dst);
+            start..end.max(start)
+        };
+        let xs = range(src_rect.x, dst.x, src_rect.width, self.width, image.width);
+        let ys = range(
+            src_rect.y,
+            dst.y,
+            src_rect.height,
+            self.height,
+            image.height,
+        );
+
+        for y in ys {
+            let src_row = ((src_rect.y as i64 + y) * self.width as i64) as usize;
+            let dst_row = ((dst.y as i64 + y) * image.width as i64) as usize;
+            for x in xs.clone() {
+                let cell = self.cells[src_row + (src_rect.x as i64 + x) as usize];
+                let i = dst_row + (dst.x as i64 + x) as usize;
+                image.text_image[i] = cell.glyph() as u32;
+                image.fore_image[i] = palette[cell.ink() as usize];
+                image.back_image[i] = palette[cell.paper() as usize];
+            }
+        }
+    }
+
+    /// Returns the rectangle covering the whole image.
+    pub fn rect(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+}
@@ -1,7 +1,18 @@
 use bytemuck::cast_slice;
+use chrono::Duration;
 use image::{load_from_memory, EncodableLayout, GenericImageView};
+use wgpu::PresentMode;
+use winit::keyboard::KeyCode;
 
-use crate::error::MageError;
+#[cfg(target_os = "android")]
+use winit::platform::android::activity::AndroidApp;
+
+#[cfg(feature = "frame-texture")]
+use crate::frame_texture::FrameSink;
+use crate::{
+    animation::DEFAULT_BLINK_PERIOD_MS, colour::ColourVision, error::MageError, input::KeyBinding,
+    render::GpuContext,
+};
 
 pub const MIN_WINDOW_SIZE: (u32, u32) = (20, 20);
 
@@ -21,6 +32,113 @@ pub struct Config {
 
     /// The font to use for rendering.
     pub font: Font,
+
+    /// Simulates a colour vision deficiency for the whole screen, so that the
+    /// game can be checked for accessibility as it runs.  Only the final image
+    /// is changed; the colours seen by the game are not.
+    pub colour_vision: ColourVision,
+
+    /// The id of the canvas element to draw to when running in a web page.
+    /// If not set, a new canvas is added to the end of the page's body.  This
+    /// is ignored on other platforms.
+    pub canvas_id: Option<String>,
+
+    /// A GPU context to draw with, so that the GPU can be shared with another
+    /// renderer in the same process.  If not set, Mage creates its own.
+    pub gpu: Option<GpuContext>,
+
+    /// Waits for the display's vertical sync before showing each frame,
+    /// which stops tearing but limits the frame rate to the display's.  The
+    /// `MAGE_NO_VSYNC` environment variable overrides this.
+    pub vsync: bool,
+
+    /// An exact present mode to use, instead of choosing one from `vsync`.
+    /// Running fails with [`MageError::UnsupportedPresentMode`] if the
+    /// surface doesn't support it.
+    ///
+    /// [`MageError::UnsupportedPresentMode`]: error/enum.MageError.html#variant.UnsupportedPresentMode
+    pub present_mode: Option<PresentMode>,
+
+    /// Uploads and draws each frame on a thread of its own, so that the
+    /// window keeps handling events while an expensive frame is drawn.  If
+    /// the GPU is still drawing the last frame when the app presents a new
+    /// one, the new one is drawn once it has finished.  This is ignored in a
+    /// web page, which has only one thread.
+    pub render_thread: bool,
+
+    /// Keeps a third screen image with [`render_thread`], so that a finished
+    /// frame can wait for the render thread while the app presents the next.
+    /// The render thread always draws the newest finished frame, dropping
+    /// any older one that it didn't get to, so the app never waits for the
+    /// GPU.  This uses more memory and is ignored without a render thread.
+    ///
+    /// [`render_thread`]: struct.Config.html#structfield.render_thread
+    pub triple_buffering: bool,
+
+    /// How the game loop catches up when frames take too long.  By default,
+    /// each frame is ticked once with however long it took and is always
+    /// presented.
+    pub frame_policy: FramePolicy,
+
+    /// How long the engine's animation clock takes to go round once, which
+    /// is how often things drawn from it blink or pulse.  See the
+    /// [`animation`] module.  By default, it is one second.
+    ///
+    /// [`animation`]: animation/index.html
+    pub blink_period: Duration,
+
+    /// Lets players type with an input method, for languages such as
+    /// Chinese and Japanese.  While on, some keys may go to the input method
+    /// instead of the app.  See [`TickInput::composition`].
+    ///
+    /// [`TickInput::composition`]: struct.TickInput.html#structfield.composition
+    pub ime: bool,
+
+    /// The key that quits the game, or `None` to leave quitting to the app.
+    /// By default, it is Escape.
+    pub quit_key: Option<KeyBinding>,
+
+    /// The key that switches the window between fullscreen and windowed, or
+    /// `None` to leave it to the app.  By default, it is Alt+Enter.
+    pub fullscreen_key: Option<KeyBinding>,
+
+    /// The key that switches selecting an area of the screen with the mouse
+    /// on and off, or `None` to not allow selecting.
+    ///
+    /// While selecting is on, dragging with the left mouse button selects a
+    /// rectangle of chars, which is shown inverted, and releasing the button
+    /// copies its text to the clipboard and passes it to
+    /// [`App::selection_copied`].  The left button is
+    /// kept from the app until the key is pressed again.  The
+    /// [`quit_key`] and [`fullscreen_key`] can't be used.
+    ///
+    /// [`App::selection_copied`]: trait.App.html#method.selection_copied
+    /// [`quit_key`]: struct.Config.html#structfield.quit_key
+    /// [`fullscreen_key`]: struct.Config.html#structfield.fullscreen_key
+    pub selection_key: Option<KeyCode>,
+
+    /// How many screen pixels wide and high to draw each pixel of the font
+    /// when the game starts, between 1 and [`MAX_ZOOM`].  See the [`zoom`]
+    /// module.
+    ///
+    /// [`MAX_ZOOM`]: zoom/constant.MAX_ZOOM.html
+    /// [`zoom`]: zoom/index.html
+    pub zoom: u32,
+
+    /// Lets players zoom the font with Ctrl+= and Ctrl+-, and go back to
+    /// [`zoom`] with Ctrl+0.
+    ///
+    /// [`zoom`]: struct.Config.html#structfield.zoom
+    pub zoom_keys: bool,
+
+    /// Receives every frame drawn as a GPU texture.
+    #[cfg(feature = "frame-texture")]
+    pub frame_sink: Option<Box<dyn FrameSink>>,
+
+    /// The app passed to `android_main` when running on Android, which the
+    /// window is attached to.  This must be set on Android.
+    #[cfg(target_os = "android")]
+    pub android_app: Option<AndroidApp>,
 }
 
 impl Default for Config {
@@ -29,6 +147,104 @@ impl Default for Config {
             title: None,
             inner_size: (800, 600),
             font: Font::Default,
+            colour_vision: ColourVision::Normal,
+            canvas_id: None,
+            gpu: None,
+            vsync: false,
+            render_thread: false,
+            triple_buffering: false,
+            frame_policy: FramePolicy::default(),
+            blink_period: Duration::milliseconds(DEFAULT_BLINK_PERIOD_MS),
+            ime: false,
+            quit_key: Some(KeyBinding::new(KeyCode::Escape)),
+            fullscreen_key: Some(KeyBinding::new(KeyCode::Enter).with_alt()),
+            selection_key: None,
+            zoom: 1,
+            zoom_keys: true,
+            present_mode: None,
+            #[cfg(feature = "frame-texture")]
+            frame_sink: None,
+            #[cfg(target_os = "android")]
+            android_app: None,
+        }
+    }
+}
+
+/// Controls how the game loop catches up when a frame takes longer than a
+/// tick should, such as when the GPU stalls or the window is dragged.
+///
+/// A frame that took longer than [`max_tick`] is split into several ticks of
+/// equal length, so that game logic never has to handle a large time step.
+/// While frames are taking that long, presenting and drawing can be skipped
+/// for a few frames in a row to give the ticks more time.
+///
+/// [`max_tick`]: struct.FramePolicy.html#structfield.max_tick
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FramePolicy {
+    /// The longest time passed to a single tick, or `None` to tick each
+    /// frame once with however long it took.  A frame longer than this is
+    /// behind.
+    pub max_tick: Option<Duration>,
+
+    /// The most ticks run for a single frame that is behind.  Any time left
+    /// over once they have run is dropped, so the game slows down rather
+    /// than spending ever longer catching up.
+    pub max_catch_up_ticks: u32,
+
+    /// The most frames in a row that skip presenting and drawing while
+    /// behind.  Zero presents every frame.
+    pub max_frame_skip: u32,
+}
+
+impl Default for FramePolicy {
+    fn default() -> Self {
+        Self {
+            max_tick: None,
+            max_catch_up_ticks: 1,
+            max_frame_skip: 0,
+        }
+    }
+}
+
+impl FramePolicy {
+    /// Returns true if a frame that took the given time is behind.
+    pub fn is_behind(&self, dt: Duration) -> bool {
+        self.max_tick.is_some_and(|max_tick| dt > max_tick)
+    }
+
+    /// Splits the time a frame took into ticks.
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - The time the frame took.
+    ///
+    /// # Returns
+    ///
+    /// The time to pass to each tick and the number of ticks to run, which
+    /// is at least one.
+    ///
+    pub fn ticks(&self, dt: Duration) -> (Duration, u32) {
+        let Some(max_tick) = self
+            .max_tick
+            .filter(|max_tick| *max_tick > Duration::zero())
+        else {
+            return (dt, 1);
+        };
+        if dt <= max_tick {
+            return (dt, 1);
+        }
+
+        let max_ticks = self.max_catch_up_ticks.max(1);
+        let (dt, max_tick) = match (dt.num_microseconds(), max_tick.num_microseconds()) {
+            (Some(dt), Some(max_tick)) => (dt, max_tick),
+            _ => return (max_tick, max_ticks),
+        };
+        let ticks = dt / max_tick + i64::from(dt % max_tick != 0);
+        if ticks > max_ticks as i64 {
+            (Duration::microseconds(max_tick), max_ticks)
+        } else {
+            (Duration::microseconds(dt / ticks), ticks as u32)
         }
     }
 }
@@ -51,6 +267,7 @@ pub enum Font {
 ///
 /// [`FontData`]: struct.FontData.html
 ///
+#[derive(Clone)]
 pub struct FontData {
     /// The RGBA data of the font.
     pub data: Vec<u32>,
@@ -62,6 +279,78 @@ pub struct FontData {
     pub char_height: u32,
 }
 
+impl FontData {
+    /// Creates font data from the pixels of a font image, checking that they
+    /// make up a font.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The RGBA pixels of the image, row by row.  The image is a
+    ///   16x16 grid of characters, so it is `16 * char_width` pixels wide and
+    ///   `16 * char_height` pixels high.
+    /// * `char_width` - The width of each character in pixels.
+    /// * `char_height` - The height of each character in pixels.
+    ///
+    /// # Returns
+    ///
+    /// The font data, or an error if it isn't valid, as described in
+    /// [`validate`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mage_core::FontData;
+    ///
+    /// // 16x16 characters of 8x8 pixels each.
+    /// assert!(FontData::new(vec![0; 128 * 128], 8, 8).is_ok());
+    /// assert!(FontData::new(vec![0; 100], 8, 8).is_err());
+    /// ```
+    ///
+    /// [`validate`]: struct.FontData.html#method.validate
+    ///
+    pub fn new(data: Vec<u32>, char_width: u32, char_height: u32) -> Result<Self, MageError> {
+        let font = Self {
+            data,
+            char_width,
+            char_height,
+        };
+        font.validate()?;
+        Ok(font)
+    }
+
+    /// Checks that the font data can be used to draw with.  This is done
+    /// before running, but can be used to check a font when it is loaded.
+    ///
+    /// # Returns
+    ///
+    /// [`MageError::InvalidFontSize`] if the characters have no width or
+    /// height, or [`MageError::InvalidFontImage`] if the data isn't exactly
+    /// a 16x16 grid of characters.
+    ///
+    /// [`MageError::InvalidFontSize`]: error/enum.MageError.html#variant.InvalidFontSize
+    /// [`MageError::InvalidFontImage`]: error/enum.MageError.html#variant.InvalidFontImage
+    ///
+    pub fn validate(&self) -> Result<(), MageError> {
+        if self.char_width == 0 || self.char_height == 0 {
+            return Err(MageError::InvalidFontSize {
+                width: self.char_width,
+                height: self.char_height,
+            });
+        }
+        let expected = (16 * self.char_width as u64) * (16 * self.char_height as u64);
+        if self.data.len() as u64 != expected {
+            return Err(MageError::InvalidFontImage(format!(
+                "a 16x16 grid of {}x{} pixel characters needs {} pixels, but the font has {}",
+                self.char_width,
+                self.char_height,
+                expected,
+                self.data.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
 pub fn load_font_image(data: &[u8]) -> Result<FontData, MageError> {
     let font_image = load_from_memory(data)?;
     let dimensions = font_image.dimensions();
@@ -70,17 +359,12 @@ pub fn load_font_image(data: &[u8]) -> Result<FontData, MageError> {
     let data_u32: &[u32] = cast_slice(font_data);
     let char_width = dimensions.0 / 16;
     let char_height = dimensions.1 / 16;
-    if char_width == 0
-        || char_height == 0
-        || char_width * 16 != dimensions.0
-        || char_height * 16 != dimensions.1
-    {
-        return Err(MageError::InvalidFontImage);
+    if char_width * 16 != dimensions.0 || char_height * 16 != dimensions.1 {
+        return Err(MageError::InvalidFontImage(format!(
+            "the image is {}x{} pixels, which isn't a 16x16 grid of whole characters",
+            dimensions.0, dimensions.1
+        )));
     }
 
-    Ok(FontData {
-        data: data_u32.to_vec(),
-        char_width,
-        char_height,
-    })
+    FontData::new(data_u32.to_vec(), char_width, char_height)
 }
@@ -0,0 +1,119 @@
+//! Controlling the window while the game runs.
+//!
+//! Each tick is given a [`MageContext`] in [`TickInput::context`], through
+//! which the app can change the window's title, switch it to fullscreen,
+//! resize it to a number of chars, hide the mouse pointer and quit.  The
+//! changes are made once the tick has finished, in the order they were
+//! asked for.
+//!
+//! ```
+//! use mage_core::{TickInput, TickResult};
+//!
+//! fn start_level(tick_input: &TickInput, level: u32) -> TickResult {
+//!     let context = tick_input.context;
+//!     context.set_title(format!("Dungeon - level {}", level));
+//!     context.set_cursor_visible(false);
+//!     if level == 1 {
+//!         context.resize_cells(80, 25);
+//!     }
+//!     TickResult::Continue
+//! }
+//! ```
+//!
+//! [`MageContext`]: struct.MageContext.html
+//! [`TickInput::context`]: ../struct.TickInput.html#structfield.context
+
+use std::cell::RefCell;
+
+/// A handle for changing the window from within a tick.
+///
+/// Changes are queued and made once the tick has finished.  In a web page,
+/// the title is the page's and resizing resizes the canvas.  Headless runs
+/// have no window, so only resizing the screen and quitting do anything.
+///
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MageContext {
+    /// The changes asked for during the tick, in order.
+    requests: RefCell<Vec<ContextRequest>>,
+}
+
+/// A change to the window asked for through a [`MageContext`].
+///
+/// [`MageContext`]: struct.MageContext.html
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ContextRequest {
+    /// Sets the title of the window.
+    SetTitle(String),
+
+    /// Switches the window between fullscreen and windowed.
+    ToggleFullscreen,
+
+    /// Resizes the window to hold a number of chars.
+    ResizeCells(u32, u32),
+
+    /// Shows or hides the mouse pointer over the window.
+    SetCursorVisible(bool),
+
+    /// Zooms the font.
+    SetZoom(u32),
+
+    /// Quits the game.
+    Quit,
+}
+
+impl MageContext {
+    /// Sets the title of the window.
+    pub fn set_title(&self, title: impl Into<String>) {
+        self.push(ContextRequest::SetTitle(title.into()));
+    }
+
+    /// Switches the window between fullscreen and windowed, as the
+    /// [`Config::fullscreen_key`] does.
+    ///
+    /// [`Config::fullscreen_key`]: ../struct.Config.html#structfield.fullscreen_key
+    ///
+    pub fn toggle_fullscreen(&self) {
+        self.push(ContextRequest::ToggleFullscreen);
+    }
+
+    /// Resizes the window to hold a number of chars at the current zoom.
+    /// The size is at least 1x1, and the window may be kept from growing
+    /// larger than the screen or shrinking below its smallest size.  The app
+    /// sees the size the window ends up as on a later tick.
+    pub fn resize_cells(&self, width: u32, height: u32) {
+        self.push(ContextRequest::ResizeCells(width.max(1), height.max(1)));
+    }
+
+    /// Shows or hides the mouse pointer while it is over the window.  The
+    /// mouse is still tracked while it is hidden.
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.push(ContextRequest::SetCursorVisible(visible));
+    }
+
+    /// Zooms the font.  See [`zoom::set_zoom`].
+    ///
+    /// [`zoom::set_zoom`]: ../zoom/fn.set_zoom.html
+    ///
+    pub fn set_zoom(&self, zoom: u32) {
+        self.push(ContextRequest::SetZoom(zoom));
+    }
+
+    /// Quits the game once the tick has finished, as returning
+    /// [`TickResult::Quit`] does.
+    ///
+    /// [`TickResult::Quit`]: ../enum.TickResult.html#variant.Quit
+    ///
+    pub fn quit(&self) {
+        self.push(ContextRequest::Quit);
+    }
+
+    /// Returns the changes asked for since this was last called, in order.
+    pub(crate) fn take_requests(&self) -> Vec<ContextRequest> {
+        self.requests.take()
+    }
+
+    fn push(&self, request: ContextRequest) {
+        self.requests.borrow_mut().push(request);
+    }
+}
@@ -0,0 +1,557 @@
+//! Animated effects drawn with coloured cells, such as fire and plasma.
+//!
+//! Each effect is advanced and drawn into an area of an image every tick with
+//! [`Effect::render`].  The colours come from a [`Gradient`] palette, and the
+//! speed can be changed, so the same effect can be a roaring blaze or a
+//! gentle glow behind a title screen:
+//!
+//! ```
+//! use chrono::Duration;
+//! use mage_core::{
+//!     effects::{Effect, Fire},
+//!     image::{Image, Rect},
+//! };
+//!
+//! let mut fire = Fire::new().with_speed(2.0);
+//! let mut image = Image::new(80, 25);
+//! fire.render(&mut image, Rect::new(0, 15, 80, 10), Duration::milliseconds(16));
+//! ```
+//!
+//! [`Effect::render`]: trait.Effect.html#tymethod.render
+//! [`Gradient`]: ../gradient/struct.Gradient.html
+
+use chrono::Duration;
+
+use crate::{
+    colour::Colour,
+    gradient::{Gradient, Interpolation},
+    image::{Char, Image, Point, Rect},
+};
+
+/// The number of times a second the simulated effects are stepped at normal
+/// speed.
+const STEPS_PER_SECOND: f32 = 30.0;
+
+/// The glyphs of stars from the furthest to the nearest, `.·+*☼`.
+const STAR_GLYPHS: [u8; 5] = [b'.', 0xfa, b'+', b'*', 0x0f];
+
+/// An animated effect that draws into an area of an image.
+pub trait Effect {
+    /// Advances the effect by the time since the last tick and draws it into
+    /// an area of an image, covering every cell of the area.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image to draw on.
+    /// * `rect` - The area of the image to fill.  If it changes size, the
+    ///   effect starts again at the new size.
+    /// * `dt` - The time since the last tick.
+    ///
+    fn render(&mut self, image: &mut Image, rect: Rect, dt: Duration);
+}
+
+/// Flames rising from the bottom of the area, in the style of the fire in
+/// old demos.
+#[derive(Clone, Debug)]
+pub struct Fire {
+    palette: Gradient,
+    speed: f32,
+    rng: Rng,
+
+    /// The time not yet used by a step, in seconds.
+    pending: f32,
+
+    /// The size of the area the heat is for.
+    size: (u32, u32),
+
+    /// The heat of each cell, row by row, from 0.0 to 1.0.
+    heat: Vec<f32>,
+}
+
+impl Fire {
+    /// Creates a new fire that burns from black through red and yellow to
+    /// white.
+    pub fn new() -> Self {
+        Self {
+            palette: Gradient::from_colours(
+                &[
+                    Colour::Rgb(0, 0, 0),
+                    Colour::Rgb(128, 0, 0),
+                    Colour::Rgb(224, 48, 0),
+                    Colour::Rgb(255, 160, 0),
+                    Colour::Rgb(255, 240, 96),
+                    Colour::Rgb(255, 255, 255),
+                ],
+                Interpolation::Linear,
+            ),
+            speed: 1.0,
+            rng: Rng::new(0x5eed),
+            pending: 0.0,
+            size: (0, 0),
+            heat: Vec::new(),
+        }
+    }
+
+    /// Sets the colours of the fire, from the coldest at 0.0 to the hottest
+    /// at 1.0.
+    pub fn with_palette(mut self, palette: Gradient) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Sets how fast the flames move, where 1.0 is normal speed.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    /// Sets the seed of the random flicker of the flames.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    /// Moves the heat up a row, cooling it and blowing it sideways at
+    /// random.  The bottom row is kept at full heat.
+    fn step(&mut self) {
+        let (width, height) = (self.size.0 as usize, self.size.1 as usize);
+        if width == 0 || height == 0 {
+            return;
+        }
+        // Taller fires cool more slowly, so the flames reach the top third.
+        let cooling = 3.0 / height as f32;
+        self.heat[(height - 1) * width..].fill(1.0);
+        for y in 0..height - 1 {
+            for x in 0..width {
+                let drift = self.rng.below(3) as isize - 1;
+                let from = (x as isize + drift).clamp(0, width as isize - 1) as usize;
+                let heat = self.heat[(y + 1) * width + from];
+                let decay = self.rng.next_f32() * cooling;
+                self.heat[y * width + x] = (heat - decay).max(0.0);
+            }
+        }
+    }
+}
+
+impl Default for Fire {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for Fire {
+    fn render(&mut self, image: &mut Image, rect: Rect, dt: Duration) {
+        if self.size != (rect.width, rect.height) {
+            self.size = (rect.width, rect.height);
+            self.heat = vec![0.0; (rect.width * rect.height) as usize];
+        }
+        for _ in 0..take_steps(&mut self.pending, dt, self.speed) {
+            self.step();
+        }
+        let width = rect.width as usize;
+        draw_cells(image, rect, |x, y| self.heat[y * width + x], &self.palette);
+    }
+}
+
+/// Ripples spreading across water from drops falling at random.
+#[derive(Clone, Debug)]
+pub struct Water {
+    palette: Gradient,
+    speed: f32,
+    rng: Rng,
+
+    /// The chance of a drop falling at each step.
+    rain: f32,
+
+    /// The time not yet used by a step, in seconds.
+    pending: f32,
+
+    /// The size of the area the heights are for.
+    size: (u32, u32),
+
+    /// The height of the water in each cell, row by row, at this step and the
+    /// last.
+    heights: [Vec<f32>; 2],
+}
+
+impl Water {
+    /// Creates a new pool of water in shades of blue.
+    pub fn new() -> Self {
+        Self {
+            palette: Gradient::from_colours(
+                &[
+                    Colour::Rgb(0, 0, 64),
+                    Colour::Rgb(0, 32, 128),
+                    Colour::Rgb(0, 96, 192),
+                    Colour::Rgb(64, 192, 255),
+                    Colour::Rgb(224, 255, 255),
+                ],
+                Interpolation::Linear,
+            ),
+            speed: 1.0,
+            rng: Rng::new(0x5eed),
+            rain: 0.2,
+            pending: 0.0,
+            size: (0, 0),
+            heights: [Vec::new(), Vec::new()],
+        }
+    }
+
+    /// Sets the colours of the water, from the troughs of the ripples at 0.0
+    /// to their crests at 1.0.  Still water is at 0.5.
+    pub fn with_palette(mut self, palette: Gradient) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Sets how fast the ripples spread, where 1.0 is normal speed.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    /// Sets the seed of where and when the drops fall.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    /// Sets the chance of a drop falling at each step, from 0.0 for still
+    /// water to 1.0 for heavy rain.  The default is 0.2.
+    pub fn with_rain(mut self, rain: f32) -> Self {
+        self.rain = rain.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Makes a ripple at a point in the area, such as where the player
+    /// clicked.
+    pub fn drop_at(&mut self, p: Point) {
+        let (width, height) = self.size;
+        if p.x >= 0 && p.y >= 0 && (p.x as u32) < width && (p.y as u32) < height {
+            self.heights[0][(p.y as u32 * width + p.x as u32) as usize] = 1.0;
+        }
+    }
+
+    /// Spreads the ripples by a step: each cell moves towards the average of
+    /// its neighbours, overshooting so the ripples carry on outwards.
+    fn step(&mut self) {
+        let (width, height) = (self.size.0 as usize, self.size.1 as usize);
+        if width < 3 || height < 3 {
+            return;
+        }
+        if self.rng.next_f32() < self.rain {
+            let x = 1 + self.rng.below(width as u32 - 2) as usize;
+            let y = 1 + self.rng.below(height as u32 - 2) as usize;
+            self.heights[0][y * width + x] = 1.0;
+        }
+        let [current, previous] = &mut self.heights;
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let i = y * width + x;
+                let neighbours =
+                    current[i - 1] + current[i + 1] + current[i - width] + current[i + width];
+                previous[i] = (neighbours / 2.0 - previous[i]) * 0.96;
+            }
+        }
+        self.heights.swap(0, 1);
+    }
+}
+
+impl Default for Water {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for Water {
+    fn render(&mut self, image: &mut Image, rect: Rect, dt: Duration) {
+        if self.size != (rect.width, rect.height) {
+            self.size = (rect.width, rect.height);
+            let cells = (rect.width * rect.height) as usize;
+            self.heights = [vec![0.0; cells], vec![0.0; cells]];
+        }
+        for _ in 0..take_steps(&mut self.pending, dt, self.speed) {
+            self.step();
+        }
+        let width = rect.width as usize;
+        let heights = &self.heights[0];
+        draw_cells(
+            image,
+            rect,
+            |x, y| 0.5 + heights[y * width + x] / 2.0,
+            &self.palette,
+        );
+    }
+}
+
+/// Stars flying out from the centre of the area, as if travelling through
+/// space.
+#[derive(Clone, Debug)]
+pub struct Starfield {
+    palette: Gradient,
+    speed: f32,
+    rng: Rng,
+
+    /// The colour of the space between the stars.
+    background: Colour,
+
+    /// The position of each star, with x and y from -1.0 to 1.0 and its
+    /// distance from 0.0 (nearest) to 1.0 (furthest).
+    stars: Vec<(f32, f32, f32)>,
+}
+
+impl Starfield {
+    /// Creates a new starfield with 100 stars, growing from dark grey to
+    /// white as they get nearer, on black.
+    pub fn new() -> Self {
+        let mut starfield = Self {
+            palette: Gradient::from_colours(
+                &[Colour::DarkGray, Colour::LightGray, Colour::White],
+                Interpolation::Linear,
+            ),
+            speed: 1.0,
+            rng: Rng::new(0x5eed),
+            background: Colour::Black,
+            stars: Vec::new(),
+        };
+        starfield.set_count(100);
+        starfield
+    }
+
+    /// Sets the colours of the stars, from the furthest at 0.0 to the nearest
+    /// at 1.0.
+    pub fn with_palette(mut self, palette: Gradient) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Sets how fast the stars move, where 1.0 is normal speed.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    /// Sets the seed of where the stars are.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        let count = self.stars.len();
+        self.stars.clear();
+        self.set_count(count);
+        self
+    }
+
+    /// Sets the number of stars.
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.set_count(count);
+        self
+    }
+
+    /// Sets the colour of the space between the stars.
+    pub fn with_background(mut self, background: Colour) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Adds or removes stars to make the given number.
+    fn set_count(&mut self, count: usize) {
+        self.stars.truncate(count);
+        while self.stars.len() < count {
+            let distance = self.rng.next_f32();
+            let star = self.new_star(distance);
+            self.stars.push(star);
+        }
+    }
+
+    /// Returns a star at a random position at a distance.
+    fn new_star(&mut self, distance: f32) -> (f32, f32, f32) {
+        let x = self.rng.next_f32() * 2.0 - 1.0;
+        let y = self.rng.next_f32() * 2.0 - 1.0;
+        (x, y, distance.max(0.01))
+    }
+}
+
+impl Default for Starfield {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for Starfield {
+    fn render(&mut self, image: &mut Image, rect: Rect, dt: Duration) {
+        let dt = dt.num_microseconds().unwrap_or(0).max(0) as f32 / 1_000_000.0;
+        image.draw_filled_rect(rect, Char::new(b' ', self.background, self.background));
+
+        let (half_width, half_height) = (rect.width as f32 / 2.0, rect.height as f32 / 2.0);
+        for i in 0..self.stars.len() {
+            let (x, y, distance) = self.stars[i];
+            let distance = distance - dt * self.speed * 0.5;
+            let (sx, sy) = (x / distance, y / distance);
+            // Stars that reach the viewer or leave the area start again far
+            // away.
+            if distance <= 0.01 || sx.abs() >= 1.0 || sy.abs() >= 1.0 {
+                self.stars[i] = self.new_star(1.0);
+                continue;
+            }
+            self.stars[i].2 = distance;
+
+            let nearness = 1.0 - distance;
+            let glyph = STAR_GLYPHS
+                [((nearness * STAR_GLYPHS.len() as f32) as usize).min(STAR_GLYPHS.len() - 1)];
+            let p = Point::new(
+                rect.x + (half_width + sx * half_width) as i32,
+                rect.y + (half_height + sy * half_height) as i32,
+            );
+            let ink = self.palette.sample(nearness);
+            image.draw_char(p, Char::new(glyph, ink, self.background));
+        }
+    }
+}
+
+/// Swirling bands of colour made by adding sine waves together.
+#[derive(Clone, Debug)]
+pub struct Plasma {
+    palette: Gradient,
+    speed: f32,
+
+    /// How long the plasma has been moving, in seconds at normal speed.
+    time: f32,
+
+    /// How many cells across the waves are.  Larger values give broader
+    /// bands of colour.
+    scale: f32,
+}
+
+impl Plasma {
+    /// Creates a new plasma that cycles through the colours of the rainbow.
+    pub fn new() -> Self {
+        Self {
+            palette: Gradient::from_colours(
+                &[
+                    Colour::Rgb(255, 0, 0),
+                    Colour::Rgb(255, 255, 0),
+                    Colour::Rgb(0, 255, 0),
+                    Colour::Rgb(0, 255, 255),
+                    Colour::Rgb(0, 0, 255),
+                    Colour::Rgb(255, 0, 255),
+                    Colour::Rgb(255, 0, 0),
+                ],
+                Interpolation::Linear,
+            ),
+            speed: 1.0,
+            time: 0.0,
+            scale: 8.0,
+        }
+    }
+
+    /// Sets the colours of the plasma.  Palettes that start and end with the
+    /// same colour give smooth bands without edges.
+    pub fn with_palette(mut self, palette: Gradient) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Sets how fast the plasma moves, where 1.0 is normal speed.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    /// Sets how many cells across the waves are.  The default is 8.0.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale.max(0.1);
+        self
+    }
+}
+
+impl Default for Plasma {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for Plasma {
+    fn render(&mut self, image: &mut Image, rect: Rect, dt: Duration) {
+        let dt = dt.num_microseconds().unwrap_or(0).max(0) as f32 / 1_000_000.0;
+        self.time += dt * self.speed;
+
+        let (t, scale) = (self.time, self.scale);
+        let (cx, cy) = (rect.width as f32 / 2.0, rect.height as f32 / 2.0);
+        let value = |x: usize, y: usize| {
+            // Cells are about twice as tall as they are wide.
+            let (x, y) = (x as f32 / scale, y as f32 * 2.0 / scale);
+            let (dx, dy) = (x - cx / scale, y - cy * 2.0 / scale);
+            let sum = (x + t).sin()
+                + (y + t * 1.3).sin()
+                + ((x + y) / 2.0 + t * 0.7).sin()
+                + ((dx * dx + dy * dy).sqrt() - t).sin();
+            (sum / 4.0 + 1.0) / 2.0
+        };
+        draw_cells(image, rect, value, &self.palette);
+    }
+}
+
+/// Fills an area with blank cells whose paper is sampled from a palette.
+///
+/// # Arguments
+///
+/// * `image` - The image to draw on.
+/// * `rect` - The area to fill.
+/// * `value` - Returns the position in the palette of a cell, given its
+///   coordinates within the area.
+/// * `palette` - The colours to sample.
+///
+fn draw_cells(
+    image: &mut Image,
+    rect: Rect,
+    mut value: impl FnMut(usize, usize) -> f32,
+    palette: &Gradient,
+) {
+    for y in 0..rect.height as usize {
+        for x in 0..rect.width as usize {
+            let colour = palette.sample(value(x, y).clamp(0.0, 1.0));
+            let p = Point::new(rect.x + x as i32, rect.y + y as i32);
+            image.draw_char(p, Char::new(b' ', colour, colour));
+        }
+    }
+}
+
+/// Adds time to the time not yet used by steps, and returns how many whole
+/// steps can be taken.  At most a second's worth of steps is taken, so a long
+/// pause doesn't stall the next tick.
+fn take_steps(pending: &mut f32, dt: Duration, speed: f32) -> u32 {
+    let dt = dt.num_microseconds().unwrap_or(0).max(0) as f32 / 1_000_000.0;
+    *pending += dt * speed * STEPS_PER_SECOND;
+    let steps = pending.floor();
+    *pending -= steps;
+    (steps as u32).min(STEPS_PER_SECOND as u32)
+}
+
+/// A small, fast random number generator (xorshift64*), so the effects work
+/// without the `rand` feature.
+#[derive(Clone, Debug)]
+struct Rng(u64);
+
+impl Rng {
+    /// Creates a new generator from a seed.
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    /// Returns a random number.
+    fn next_u32(&mut self) -> u32 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 32) as u32
+    }
+
+    /// Returns a random number from 0 up to but not including `n`.
+    fn below(&mut self, n: u32) -> u32 {
+        ((self.next_u32() as u64 * n as u64) >> 32) as u32
+    }
+
+    /// Returns a random number from 0.0 up to but not including 1.0.
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+}
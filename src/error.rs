@@ -1,9 +1,12 @@
+use std::io;
+
 use image::ImageError;
 use thiserror::Error;
-use wgpu::{CreateSurfaceError, RequestDeviceError};
+use wgpu::{CreateSurfaceError, PresentMode, RequestDeviceError, SurfaceError, TextureFormat};
 use winit::error::{EventLoopError, OsError};
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum MageError {
     #[error("unable to create event loop")]
     EventLoopError(#[from] EventLoopError),
@@ -11,18 +14,91 @@ pub enum MageError {
     #[error("unable to open window")]
     WindowError(#[from] OsError),
 
+    #[error("no canvas element with id \"{0}\" in the web page")]
+    CanvasNotFound(String),
+
+    #[cfg(target_os = "android")]
+    #[error("no Android app was given in the config")]
+    NoAndroidApp,
+
     #[error("unable to create rendering surface")]
     CreateSurfaceError(#[from] CreateSurfaceError),
 
+    #[error("unable to draw to the window's surface")]
+    RenderError(#[from] SurfaceError),
+
+    #[error("the render thread has stopped")]
+    RenderThreadStopped,
+
     #[error("unable to create GPU adapter")]
     BadAdapter,
 
+    #[error("no GPU adapter can draw to the window; tried: {}", .0.join("; "))]
+    NoAdapter(Vec<String>),
+
+    #[error(
+        "window size {width}x{height} is invalid; the width and height in \
+         Config::inner_size must both be at least 1 pixel"
+    )]
+    InvalidWindowSize { width: u32, height: u32 },
+
+    #[error(
+        "font characters are {width}x{height} pixels; characters must be at \
+         least 1 pixel wide and high"
+    )]
+    InvalidFontSize { width: u32, height: u32 },
+
+    #[error(
+        "present mode {requested:?} is not supported by this surface; choose \
+         one of {supported:?} in Config::present_mode, or leave it unset"
+    )]
+    UnsupportedPresentMode {
+        requested: PresentMode,
+        supported: Vec<PresentMode>,
+    },
+
+    #[error(
+        "the surface has no format that can be drawn to without sRGB \
+         conversion; its formats are {available:?}"
+    )]
+    NoSurfaceFormat { available: Vec<TextureFormat> },
+
+    #[error(
+        "{what} texture of {width}x{height} pixels is larger than the GPU's \
+         limit of {max}x{max}; use a smaller font"
+    )]
+    TextureTooLarge {
+        what: &'static str,
+        width: u32,
+        height: u32,
+        max: u32,
+    },
+
     #[error("unable to create GPU device")]
     BadDevice(#[from] RequestDeviceError),
 
     #[error("unable to load font")]
     BadFont(#[from] ImageError),
 
-    #[error("font image is invalid")]
-    InvalidFontImage,
+    #[error("font image is invalid: {0}")]
+    InvalidFontImage(String),
+
+    #[error("unable to read or write file")]
+    IoError(#[from] io::Error),
+
+    #[error("image data is invalid")]
+    InvalidImageData,
+
+    #[error("recording data is invalid")]
+    InvalidRecording,
+
+    #[error("asset is invalid: {0}")]
+    InvalidAsset(String),
+
+    #[error("invalid colour \"{0}\"")]
+    InvalidColour(String),
+
+    #[cfg(feature = "scripting")]
+    #[error("script error: {0}")]
+    ScriptError(String),
 }
@@ -0,0 +1,123 @@
+//! Exporting images as text for sharing in terminals, issue reports and
+//! documentation.
+//!
+//! Glyphs are mapped from code page 437 (the layout of the default font) to
+//! Unicode with the table in [`codepage`].  Only the lowest 8 bits of each
+//! char are used.
+//!
+//! [`codepage`]: ../codepage/index.html
+
+use std::{fmt::Write, fs, path::Path};
+
+use crate::{
+    codepage::cp437_to_unicode,
+    error::MageError,
+    image::{Image, Rect},
+    PresentInput,
+};
+
+impl Image {
+    /// Converts the image to plain UTF-8 text, one line per row.
+    pub fn to_plain_text(&self) -> String {
+        let mut text = String::with_capacity(((self.width + 1) * self.height) as usize);
+        for row in self.rows() {
+            text.extend(row.iter().map(|&ch| cp437_to_unicode(ch as u8)));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Converts an area of the image to plain UTF-8 text, one line per row.
+    /// The area is clipped to the image, and is empty if it is outside it.
+    pub fn region_to_plain_text(&self, rect: Rect) -> String {
+        let Some((rect, _)) = rect.clip_within(self.width, self.height) else {
+            return String::new();
+        };
+        let mut text = String::with_capacity(((rect.width + 1) * rect.height) as usize);
+        for row in self.rows().skip(rect.y as usize).take(rect.height as usize) {
+            let row = &row[rect.x as usize..(rect.x as u32 + rect.width) as usize];
+            text.extend(row.iter().map(|&ch| cp437_to_unicode(ch as u8)));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Converts the image to UTF-8 text with ANSI escape codes for the ink and
+    /// paper colours, one line per row.
+    ///
+    /// # Notes
+    ///
+    /// 24-bit colour escape codes are used, which are supported by most modern
+    /// terminals.  The colours are reset at the end of each line.
+    ///
+    pub fn to_ansi(&self) -> String {
+        let mut text = String::new();
+        for y in 0..self.height as usize {
+            self.write_ansi_row(&mut text, y);
+            text.push_str("\x1b[0m\n");
+        }
+        text
+    }
+
+    /// Writes a row of the image as UTF-8 text with ANSI escape codes for the
+    /// ink and paper colours, leaving the colours set at the end.
+    pub(crate) fn write_ansi_row(&self, text: &mut String, y: usize) {
+        let w = self.width as usize;
+        let mut colours = None;
+        for x in 0..w {
+            let i = y * w + x;
+            let ink = self.fore_image[i];
+            let paper = self.back_image[i];
+            if colours != Some((ink, paper)) {
+                let (ir, ig, ib) = rgb(ink);
+                let (pr, pg, pb) = rgb(paper);
+                let _ = write!(text, "\x1b[38;2;{ir};{ig};{ib};48;2;{pr};{pg};{pb}m");
+                colours = Some((ink, paper));
+            }
+            text.push(cp437_to_unicode(self.text_image[i] as u8));
+        }
+    }
+
+    /// Writes the image to a file as plain UTF-8 text.
+    ///
+    /// See [`to_plain_text`].
+    ///
+    /// [`to_plain_text`]: struct.Image.html#method.to_plain_text
+    ///
+    pub fn save_plain_text(&self, path: impl AsRef<Path>) -> Result<(), MageError> {
+        fs::write(path, self.to_plain_text())?;
+        Ok(())
+    }
+
+    /// Writes the image to a file as UTF-8 text with ANSI colour codes.
+    ///
+    /// See [`to_ansi`].
+    ///
+    /// [`to_ansi`]: struct.Image.html#method.to_ansi
+    ///
+    pub fn save_ansi(&self, path: impl AsRef<Path>) -> Result<(), MageError> {
+        fs::write(path, self.to_ansi())?;
+        Ok(())
+    }
+
+    /// Returns an iterator over the rows of the text plane.
+    fn rows(&self) -> impl Iterator<Item = &[u32]> {
+        (0..self.height as usize).map(|y| {
+            let w = self.width as usize;
+            &self.text_image[y * w..(y + 1) * w]
+        })
+    }
+}
+
+impl<'t> PresentInput<'t> {
+    /// Copies the current contents of the screen into a new image, for example
+    /// to export it as text.
+    pub fn to_image(&self) -> Image {
+        self.screen.clone()
+    }
+}
+
+/// Unpacks a colour into its red, green and blue components.
+fn rgb(colour: u32) -> (u8, u8, u8) {
+    (colour as u8, (colour >> 8) as u8, (colour >> 16) as u8)
+}
@@ -0,0 +1,255 @@
+//! Working out which cells can be seen from a point.
+
+use crate::{image::Point, mask::Mask};
+
+/// Works out which cells of a map can be seen from a point, using symmetric
+/// shadowcasting.
+///
+/// Light spreads out from the origin until it is blocked by opaque cells,
+/// such as walls.  Opaque cells that are lit are visible, so the walls around
+/// a room can be seen from inside it.  The results are symmetric: if a floor
+/// cell can be seen from another, the other can be seen from it.
+///
+/// The mask returned can be used with [`Image::blit_masked`] to draw only the
+/// visible part of a map, or to tint the cells outside it with
+/// [`Image::blit_tint`] to show fog-of-war.
+///
+/// ```
+/// use mage_core::{fov::field_of_view, image::Point};
+///
+/// let map = [
+///     "#######",
+///     "#.....#",
+///     "#.#####",
+///     "#.....#",
+///     "#######",
+/// ];
+/// let is_opaque = |p: Point| map[p.y as usize].as_bytes()[p.x as usize] == b'#';
+/// let visible = field_of_view(7, 5, Point::new(1, 1), None, is_opaque);
+///
+/// assert!(visible.get(Point::new(5, 1)));
+/// assert!(visible.get(Point::new(1, 3)));
+/// assert!(!visible.get(Point::new(5, 3)));
+/// ```
+///
+/// # Arguments
+///
+/// * `width` - The width of the map in cells.
+/// * `height` - The height of the map in cells.
+/// * `origin` - The cell being seen from.  It is always visible if it is
+///   within the map.
+/// * `radius` - How far can be seen, or `None` for no limit.  Cells are
+///   visible if they are within a circle of this radius.
+/// * `is_opaque` - Returns true if a cell blocks the view.  It is only called
+///   for cells within the map; cells outside it block the view.
+///
+/// # Returns
+///
+/// A mask the size of the map with the visible cells set.
+///
+/// [`Image::blit_masked`]: ../image/struct.Image.html#method.blit_masked
+/// [`Image::blit_tint`]: ../image/struct.Image.html#method.blit_tint
+///
+pub fn field_of_view(
+    width: u32,
+    height: u32,
+    origin: Point,
+    radius: Option<u32>,
+    mut is_opaque: impl FnMut(Point) -> bool,
+) -> Mask {
+    let mut fov = Fov {
+        visible: Mask::new(width, height),
+        origin,
+        radius: radius.map(|radius| {
+            let radius = radius as i64;
+            radius.saturating_mul(radius).saturating_add(radius)
+        }),
+        is_opaque: &mut is_opaque,
+    };
+    fov.visible.set(origin, true);
+    for quadrant in [
+        Quadrant::North,
+        Quadrant::East,
+        Quadrant::South,
+        Quadrant::West,
+    ] {
+        let row = Row {
+            depth: 1,
+            start: (-1, 1),
+            end: (1, 1),
+        };
+        fov.scan(quadrant, row);
+    }
+    fov.visible
+}
+
+/// A quarter of the view around the origin, each looking along one of the
+/// axes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Quadrant {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// A row of cells within a quadrant, at a distance from the origin, between
+/// two slopes.  Slopes are fractions, stored as a numerator and a positive
+/// denominator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Row {
+    depth: i64,
+    start: (i64, i64),
+    end: (i64, i64),
+}
+
+impl Row {
+    /// Returns the columns of the cells in the row, rounding the start slope
+    /// up and the end slope down at half cells.
+    fn columns(&self) -> std::ops::RangeInclusive<i64> {
+        let (start, end) = (self.start, self.end);
+        let min = (2 * self.depth * start.0 + start.1).div_euclid(2 * start.1);
+        let max = -(-(2 * self.depth * end.0 - end.1)).div_euclid(2 * end.1);
+        min..=max
+    }
+
+    /// Returns the next row out from the origin between the same slopes.
+    fn next(&self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+
+    /// Returns true if a floor cell in the row is visible, which it is if its
+    /// centre is between the slopes.
+    fn is_symmetric(&self, column: i64) -> bool {
+        column * self.start.1 >= self.depth * self.start.0
+            && column * self.end.1 <= self.depth * self.end.0
+    }
+}
+
+/// The state of a field of view being worked out.
+struct Fov<'a> {
+    visible: Mask,
+    origin: Point,
+
+    /// The square of the radius, plus the radius to round the circle, or
+    /// `None` for no limit.
+    radius: Option<i64>,
+
+    is_opaque: &'a mut dyn FnMut(Point) -> bool,
+}
+
+impl Fov<'_> {
+    /// Lights the cells of a row and the rows beyond it that can be seen
+    /// through it.
+    ///
+    /// The rows still to be scanned are kept on a stack rather than scanned
+    /// recursively, as a long open map has a row for every cell along it.
+    fn scan(&mut self, quadrant: Quadrant, first: Row) {
+        let mut rows = vec![first];
+        while let Some(mut row) = rows.pop() {
+            if self
+                .radius
+                .is_some_and(|radius| row.depth * row.depth > radius)
+            {
+                continue;
+            }
+
+            let mut previous = None;
+            for column in row.columns() {
+                let p = self.transform(quadrant, row.depth, column);
+                let opaque = self.is_opaque(p);
+                let in_radius = self
+                    .radius
+                    .is_none_or(|radius| row.depth * row.depth + column * column <= radius);
+                if in_radius && (opaque || row.is_symmetric(column)) {
+                    self.visible.set(p, true);
+                }
+                // Slope of the edge of the cell nearest the start of the row.
+                let slope = (2 * column - 1, 2 * row.depth);
+                match previous {
+                    Some(true) if !opaque => row.start = slope,
+                    Some(false) if opaque => {
+                        let mut next = row.next();
+                        next.end = slope;
+                        rows.push(next);
+                    }
+                    _ => {}
+                }
+                previous = Some(opaque);
+            }
+            if previous == Some(false) {
+                rows.push(row.next());
+            }
+        }
+    }
+
+    /// Returns true if a cell blocks the view.  Cells outside the map do.
+    fn is_opaque(&mut self, p: Point) -> bool {
+        let (width, height) = (self.visible.width(), self.visible.height());
+        let inside = p.x >= 0 && p.y >= 0 && (p.x as u32) < width && (p.y as u32) < height;
+        !inside || (self.is_opaque)(p)
+    }
+
+    /// Converts a row and column within a quadrant to map coordinates.
+    fn transform(&self, quadrant: Quadrant, depth: i64, column: i64) -> Point {
+        let (depth, column) = (depth as i32, column as i32);
+        let Point { x, y } = self.origin;
+        match quadrant {
+            Quadrant::North => Point::new(x + column, y - depth),
+            Quadrant::South => Point::new(x + column, y + depth),
+            Quadrant::East => Point::new(x + depth, y + column),
+            Quadrant::West => Point::new(x - depth, y + column),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the field of view of a map drawn with `#` for walls.
+    fn fov_of(map: &[&str], origin: Point, radius: Option<u32>) -> Mask {
+        let is_opaque = |p: Point| map[p.y as usize].as_bytes()[p.x as usize] == b'#';
+        field_of_view(
+            map[0].len() as u32,
+            map.len() as u32,
+            origin,
+            radius,
+            is_opaque,
+        )
+    }
+
+    #[test]
+    fn huge_radius_is_unlimited() {
+        let map = [
+            "#########",
+            "#.......#",
+            "#..#....#",
+            "#.......#",
+            "#########",
+        ];
+        let origin = Point::new(1, 1);
+        assert_eq!(
+            fov_of(&map, origin, Some(u32::MAX)),
+            fov_of(&map, origin, None)
+        );
+    }
+
+    #[test]
+    fn radius_limits_the_view() {
+        let map = ["...........", "...........", "..........."];
+        let visible = fov_of(&map, Point::new(0, 1), Some(3));
+        assert!(visible.get(Point::new(3, 1)));
+        assert!(!visible.get(Point::new(4, 1)));
+    }
+
+    #[test]
+    fn long_open_map_does_not_overflow_the_stack() {
+        let width = 200_000;
+        let visible = field_of_view(width, 1, Point::new(0, 0), None, |_| false);
+        assert!(visible.get(Point::new(width as i32 - 1, 0)));
+    }
+}
@@ -0,0 +1,173 @@
+//! Handing each frame to the app as a GPU texture.
+//!
+//! With the `frame-texture` feature, set a [`FrameSink`] as
+//! [`Config::frame_sink`] and Mage copies every finished frame on the GPU
+//! into a texture that is handed to the sink once the frame has been
+//! submitted.  The app can then use the frame on the GPU without capturing
+//! the window, such as by recording it or drawing it elsewhere.
+//!
+//! Mage doesn't send frames to other programs.  To share them with capture
+//! tools such as Spout or Syphon, a sink can get the native texture of the
+//! backend in use from wgpu with `Texture::as_hal` and pass it to a sender.
+//!
+//! # Example
+//!
+//! ```
+//! use mage_core::{
+//!     frame_texture::{FrameSink, SharedFrame},
+//!     Config,
+//! };
+//!
+//! struct Sender {
+//!     frames: u64,
+//! }
+//!
+//! impl FrameSink for Sender {
+//!     fn publish(&mut self, frame: SharedFrame) {
+//!         // Use `frame.texture` here.
+//!         self.frames += 1;
+//!     }
+//! }
+//!
+//! let config = Config {
+//!     frame_sink: Some(Box::new(Sender { frames: 0 })),
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! [`FrameSink`]: trait.FrameSink.html
+//! [`Config::frame_sink`]: ../struct.Config.html#structfield.frame_sink
+
+use wgpu::{
+    CommandEncoder, Device, Extent3d, Queue, Texture, TextureDescriptor, TextureDimension,
+    TextureFormat, TextureUsages, TextureView, TextureViewDescriptor,
+};
+
+/// Receives every frame drawn, as a GPU texture.  Sinks must be `Send`, as
+/// frames are published from the render thread when [`Config::render_thread`]
+/// is set.
+///
+/// [`Config::render_thread`]: ../struct.Config.html#structfield.render_thread
+///
+pub trait FrameSink: Send {
+    /// Called after each frame has been submitted to the GPU.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The texture that the frame was drawn to, and the device
+    ///   that owns it.
+    ///
+    fn publish(&mut self, frame: SharedFrame);
+}
+
+/// A frame drawn into a texture that is handed to the app.
+pub struct SharedFrame<'a> {
+    /// The device that owns the texture.
+    pub device: &'a Device,
+
+    /// The queue that the frame was submitted to.
+    pub queue: &'a Queue,
+
+    /// The texture holding the frame.  It can be copied from, bound in a
+    /// shader or drawn to, and is replaced whenever the window is resized.
+    pub texture: &'a Texture,
+
+    /// The width of the frame in pixels.
+    pub width: u32,
+
+    /// The height of the frame in pixels.
+    pub height: u32,
+}
+
+/// The sink that frames are published to, and the texture they are drawn
+/// into for it.
+pub(crate) struct SharedOutput {
+    sink: Box<dyn FrameSink>,
+    texture: Texture,
+    pub(crate) view: TextureView,
+}
+
+impl SharedOutput {
+    /// Creates the texture that frames are drawn into.
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - The sink to publish frames to.
+    /// * `device` - The device to create the texture with.
+    /// * `format` - The format of the window's surface, which the render
+    ///   pipeline draws in.
+    /// * `size` - The size of the window's surface in pixels.
+    ///
+    pub(crate) fn new(
+        sink: Box<dyn FrameSink>,
+        device: &Device,
+        format: TextureFormat,
+        size: (u32, u32),
+    ) -> Self {
+        let (texture, view) = create_texture(device, format, size);
+        Self {
+            sink,
+            texture,
+            view,
+        }
+    }
+
+    /// Recreates the texture if the window's surface has changed size.
+    pub(crate) fn resize(&mut self, device: &Device, size: (u32, u32)) {
+        if size != (self.texture.width(), self.texture.height()) {
+            (self.texture, self.view) = create_texture(device, self.texture.format(), size);
+        }
+    }
+
+    /// Records a copy of the finished frame from the window's surface into
+    /// the shared texture.
+    pub(crate) fn copy_frame(&self, encoder: &mut CommandEncoder, frame: &Texture) {
+        encoder.copy_texture_to_texture(
+            frame.as_image_copy(),
+            self.texture.as_image_copy(),
+            Extent3d {
+                width: frame.width().min(self.texture.width()),
+                height: frame.height().min(self.texture.height()),
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Hands the frame drawn to the sink.
+    pub(crate) fn publish(&mut self, device: &Device, queue: &Queue) {
+        self.sink.publish(SharedFrame {
+            device,
+            queue,
+            texture: &self.texture,
+            width: self.texture.width(),
+            height: self.texture.height(),
+        });
+    }
+}
+
+/// Creates a texture that frames can be drawn into and shared from.
+fn create_texture(
+    device: &Device,
+    format: TextureFormat,
+    (width, height): (u32, u32),
+) -> (Texture, TextureView) {
+    let texture = device.create_texture(&TextureDescriptor {
+        label: Some("Shared Frame Texture"),
+        size: Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format,
+        usage: TextureUsages::RENDER_ATTACHMENT
+            | TextureUsages::COPY_SRC
+            | TextureUsages::COPY_DST
+            | TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&TextureViewDescriptor::default());
+    (texture, view)
+}
@@ -0,0 +1,224 @@
+//! Reading game controllers, for playing from the sofa or on handhelds.
+//!
+//! Each tick is given the controllers that are connected in
+//! [`TickInput::gamepads`], with their buttons and sticks laid out as on an
+//! Xbox controller.  Buttons report whether they are down and whether they
+//! were pressed or released since the last tick, as mouse buttons do.
+//!
+//! ```
+//! use mage_core::{
+//!     gamepad::{GamepadAxis, GamepadButton},
+//!     TickInput,
+//! };
+//!
+//! fn movement(tick_input: &TickInput) -> (i32, i32) {
+//!     let Some(gamepad) = tick_input.gamepads.first() else {
+//!         return (0, 0);
+//!     };
+//!     let dx = gamepad.is_down(GamepadButton::DPadRight) as i32
+//!         - gamepad.is_down(GamepadButton::DPadLeft) as i32;
+//!     let dy = gamepad.is_down(GamepadButton::DPadDown) as i32
+//!         - gamepad.is_down(GamepadButton::DPadUp) as i32;
+//!     if (dx, dy) != (0, 0) {
+//!         return (dx, dy);
+//!     }
+//!     let stick = |axis| gamepad.axis(axis).round() as i32;
+//!     (stick(GamepadAxis::LeftStickX), stick(GamepadAxis::LeftStickY))
+//! }
+//! ```
+//!
+//! Controllers are read in web pages, through the browser's Gamepad API, for
+//! those it maps to its standard layout.  On desktops they are read through
+//! gilrs with the `gilrs` feature, which on Linux needs libudev.  Elsewhere
+//! none are connected.
+//! Headless runs and tests can connect their own with
+//! [`Headless::connect_gamepad`].  Gamepads are not recorded for replays.
+//!
+//! [`TickInput::gamepads`]: ../struct.TickInput.html#structfield.gamepads
+//! [`Headless::connect_gamepad`]: ../headless/struct.Headless.html#method.connect_gamepad
+
+/// A button on a controller, named by where it is on an Xbox controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    /// The bottom face button: A on Xbox and cross on PlayStation.
+    South,
+
+    /// The right face button: B on Xbox and circle on PlayStation.
+    East,
+
+    /// The left face button: X on Xbox and square on PlayStation.
+    West,
+
+    /// The top face button: Y on Xbox and triangle on PlayStation.
+    North,
+
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+
+    /// The left centre button: View on Xbox and Share on PlayStation.
+    Select,
+
+    /// The right centre button: Menu on Xbox and Options on PlayStation.
+    Start,
+
+    /// Pressing the left stick in.
+    LeftStick,
+
+    /// Pressing the right stick in.
+    RightStick,
+
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+
+    /// The button in the middle, such as the Xbox or PS button.
+    Mode,
+}
+
+impl GamepadButton {
+    /// Every button, in the order of the browser's standard layout.
+    pub const ALL: [GamepadButton; 17] = [
+        GamepadButton::South,
+        GamepadButton::East,
+        GamepadButton::West,
+        GamepadButton::North,
+        GamepadButton::LeftBumper,
+        GamepadButton::RightBumper,
+        GamepadButton::LeftTrigger,
+        GamepadButton::RightTrigger,
+        GamepadButton::Select,
+        GamepadButton::Start,
+        GamepadButton::LeftStick,
+        GamepadButton::RightStick,
+        GamepadButton::DPadUp,
+        GamepadButton::DPadDown,
+        GamepadButton::DPadLeft,
+        GamepadButton::DPadRight,
+        GamepadButton::Mode,
+    ];
+
+    /// Returns the bit the button is held in.
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// A direction a stick on a controller can be pushed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    /// The left stick, from -1 at the left to 1 at the right.
+    LeftStickX,
+
+    /// The left stick, from -1 at the top to 1 at the bottom.
+    LeftStickY,
+
+    /// The right stick, from -1 at the left to 1 at the right.
+    RightStickX,
+
+    /// The right stick, from -1 at the top to 1 at the bottom.
+    RightStickY,
+}
+
+impl GamepadAxis {
+    /// Every axis, in the order of the browser's standard layout.
+    pub const ALL: [GamepadAxis; 4] = [
+        GamepadAxis::LeftStickX,
+        GamepadAxis::LeftStickY,
+        GamepadAxis::RightStickX,
+        GamepadAxis::RightStickY,
+    ];
+}
+
+/// The state of a connected controller, passed to the app in
+/// [`TickInput::gamepads`].
+///
+/// [`TickInput::gamepads`]: ../struct.TickInput.html#structfield.gamepads
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gamepad {
+    /// The number of the controller, which stays the same while it is
+    /// connected.
+    id: u32,
+
+    /// The buttons held down, one bit each.
+    down: u32,
+
+    /// The buttons pressed since the last tick.
+    pressed: u32,
+
+    /// The buttons released since the last tick.
+    released: u32,
+
+    /// How far each stick is pushed, from -32767 to 32767.
+    axes: [i16; 4],
+}
+
+impl Gamepad {
+    /// Creates a controller with no buttons held and its sticks centred.
+    pub fn new(id: u32) -> Self {
+        Self {
+            id,
+            down: 0,
+            pressed: 0,
+            released: 0,
+            axes: [0; 4],
+        }
+    }
+
+    /// Returns the number of the controller, which stays the same while it
+    /// is connected.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns true if a button is held down.
+    pub fn is_down(&self, button: GamepadButton) -> bool {
+        self.down & button.bit() != 0
+    }
+
+    /// Returns true if a button was pressed since the last tick.
+    pub fn was_pressed(&self, button: GamepadButton) -> bool {
+        self.pressed & button.bit() != 0
+    }
+
+    /// Returns true if a button was released since the last tick.
+    pub fn was_released(&self, button: GamepadButton) -> bool {
+        self.released & button.bit() != 0
+    }
+
+    /// Returns how far a stick is pushed along an axis, from -1 to 1.
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize] as f32 / i16::MAX as f32
+    }
+
+    /// Presses or releases a button, as a controller does.
+    pub fn set_button(&mut self, button: GamepadButton, down: bool) {
+        let bit = button.bit();
+        if down && self.down & bit == 0 {
+            self.pressed |= bit;
+        } else if !down && self.down & bit != 0 {
+            self.released |= bit;
+        }
+        if down {
+            self.down |= bit;
+        } else {
+            self.down &= !bit;
+        }
+    }
+
+    /// Pushes a stick along an axis, as a controller does.  The value is kept
+    /// between -1 and 1.
+    pub fn set_axis(&mut self, axis: GamepadAxis, value: f32) {
+        let value = if value.is_nan() { 0.0 } else { value };
+        self.axes[axis as usize] = (value.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+    }
+
+    /// Clears the changes since the last tick, ready for the next tick.
+    pub(crate) fn end_tick(&mut self) {
+        self.pressed = 0;
+        self.released = 0;
+    }
+}
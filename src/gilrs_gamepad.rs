@@ -0,0 +1,148 @@
+//! Reading game controllers on desktops through gilrs, with the `gilrs`
+//! feature.
+
+use gilrs::{Axis, Button, Event, EventType, Gilrs};
+use tracing::warn;
+
+use crate::gamepad::{Gamepad, GamepadAxis, GamepadButton};
+
+/// The controllers connected to the computer, read through gilrs.
+pub(crate) struct Gamepads {
+    /// The gilrs context, or `None` if controllers can't be read on this
+    /// computer.
+    gilrs: Option<Gilrs>,
+}
+
+impl Gamepads {
+    /// Starts reading controllers.  If they can't be read, a warning is logged
+    /// and none are ever connected.
+    pub(crate) fn new() -> Self {
+        let gilrs = Gilrs::new()
+            .map_err(|e| warn!("Game controllers can't be read: {}", e))
+            .ok();
+        Self { gilrs }
+    }
+
+    /// Reads the changes to the controllers since the last poll, keeping
+    /// those already connected so their buttons' changes since the last tick
+    /// are kept.  Buttons pressed and released between polls are still seen
+    /// as pressed.
+    pub(crate) fn poll(&mut self, gamepads: &mut Vec<Gamepad>) {
+        let Some(gilrs) = &mut self.gilrs else {
+            return;
+        };
+
+        while let Some(Event { id, event, .. }) = gilrs.next_event() {
+            let id = usize::from(id) as u32;
+            if event == EventType::Disconnected {
+                gamepads.retain(|gamepad| gamepad.id() != id);
+                continue;
+            }
+            let gamepad = find_or_connect(gamepads, id);
+            match event {
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(button) = to_button(button) {
+                        gamepad.set_button(button, true);
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(button) = to_button(button) {
+                        gamepad.set_button(button, false);
+                    }
+                }
+                EventType::AxisChanged(axis, value, _) => {
+                    if let Some((axis, sign)) = to_axis(axis) {
+                        gamepad.set_axis(axis, value * sign);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Controllers connected before Mage started don't send an event until
+        // they are used.
+        for (id, pad) in gilrs.gamepads() {
+            if pad.is_connected() {
+                find_or_connect(gamepads, usize::from(id) as u32);
+            }
+        }
+    }
+}
+
+/// Returns the controller with an id, adding it if it isn't connected yet.
+fn find_or_connect(gamepads: &mut Vec<Gamepad>, id: u32) -> &mut Gamepad {
+    match gamepads.iter().position(|gamepad| gamepad.id() == id) {
+        Some(i) => &mut gamepads[i],
+        None => {
+            gamepads.push(Gamepad::new(id));
+            gamepads.last_mut().unwrap()
+        }
+    }
+}
+
+/// Returns the button on an Xbox controller for a gilrs button, if it has
+/// one.
+fn to_button(button: Button) -> Option<GamepadButton> {
+    Some(match button {
+        Button::South => GamepadButton::South,
+        Button::East => GamepadButton::East,
+        Button::West => GamepadButton::West,
+        Button::North => GamepadButton::North,
+        Button::LeftTrigger => GamepadButton::LeftBumper,
+        Button::RightTrigger => GamepadButton::RightBumper,
+        Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+        Button::RightTrigger2 => GamepadButton::RightTrigger,
+        Button::Select => GamepadButton::Select,
+        Button::Start => GamepadButton::Start,
+        Button::LeftThumb => GamepadButton::LeftStick,
+        Button::RightThumb => GamepadButton::RightStick,
+        Button::DPadUp => GamepadButton::DPadUp,
+        Button::DPadDown => GamepadButton::DPadDown,
+        Button::DPadLeft => GamepadButton::DPadLeft,
+        Button::DPadRight => GamepadButton::DPadRight,
+        Button::Mode => GamepadButton::Mode,
+        _ => return None,
+    })
+}
+
+/// Returns the axis for a gilrs stick axis, if it is one, and what to
+/// multiply its values by.  gilrs has up as positive, and Mage has down.
+fn to_axis(axis: Axis) -> Option<(GamepadAxis, f32)> {
+    match axis {
+        Axis::LeftStickX => Some((GamepadAxis::LeftStickX, 1.0)),
+        Axis::LeftStickY => Some((GamepadAxis::LeftStickY, -1.0)),
+        Axis::RightStickX => Some((GamepadAxis::RightStickX, 1.0)),
+        Axis::RightStickY => Some((GamepadAxis::RightStickY, -1.0)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buttons_map_to_the_xbox_layout() {
+        assert_eq!(
+            to_button(Button::LeftTrigger),
+            Some(GamepadButton::LeftBumper)
+        );
+        assert_eq!(
+            to_button(Button::RightTrigger2),
+            Some(GamepadButton::RightTrigger)
+        );
+        assert_eq!(to_button(Button::C), None);
+    }
+
+    #[test]
+    fn stick_y_axes_point_down() {
+        let mut gamepad = Gamepad::new(0);
+        for (axis, value) in [(Axis::LeftStickY, 1.0), (Axis::RightStickX, 0.5)] {
+            let (axis, sign) = to_axis(axis).unwrap();
+            gamepad.set_axis(axis, value * sign);
+        }
+        assert_eq!(gamepad.axis(GamepadAxis::LeftStickY), -1.0);
+        assert!((gamepad.axis(GamepadAxis::RightStickX) - 0.5).abs() < 0.001);
+        assert!(to_axis(Axis::LeftZ).is_none());
+    }
+}
@@ -0,0 +1,211 @@
+//! Smooth transitions between several colours.
+//!
+//! A [`Gradient`] is a list of colour stops that can be sampled anywhere
+//! between 0.0 and 1.0, and painted across the ink or paper of an area of an
+//! image for gradient text, sky backgrounds or heatmaps.
+//!
+//! [`Gradient`]: struct.Gradient.html
+
+use crate::{
+    colour::Colour,
+    image::{Image, Rect},
+};
+
+/// How colours are mixed between the stops of a [`Gradient`].
+///
+/// [`Gradient`]: struct.Gradient.html
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Mix the red, green and blue components, as [`Colour::lerp`].
+    ///
+    /// [`Colour::lerp`]: ../colour/enum.Colour.html#method.lerp
+    ///
+    #[default]
+    Linear,
+
+    /// Mix in a perceptual colour space, as [`Colour::lerp_perceptual`].
+    ///
+    /// [`Colour::lerp_perceptual`]: ../colour/enum.Colour.html#method.lerp_perceptual
+    ///
+    Perceptual,
+}
+
+/// The direction a gradient is painted across an area of an image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientDirection {
+    /// The first stop is on the left and the last stop on the right.
+    Horizontal,
+
+    /// The first stop is at the top and the last stop at the bottom.
+    Vertical,
+
+    /// The first stop is at the top-left and the last stop at the
+    /// bottom-right.
+    Diagonal,
+}
+
+/// A list of colours at positions between 0.0 and 1.0, with the colours in
+/// between mixed smoothly.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Gradient {
+    /// The position and colour of each stop, in order of position.
+    stops: Vec<(f32, Colour)>,
+
+    /// How colours are mixed between the stops.
+    interpolation: Interpolation,
+}
+
+impl Gradient {
+    /// Creates a new gradient with no stops.
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            stops: Vec::new(),
+            interpolation,
+        }
+    }
+
+    /// Creates a new gradient with the given colours spaced evenly between 0.0
+    /// and 1.0.
+    pub fn from_colours(colours: &[Colour], interpolation: Interpolation) -> Self {
+        let mut gradient = Self::new(interpolation);
+        let last = colours.len().saturating_sub(1).max(1) as f32;
+        for (i, &colour) in colours.iter().enumerate() {
+            gradient.add_stop(i as f32 / last, colour);
+        }
+        gradient
+    }
+
+    /// Adds a colour stop to the gradient.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Where the stop is, from 0.0 to 1.0.  Values outside this
+    ///   range are clamped.
+    /// * `colour` - The colour of the gradient at that position.
+    ///
+    /// # Notes
+    ///
+    /// Stops can be added in any order.  If two stops share a position, the
+    /// gradient changes sharply from the first added to the second.
+    ///
+    pub fn add_stop(&mut self, position: f32, colour: Colour) {
+        let position = position.clamp(0.0, 1.0);
+        let index = self.stops.partition_point(|(p, _)| *p <= position);
+        self.stops.insert(index, (position, colour));
+    }
+
+    /// Returns the position and colour of each stop, in order of position.
+    pub fn stops(&self) -> &[(f32, Colour)] {
+        &self.stops
+    }
+
+    /// Returns the colour of the gradient at the given position.
+    ///
+    /// # Arguments
+    ///
+    /// * `t` - The position to sample, from 0.0 to 1.0.  Positions before the
+    ///   first stop or after the last stop have the colour of that stop.
+    ///
+    /// # Returns
+    ///
+    /// The colour at that position, or black if the gradient has no stops.
+    ///
+    pub fn sample(&self, t: f32) -> Colour {
+        let index = self.stops.partition_point(|(p, _)| *p <= t);
+        match (index.checked_sub(1), self.stops.get(index)) {
+            (None, None) => Colour::Black,
+            (None, Some(&(_, colour))) => colour,
+            (Some(prev), None) => self.stops[prev].1,
+            (Some(prev), Some(&(p1, c1))) => {
+                let (p0, c0) = self.stops[prev];
+                let t = (t - p0) / (p1 - p0);
+                match self.interpolation {
+                    Interpolation::Linear => Colour::lerp(c0, c1, t),
+                    Interpolation::Perceptual => Colour::lerp_perceptual(c0, c1, t),
+                }
+            }
+        }
+    }
+
+    /// Returns an iterator over `count` colours sampled evenly from the start
+    /// to the end of the gradient.
+    pub fn samples(&self, count: usize) -> impl Iterator<Item = Colour> + '_ {
+        let last = count.saturating_sub(1).max(1) as f32;
+        (0..count).map(move |i| self.sample(i as f32 / last))
+    }
+
+    /// Returns the position of a cell within an area for the given direction.
+    fn position(direction: GradientDirection, rect: Rect, x: i32, y: i32) -> f32 {
+        let fraction = |offset: i32, size: u32| {
+            if size > 1 {
+                offset as f32 / (size - 1) as f32
+            } else {
+                0.0
+            }
+        };
+        let dx = x - rect.x;
+        let dy = y - rect.y;
+        match direction {
+            GradientDirection::Horizontal => fraction(dx, rect.width),
+            GradientDirection::Vertical => fraction(dy, rect.height),
+            GradientDirection::Diagonal => fraction(dx + dy, rect.width + rect.height - 1),
+        }
+    }
+}
+
+impl Image {
+    /// Paints a gradient across the ink colours of an area of the image,
+    /// leaving the glyphs and paper colours unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area of the image to paint.  The gradient runs from one
+    ///   edge of this area to the other.
+    /// * `gradient` - The gradient to paint.
+    /// * `direction` - The direction the gradient runs across the area.
+    ///
+    /// # Notes
+    ///
+    /// The rectangle is clipped to the image.
+    ///
+    pub fn paint_ink_gradient(
+        &mut self,
+        rect: Rect,
+        gradient: &Gradient,
+        direction: GradientDirection,
+    ) {
+        self.map_cells(rect, |p, mut cell| {
+            let t = Gradient::position(direction, rect, p.x, p.y);
+            cell.ink = gradient.sample(t);
+            cell
+        });
+    }
+
+    /// Paints a gradient across the paper colours of an area of the image,
+    /// leaving the glyphs and ink colours unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area of the image to paint.  The gradient runs from one
+    ///   edge of this area to the other.
+    /// * `gradient` - The gradient to paint.
+    /// * `direction` - The direction the gradient runs across the area.
+    ///
+    /// # Notes
+    ///
+    /// The rectangle is clipped to the image.
+    ///
+    pub fn paint_paper_gradient(
+        &mut self,
+        rect: Rect,
+        gradient: &Gradient,
+        direction: GradientDirection,
+    ) {
+        self.map_cells(rect, |p, mut cell| {
+            let t = Gradient::position(direction, rect, p.x, p.y);
+            cell.paper = gradient.sample(t);
+            cell
+        });
+    }
+}
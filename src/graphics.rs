@@ -0,0 +1,197 @@
+//! Drawing on images with the `embedded-graphics` crate.
+//!
+//! With the `embedded-graphics` feature, an [`Image`] is a `DrawTarget` that
+//! draws one pixel per cell, using [`Image::draw_pixel`].  For twice the
+//! vertical resolution, draw on [`Image::half_blocks`] instead, which uses
+//! [`Image::draw_half_block_pixel`] to draw two pixels in each cell.
+//!
+//! Colours are `Rgb888` and every pixel is opaque.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_graphics_core::{
+//!     pixelcolor::Rgb888, prelude::*, primitives::Rectangle,
+//! };
+//! use mage_core::image::{Image, Point as CellPoint};
+//!
+//! let mut image = Image::new(8, 4);
+//!
+//! // One pixel per cell.
+//! image
+//!     .fill_solid(&Rectangle::new(Point::zero(), Size::new(2, 2)), Rgb888::RED)
+//!     .unwrap();
+//!
+//! // Two pixels per cell, so the half block view is 8 by 8 pixels.
+//! let mut half_blocks = image.half_blocks();
+//! assert_eq!(half_blocks.size(), Size::new(8, 8));
+//! Pixel(Point::new(4, 7), Rgb888::BLUE)
+//!     .draw(&mut half_blocks)
+//!     .unwrap();
+//!
+//! assert_eq!(image.get_char(CellPoint::new(0, 0)).unwrap().ink.to_rgb(), (255, 0, 0));
+//! assert_eq!(image.get_char(CellPoint::new(4, 3)).unwrap().paper.to_rgb(), (0, 0, 255));
+//! ```
+//!
+
+use std::convert::Infallible;
+
+use embedded_graphics_core::{
+    pixelcolor::{Rgb888, RgbColor},
+    prelude::{DrawTarget, OriginDimensions, Pixel, Size},
+    primitives::Rectangle,
+};
+
+use crate::{
+    colour::Colour,
+    image::{Char, Image, Point, Rect},
+};
+
+impl Image {
+    /// Returns a view of the image for drawing with `embedded-graphics` at
+    /// two pixels per cell.
+    ///
+    /// # Returns
+    ///
+    /// A [`HalfBlocks`] draw target that is `width` pixels across and
+    /// `height * 2` pixels down.
+    ///
+    /// [`HalfBlocks`]: struct.HalfBlocks.html
+    ///
+    pub fn half_blocks(&mut self) -> HalfBlocks<'_> {
+        HalfBlocks { image: self }
+    }
+}
+
+/// An image drawn on by `embedded-graphics` at two pixels per cell, using
+/// upper half block glyphs.
+///
+/// Created by [`Image::half_blocks`].
+///
+/// [`Image::half_blocks`]: ../image/struct.Image.html#method.half_blocks
+///
+pub struct HalfBlocks<'a> {
+    image: &'a mut Image,
+}
+
+fn to_colour(colour: Rgb888) -> Colour {
+    Colour::Rgb(colour.r(), colour.g(), colour.b())
+}
+
+fn to_point(p: embedded_graphics_core::geometry::Point) -> Point {
+    Point::new(p.x, p.y)
+}
+
+impl OriginDimensions for Image {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+impl DrawTarget for Image {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(p, colour) in pixels {
+            self.draw_pixel(to_point(p), to_colour(colour));
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, colour: Self::Color) -> Result<(), Self::Error> {
+        let colour = to_colour(colour);
+        let rect = Rect::new(
+            area.top_left.x,
+            area.top_left.y,
+            area.size.width,
+            area.size.height,
+        );
+        self.draw_filled_rect(rect, Char::new_u32(0, colour, colour));
+        Ok(())
+    }
+}
+
+impl OriginDimensions for HalfBlocks<'_> {
+    fn size(&self) -> Size {
+        Size::new(self.image.width, self.image.height.saturating_mul(2))
+    }
+}
+
+impl DrawTarget for HalfBlocks<'_> {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(p, colour) in pixels {
+            self.image
+                .draw_half_block_pixel(to_point(p), to_colour(colour));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics_core::{geometry::Point as EgPoint, Drawable};
+
+    use super::*;
+    use crate::image::UPPER_HALF_GLYPH;
+
+    #[test]
+    fn whole_cell_pixels_fill_cells() {
+        let mut image = Image::new(4, 3);
+        Pixel(EgPoint::new(1, 2), Rgb888::GREEN)
+            .draw(&mut image)
+            .unwrap();
+        Pixel(EgPoint::new(-1, 0), Rgb888::GREEN)
+            .draw(&mut image)
+            .unwrap();
+
+        let ch = image.get_char(Point::new(1, 2)).unwrap();
+        assert_eq!(ch.ink.to_rgb(), (0, 255, 0));
+        assert_eq!(ch.paper.to_rgb(), (0, 255, 0));
+        assert_eq!(image.get_char(Point::new(0, 0)).unwrap().ink.colour(), 0);
+    }
+
+    #[test]
+    fn fill_solid_is_clipped() {
+        let mut image = Image::new(4, 3);
+        let area = Rectangle::new(EgPoint::new(2, 1), Size::new(10, 10));
+        image.fill_solid(&area, Rgb888::WHITE).unwrap();
+
+        assert_eq!(
+            image.get_char(Point::new(3, 2)).unwrap().paper.to_rgb(),
+            (255, 255, 255)
+        );
+        assert_eq!(image.get_char(Point::new(1, 1)).unwrap().paper.colour(), 0);
+    }
+
+    #[test]
+    fn half_block_pixels_share_a_cell() {
+        let mut image = Image::new(2, 2);
+        let mut half_blocks = image.half_blocks();
+        assert_eq!(half_blocks.size(), Size::new(2, 4));
+        Pixel(EgPoint::new(1, 2), Rgb888::RED)
+            .draw(&mut half_blocks)
+            .unwrap();
+        Pixel(EgPoint::new(1, 3), Rgb888::BLUE)
+            .draw(&mut half_blocks)
+            .unwrap();
+        Pixel(EgPoint::new(1, 4), Rgb888::BLUE)
+            .draw(&mut half_blocks)
+            .unwrap();
+
+        let ch = image.get_char(Point::new(1, 1)).unwrap();
+        assert_eq!(ch.ch, UPPER_HALF_GLYPH as u32);
+        assert_eq!(ch.ink.to_rgb(), (255, 0, 0));
+        assert_eq!(ch.paper.to_rgb(), (0, 0, 255));
+        assert_eq!(image.get_char(Point::new(1, 0)).unwrap().ch, 0);
+    }
+}
@@ -0,0 +1,460 @@
+//! Running apps without a window or GPU, such as for tests, bots and servers.
+//!
+//! A [`Headless`] runner calls an app's [`tick`] and [`present`] methods at a
+//! steady frame rate and keeps the cells it presents in an [`Image`], which
+//! the host can read after each frame.
+//!
+//! ```
+//! use mage_core::{
+//!     headless::Headless, image::Point, App, Colour, PresentInput, PresentResult, TickInput,
+//!     TickResult,
+//! };
+//!
+//! struct Counter(u32);
+//!
+//! impl App for Counter {
+//!     fn tick(&mut self, _tick_input: TickInput) -> TickResult {
+//!         self.0 += 1;
+//!         if self.0 < 10 {
+//!             TickResult::Continue
+//!         } else {
+//!             TickResult::Quit
+//!         }
+//!     }
+//!
+//!     fn present(&mut self, mut present_input: PresentInput) -> PresentResult {
+//!         let mut image = present_input.new_image();
+//!         image.draw_string(Point::new(0, 0), &self.0.to_string(), Colour::White, Colour::Black);
+//!         present_input.blit(present_input.rect(), image.rect(), &image, Colour::Black);
+//!         PresentResult::Changed
+//!     }
+//! }
+//!
+//! let mut headless = Headless::new(Counter(0), 20, 5);
+//! headless.run_frames(3);
+//! assert!(headless.screen().to_plain_text().starts_with("3 "));
+//!
+//! headless.run_frames(100);
+//! assert!(headless.is_finished());
+//! assert_eq!(headless.frame_count(), 10);
+//! ```
+//!
+//! [`Headless`]: struct.Headless.html
+//! [`tick`]: ../trait.App.html#tymethod.tick
+//! [`present`]: ../trait.App.html#tymethod.present
+//! [`Image`]: ../image/struct.Image.html
+
+use chrono::Duration;
+
+use crate::{
+    accessibility::ScreenRegions,
+    animation::AnimationPhase,
+    context::{ContextRequest, MageContext},
+    gamepad::Gamepad,
+    image::Image,
+    input::{Composition, KeyEvent, MouseState},
+    profile::{self, profile_scope},
+    replay::Recording,
+    stats::MemoryStats,
+    App, PresentInput, PresentResult, TickInput, TickResult,
+};
+
+/// Runs an app without a window or GPU.
+///
+/// Each frame, the app is ticked with the time of one frame at the chosen
+/// frame rate, whatever the real time taken, so runs are repeatable.  The app
+/// then presents to a screen image that is kept between frames, as the
+/// window's buffers are.
+///
+pub struct Headless<A: App> {
+    /// The app being run.
+    app: A,
+
+    /// The cells the app has presented.
+    screen: Image,
+
+    /// The time between frames.
+    frame_time: Duration,
+
+    /// The number of frames run each second.
+    frame_rate: u32,
+
+    /// The state of the mouse given to the app on the next tick.
+    mouse: MouseState,
+
+    /// The keys given to the app on the next tick.
+    keys: Vec<KeyEvent>,
+
+    /// The text typed on the next tick.
+    text: String,
+
+    /// The text being composed with an input method.
+    composition: Option<Composition>,
+
+    /// The controllers connected.
+    gamepads: Vec<Gamepad>,
+
+    /// The changes the app asks for while it is ticked.
+    context: MageContext,
+
+    /// The engine's animation clock, moved on by each tick.
+    animation: AnimationPhase,
+
+    /// The number of frames ticked so far.
+    frames: u64,
+
+    /// True once the app has asked to quit.
+    finished: bool,
+
+    /// The session so far, if it is being recorded.
+    recording: Option<Recording>,
+}
+
+impl<A: App> Headless<A> {
+    /// Creates a new runner for an app with a screen of the given size in
+    /// chars, running at 60 frames per second.  The screen is at least 1x1,
+    /// as a window's is.
+    pub fn new(app: A, width: u32, height: u32) -> Self {
+        Self {
+            app,
+            screen: Image::new(width.max(1), height.max(1)),
+            frame_time: Duration::microseconds(1_000_000 / 60),
+            frame_rate: 60,
+            mouse: MouseState::default(),
+            keys: Vec::new(),
+            text: String::new(),
+            composition: None,
+            gamepads: Vec::new(),
+            context: MageContext::default(),
+            animation: AnimationPhase::default(),
+            frames: 0,
+            finished: false,
+            recording: None,
+        }
+    }
+
+    /// Sets the number of frames run each second.  This sets the time passed
+    /// to each tick, and how fast [`run`] runs in real time.
+    ///
+    /// [`run`]: struct.Headless.html#method.run
+    ///
+    pub fn with_frame_rate(mut self, frames_per_second: u32) -> Self {
+        self.frame_rate = frames_per_second.max(1);
+        self.frame_time = Duration::microseconds(1_000_000 / self.frame_rate as i64);
+        if let Some(recording) = &mut self.recording {
+            recording.frame_rate = self.frame_rate;
+        }
+        self
+    }
+
+    /// Sets how long the animation clock takes to go round once, as
+    /// [`Config::blink_period`] does for a window.
+    ///
+    /// [`Config::blink_period`]: ../struct.Config.html#structfield.blink_period
+    ///
+    pub fn with_blink_period(mut self, period: Duration) -> Self {
+        self.animation = AnimationPhase::new(period);
+        self
+    }
+
+    /// Records the session from the first frame, so that it can be replayed
+    /// with a [`Replay`].  This must be called before any frames are run.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed the app's random numbers are generated from,
+    ///   which is stored in the recording and given back to the app when it
+    ///   is replayed.
+    ///
+    /// [`Replay`]: ../replay/struct.Replay.html
+    ///
+    pub fn with_recording(mut self, seed: u64) -> Self {
+        self.recording = Some(Recording::new(
+            self.screen.width,
+            self.screen.height,
+            self.frame_rate,
+            seed,
+        ));
+        self
+    }
+
+    /// Returns the session recorded so far, if it is being recorded.
+    pub fn recording(&self) -> Option<&Recording> {
+        self.recording.as_ref()
+    }
+
+    /// Stops recording and returns the session recorded.
+    pub fn take_recording(&mut self) -> Option<Recording> {
+        self.recording.take()
+    }
+
+    /// Runs a single frame, ticking the app and then presenting it if it
+    /// didn't ask to quit.
+    ///
+    /// # Returns
+    ///
+    /// The result of the tick.  Once the app has asked to quit, it is no
+    /// longer ticked and [`TickResult::Quit`] is always returned.
+    ///
+    /// [`TickResult::Quit`]: ../enum.TickResult.html#variant.Quit
+    ///
+    pub fn step(&mut self) -> TickResult {
+        self.step_with_result().0
+    }
+
+    /// Runs a single frame that took the given time, rather than the time of
+    /// one frame at the frame rate.
+    pub(crate) fn step_with_dt(&mut self, dt: Duration) -> TickResult {
+        self.step_with_dt_and_result(dt).0
+    }
+
+    /// Runs a single frame, returning the results of the tick and, if the
+    /// app didn't quit, the present.
+    fn step_with_result(&mut self) -> (TickResult, Option<PresentResult>) {
+        self.step_with_dt_and_result(self.frame_time)
+    }
+
+    /// Runs a single frame that took the given time, returning the results
+    /// of the tick and, if the app didn't quit, the present.
+    fn step_with_dt_and_result(&mut self, dt: Duration) -> (TickResult, Option<PresentResult>) {
+        if self.finished {
+            return (TickResult::Quit, None);
+        }
+
+        self.animation.advance(dt);
+        let tick_input = TickInput {
+            dt,
+            width: self.screen.width,
+            height: self.screen.height,
+            mouse: self.mouse,
+            keys: &self.keys,
+            text: &self.text,
+            composition: self.composition.as_ref(),
+            gamepads: &self.gamepads,
+            memory: MemoryStats {
+                screen_bytes: self.screen.memory_bytes(),
+                screens: 1,
+                ..MemoryStats::default()
+            },
+            zoom: 1,
+            animation: self.animation,
+            context: &self.context,
+        };
+        if let Some(recording) = &mut self.recording {
+            recording.record_input(&tick_input);
+        }
+        let mut tick_result = {
+            profile_scope!("tick");
+            self.app.tick(tick_input)
+        };
+        // There is no window, so only resizing the screen and quitting do
+        // anything.
+        for request in self.context.take_requests() {
+            match request {
+                ContextRequest::ResizeCells(width, height) => self.resize(width, height),
+                ContextRequest::Quit => tick_result = TickResult::Quit,
+                _ => {}
+            }
+        }
+        self.mouse.end_tick();
+        self.keys.clear();
+        self.text.clear();
+        self.gamepads.iter_mut().for_each(Gamepad::end_tick);
+        self.frames += 1;
+        if tick_result == TickResult::Quit {
+            self.finished = true;
+            if let Some(recording) = &mut self.recording {
+                recording.record_screen(&self.screen);
+            }
+            return (tick_result, None);
+        }
+
+        let present_input = PresentInput {
+            screen: &mut self.screen,
+            animation: self.animation,
+        };
+        let present_result = {
+            profile_scope!("present");
+            self.app.present(present_input)
+        };
+        if let Some(recording) = &mut self.recording {
+            recording.record_screen(&self.screen);
+        }
+        profile::end_frame();
+        (tick_result, Some(present_result))
+    }
+
+    /// Runs a number of frames as fast as possible, stopping early if the
+    /// app asks to quit.
+    ///
+    /// # Returns
+    ///
+    /// The result of the last tick.
+    ///
+    pub fn run_frames(&mut self, frames: u64) -> TickResult {
+        let mut result = TickResult::Continue;
+        for _ in 0..frames {
+            result = self.step();
+            if result == TickResult::Quit {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Runs frames at the frame rate in real time until the app quits, such
+    /// as when running a game on a server.
+    ///
+    /// # Arguments
+    ///
+    /// * `on_frame` - Called with the screen after each frame the app says
+    ///   has changed.  Returning [`TickResult::Quit`] stops running frames.
+    ///
+    /// [`TickResult::Quit`]: ../enum.TickResult.html#variant.Quit
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn run(&mut self, mut on_frame: impl FnMut(&Image) -> TickResult) {
+        let frame_time = self.frame_time.to_std().unwrap_or_default();
+        let mut next_frame = std::time::Instant::now();
+        loop {
+            let (tick_result, present_result) = self.step_with_result();
+            if tick_result == TickResult::Quit {
+                break;
+            }
+            if present_result == Some(PresentResult::Changed)
+                && on_frame(&self.screen) == TickResult::Quit
+            {
+                break;
+            }
+
+            // Wait for the next frame, without trying to catch up on frames
+            // that are already late.
+            next_frame += frame_time;
+            let now = std::time::Instant::now();
+            if next_frame > now {
+                std::thread::sleep(next_frame - now);
+            } else {
+                next_frame = now;
+            }
+        }
+    }
+
+    /// Returns the cells the app has presented so far.
+    pub fn screen(&self) -> &Image {
+        &self.screen
+    }
+
+    /// Returns the text of the screen, split into the areas the app has
+    /// tagged with [`App::screen_regions`], as [`ScreenRegions::to_text`]
+    /// gives it.
+    ///
+    /// [`App::screen_regions`]: ../trait.App.html#method.screen_regions
+    /// [`ScreenRegions::to_text`]: ../accessibility/struct.ScreenRegions.html#method.to_text
+    ///
+    pub fn screen_text(&self) -> String {
+        match self.app.screen_regions() {
+            Some(regions) => regions.to_text(&self.screen),
+            None => ScreenRegions::new().to_text(&self.screen),
+        }
+    }
+
+    /// Changes the size of the screen in chars, as a window being resized
+    /// does.  The screen is cleared, and the app sees the new size on its
+    /// next tick.  The screen is at least 1x1, as a window's is.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.screen = Image::new(width.max(1), height.max(1));
+    }
+
+    /// Sets the state of the mouse given to the app on the next tick.  After
+    /// each tick, the buttons' pressed and released flags and the wheel are
+    /// cleared, as they are for a real mouse.
+    pub fn set_mouse(&mut self, mouse: MouseState) {
+        self.mouse = mouse;
+    }
+
+    /// Returns the state of the mouse given to the app on the next tick.
+    pub fn mouse(&self) -> MouseState {
+        self.mouse
+    }
+
+    /// Adds a key pressed or released to those given to the app on the next
+    /// tick.  They are cleared after each tick, as a window's are.
+    pub fn send_key(&mut self, event: KeyEvent) {
+        self.keys.push(event);
+    }
+
+    /// Returns the keys given to the app on the next tick.
+    pub fn keys(&self) -> &[KeyEvent] {
+        &self.keys
+    }
+
+    /// Adds text to that typed on the next tick, as typing it in a window
+    /// does.  It is cleared after each tick.
+    pub fn type_text(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    /// Sets the text being composed with an input method, which is given to
+    /// the app on each tick until it is changed.
+    pub fn set_composition(&mut self, composition: Option<Composition>) {
+        self.composition = composition;
+    }
+
+    /// Connects a controller, given to the app from the next tick.  If one
+    /// with the same id is already connected, it is replaced.
+    pub fn connect_gamepad(&mut self, gamepad: Gamepad) {
+        match self.gamepad_mut(gamepad.id()) {
+            Some(connected) => *connected = gamepad,
+            None => self.gamepads.push(gamepad),
+        }
+    }
+
+    /// Disconnects the controller with the given id, if it is connected.
+    pub fn disconnect_gamepad(&mut self, id: u32) {
+        self.gamepads.retain(|gamepad| gamepad.id() != id);
+    }
+
+    /// Returns the connected controller with the given id, so its buttons
+    /// and sticks can be changed for the next tick.  After each tick, the
+    /// buttons' pressed and released flags are cleared, as they are for a
+    /// real controller.
+    pub fn gamepad_mut(&mut self, id: u32) -> Option<&mut Gamepad> {
+        self.gamepads.iter_mut().find(|gamepad| gamepad.id() == id)
+    }
+
+    /// Returns the time between frames.
+    pub(crate) fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+
+    /// Returns the number of frames ticked so far.
+    pub fn frame_count(&self) -> u64 {
+        self.frames
+    }
+
+    /// Returns the time the app has been running for, as it has seen it.
+    pub fn elapsed(&self) -> Duration {
+        let frame_time = self.frame_time.num_microseconds().unwrap_or(0);
+        Duration::microseconds(frame_time.saturating_mul(self.frames as i64))
+    }
+
+    /// Returns true if the app has asked to quit.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the app being run.
+    pub fn app(&self) -> &A {
+        &self.app
+    }
+
+    /// Returns the app being run, so its state can be changed.
+    pub fn app_mut(&mut self) -> &mut A {
+        &mut self.app
+    }
+
+    /// Stops running the app and returns it.
+    pub fn into_app(self) -> A {
+        self.app
+    }
+}
@@ -1,5 +1,31 @@
+use std::ops::Range;
+
+use crate::{
+    canvas::Canvas,
+    codepage::{char_to_glyph, unicode_to_cp437},
+    colour::{Colour, ColourVision},
+    simd,
+};
+
+/// The glyph drawn for characters that cannot be mapped on to the font.
+pub const DEFAULT_FALLBACK_GLYPH: u8 = b'?';
+
+/// The glyph for an upper half block, used to draw two pixels in each cell.
+pub(crate) const UPPER_HALF_GLYPH: u8 = 0xdf;
+
+/// A reserved glyph value marking a transparent cell.
+///
+/// Blit operations never copy a transparent cell, leaving the destination
+/// untouched, so sprites can have holes in them.  Drawing a transparent char
+/// on to an image marks that cell as transparent.  See [`Char::transparent`].
+///
+/// [`Char::transparent`]: struct.Char.html#method.transparent
+///
+pub const TRANSPARENT_GLYPH: u32 = u32::MAX;
+
 /// Represents a rectangular collection of chars to render as sprites or
 /// screens.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Image {
     /// The width of the image in chars.
     pub width: u32,
@@ -68,15 +94,11 @@ impl Rect {
     /// A new rectangle that contains both points.
     ///
     pub fn from_points(p1: Point, p2: Point) -> Self {
-        let x = p1.x.min(p2.x);
-        let y = p1.y.min(p2.y);
-        let width = (p1.x - p2.x).unsigned_abs();
-        let height = (p1.y - p2.y).unsigned_abs();
         Self {
-            x,
-            y,
-            width,
-            height,
+            x: p1.x.min(p2.x),
+            y: p1.y.min(p2.y),
+            width: p1.x.abs_diff(p2.x),
+            height: p1.y.abs_diff(p2.y),
         }
     }
 
@@ -103,10 +125,17 @@ impl Rect {
         }
     }
 
+    /// Returns true if the rectangle has no area, because its width or height
+    /// is zero.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
     /// Returns the union of this rectangle and another rectangle.
     ///
     /// The union of two rectangles is the smallest rectangle that contains both
-    /// rectangles.
+    /// rectangles.  Empty rectangles contain nothing, so the union of an empty
+    /// rectangle and another rectangle is the other rectangle.
     ///
     /// # Arguments
     ///
@@ -114,18 +143,23 @@ impl Rect {
     ///
     /// # Returns
     ///
-    /// A new rectangle that contains both rectangles.
+    /// A new rectangle that contains both rectangles.  Its size is limited to
+    /// `u32::MAX` if the rectangles are further apart than that.
     ///
     pub fn union(&self, other: Self) -> Self {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return other;
+        }
         let x = self.x.min(other.x);
         let y = self.y.min(other.y);
-        let width = (self.x + self.width as i32).max(other.x + other.width as i32) - x;
-        let height = (self.y + self.height as i32).max(other.y + other.height as i32) - y;
         Self {
             x,
             y,
-            width: width as u32,
-            height: height as u32,
+            width: saturate(self.x_end().max(other.x_end()) - x as i64),
+            height: saturate(self.y_end().max(other.y_end()) - y as i64),
         }
     }
 
@@ -140,19 +174,19 @@ impl Rect {
     ///
     /// # Returns
     ///
-    /// A new rectangle that is contained within both rectangles.
+    /// A new rectangle that is contained within both rectangles, or `None` if
+    /// the rectangles don't overlap.
     ///
-    pub fn intersect(&self, other: Self) -> Self {
+    pub fn intersect(&self, other: Self) -> Option<Self> {
         let x = self.x.max(other.x);
         let y = self.y.max(other.y);
-        let width = (self.x + self.width as i32).min(other.x + other.width as i32) - x;
-        let height = (self.y + self.height as i32).min(other.y + other.height as i32) - y;
-        Self {
+        let rect = Self {
             x,
             y,
-            width: width as u32,
-            height: height as u32,
-        }
+            width: saturate(self.x_end().min(other.x_end()) - x as i64),
+            height: saturate(self.y_end().min(other.y_end()) - y as i64),
+        };
+        (!rect.is_empty()).then_some(rect)
     }
 
     /// Creates a new rectangle by clipping this rectangle to the given
@@ -173,23 +207,234 @@ impl Rect {
     /// # Returns
     ///
     /// A new rectangle that fits within the given dimensions and is contained
-    /// within this rectangle.  Also returns the offset of the top-left corner
-    /// of the new rectangle within the original rectangle.
+    /// within this rectangle, and the offset of the top-left corner of the new
+    /// rectangle within the original rectangle.  Returns `None` if none of
+    /// this rectangle is within the dimensions.
+    ///
+    pub fn clip_within(&self, width: u32, height: u32) -> Option<(Self, Point)> {
+        let rect = self.intersect(Rect::new(0, 0, width, height))?;
+        Some((
+            rect,
+            Point::new(
+                self.x.min(0).saturating_neg(),
+                self.y.min(0).saturating_neg(),
+            ),
+        ))
+    }
+
+    /// Returns true if a point is within the rectangle.
+    pub fn contains(&self, p: Point) -> bool {
+        (self.x as i64..self.x_end()).contains(&(p.x as i64))
+            && (self.y as i64..self.y_end()).contains(&(p.y as i64))
+    }
+
+    /// Returns true if another rectangle lies entirely within this one.  An
+    /// empty rectangle is contained if its position is within this rectangle
+    /// or on its edges.
+    pub fn contains_rect(&self, other: Rect) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.x_end() <= self.x_end()
+            && other.y_end() <= self.y_end()
+    }
+
+    /// Returns the rectangle moved by an offset.
+    ///
+    /// # Arguments
+    ///
+    /// * `dx` - The number of columns to move right, or left if negative.
+    /// * `dy` - The number of rows to move down, or up if negative.
+    ///
+    pub fn translate(&self, dx: i32, dy: i32) -> Self {
+        Self {
+            x: self.x.saturating_add(dx),
+            y: self.y.saturating_add(dy),
+            ..*self
+        }
+    }
+
+    /// Returns the rectangle grown on every side, such as to draw a border
+    /// around it.
+    ///
+    /// # Arguments
+    ///
+    /// * `dx` - The number of columns to add to the left and right sides.
+    /// * `dy` - The number of rows to add to the top and bottom sides.
+    ///
+    pub fn inflate(&self, dx: u32, dy: u32) -> Self {
+        Self {
+            x: self.x.saturating_sub_unsigned(dx),
+            y: self.y.saturating_sub_unsigned(dy),
+            width: self.width.saturating_add(dx.saturating_mul(2)),
+            height: self.height.saturating_add(dy.saturating_mul(2)),
+        }
+    }
+
+    /// Returns the rectangle shrunk on every side, such as to find the area
+    /// inside a border.
+    ///
+    /// # Arguments
+    ///
+    /// * `dx` - The number of columns to remove from the left and right
+    ///   sides.
+    /// * `dy` - The number of rows to remove from the top and bottom sides.
+    ///
+    /// # Returns
     ///
-    pub fn clip_within(&self, width: u32, height: u32) -> (Self, Point) {
+    /// The shrunk rectangle, which is empty if the rectangle is too small to
+    /// shrink that much.
+    ///
+    pub fn deflate(&self, dx: u32, dy: u32) -> Self {
+        let dx = dx.min(self.width / 2);
+        let dy = dy.min(self.height / 2);
+        Self {
+            x: self.x.saturating_add_unsigned(dx),
+            y: self.y.saturating_add_unsigned(dy),
+            width: self.width - dx * 2,
+            height: self.height - dy * 2,
+        }
+    }
+
+    /// Splits the rectangle into a top part and a bottom part.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The height of the top part.  If this is more than the
+    ///   height of the rectangle, the bottom part is empty.
+    ///
+    /// # Returns
+    ///
+    /// The top part and the bottom part.
+    ///
+    pub fn split_rows(&self, rows: u32) -> (Self, Self) {
+        let rows = rows.min(self.height);
         (
-            self.intersect(Rect::new(0, 0, width, height)),
-            Point::new(-(self.x.min(0)), -(self.y.min(0))),
+            Self {
+                height: rows,
+                ..*self
+            },
+            Self {
+                y: self.y.saturating_add_unsigned(rows),
+                height: self.height - rows,
+                ..*self
+            },
         )
     }
+
+    /// Splits the rectangle into a left part and a right part.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - The width of the left part.  If this is more than the
+    ///   width of the rectangle, the right part is empty.
+    ///
+    /// # Returns
+    ///
+    /// The left part and the right part.
+    ///
+    pub fn split_columns(&self, columns: u32) -> (Self, Self) {
+        let columns = columns.min(self.width);
+        (
+            Self {
+                width: columns,
+                ..*self
+            },
+            Self {
+                x: self.x.saturating_add_unsigned(columns),
+                width: self.width - columns,
+                ..*self
+            },
+        )
+    }
+
+    /// Returns the cell at the centre of the rectangle.  If the width or
+    /// height is even, the cell is the one just right of or below the centre.
+    pub fn centre(&self) -> Point {
+        Point::new(
+            self.x.saturating_add_unsigned(self.width / 2),
+            self.y.saturating_add_unsigned(self.height / 2),
+        )
+    }
+
+    /// Returns the top-left cell of the rectangle.
+    pub fn top_left(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+
+    /// Returns the top-right cell of the rectangle.
+    pub fn top_right(&self) -> Point {
+        Point::new(self.last_x(), self.y)
+    }
+
+    /// Returns the bottom-left cell of the rectangle.
+    pub fn bottom_left(&self) -> Point {
+        Point::new(self.x, self.last_y())
+    }
+
+    /// Returns the bottom-right cell of the rectangle.
+    pub fn bottom_right(&self) -> Point {
+        Point::new(self.last_x(), self.last_y())
+    }
+
+    /// Returns an iterator over every point within the rectangle, row by row
+    /// from the top-left.
+    ///
+    /// ```
+    /// use mage_core::image::{Point, Rect};
+    ///
+    /// let points: Vec<Point> = Rect::new(1, 2, 2, 2).points().collect();
+    /// assert_eq!(
+    ///     points,
+    ///     [Point::new(1, 2), Point::new(2, 2), Point::new(1, 3), Point::new(2, 3)]
+    /// );
+    /// ```
+    ///
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        // Points beyond the range of an `i32` can't be returned.
+        let end = |end: i64| end.min(i32::MAX as i64 + 1);
+        let xs = self.x as i64..end(self.x_end());
+        (self.y as i64..end(self.y_end()))
+            .flat_map(move |y| xs.clone().map(move |x| Point::new(x as i32, y as i32)))
+    }
+
+    /// Returns the last column of the rectangle, which is the first column if
+    /// the rectangle is empty.
+    fn last_x(&self) -> i32 {
+        self.x.saturating_add_unsigned(self.width.saturating_sub(1))
+    }
+
+    /// Returns the last row of the rectangle, which is the first row if the
+    /// rectangle is empty.
+    fn last_y(&self) -> i32 {
+        self.y
+            .saturating_add_unsigned(self.height.saturating_sub(1))
+    }
+
+    /// Returns the column just past the right edge, which can be beyond the
+    /// range of an `i32`.
+    fn x_end(&self) -> i64 {
+        self.x as i64 + self.width as i64
+    }
+
+    /// Returns the row just past the bottom edge, which can be beyond the
+    /// range of an `i32`.
+    fn y_end(&self) -> i64 {
+        self.y as i64 + self.height as i64
+    }
+}
+
+/// Converts a length that may be negative or too large to a `u32`, clamping
+/// it to zero or `u32::MAX`.
+fn saturate(len: i64) -> u32 {
+    len.clamp(0, u32::MAX as i64) as u32
 }
 
 /// A single character to render with colour information.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Char {
     pub ch: u32,
-    pub ink: u32,
-    pub paper: u32,
+    pub ink: Colour,
+    pub paper: Colour,
 }
 
 impl Char {
@@ -206,7 +451,7 @@ impl Char {
     ///
     /// A new character with the given char, ink and paper colours.
     ///
-    pub fn new(ch: u8, ink: u32, paper: u32) -> Self {
+    pub fn new(ch: u8, ink: Colour, paper: Colour) -> Self {
         Self {
             ch: ch as u32,
             ink,
@@ -227,7 +472,7 @@ impl Char {
     ///
     /// A new character with the given char, ink and paper colours.
     ///
-    pub fn new_u32(ch: u32, ink: u32, paper: u32) -> Self {
+    pub fn new_u32(ch: u32, ink: Colour, paper: Colour) -> Self {
         Self { ch, ink, paper }
     }
 
@@ -241,10 +486,123 @@ impl Char {
     /// * `ink` - The foreground colour of the char.
     /// * `paper` - The background colour of the char.
     ///
-    pub fn new_char(ch: char, ink: u32, paper: u32) -> Self {
+    pub fn new_char(ch: char, ink: Colour, paper: Colour) -> Self {
         let char_byte = ch as u8;
         Self::new(char_byte, ink, paper)
     }
+
+    /// Creates a transparent character.
+    ///
+    /// Blit operations leave the destination untouched wherever the source has
+    /// a transparent character.  See [`TRANSPARENT_GLYPH`].
+    ///
+    /// [`TRANSPARENT_GLYPH`]: constant.TRANSPARENT_GLYPH.html
+    ///
+    pub fn transparent() -> Self {
+        Self {
+            ch: TRANSPARENT_GLYPH,
+            ink: Colour::transparent(),
+            paper: Colour::transparent(),
+        }
+    }
+
+    /// Returns true if this is a transparent character.
+    pub fn is_transparent(&self) -> bool {
+        self.ch == TRANSPARENT_GLYPH
+    }
+}
+
+/// Options controlling how [`Image::blit`] copies chars from one image to
+/// another.
+///
+/// Chars with the [`TRANSPARENT_GLYPH`] are never copied.  The default options
+/// copy every other char.
+///
+/// [`Image::blit`]: struct.Image.html#method.blit
+/// [`TRANSPARENT_GLYPH`]: constant.TRANSPARENT_GLYPH.html
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlitOptions {
+    /// If set, source chars with this glyph are not copied and leave the
+    /// destination untouched.
+    pub transparent_char: Option<u32>,
+
+    /// If true, source chars whose paper colour has an alpha of zero are not
+    /// copied and leave the destination untouched.
+    pub transparent_paper: bool,
+}
+
+/// How [`Image::blit_tint`] combines source colours with destination colours.
+///
+/// [`Image::blit_tint`]: struct.Image.html#method.blit_tint
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TintMode {
+    /// The destination colours are replaced by the source colours.
+    #[default]
+    Replace,
+
+    /// Each colour channel of the destination is multiplied by the source, so
+    /// white leaves the destination unchanged and black makes it black.  See
+    /// [`Colour::multiply`].
+    ///
+    /// [`Colour::multiply`]: ../colour/enum.Colour.html#method.multiply
+    ///
+    Multiply,
+
+    /// The destination is lightened by the source, so black leaves the
+    /// destination unchanged and white makes it white.  See
+    /// [`Colour::screen`].
+    ///
+    /// [`Colour::screen`]: ../colour/enum.Colour.html#method.screen
+    ///
+    Screen,
+
+    /// The destination's contrast is increased by the source.  See
+    /// [`Colour::overlay`].
+    ///
+    /// [`Colour::overlay`]: ../colour/enum.Colour.html#method.overlay
+    ///
+    Overlay,
+
+    /// The source is added to the destination.  See [`Colour::additive`].
+    ///
+    /// [`Colour::additive`]: ../colour/enum.Colour.html#method.additive
+    ///
+    Additive,
+
+    /// The source is composited over the destination according to the
+    /// source's alpha.  See [`Colour::over`].
+    ///
+    /// [`Colour::over`]: ../colour/enum.Colour.html#method.over
+    ///
+    Over,
+}
+
+impl TintMode {
+    /// Combines a destination colour with a source colour.
+    fn apply(self, dst: u32, src: u32) -> u32 {
+        let (dst, src) = (Colour::from(dst), Colour::from(src));
+        match self {
+            TintMode::Replace => src,
+            TintMode::Multiply => dst.multiply(src),
+            TintMode::Screen => dst.screen(src),
+            TintMode::Overlay => dst.overlay(src),
+            TintMode::Additive => dst.additive(src),
+            TintMode::Over => src.over(dst),
+        }
+        .colour()
+    }
+}
+
+impl BlitOptions {
+    /// Returns true if a source char with the given glyph and paper colour
+    /// should be skipped.
+    pub(crate) fn is_transparent(&self, ch: u32, paper: u32) -> bool {
+        ch == TRANSPARENT_GLYPH
+            || self.transparent_char == Some(ch)
+            || (self.transparent_paper && paper >> 24 == 0)
+    }
 }
 
 impl Image {
@@ -260,8 +618,12 @@ impl Image {
     /// A new image with the given dimensions.  The image is filled with
     /// character zero.
     ///
+    /// # Panics
+    ///
+    /// If the number of chars doesn't fit in a `usize`.
+    ///
     pub fn new(width: u32, height: u32) -> Self {
-        let size = (width * height) as usize;
+        let size = cell_count(width, height);
         Self {
             width,
             height,
@@ -271,6 +633,66 @@ impl Image {
         }
     }
 
+    /// Creates a new image with the given dimensions where every char is
+    /// transparent.
+    ///
+    /// This is a convenient starting point for sprites and overlays that only
+    /// cover part of the area they are blitted to.
+    ///
+    pub fn new_transparent(width: u32, height: u32) -> Self {
+        let mut image = Self::new(width, height);
+        image.text_image.fill(TRANSPARENT_GLYPH);
+        image
+    }
+
+    /// Changes the size of the image, filling it with character zero.  The
+    /// image keeps its memory, so shrinking, or growing back, doesn't
+    /// allocate.
+    pub(crate) fn resize(&mut self, width: u32, height: u32) {
+        let size = cell_count(width, height);
+        self.width = width;
+        self.height = height;
+        for plane in [
+            &mut self.fore_image,
+            &mut self.back_image,
+            &mut self.text_image,
+        ] {
+            plane.clear();
+            plane.resize(size, 0);
+        }
+    }
+
+    /// Makes this image a copy of another, reusing this image's memory.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    pub(crate) fn copy_from(&mut self, other: &Image) {
+        self.width = other.width;
+        self.height = other.height;
+        for (plane, other) in [
+            (&mut self.fore_image, &other.fore_image),
+            (&mut self.back_image, &other.back_image),
+            (&mut self.text_image, &other.text_image),
+        ] {
+            plane.clear();
+            plane.extend_from_slice(other);
+        }
+    }
+
+    /// Returns a canvas for drawing within an area of the image, such as a
+    /// panel of a user interface.  See [`Canvas`].
+    ///
+    /// [`Canvas`]: ../canvas/struct.Canvas.html
+    ///
+    pub fn canvas(&mut self, rect: Rect) -> Canvas<'_> {
+        Canvas::new(self, rect)
+    }
+
+    /// Returns the memory used by the image's chars, in bytes.  This counts
+    /// all the memory kept for them, which can be more than they fill.
+    pub fn memory_bytes(&self) -> usize {
+        (self.fore_image.capacity() + self.back_image.capacity() + self.text_image.capacity())
+            * size_of::<u32>()
+    }
+
     /// Returns the index of the char at the given coordinates.
     ///
     /// # Arguments
@@ -319,16 +741,47 @@ impl Image {
     /// * `ink` - The foreground colour to clear the image with.
     /// * `paper` - The background colour to clear the image with.
     ///
-    pub fn clear(&mut self, ink: u32, paper: u32) {
-        self.fore_image
-            .iter_mut()
-            .for_each(|fore_image| *fore_image = ink);
-        self.back_image
-            .iter_mut()
-            .for_each(|back_image| *back_image = paper);
-        self.text_image
-            .iter_mut()
-            .for_each(|text_image| *text_image = 0);
+    pub fn clear(&mut self, ink: Colour, paper: Colour) {
+        self.fore_image.fill(ink.colour());
+        self.back_image.fill(paper.colour());
+        self.text_image.fill(0);
+    }
+
+    /// Returns the character at the given coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The coordinates of the character.
+    ///
+    /// # Returns
+    ///
+    /// The character with its ink and paper colours, or `None` if the
+    /// coordinates are out of bounds.
+    ///
+    pub fn get_char(&self, p: Point) -> Option<Char> {
+        self.point_to_index(p).map(|i| Char {
+            ch: self.text_image[i],
+            ink: self.fore_image[i].into(),
+            paper: self.back_image[i].into(),
+        })
+    }
+
+    /// Returns the glyph at the given coordinates, or `None` if the
+    /// coordinates are out of bounds.
+    pub fn get_glyph(&self, p: Point) -> Option<u32> {
+        self.point_to_index(p).map(|i| self.text_image[i])
+    }
+
+    /// Returns the foreground colour at the given coordinates, or `None` if
+    /// the coordinates are out of bounds.
+    pub fn get_ink(&self, p: Point) -> Option<Colour> {
+        self.point_to_index(p).map(|i| self.fore_image[i].into())
+    }
+
+    /// Returns the background colour at the given coordinates, or `None` if
+    /// the coordinates are out of bounds.
+    pub fn get_paper(&self, p: Point) -> Option<Colour> {
+        self.point_to_index(p).map(|i| self.back_image[i].into())
     }
 
     /// Draws a character at the given coordinates.
@@ -344,42 +797,119 @@ impl Image {
     ///
     pub fn draw_char(&mut self, p: Point, ch: Char) {
         if let Some(index) = self.point_to_index(p) {
-            self.fore_image[index] = ch.ink;
-            self.back_image[index] = ch.paper;
+            self.fore_image[index] = ch.ink.colour();
+            self.back_image[index] = ch.paper.colour();
             self.text_image[index] = ch.ch;
         }
     }
 
     /// Draws a string at the given coordinates.
     ///
+    /// ASCII characters are used directly as the glyph to draw, so control
+    /// codes still reach the symbols in the first 32 glyphs.  Other characters,
+    /// such as `é` or `£`, are drawn with their code page 437 glyph.  To also
+    /// map Unicode symbols that overlap ASCII control codes, such as `☺`, use
+    /// [`draw_string_unicode`] instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The coordinates to draw the string at.
+    /// * `text` - The string to draw.
+    /// * `ink` - The foreground colour of the string.
+    /// * `paper` - The background colour of the string.
+    ///
+    /// # Notes
+    ///
+    /// Characters with no code page 437 glyph are drawn as
+    /// [`DEFAULT_FALLBACK_GLYPH`].  If the coordinates are out of bounds, the
+    /// string is clipped.
+    ///
+    /// [`draw_string_unicode`]: struct.Image.html#method.draw_string_unicode
+    /// [`DEFAULT_FALLBACK_GLYPH`]: constant.DEFAULT_FALLBACK_GLYPH.html
+    ///
+    pub fn draw_string(&mut self, p: Point, text: &str, ink: Colour, paper: Colour) {
+        self.draw_string_with_fallback(p, text, ink, paper, DEFAULT_FALLBACK_GLYPH);
+    }
+
+    /// Draws a string at the given coordinates, using the given glyph for
+    /// characters that cannot be drawn.
+    ///
     /// # Arguments
     ///
     /// * `p` - The coordinates to draw the string at.
     /// * `text` - The string to draw.
     /// * `ink` - The foreground colour of the string.
     /// * `paper` - The background colour of the string.
+    /// * `fallback` - The glyph to draw for characters with no code page 437
+    ///   glyph.
     ///
     /// # Notes
     ///
     /// If the coordinates are out of bounds, the string is clipped.
     ///
-    pub fn draw_string(&mut self, p: Point, text: &str, ink: u32, paper: u32) {
-        let (text_rect, str_offset) =
-            Rect::from_point_and_size(p, text.len() as u32, 1).clip_within(self.width, self.height);
-        if str_offset.y == 0 {
-            let str_slice =
-                &text[str_offset.x as usize..(str_offset.x + text_rect.width as i32) as usize];
-
-            if let Some(i) = self.coords_to_index(text_rect.x, text_rect.y) {
-                let w = text_rect.width as usize;
-                self.fore_image[i..i + w].iter_mut().for_each(|x| *x = ink);
-                self.back_image[i..i + w]
-                    .iter_mut()
-                    .for_each(|x| *x = paper);
-                self.text_image[i..i + w]
-                    .iter_mut()
-                    .zip(str_slice.bytes())
-                    .for_each(|(x, y)| *x = y as u32);
+    pub fn draw_string_with_fallback(
+        &mut self,
+        p: Point,
+        text: &str,
+        ink: Colour,
+        paper: Colour,
+        fallback: u8,
+    ) {
+        let glyphs = text.chars().map(|ch| char_to_glyph(ch, fallback));
+        self.draw_glyphs(p, glyphs, ink, paper);
+    }
+
+    /// Draws a string of Unicode text at the given coordinates, mapping each
+    /// character to its code page 437 glyph.
+    ///
+    /// This allows box drawing characters, shading blocks, arrows and other
+    /// symbols from the default font to be written directly, e.g. `"╔═╗"`.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The coordinates to draw the string at.
+    /// * `text` - The string to draw.
+    /// * `ink` - The foreground colour of the string.
+    /// * `paper` - The background colour of the string.
+    ///
+    /// # Notes
+    ///
+    /// Characters with no code page 437 equivalent are drawn as
+    /// [`DEFAULT_FALLBACK_GLYPH`].  If the coordinates are out of bounds, the
+    /// string is clipped.
+    ///
+    /// [`DEFAULT_FALLBACK_GLYPH`]: constant.DEFAULT_FALLBACK_GLYPH.html
+    ///
+    pub fn draw_string_unicode(&mut self, p: Point, text: &str, ink: Colour, paper: Colour) {
+        let glyphs = text
+            .chars()
+            .map(|ch| unicode_to_cp437(ch).unwrap_or(DEFAULT_FALLBACK_GLYPH));
+        self.draw_glyphs(p, glyphs, ink, paper);
+    }
+
+    /// Draws a horizontal run of glyphs starting at the given coordinates,
+    /// clipping by the number of glyphs.
+    fn draw_glyphs(
+        &mut self,
+        p: Point,
+        glyphs: impl Iterator<Item = u8>,
+        ink: Colour,
+        paper: Colour,
+    ) {
+        if p.y < 0 || p.y >= self.height as i32 || p.x >= self.width as i32 {
+            return;
+        }
+
+        let ink = ink.colour();
+        let paper = paper.colour();
+        let skip = p.x.min(0).unsigned_abs() as usize;
+        let x = p.x.max(0);
+        let max_len = (self.width as i32 - x) as usize;
+        if let Some(i) = self.coords_to_index(x, p.y) {
+            for (j, glyph) in glyphs.skip(skip).take(max_len).enumerate() {
+                self.fore_image[i + j] = ink;
+                self.back_image[i + j] = paper;
+                self.text_image[i + j] = glyph as u32;
             }
         }
     }
@@ -399,27 +929,693 @@ impl Image {
     /// If the coordinates are out of bounds, the rectangle is clipped.
     ///
     pub fn draw_filled_rect(&mut self, rect: Rect, ch: Char) {
-        let (rect, _) = rect.clip_within(self.width, self.height);
+        let Some((rect, _)) = rect.clip_within(self.width, self.height) else {
+            return;
+        };
 
         if let Some(mut i) = self.coords_to_index(rect.x, rect.y) {
             let w = rect.width as usize;
             let h = rect.height;
+            let ink = ch.ink.colour();
+            let paper = ch.paper.colour();
             (0..h).for_each(|_| {
-                self.fore_image[i..i + w]
-                    .iter_mut()
-                    .for_each(|x| *x = ch.ink);
-                self.back_image[i..i + w]
-                    .iter_mut()
-                    .for_each(|x| *x = ch.paper);
-                self.text_image[i..i + w]
-                    .iter_mut()
-                    .for_each(|x| *x = ch.ch);
+                self.fore_image[i..i + w].fill(ink);
+                self.back_image[i..i + w].fill(paper);
+                self.text_image[i..i + w].fill(ch.ch);
 
                 i += self.width as usize;
             });
         }
     }
 
+    /// Draws a pixel that fills a whole cell with a colour, for drawing
+    /// graphics at one pixel per char.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The coordinates of the cell.
+    /// * `colour` - The colour of the pixel.
+    ///
+    /// # Notes
+    ///
+    /// If the coordinates are out of bounds, the pixel is not drawn.
+    ///
+    pub fn draw_pixel(&mut self, p: Point, colour: Colour) {
+        self.draw_char(p, Char::new_u32(0, colour, colour));
+    }
+
+    /// Draws a pixel at twice the vertical resolution of the cells, using
+    /// upper half block glyphs: the top pixel of each cell is its ink and the
+    /// bottom one is its paper.
+    ///
+    /// A cell that is not already a half block becomes one with both pixels
+    /// its old paper colour, so drawing a pixel leaves the other half of the
+    /// cell looking as it did.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The coordinates of the pixel.  The image is `width` pixels
+    ///   across and `height * 2` pixels down.
+    /// * `colour` - The colour of the pixel.
+    ///
+    /// # Notes
+    ///
+    /// If the coordinates are out of bounds, the pixel is not drawn.
+    ///
+    pub fn draw_half_block_pixel(&mut self, p: Point, colour: Colour) {
+        let Some(i) = self.coords_to_index(p.x, p.y.div_euclid(2)) else {
+            return;
+        };
+        if self.text_image[i] != UPPER_HALF_GLYPH as u32 {
+            self.text_image[i] = UPPER_HALF_GLYPH as u32;
+            self.fore_image[i] = self.back_image[i];
+        }
+        if p.y % 2 == 0 {
+            self.fore_image[i] = colour.colour();
+        } else {
+            self.back_image[i] = colour.colour();
+        }
+    }
+
+    /// Replaces every char within a rectangle with the result of a function.
+    ///
+    /// This allows effects such as noise, vignettes and plasma to be written
+    /// as a single function of the coordinates and current contents of each
+    /// cell.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area of the image to map.
+    /// * `f` - Called with the coordinates and current char of each cell, and
+    ///   returns the new char for that cell.
+    ///
+    /// # Notes
+    ///
+    /// The rectangle is clipped to the image.
+    ///
+    pub fn map_cells(&mut self, rect: Rect, mut f: impl FnMut(Point, Char) -> Char) {
+        let x0 = (rect.x as i64).max(0);
+        let y0 = (rect.y as i64).max(0);
+        let x1 = (rect.x as i64 + rect.width as i64).min(self.width as i64);
+        let y1 = (rect.y as i64 + rect.height as i64).min(self.height as i64);
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let i = y as usize * self.width as usize + x as usize;
+                let cell = Char {
+                    ch: self.text_image[i],
+                    ink: self.fore_image[i].into(),
+                    paper: self.back_image[i].into(),
+                };
+                let cell = f(Point::new(x as i32, y as i32), cell);
+                self.text_image[i] = cell.ch;
+                self.fore_image[i] = cell.ink.colour();
+                self.back_image[i] = cell.paper.colour();
+            }
+        }
+    }
+
+    /// Replaces every ink and paper colour in the image with the result of a
+    /// function, leaving the glyphs unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Called with each colour, and returns the new colour.
+    ///
+    pub fn map_colours(&mut self, mut f: impl FnMut(Colour) -> Colour) {
+        for colour in self.fore_image.iter_mut().chain(self.back_image.iter_mut()) {
+            *colour = f(Colour::from(*colour)).colour();
+        }
+    }
+
+    /// Converts every ink and paper colour in the image to grey.  See
+    /// [`Colour::grayscale`].
+    ///
+    /// [`Colour::grayscale`]: ../colour/enum.Colour.html#method.grayscale
+    ///
+    pub fn grayscale(&mut self) {
+        self.map_colours(|colour| colour.grayscale());
+    }
+
+    /// Tints every ink and paper colour in the image brown like an old
+    /// photograph.  See [`Colour::sepia`].
+    ///
+    /// [`Colour::sepia`]: ../colour/enum.Colour.html#method.sepia
+    ///
+    pub fn sepia(&mut self) {
+        self.map_colours(|colour| colour.sepia());
+    }
+
+    /// Inverts the ink and paper colours within a rectangle, such as for a
+    /// selection highlight.  See [`Colour::invert`].
+    ///
+    /// # Notes
+    ///
+    /// The rectangle is clipped to the image.
+    ///
+    /// [`Colour::invert`]: ../colour/enum.Colour.html#method.invert
+    ///
+    pub fn invert(&mut self, rect: Rect) {
+        self.map_cells(rect, |_, cell| Char {
+            ink: cell.ink.invert(),
+            paper: cell.paper.invert(),
+            ..cell
+        });
+    }
+
+    /// Rotates the hue of the ink and paper colours within a rectangle.  See
+    /// [`Colour::rotate_hue`].
+    ///
+    /// # Notes
+    ///
+    /// The rectangle is clipped to the image.
+    ///
+    /// [`Colour::rotate_hue`]: ../colour/enum.Colour.html#method.rotate_hue
+    ///
+    pub fn rotate_hue(&mut self, rect: Rect, degrees: f32) {
+        self.map_cells(rect, |_, cell| Char {
+            ink: cell.ink.rotate_hue(degrees),
+            paper: cell.paper.rotate_hue(degrees),
+            ..cell
+        });
+    }
+
+    /// Converts every ink and paper colour in the image to how it looks to
+    /// someone with the given type of colour vision.  See
+    /// [`Colour::simulate_vision`].
+    ///
+    /// [`Colour::simulate_vision`]: ../colour/enum.Colour.html#method.simulate_vision
+    ///
+    pub fn simulate_vision(&mut self, vision: ColourVision) {
+        if vision != ColourVision::Normal {
+            self.map_colours(|colour| colour.simulate_vision(vision));
+        }
+    }
+
+    /// Scrolls the contents of the image by the given number of chars.
+    ///
+    /// # Arguments
+    ///
+    /// * `dx` - The number of chars to scroll horizontally.  Positive values
+    ///   move the contents to the right.
+    /// * `dy` - The number of chars to scroll vertically.  Positive values move
+    ///   the contents down.
+    /// * `fill` - The character to fill the exposed edges with.
+    ///
+    /// # Notes
+    ///
+    /// If the scroll distance is larger than the image in either direction, the
+    /// whole image is filled with `fill`.
+    ///
+    pub fn scroll(&mut self, dx: i32, dy: i32, fill: Char) {
+        if dx.unsigned_abs() >= self.width || dy.unsigned_abs() >= self.height {
+            self.draw_filled_rect(self.rect(), fill);
+            return;
+        }
+
+        let width = self.width as i32;
+        let height = self.height as i32;
+        let w = self.width as usize;
+        let row_len = (width - dx.abs()) as usize;
+        let (src_x, dst_x) = if dx >= 0 {
+            (0, dx as usize)
+        } else {
+            (-dx as usize, 0)
+        };
+
+        let mut copy_row = |dst_y: i32| {
+            let src_i = (dst_y - dy) as usize * w + src_x;
+            let dst_i = dst_y as usize * w + dst_x;
+            for plane in [
+                &mut self.fore_image,
+                &mut self.back_image,
+                &mut self.text_image,
+            ] {
+                plane.copy_within(src_i..src_i + row_len, dst_i);
+            }
+        };
+
+        // When moving down, rows must be copied from the bottom up so that
+        // source rows are not overwritten before they are read.
+        let rows = dy.max(0)..height + dy.min(0);
+        if dy > 0 {
+            rows.rev().for_each(&mut copy_row);
+        } else {
+            rows.for_each(&mut copy_row);
+        }
+
+        // Fill the exposed edges.
+        if dy > 0 {
+            self.draw_filled_rect(Rect::new(0, 0, self.width, dy as u32), fill);
+        } else if dy < 0 {
+            self.draw_filled_rect(Rect::new(0, height + dy, self.width, -dy as u32), fill);
+        }
+        if dx > 0 {
+            self.draw_filled_rect(Rect::new(0, 0, dx as u32, self.height), fill);
+        } else if dx < 0 {
+            self.draw_filled_rect(Rect::new(width + dx, 0, -dx as u32, self.height), fill);
+        }
+    }
+
+    /// Blits an area of another image on to this image.
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - The coordinates on this image to blit the top-left corner of
+    ///   the source area to.
+    /// * `src_image` - The image to blit from.
+    /// * `src_rect` - The area of the source image to blit.
+    /// * `options` - Controls which source chars are considered transparent.
+    ///
+    /// # Notes
+    ///
+    /// The source area is clipped to the source image, and the destination is
+    /// clipped to this image.  Transparent source chars leave the destination
+    /// untouched.
+    ///
+    pub fn blit(&mut self, dst: Point, src_image: &Image, src_rect: Rect, options: BlitOptions) {
+        for (d, s) in self.blit_rows(dst, src_image, src_rect) {
+            self.copy_row(d, src_image, s, options);
+        }
+    }
+
+    /// Blits the colours of an area of another image on to this image, leaving
+    /// the glyphs of this image untouched.
+    ///
+    /// This is useful for selection highlights, damage flashes and dimming
+    /// areas such as fog-of-war.
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - The coordinates on this image to blit the top-left corner of
+    ///   the source area to.
+    /// * `src_image` - The image to take the colours from.
+    /// * `src_rect` - The area of the source image to blit.
+    /// * `mode` - How the source colours are combined with the destination
+    ///   colours.
+    /// * `options` - Controls which source chars are considered transparent.
+    ///
+    /// # Notes
+    ///
+    /// The source area is clipped to the source image, and the destination is
+    /// clipped to this image.
+    ///
+    pub fn blit_tint(
+        &mut self,
+        dst: Point,
+        src_image: &Image,
+        src_rect: Rect,
+        mode: TintMode,
+        options: BlitOptions,
+    ) {
+        for (d, s) in self.blit_cells(dst, src_image, src_rect) {
+            if options.is_transparent(src_image.text_image[s], src_image.back_image[s]) {
+                continue;
+            }
+            self.fore_image[d] = mode.apply(self.fore_image[d], src_image.fore_image[s]);
+            self.back_image[d] = mode.apply(self.back_image[d], src_image.back_image[s]);
+        }
+    }
+
+    /// Blits the glyphs of an area of another image on to this image, leaving
+    /// the colours of this image untouched.
+    ///
+    /// This is useful for stamping monochrome stencils on to pre-coloured
+    /// backgrounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - The coordinates on this image to blit the top-left corner of
+    ///   the source area to.
+    /// * `src_image` - The image to take the glyphs from.
+    /// * `src_rect` - The area of the source image to blit.
+    /// * `options` - Controls which source chars are considered transparent.
+    ///
+    /// # Notes
+    ///
+    /// The source area is clipped to the source image, and the destination is
+    /// clipped to this image.
+    ///
+    pub fn blit_glyphs(
+        &mut self,
+        dst: Point,
+        src_image: &Image,
+        src_rect: Rect,
+        options: BlitOptions,
+    ) {
+        for (d, s) in self.blit_rows(dst, src_image, src_rect) {
+            simd::copy_opaque(
+                [&mut self.text_image[d]],
+                [&src_image.text_image[s.clone()]],
+                &src_image.text_image[s.clone()],
+                &src_image.back_image[s],
+                options,
+            );
+        }
+    }
+
+    /// Returns the indices of each pair of destination and source chars for a
+    /// blit of an area of a source image to a position on this image, after
+    /// clipping.
+    pub(crate) fn blit_cells(
+        &self,
+        dst: Point,
+        src_image: &Image,
+        src_rect: Rect,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (dst, src, width, height) =
+            self.clip_blit(dst, src_image, src_rect).unwrap_or_default();
+        let dst_width = self.width as usize;
+        let src_width = src_image.width as usize;
+
+        (0..height as usize).flat_map(move |row| {
+            let dst_i = (dst.y as usize + row) * dst_width + dst.x as usize;
+            let src_i = (src.y as usize + row) * src_width + src.x as usize;
+            (0..width as usize).map(move |col| (dst_i + col, src_i + col))
+        })
+    }
+
+    /// Returns the ranges of each pair of destination and source rows for a
+    /// blit of an area of a source image to a position on this image, after
+    /// clipping.
+    fn blit_rows(
+        &self,
+        dst: Point,
+        src_image: &Image,
+        src_rect: Rect,
+    ) -> impl Iterator<Item = (Range<usize>, Range<usize>)> {
+        let (dst, src, width, height) =
+            self.clip_blit(dst, src_image, src_rect).unwrap_or_default();
+        let dst_width = self.width as usize;
+        let src_width = src_image.width as usize;
+        let width = width as usize;
+
+        (0..height as usize).map(move |row| {
+            let dst_i = (dst.y as usize + row) * dst_width + dst.x as usize;
+            let src_i = (src.y as usize + row) * src_width + src.x as usize;
+            (dst_i..dst_i + width, src_i..src_i + width)
+        })
+    }
+
+    /// Copies a row of chars from another image, skipping transparent ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - The cells of this image to copy to.
+    /// * `src_image` - The image to copy from.
+    /// * `src` - The cells of the source image to copy, which is as long as
+    ///   `dst`.
+    /// * `options` - Controls which source chars are considered transparent.
+    ///
+    fn copy_row(
+        &mut self,
+        dst: Range<usize>,
+        src_image: &Image,
+        src: Range<usize>,
+        options: BlitOptions,
+    ) {
+        simd::copy_opaque(
+            [
+                &mut self.fore_image[dst.clone()],
+                &mut self.back_image[dst.clone()],
+                &mut self.text_image[dst],
+            ],
+            [
+                &src_image.fore_image[src.clone()],
+                &src_image.back_image[src.clone()],
+                &src_image.text_image[src.clone()],
+            ],
+            &src_image.text_image[src.clone()],
+            &src_image.back_image[src],
+            options,
+        );
+    }
+
+    /// Fills an area of this image by repeating an area of another image.
+    ///
+    /// # Arguments
+    ///
+    /// * `dst_rect` - The area of this image to fill.  The top-left corner of
+    ///   the first tile is placed at the top-left corner of this rectangle.
+    /// * `src_image` - The image to take the tile from.
+    /// * `src_rect` - The area of the source image to use as the tile.
+    /// * `options` - Controls which source chars are considered transparent.
+    ///
+    /// # Notes
+    ///
+    /// The tile is clipped to the source image, and the destination is clipped
+    /// to this image.  Transparent source chars leave the destination
+    /// untouched.
+    ///
+    pub fn blit_tiled(
+        &mut self,
+        dst_rect: Rect,
+        src_image: &Image,
+        src_rect: Rect,
+        options: BlitOptions,
+    ) {
+        let clip = |start: i32, len: u32, max: u32| {
+            let end = (start as i64 + len as i64).min(max as i64);
+            let start = (start as i64).max(0);
+            (start < end).then_some((start as usize, (end - start) as usize))
+        };
+        let (Some((tile_x, tile_w)), Some((tile_y, tile_h))) = (
+            clip(src_rect.x, src_rect.width, src_image.width),
+            clip(src_rect.y, src_rect.height, src_image.height),
+        ) else {
+            return;
+        };
+        let (Some((x0, w)), Some((y0, h))) = (
+            clip(dst_rect.x, dst_rect.width, self.width),
+            clip(dst_rect.y, dst_rect.height, self.height),
+        ) else {
+            return;
+        };
+
+        for y in y0..y0 + h {
+            let src_y = tile_y + (y as i64 - dst_rect.y as i64) as usize % tile_h;
+            let src_row = src_y * src_image.width as usize + tile_x;
+            let dst_row = y * self.width as usize;
+
+            // Each row is copied a tile, or part of one, at a time.
+            let mut x = x0;
+            while x < x0 + w {
+                let offset = (x as i64 - dst_rect.x as i64) as usize % tile_w;
+                let span = (tile_w - offset).min(x0 + w - x);
+                let s = src_row + offset;
+                let d = dst_row + x;
+                self.copy_row(d..d + span, src_image, s..s + span, options);
+                x += span;
+            }
+        }
+    }
+
+    /// Clips a blit of an area of a source image to a position on this image.
+    ///
+    /// # Returns
+    ///
+    /// The clipped top-left corner on this image, the clipped top-left corner
+    /// on the source image and the size of the area to copy, or `None` if
+    /// nothing would be copied.
+    ///
+    fn clip_blit(
+        &self,
+        dst: Point,
+        src_image: &Image,
+        src_rect: Rect,
+    ) -> Option<(Point, Point, u32, u32)> {
+        let clip_axis = |dst: i32, src: i32, len: u32, src_len: u32, dst_len: u32| {
+            let (mut dst, mut src, mut len) = (dst as i64, src as i64, len as i64);
+            if src < 0 {
+                dst -= src;
+                len += src;
+                src = 0;
+            }
+            if dst < 0 {
+                src -= dst;
+                len += dst;
+                dst = 0;
+            }
+            len = len.min(src_len as i64 - src).min(dst_len as i64 - dst);
+            (len > 0).then_some((dst as i32, src as i32, len as u32))
+        };
+
+        let (dst_x, src_x, width) = clip_axis(
+            dst.x,
+            src_rect.x,
+            src_rect.width,
+            src_image.width,
+            self.width,
+        )?;
+        let (dst_y, src_y, height) = clip_axis(
+            dst.y,
+            src_rect.y,
+            src_rect.height,
+            src_image.height,
+            self.height,
+        )?;
+
+        Some((
+            Point::new(dst_x, dst_y),
+            Point::new(src_x, src_y),
+            width,
+            height,
+        ))
+    }
+
+    /// Returns a new image with the contents rotated 90 degrees clockwise.
+    ///
+    /// # Returns
+    ///
+    /// A new image whose width is this image's height and whose height is this
+    /// image's width.
+    ///
+    pub fn rotated_cw(&self) -> Image {
+        self.remapped(self.height, self.width, |x, y| (y, self.height - 1 - x))
+    }
+
+    /// Returns a new image with the contents rotated 90 degrees
+    /// anti-clockwise.
+    ///
+    /// # Returns
+    ///
+    /// A new image whose width is this image's height and whose height is this
+    /// image's width.
+    ///
+    pub fn rotated_ccw(&self) -> Image {
+        self.remapped(self.height, self.width, |x, y| (self.width - 1 - y, x))
+    }
+
+    /// Returns a new image with the contents rotated 180 degrees.
+    pub fn rotated_180(&self) -> Image {
+        let mut image = self.clone();
+        image.rotate_180();
+        image
+    }
+
+    /// Returns a new image with the contents mirrored left to right.
+    pub fn flipped_horizontally(&self) -> Image {
+        let mut image = self.clone();
+        image.flip_horizontally();
+        image
+    }
+
+    /// Returns a new image with the contents mirrored top to bottom.
+    pub fn flipped_vertically(&self) -> Image {
+        let mut image = self.clone();
+        image.flip_vertically();
+        image
+    }
+
+    /// Rotates the contents of the image 180 degrees in place.
+    pub fn rotate_180(&mut self) {
+        self.fore_image.reverse();
+        self.back_image.reverse();
+        self.text_image.reverse();
+    }
+
+    /// Mirrors the contents of the image left to right in place.
+    pub fn flip_horizontally(&mut self) {
+        if self.width == 0 {
+            return;
+        }
+
+        let w = self.width as usize;
+        for plane in [
+            &mut self.fore_image,
+            &mut self.back_image,
+            &mut self.text_image,
+        ] {
+            plane.chunks_mut(w).for_each(|row| row.reverse());
+        }
+    }
+
+    /// Mirrors the contents of the image top to bottom in place.
+    pub fn flip_vertically(&mut self) {
+        // Reversing the whole buffer flips both ways, so undo the horizontal
+        // part.
+        self.rotate_180();
+        self.flip_horizontally();
+    }
+
+    /// Rotates the contents of a square image 90 degrees clockwise in place.
+    ///
+    /// # Notes
+    ///
+    /// Non-square images cannot be rotated in place as their dimensions would
+    /// change.  In that case the image is left untouched and `false` is
+    /// returned.  Use [`rotated_cw`] instead.
+    ///
+    /// [`rotated_cw`]: struct.Image.html#method.rotated_cw
+    ///
+    pub fn rotate_cw(&mut self) -> bool {
+        if self.width != self.height {
+            return false;
+        }
+        *self = self.rotated_cw();
+        true
+    }
+
+    /// Rotates the contents of a square image 90 degrees anti-clockwise in
+    /// place.
+    ///
+    /// # Notes
+    ///
+    /// Non-square images cannot be rotated in place as their dimensions would
+    /// change.  In that case the image is left untouched and `false` is
+    /// returned.  Use [`rotated_ccw`] instead.
+    ///
+    /// [`rotated_ccw`]: struct.Image.html#method.rotated_ccw
+    ///
+    pub fn rotate_ccw(&mut self) -> bool {
+        if self.width != self.height {
+            return false;
+        }
+        *self = self.rotated_ccw();
+        true
+    }
+
+    /// Returns a new image with each char repeated to scale up the contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `nx` - The number of times to repeat each char horizontally.
+    /// * `ny` - The number of times to repeat each char vertically.
+    ///
+    /// # Returns
+    ///
+    /// A new image that is `nx` times wider and `ny` times taller than this
+    /// image, or `None` if its width or height would be more than
+    /// `u32::MAX`.
+    ///
+    pub fn scaled(&self, nx: u32, ny: u32) -> Option<Image> {
+        let width = self.width.checked_mul(nx)?;
+        let height = self.height.checked_mul(ny)?;
+        Some(self.remapped(width, height, |x, y| (x / nx, y / ny)))
+    }
+
+    /// Builds a new image of the given size where each char is taken from the
+    /// position in this image returned by `src_coords`.
+    fn remapped(
+        &self,
+        width: u32,
+        height: u32,
+        src_coords: impl Fn(u32, u32) -> (u32, u32),
+    ) -> Image {
+        let mut image = Image::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let (src_x, src_y) = src_coords(x, y);
+                let src_i = src_y as usize * self.width as usize + src_x as usize;
+                let dst_i = y as usize * width as usize + x as usize;
+                image.fore_image[dst_i] = self.fore_image[src_i];
+                image.back_image[dst_i] = self.back_image[src_i];
+                image.text_image[dst_i] = self.text_image[src_i];
+            }
+        }
+        image
+    }
+
     /// Returns a rectangle representing the bounds of the image.
     ///
     /// # Returns
@@ -437,3 +1633,192 @@ impl Image {
         Rect::from_point_and_size(Point::new(0, 0), self.width, self.height)
     }
 }
+
+/// Returns the number of chars in an image of the given size, panicking
+/// rather than wrapping to a buffer that is too small.
+fn cell_count(width: u32, height: u32) -> usize {
+    (width as usize)
+        .checked_mul(height as usize)
+        .expect("image size overflows usize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_repeats_each_char() {
+        let mut image = Image::new(2, 1);
+        image.draw_char(
+            Point::new(1, 0),
+            Char::new(b'x', Colour::Red, Colour::Black),
+        );
+        let scaled = image.scaled(2, 3).unwrap();
+        assert_eq!((scaled.width, scaled.height), (4, 3));
+        assert_eq!(scaled.get_glyph(Point::new(1, 2)), Some(0));
+        assert_eq!(scaled.get_glyph(Point::new(2, 0)), Some(b'x' as u32));
+        assert_eq!(scaled.get_glyph(Point::new(3, 2)), Some(b'x' as u32));
+    }
+
+    #[test]
+    fn scaled_rejects_sizes_past_u32() {
+        assert!(Image::new(70000, 1).scaled(70000, 1).is_none());
+        assert!(Image::new(1, 2).scaled(1, u32::MAX).is_none());
+        assert_eq!(Image::new(5, 5).scaled(0, 2).unwrap().width, 0);
+    }
+
+    #[test]
+    fn union_covers_both_rects() {
+        let a = Rect::new(0, 0, 2, 2);
+        let b = Rect::new(5, 6, 1, 1);
+        assert_eq!(a.union(b), Rect::new(0, 0, 6, 7));
+        assert_eq!(b.union(a), Rect::new(0, 0, 6, 7));
+        assert_eq!(a.union(Rect::new(2, 0, 3, 2)), Rect::new(0, 0, 5, 2));
+    }
+
+    #[test]
+    fn union_ignores_empty_rects() {
+        let a = Rect::new(3, 4, 5, 6);
+        assert_eq!(a.union(Rect::new(-100, -100, 0, 10)), a);
+        assert_eq!(Rect::new(100, 100, 10, 0).union(a), a);
+        assert!(Rect::default().union(Rect::default()).is_empty());
+    }
+
+    #[test]
+    fn union_saturates_at_extremes() {
+        let a = Rect::new(i32::MIN, i32::MIN, 1, 1);
+        let b = Rect::new(i32::MAX, i32::MAX, u32::MAX, u32::MAX);
+        assert_eq!(
+            a.union(b),
+            Rect::new(i32::MIN, i32::MIN, u32::MAX, u32::MAX)
+        );
+    }
+
+    #[test]
+    fn intersect_overlapping_rects() {
+        let a = Rect::new(0, 0, 4, 4);
+        let b = Rect::new(2, -1, 4, 2);
+        assert_eq!(a.intersect(b), Some(Rect::new(2, 0, 2, 1)));
+        assert_eq!(b.intersect(a), Some(Rect::new(2, 0, 2, 1)));
+        assert_eq!(a.intersect(a), Some(a));
+    }
+
+    #[test]
+    fn intersect_disjoint_and_touching_rects() {
+        let a = Rect::new(0, 0, 4, 4);
+        assert_eq!(a.intersect(Rect::new(10, 10, 4, 4)), None);
+        assert_eq!(a.intersect(Rect::new(-10, 0, 4, 4)), None);
+        // Sharing an edge isn't overlapping.
+        assert_eq!(a.intersect(Rect::new(4, 0, 4, 4)), None);
+        assert_eq!(a.intersect(Rect::new(0, 4, 4, 4)), None);
+        assert_eq!(a.intersect(Rect::new(-4, -4, 4, 4)), None);
+    }
+
+    #[test]
+    fn intersect_empty_rects() {
+        let a = Rect::new(0, 0, 4, 4);
+        assert_eq!(a.intersect(Rect::new(1, 1, 0, 2)), None);
+        assert_eq!(a.intersect(Rect::new(1, 1, 2, 0)), None);
+        assert_eq!(Rect::default().intersect(Rect::default()), None);
+    }
+
+    #[test]
+    fn intersect_at_extremes() {
+        let huge = Rect::new(i32::MIN, i32::MIN, u32::MAX, u32::MAX);
+        let a = Rect::new(-5, 7, 10, 10);
+        assert_eq!(huge.intersect(a), Some(a));
+        assert_eq!(a.intersect(huge), Some(a));
+
+        let far = Rect::new(i32::MAX, i32::MAX, u32::MAX, u32::MAX);
+        assert_eq!(far.intersect(a), None);
+        assert_eq!(
+            far.intersect(huge.translate(1, 1)),
+            Some(Rect::new(i32::MAX, i32::MAX, 1, 1))
+        );
+        assert_eq!(
+            Rect::new(i32::MIN, i32::MIN, 1, 1).intersect(Rect::new(i32::MAX, i32::MAX, 1, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn clip_within_returns_the_visible_part() {
+        assert_eq!(
+            Rect::new(2, 3, 4, 5).clip_within(10, 10),
+            Some((Rect::new(2, 3, 4, 5), Point::new(0, 0)))
+        );
+        assert_eq!(
+            Rect::new(-2, -3, 4, 5).clip_within(10, 10),
+            Some((Rect::new(0, 0, 2, 2), Point::new(2, 3)))
+        );
+        assert_eq!(
+            Rect::new(8, 9, 4, 5).clip_within(10, 10),
+            Some((Rect::new(8, 9, 2, 1), Point::new(0, 0)))
+        );
+    }
+
+    #[test]
+    fn clip_within_outside_or_empty() {
+        assert_eq!(Rect::new(10, 0, 4, 4).clip_within(10, 10), None);
+        assert_eq!(Rect::new(-4, 0, 4, 4).clip_within(10, 10), None);
+        assert_eq!(Rect::new(1, 1, 0, 4).clip_within(10, 10), None);
+        assert_eq!(Rect::new(0, 0, 4, 4).clip_within(0, 0), None);
+    }
+
+    #[test]
+    fn clip_within_at_extremes() {
+        assert_eq!(
+            Rect::new(i32::MAX, i32::MAX, u32::MAX, u32::MAX).clip_within(u32::MAX, u32::MAX),
+            Some((
+                Rect::new(
+                    i32::MAX,
+                    i32::MAX,
+                    u32::MAX - i32::MAX as u32,
+                    u32::MAX - i32::MAX as u32
+                ),
+                Point::new(0, 0)
+            ))
+        );
+        assert_eq!(
+            Rect::new(-10, -10, u32::MAX, u32::MAX).clip_within(4, 4),
+            Some((Rect::new(0, 0, 4, 4), Point::new(10, 10)))
+        );
+        assert_eq!(
+            Rect::new(i32::MIN, 0, 10, 10).clip_within(u32::MAX, 10),
+            None
+        );
+    }
+
+    #[test]
+    fn contains_at_extremes() {
+        let huge = Rect::new(i32::MIN, i32::MIN, u32::MAX, u32::MAX);
+        assert!(huge.contains(Point::new(i32::MIN, i32::MIN)));
+        assert!(huge.contains(Point::new(i32::MAX - 1, 0)));
+        assert!(!huge.contains(Point::new(i32::MAX, 0)));
+        assert!(!Rect::new(0, 0, 0, 0).contains(Point::new(0, 0)));
+        assert!(huge.contains_rect(Rect::new(0, 0, 10, 10)));
+        assert!(!Rect::new(0, 0, 10, 10).contains_rect(huge));
+    }
+
+    #[test]
+    fn translate_inflate_and_deflate_saturate() {
+        let a = Rect::new(i32::MAX - 1, i32::MIN + 1, 2, 2);
+        assert_eq!(a.translate(5, -5), Rect::new(i32::MAX, i32::MIN, 2, 2));
+        assert_eq!(
+            a.inflate(u32::MAX, u32::MAX),
+            Rect::new(i32::MIN, i32::MIN, u32::MAX, u32::MAX)
+        );
+        assert_eq!(Rect::new(0, 0, 5, 4).deflate(10, 1), Rect::new(2, 1, 1, 2));
+    }
+
+    #[test]
+    fn splits_clamp_to_the_rect() {
+        let a = Rect::new(1, 2, 3, 4);
+        assert_eq!(
+            a.split_rows(1),
+            (Rect::new(1, 2, 3, 1), Rect::new(1, 3, 3, 3))
+        );
+        assert_eq!(a.split_rows(10), (a, Rect::new(1, 6, 3, 0)));
+        assert_eq!(a.split_columns(u32::MAX), (a, Rect::new(4, 2, 0, 4)));
+    }
+}
@@ -0,0 +1,275 @@
+//! A compact binary file format for storing [`Image`]s.
+//!
+//! The format starts with a header:
+//!
+//! | Offset | Size | Description                               |
+//! |--------|------|-------------------------------------------|
+//! | 0      | 4    | The magic bytes `MAGI`                    |
+//! | 4      | 1    | The format version                        |
+//! | 5      | 1    | Flags (bit 0 set means the data is RLE'd) |
+//! | 6      | 4    | The width of the image (little-endian)    |
+//! | 10     | 4    | The height of the image (little-endian)   |
+//!
+//! This is followed by the foreground, background and text planes in that
+//! order, each as a sequence of little-endian `u32`s.  If the data is
+//! compressed, each plane is instead stored as a sequence of `(count, value)`
+//! pairs of little-endian `u32`s.
+//!
+//! [`Image`]: ../image/struct.Image.html
+
+use std::{fs, path::Path};
+
+use crate::{error::MageError, image::Image};
+
+/// The magic bytes at the start of every image file.
+const MAGIC: &[u8; 4] = b"MAGI";
+
+/// The current version of the image file format.
+pub const IMAGE_FORMAT_VERSION: u8 = 1;
+
+/// Set in the flags byte if the planes are run-length encoded.
+const FLAG_COMPRESSED: u8 = 1;
+
+/// The most chars an image file can have.  Larger images are rejected before
+/// any memory is allocated for them, as run-length encoded files can claim
+/// sizes far larger than their data.
+const MAX_IMAGE_CELLS: u64 = 1 << 24;
+
+impl Image {
+    /// Saves the image to a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file to write.
+    /// * `compressed` - Whether to run-length encode the image data.  This
+    ///   usually makes ASCII art much smaller.
+    ///
+    pub fn save(&self, path: impl AsRef<Path>, compressed: bool) -> Result<(), MageError> {
+        fs::write(path, self.to_bytes(compressed))?;
+        Ok(())
+    }
+
+    /// Loads an image from a file written by [`save`].
+    ///
+    /// [`save`]: struct.Image.html#method.save
+    ///
+    pub fn load(path: impl AsRef<Path>) -> Result<Image, MageError> {
+        Image::from_bytes(&fs::read(path)?)
+    }
+
+    /// Encodes the image into the binary image format.
+    ///
+    /// # Arguments
+    ///
+    /// * `compressed` - Whether to run-length encode the image data.
+    ///
+    pub fn to_bytes(&self, compressed: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(14 + self.fore_image.len() * 12);
+        data.extend_from_slice(MAGIC);
+        data.push(IMAGE_FORMAT_VERSION);
+        data.push(if compressed { FLAG_COMPRESSED } else { 0 });
+        data.extend_from_slice(&self.width.to_le_bytes());
+        data.extend_from_slice(&self.height.to_le_bytes());
+
+        for plane in [&self.fore_image, &self.back_image, &self.text_image] {
+            if compressed {
+                let mut values = plane.iter().peekable();
+                while let Some(&value) = values.next() {
+                    let mut count = 1u32;
+                    while values.next_if_eq(&&value).is_some() {
+                        count += 1;
+                    }
+                    data.extend_from_slice(&count.to_le_bytes());
+                    data.extend_from_slice(&value.to_le_bytes());
+                }
+            } else {
+                plane
+                    .iter()
+                    .for_each(|value| data.extend_from_slice(&value.to_le_bytes()));
+            }
+        }
+
+        data
+    }
+
+    /// Decodes an image from the binary image format, such as data embedded
+    /// with `include_bytes!`.
+    ///
+    /// # Returns
+    ///
+    /// The decoded image, or [`MageError::InvalidImageData`] if the data is
+    /// not a valid image or the image has more than 16,777,216 chars.
+    ///
+    /// [`MageError::InvalidImageData`]: ../error/enum.MageError.html#variant.InvalidImageData
+    ///
+    pub fn from_bytes(data: &[u8]) -> Result<Image, MageError> {
+        let mut reader = Reader { data };
+        if reader.take(4)? != MAGIC || reader.take(1)?[0] != IMAGE_FORMAT_VERSION {
+            return Err(MageError::InvalidImageData);
+        }
+        let compressed = reader.take(1)?[0] & FLAG_COMPRESSED != 0;
+        let width = reader.read_u32()?;
+        let height = reader.read_u32()?;
+        let size = width as u64 * height as u64;
+        if size > MAX_IMAGE_CELLS {
+            return Err(MageError::InvalidImageData);
+        }
+        let size = size as usize;
+
+        let mut read_plane = || -> Result<Vec<u32>, MageError> {
+            let mut plane = Vec::with_capacity(size.min(reader.data.len() / 4));
+            while plane.len() < size {
+                let (count, value) = if compressed {
+                    (reader.read_u32()? as usize, reader.read_u32()?)
+                } else {
+                    (1, reader.read_u32()?)
+                };
+                if count == 0 || count > size - plane.len() {
+                    return Err(MageError::InvalidImageData);
+                }
+                plane.resize(plane.len() + count, value);
+            }
+            Ok(plane)
+        };
+
+        let fore_image = read_plane()?;
+        let back_image = read_plane()?;
+        let text_image = read_plane()?;
+        if !reader.data.is_empty() {
+            return Err(MageError::InvalidImageData);
+        }
+
+        Ok(Image {
+            width,
+            height,
+            fore_image,
+            back_image,
+            text_image,
+        })
+    }
+}
+
+/// Reads values from the front of a byte slice.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MageError> {
+        if self.data.len() < len {
+            return Err(MageError::InvalidImageData);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, MageError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{colour::Colour, image::Point};
+
+    fn sample() -> Image {
+        let mut image = Image::new(5, 3);
+        image.clear(Colour::White, Colour::Blue);
+        image.draw_string(
+            Point::new(1, 1),
+            "abc",
+            Colour::Yellow,
+            Colour::Rgb(1, 2, 3),
+        );
+        image
+    }
+
+    #[test]
+    fn round_trips_uncompressed() {
+        let image = sample();
+        assert_eq!(Image::from_bytes(&image.to_bytes(false)).unwrap(), image);
+    }
+
+    #[test]
+    fn round_trips_compressed() {
+        let image = sample();
+        let data = image.to_bytes(true);
+        assert!(data.len() < image.to_bytes(false).len());
+        assert_eq!(Image::from_bytes(&data).unwrap(), image);
+    }
+
+    #[test]
+    fn round_trips_empty_image() {
+        let image = Image::new(0, 0);
+        assert_eq!(Image::from_bytes(&image.to_bytes(true)).unwrap(), image);
+    }
+
+    #[test]
+    fn rejects_bad_header() {
+        let mut data = sample().to_bytes(false);
+        data[0] = b'X';
+        assert!(matches!(
+            Image::from_bytes(&data),
+            Err(MageError::InvalidImageData)
+        ));
+
+        let mut data = sample().to_bytes(false);
+        data[4] = IMAGE_FORMAT_VERSION + 1;
+        assert!(matches!(
+            Image::from_bytes(&data),
+            Err(MageError::InvalidImageData)
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_and_trailing_data() {
+        let data = sample().to_bytes(true);
+        for len in 0..data.len() {
+            assert!(Image::from_bytes(&data[..len]).is_err());
+        }
+
+        let mut data = data;
+        data.push(0);
+        assert!(Image::from_bytes(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_runs_past_the_plane() {
+        let mut data = Image::new(2, 2).to_bytes(true);
+        // The first run covers the whole foreground plane; make it one longer.
+        data[14] = 5;
+        assert!(Image::from_bytes(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_overflowing_size() {
+        let mut data = Vec::from(&MAGIC[..]);
+        data.push(IMAGE_FORMAT_VERSION);
+        data.push(0);
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Image::from_bytes(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_images_over_the_cell_cap() {
+        // 65536x32768, with a single run covering each plane.
+        let mut data = Vec::from(&MAGIC[..]);
+        data.push(IMAGE_FORMAT_VERSION);
+        data.push(FLAG_COMPRESSED);
+        data.extend_from_slice(&65536u32.to_le_bytes());
+        data.extend_from_slice(&32768u32.to_le_bytes());
+        for _ in 0..3 {
+            data.extend_from_slice(&(1u32 << 31).to_le_bytes());
+            data.extend_from_slice(&0u32.to_le_bytes());
+        }
+        assert!(Image::from_bytes(&data).is_err());
+
+        // The largest image allowed still loads.
+        let image = Image::new(4096, 4096);
+        assert_eq!(Image::from_bytes(&image.to_bytes(true)).unwrap(), image);
+    }
+}
@@ -1,4 +1,12 @@
-use winit::keyboard::ModifiersState;
+use winit::{
+    event::MouseButton,
+    keyboard::{KeyCode, ModifiersState},
+};
+
+use crate::{
+    image::Point,
+    ui::{Key, KeyPress},
+};
 
 pub struct ShiftState {
     shift: bool,
@@ -67,3 +75,482 @@ impl Default for ShiftState {
         Self::new()
     }
 }
+
+/// The state of the mouse, passed to the app in [`TickInput::mouse`].
+///
+/// [`TickInput::mouse`]: ../struct.TickInput.html#structfield.mouse
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MouseState {
+    /// The coordinates of the char under the mouse pointer, or `None` if the
+    /// pointer is outside the window.
+    pub position: Option<Point>,
+
+    /// The state of the left mouse button.
+    pub left: MouseButtonState,
+
+    /// The state of the right mouse button.
+    pub right: MouseButtonState,
+
+    /// The state of the middle mouse button.
+    pub middle: MouseButtonState,
+
+    /// The number of lines scrolled by the mouse wheel since the last tick.
+    /// Positive values scroll up, away from the user.
+    pub wheel: i32,
+}
+
+/// The state of a single mouse button.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MouseButtonState {
+    /// True if the button is currently held down.
+    pub down: bool,
+
+    /// True if the button was pressed since the last tick.
+    pub pressed: bool,
+
+    /// True if the button was released since the last tick.
+    pub released: bool,
+}
+
+impl MouseState {
+    /// Updates the state of a mouse button.
+    pub(crate) fn update_button(&mut self, button: MouseButton, down: bool) {
+        let state = match button {
+            MouseButton::Left => &mut self.left,
+            MouseButton::Right => &mut self.right,
+            MouseButton::Middle => &mut self.middle,
+            _ => return,
+        };
+        if down && !state.down {
+            state.pressed = true;
+        } else if !down && state.down {
+            state.released = true;
+        }
+        state.down = down;
+    }
+
+    /// Clears the changes since the last tick, ready for the next tick.
+    pub(crate) fn end_tick(&mut self) {
+        for button in [&mut self.left, &mut self.right, &mut self.middle] {
+            button.pressed = false;
+            button.released = false;
+        }
+        self.wheel = 0;
+    }
+}
+
+/// A key pressed or released, passed to the app in [`TickInput::keys`].
+///
+/// Keys are the physical keys on the keyboard, named after what they type on
+/// a US layout, so `KeyCode::KeyW` is the key above `KeyCode::KeyS` whatever
+/// the player's layout.  This suits movement and shortcuts; the text a key
+/// types depends on the layout.
+///
+/// [`TickInput::keys`]: ../struct.TickInput.html#structfield.keys
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    /// The key that was pressed or released.
+    pub key: KeyCode,
+
+    /// True if the key was pressed, or false if it was released.
+    pub pressed: bool,
+
+    /// True if the key was pressed again by being held down.
+    pub repeat: bool,
+
+    /// True if a shift key was held.
+    pub shift: bool,
+
+    /// True if a control key was held.
+    pub ctrl: bool,
+
+    /// True if an alt key was held.
+    pub alt: bool,
+}
+
+impl KeyEvent {
+    /// Creates a key press with no modifier keys held.
+    pub fn press(key: KeyCode) -> Self {
+        Self {
+            key,
+            pressed: true,
+            repeat: false,
+            shift: false,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    /// Creates a key release with no modifier keys held.
+    pub fn release(key: KeyCode) -> Self {
+        Self {
+            pressed: false,
+            ..Self::press(key)
+        }
+    }
+
+    /// Returns the key event with shift held.
+    pub fn with_shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    /// Returns the key event with control held.
+    pub fn with_ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    /// Returns the key event with alt held.
+    pub fn with_alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Returns the key press that controls widgets, or `None` if this is a
+    /// release or the key doesn't control widgets.  Only the space bar types
+    /// a char, as the chars other keys type depend on the layout.
+    pub fn to_key_press(&self) -> Option<KeyPress> {
+        if !self.pressed {
+            return None;
+        }
+        let key = match self.key {
+            KeyCode::ArrowUp => Key::Up,
+            KeyCode::ArrowDown => Key::Down,
+            KeyCode::ArrowLeft => Key::Left,
+            KeyCode::ArrowRight => Key::Right,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::Enter | KeyCode::NumpadEnter => Key::Enter,
+            KeyCode::Escape => Key::Escape,
+            KeyCode::Tab => Key::Tab,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Delete => Key::Delete,
+            KeyCode::Space => Key::Char(' '),
+            _ => return None,
+        };
+        Some(KeyPress {
+            key,
+            shift: self.shift,
+            ctrl: self.ctrl,
+            alt: self.alt,
+        })
+    }
+}
+
+/// Text being composed with an input method, such as for Chinese or
+/// Japanese, passed to the app in [`TickInput::composition`].  It isn't typed
+/// until the player commits it, when it is given in [`TickInput::text`].
+///
+/// [`TickInput::composition`]: ../struct.TickInput.html#structfield.composition
+/// [`TickInput::text`]: ../struct.TickInput.html#structfield.text
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Composition {
+    /// The text composed so far.
+    pub text: String,
+
+    /// The start and end of the cursor or selection within the text, as byte
+    /// offsets, or `None` if the cursor should be hidden.
+    pub cursor: Option<(usize, usize)>,
+}
+
+/// Returns true if text typed with a key should be given to the app, which
+/// it isn't for control chars, such as from Enter and Backspace, or for
+/// shortcuts with control held.  Control and alt together are AltGr, which
+/// types chars on many layouts.
+pub(crate) fn is_typed_text(text: &str, shift_state: &ShiftState) -> bool {
+    !text.chars().any(char::is_control) && (!shift_state.ctrl_down() || shift_state.alt_down())
+}
+
+/// A key along with the modifier keys that must be held with it, such as
+/// Alt+Enter, for the keys the engine handles itself.
+///
+/// A binding only matches when exactly its modifier keys are held, so
+/// Escape doesn't match Shift+Escape.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    /// The key that must be pressed.
+    pub key: KeyCode,
+
+    /// True if a shift key must be held.
+    pub shift: bool,
+
+    /// True if a control key must be held.
+    pub ctrl: bool,
+
+    /// True if an alt key must be held.
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    /// Creates a binding for a key with no modifier keys held.
+    pub fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            shift: false,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    /// Returns the binding with shift held.
+    pub fn with_shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    /// Returns the binding with control held.
+    pub fn with_ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    /// Returns the binding with alt held.
+    pub fn with_alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Returns true if a key event is a press of this binding.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        event.pressed
+            && (event.key, event.shift, event.ctrl, event.alt)
+                == (self.key, self.shift, self.ctrl, self.alt)
+    }
+
+    /// Returns true if a key pressed with the modifier keys held matches this
+    /// binding.
+    pub(crate) fn is_pressed(&self, key: KeyCode, shift_state: &ShiftState) -> bool {
+        (
+            key,
+            shift_state.shift_down(),
+            shift_state.ctrl_down(),
+            shift_state.alt_down(),
+        ) == (self.key, self.shift, self.ctrl, self.alt)
+    }
+}
+
+impl From<KeyCode> for KeyBinding {
+    fn from(key: KeyCode) -> Self {
+        Self::new(key)
+    }
+}
+
+/// Returns the number a key is stored as in recordings, or `None` if it
+/// can't be stored.
+pub(crate) fn key_code_index(key: KeyCode) -> Option<u16> {
+    KEY_CODES
+        .iter()
+        .position(|&code| code == key)
+        .map(|index| index as u16)
+}
+
+/// Returns the key stored as a number in recordings, or `None` if no key is.
+pub(crate) fn key_code_from_index(index: u16) -> Option<KeyCode> {
+    KEY_CODES.get(index as usize).copied()
+}
+
+/// Every key, in the order they are numbered in recordings.  New keys must
+/// only be added to the end, so that older recordings can still be read.
+const KEY_CODES: &[KeyCode] = &[
+    KeyCode::Backquote,
+    KeyCode::Backslash,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Comma,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Equal,
+    KeyCode::IntlBackslash,
+    KeyCode::IntlRo,
+    KeyCode::IntlYen,
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Minus,
+    KeyCode::Period,
+    KeyCode::Quote,
+    KeyCode::Semicolon,
+    KeyCode::Slash,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::Backspace,
+    KeyCode::CapsLock,
+    KeyCode::ContextMenu,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::Enter,
+    KeyCode::SuperLeft,
+    KeyCode::SuperRight,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::Space,
+    KeyCode::Tab,
+    KeyCode::Convert,
+    KeyCode::KanaMode,
+    KeyCode::Lang1,
+    KeyCode::Lang2,
+    KeyCode::Lang3,
+    KeyCode::Lang4,
+    KeyCode::Lang5,
+    KeyCode::NonConvert,
+    KeyCode::Delete,
+    KeyCode::End,
+    KeyCode::Help,
+    KeyCode::Home,
+    KeyCode::Insert,
+    KeyCode::PageDown,
+    KeyCode::PageUp,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::ArrowUp,
+    KeyCode::NumLock,
+    KeyCode::Numpad0,
+    KeyCode::Numpad1,
+    KeyCode::Numpad2,
+    KeyCode::Numpad3,
+    KeyCode::Numpad4,
+    KeyCode::Numpad5,
+    KeyCode::Numpad6,
+    KeyCode::Numpad7,
+    KeyCode::Numpad8,
+    KeyCode::Numpad9,
+    KeyCode::NumpadAdd,
+    KeyCode::NumpadBackspace,
+    KeyCode::NumpadClear,
+    KeyCode::NumpadClearEntry,
+    KeyCode::NumpadComma,
+    KeyCode::NumpadDecimal,
+    KeyCode::NumpadDivide,
+    KeyCode::NumpadEnter,
+    KeyCode::NumpadEqual,
+    KeyCode::NumpadHash,
+    KeyCode::NumpadMemoryAdd,
+    KeyCode::NumpadMemoryClear,
+    KeyCode::NumpadMemoryRecall,
+    KeyCode::NumpadMemoryStore,
+    KeyCode::NumpadMemorySubtract,
+    KeyCode::NumpadMultiply,
+    KeyCode::NumpadParenLeft,
+    KeyCode::NumpadParenRight,
+    KeyCode::NumpadStar,
+    KeyCode::NumpadSubtract,
+    KeyCode::Escape,
+    KeyCode::Fn,
+    KeyCode::FnLock,
+    KeyCode::PrintScreen,
+    KeyCode::ScrollLock,
+    KeyCode::Pause,
+    KeyCode::BrowserBack,
+    KeyCode::BrowserFavorites,
+    KeyCode::BrowserForward,
+    KeyCode::BrowserHome,
+    KeyCode::BrowserRefresh,
+    KeyCode::BrowserSearch,
+    KeyCode::BrowserStop,
+    KeyCode::Eject,
+    KeyCode::LaunchApp1,
+    KeyCode::LaunchApp2,
+    KeyCode::LaunchMail,
+    KeyCode::MediaPlayPause,
+    KeyCode::MediaSelect,
+    KeyCode::MediaStop,
+    KeyCode::MediaTrackNext,
+    KeyCode::MediaTrackPrevious,
+    KeyCode::Power,
+    KeyCode::Sleep,
+    KeyCode::AudioVolumeDown,
+    KeyCode::AudioVolumeMute,
+    KeyCode::AudioVolumeUp,
+    KeyCode::WakeUp,
+    KeyCode::Meta,
+    KeyCode::Hyper,
+    KeyCode::Turbo,
+    KeyCode::Abort,
+    KeyCode::Resume,
+    KeyCode::Suspend,
+    KeyCode::Again,
+    KeyCode::Copy,
+    KeyCode::Cut,
+    KeyCode::Find,
+    KeyCode::Open,
+    KeyCode::Paste,
+    KeyCode::Props,
+    KeyCode::Select,
+    KeyCode::Undo,
+    KeyCode::Hiragana,
+    KeyCode::Katakana,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::F13,
+    KeyCode::F14,
+    KeyCode::F15,
+    KeyCode::F16,
+    KeyCode::F17,
+    KeyCode::F18,
+    KeyCode::F19,
+    KeyCode::F20,
+    KeyCode::F21,
+    KeyCode::F22,
+    KeyCode::F23,
+    KeyCode::F24,
+    KeyCode::F25,
+    KeyCode::F26,
+    KeyCode::F27,
+    KeyCode::F28,
+    KeyCode::F29,
+    KeyCode::F30,
+    KeyCode::F31,
+    KeyCode::F32,
+    KeyCode::F33,
+    KeyCode::F34,
+    KeyCode::F35,
+];
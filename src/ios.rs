@@ -0,0 +1,20 @@
+//! Support for running as an iOS app.
+
+use winit::{
+    platform::ios::WindowBuilderExtIOS,
+    window::{Window, WindowBuilder},
+};
+
+/// Sets up a window builder to fill the screen, hiding the status bar and
+/// the home indicator so the whole screen belongs to the game.
+pub(crate) fn with_full_screen(builder: WindowBuilder) -> WindowBuilder {
+    builder
+        .with_prefers_status_bar_hidden(true)
+        .with_prefers_home_indicator_hidden(true)
+}
+
+/// Returns how many screen pixels wide and high each pixel of the font should
+/// be drawn, so that text on a Retina display is as big as on other displays.
+pub(crate) fn pixel_scale(window: &Window) -> u32 {
+    window.scale_factor().round().max(1.0) as u32
+}
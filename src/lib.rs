@@ -1,35 +1,130 @@
+pub mod accessibility;
+#[cfg(target_os = "android")]
+mod android;
+pub mod animation;
+pub mod ansi;
 pub mod app;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod assets;
+pub mod canvas;
+#[cfg(all(
+    feature = "clipboard",
+    not(any(target_arch = "wasm32", target_os = "android", target_os = "ios"))
+))]
+mod clipboard;
+pub mod codepage;
 pub mod colour;
+pub mod compact;
 pub mod config;
+pub mod context;
+pub mod effects;
 pub mod error;
+pub mod export;
+pub mod fov;
+#[cfg(feature = "frame-texture")]
+pub mod frame_texture;
+pub mod gamepad;
+#[cfg(all(feature = "gilrs", not(target_arch = "wasm32")))]
+mod gilrs_gamepad;
+pub mod gradient;
+#[cfg(feature = "embedded-graphics")]
+pub mod graphics;
+pub mod headless;
 pub mod image;
+pub mod imagefile;
 pub mod input;
+#[cfg(target_os = "ios")]
+mod ios;
+pub mod lines;
+pub mod mask;
+pub mod minimap;
+pub mod palette;
+pub mod path;
 pub mod present;
+mod profile;
 pub mod render;
+mod renderer;
+pub mod replay;
+pub mod rexpaint;
+#[cfg(feature = "scripting")]
+pub mod script;
+mod selection;
+mod simd;
+pub mod sprite;
+pub mod stats;
+pub mod table;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod telnet;
+pub mod testing;
+pub mod transition;
+pub mod tween;
+pub mod ui;
+pub mod viewport;
+#[cfg(target_arch = "wasm32")]
+mod web;
+pub mod zoom;
 
-use std::cmp::max;
+use std::{cell::RefCell, cmp::max, rc::Rc, sync::Arc};
 
-use chrono::{Duration, Local};
+use chrono::Local;
 use error::MageError;
 use render::RenderState;
+use renderer::Renderer;
 use tracing::{error, info};
 use wgpu::SurfaceError;
 use winit::{
     dpi::PhysicalSize,
-    event::{ElementState, Event, KeyEvent, WindowEvent},
-    event_loop::{ControlFlow, EventLoop},
+    event::{
+        ElementState, Event, Ime, KeyEvent, MouseButton, MouseScrollDelta, Touch, TouchPhase,
+        WindowEvent,
+    },
+    event_loop::{ControlFlow, EventLoopWindowTarget},
     keyboard::{KeyCode, PhysicalKey},
     window::WindowBuilder,
 };
 
 use winit_fullscreen::WindowFullScreen;
 
-use crate::input::ShiftState;
+#[cfg(target_os = "ios")]
+use crate::ios::pixel_scale as base_pixel_scale;
+use crate::{
+    animation::AnimationPhase,
+    context::{ContextRequest, MageContext},
+    gamepad::Gamepad,
+    input::{Composition, MouseState, ShiftState},
+    selection::Selection,
+};
 
 pub use app::*;
 pub use colour::*;
 pub use config::*;
+#[cfg(feature = "raw-window-handle")]
+pub use raw_window_handle;
+
+/// The number of frames in a row that can fail to draw before the app is
+/// told with [`App::render_failed`].
+///
+/// [`App::render_failed`]: trait.App.html#method.render_failed
+///
+const RENDER_FAILURE_LIMIT: u32 = 3;
 
+/// Opens a window and runs a game in it until it quits.
+///
+/// # Arguments
+///
+/// * `app` - The game to run.
+/// * `config` - How to set up the window.
+///
+/// # Notes
+///
+/// In a web page, the game draws to a canvas and this returns as soon as the
+/// game has started, as the browser drives the game from then on.  Run the
+/// future returned with `wasm_bindgen_futures::spawn_local`.
+///
+/// Once the game has started, an error is returned if the event loop fails,
+/// the window's surface can't be recreated after the app is resumed, or the
+/// GPU runs out of memory while drawing.
+///
 pub async fn run<A>(mut app: A, config: Config) -> Result<(), MageError>
 where
     A: App + 'static,
@@ -42,6 +137,14 @@ where
         Font::Default => load_font_image(include_bytes!("font1.png"))?,
         Font::Custom(font) => font,
     };
+    font_data.validate()?;
+    let (inner_width, inner_height) = config.inner_size;
+    if inner_width == 0 || inner_height == 0 {
+        return Err(MageError::InvalidWindowSize {
+            width: inner_width,
+            height: inner_height,
+        });
+    }
 
     // Adjust the dimensions of the window to fit character cells exactly.
     let width = max(
@@ -65,68 +168,222 @@ where
     // Set up window, game state and event loop
     //
 
-    let event_loop = EventLoop::new()?;
+    #[cfg(target_os = "android")]
+    let mut event_loop = android::event_loop(config.android_app)?;
+    #[cfg(not(target_os = "android"))]
+    let event_loop = winit::event_loop::EventLoop::new()?;
 
-    let window = WindowBuilder::new()
+    let window_builder = WindowBuilder::new()
         .with_inner_size(PhysicalSize::new(width, height))
         .with_title(config.title.unwrap_or("Mage Game".to_string()))
         .with_min_inner_size(PhysicalSize::new(
             MIN_WINDOW_SIZE.0 * font_data.char_width,
             MIN_WINDOW_SIZE.1 * font_data.char_height,
-        ))
-        .build(&event_loop)?;
+        ));
+    #[cfg(target_arch = "wasm32")]
+    let window_builder = web::with_canvas(window_builder, config.canvas_id.as_deref())?;
+    #[cfg(target_os = "ios")]
+    let window_builder = ios::with_full_screen(window_builder);
+    // The window is shared with the surface, which may be on the render
+    // thread, and on the web outlives this function with the event loop.
+    let window = Arc::new(window_builder.build(&event_loop)?);
+    window.set_ime_allowed(config.ime);
+
+    // Android apps can't draw to their window until they are first resumed.
+    #[cfg(target_os = "android")]
+    if !android::wait_for_resume(&mut event_loop) {
+        return Ok(());
+    }
 
-    let mut render_state = RenderState::new(&window, font_data).await?;
+    let mut render_state = RenderState::new(
+        window.clone(),
+        font_data,
+        config.gpu,
+        config.vsync,
+        config.present_mode,
+    )
+    .await?;
+    render_state.set_colour_vision(config.colour_vision);
+    let start_zoom = zoom::clamp_zoom(config.zoom);
+    let mut current_zoom = start_zoom;
+    render_state.set_pixel_scale(base_pixel_scale(&window) * current_zoom);
+    #[cfg(feature = "frame-texture")]
+    if let Some(sink) = config.frame_sink {
+        render_state.set_frame_sink(sink);
+    }
+    let mut renderer = Renderer::new(render_state, config.render_thread, config.triple_buffering)?;
+    #[cfg(feature = "raw-window-handle")]
+    app.window_created(WindowHandles::new(&window));
     let mut shift_state = ShiftState::new();
+    let mut mouse_state = MouseState::default();
+    let mut keys = Vec::new();
+    let mut typed_text = String::new();
+    let mut composition = None;
+    let mut gamepads = Vec::new();
+    #[cfg(all(
+        feature = "clipboard",
+        not(any(target_arch = "wasm32", target_os = "android", target_os = "ios"))
+    ))]
+    let mut clipboard = clipboard::Clipboard::new();
+    #[cfg(all(feature = "gilrs", not(target_arch = "wasm32")))]
+    let mut gilrs_gamepads = gilrs_gamepad::Gamepads::new();
+    let context = MageContext::default();
+    let mut wheel_pixels = 0.0;
+    let mut touch_id = None;
 
+    let frame_policy = config.frame_policy;
+    let mut animation = AnimationPhase::new(config.blink_period);
+    let quit_key = config.quit_key;
+    let fullscreen_key = config.fullscreen_key;
+    let selection_key = config.selection_key;
+    let zoom_keys = config.zoom_keys;
+    let mut selection = Selection::default();
+    let mut skipped_frames = 0;
+    let mut skip_frame = false;
     let mut current_time = Local::now();
+    let mut render_failures = 0;
+    let mut window_hidden = false;
+
+    // An error that ended the game loop, to return once it has finished.
+    let run_error = Rc::new(RefCell::new(None));
+    let loop_error = run_error.clone();
 
     //
     // Run the game loop
     //
 
-    let _ = event_loop.run(move |event, ev_loop| {
-        ev_loop.set_control_flow(ControlFlow::Poll);
+    let event_handler = move |event: Event<()>, ev_loop: &EventLoopWindowTarget<()>| {
+        // Browsers call back for each frame when a redraw is requested, so
+        // there is no need to poll there.
+        if cfg!(target_arch = "wasm32") {
+            ev_loop.set_control_flow(ControlFlow::Wait);
+        } else {
+            ev_loop.set_control_flow(ControlFlow::Poll);
+        }
 
         match event {
-            Event::WindowEvent { window_id, event } if window_id == render_state.window.id() => {
+            Event::WindowEvent { window_id, event } if window_id == renderer.window().id() => {
                 match event {
-                    // Detect window close and escape key for application exit
+                    // Detect window close and the quit key for application exit
                     WindowEvent::CloseRequested => ev_loop.exit(),
                     WindowEvent::KeyboardInput {
                         event:
                             KeyEvent {
                                 state: ElementState::Pressed,
+                                physical_key: PhysicalKey::Code(key),
+                                ..
+                            },
+                        ..
+                    } if quit_key.is_some_and(|binding| binding.is_pressed(key, &shift_state)) => {
+                        ev_loop.exit()
+                    }
 
-                                physical_key: PhysicalKey::Code(KeyCode::Escape),
+                    // Detect the fullscreen key (ALT+ENTER by default) for fullscreen toggle
+                    WindowEvent::KeyboardInput {
+                        event:
+                            KeyEvent {
+                                state: ElementState::Pressed,
+                                physical_key: PhysicalKey::Code(key),
                                 ..
                             },
                         ..
-                    } => ev_loop.exit(),
+                    } if fullscreen_key
+                        .is_some_and(|binding| binding.is_pressed(key, &shift_state)) =>
+                    {
+                        renderer.window().toggle_fullscreen();
+                    }
 
-                    // Detect ALT+ENTER for fullscreen toggle
+                    // Switch selecting text with the mouse on and off
                     WindowEvent::KeyboardInput {
                         event:
                             KeyEvent {
                                 state: ElementState::Pressed,
-                                physical_key: PhysicalKey::Code(KeyCode::Enter),
+                                physical_key: PhysicalKey::Code(key),
+                                repeat: false,
                                 ..
                             },
                         ..
-                    } if shift_state.alt_only() => {
-                        render_state.window.toggle_fullscreen();
+                    } if Some(key) == selection_key => selection.toggle(),
+
+                    // Detect CTRL+=, CTRL+- and CTRL+0 for zooming the font
+                    WindowEvent::KeyboardInput {
+                        event:
+                            KeyEvent {
+                                state: ElementState::Pressed,
+                                physical_key:
+                                    PhysicalKey::Code(
+                                        key @ (KeyCode::Equal
+                                        | KeyCode::NumpadAdd
+                                        | KeyCode::Minus
+                                        | KeyCode::NumpadSubtract
+                                        | KeyCode::Digit0
+                                        | KeyCode::Numpad0),
+                                    ),
+                                ..
+                            },
+                        ..
+                    } if zoom_keys && shift_state.ctrl_only() => {
+                        // The zoom is applied with any the app asks for.
+                        match key {
+                            KeyCode::Equal | KeyCode::NumpadAdd => zoom::set_zoom(current_zoom + 1),
+                            KeyCode::Minus | KeyCode::NumpadSubtract => {
+                                zoom::set_zoom(current_zoom - 1)
+                            }
+                            _ => zoom::set_zoom(start_zoom),
+                        }
+                    }
+
+                    // Queue every other key, and the text it types, for the app's next tick
+                    WindowEvent::KeyboardInput { event, .. } => {
+                        let pressed = event.state == ElementState::Pressed;
+                        #[cfg(target_os = "android")]
+                        let text = android::key_text(&event);
+                        #[cfg(not(target_os = "android"))]
+                        let text = event.text;
+                        if let Some(text) = text
+                            .filter(|text| input::is_typed_text(text, &shift_state))
+                            .filter(|_| pressed)
+                        {
+                            typed_text.push_str(&text);
+                        }
+                        if let PhysicalKey::Code(key) = event.physical_key {
+                            keys.push(input::KeyEvent {
+                                key,
+                                pressed,
+                                repeat: event.repeat,
+                                shift: shift_state.shift_down(),
+                                ctrl: shift_state.ctrl_down(),
+                                alt: shift_state.alt_down(),
+                            });
+                        }
                     }
 
+                    // Text typed with an input method, such as for Chinese or Japanese
+                    WindowEvent::Ime(ime) => match ime {
+                        Ime::Commit(text) => {
+                            typed_text.push_str(&text);
+                            composition = None;
+                        }
+                        Ime::Preedit(text, cursor) => {
+                            composition =
+                                (!text.is_empty()).then_some(Composition { text, cursor });
+                        }
+                        Ime::Enabled => {}
+                        Ime::Disabled => composition = None,
+                    },
+
                     // Detect window resize and scale factor change.  When this happens, the
                     // GPU surface is lost and must be recreated.
                     WindowEvent::Resized(new_size) => {
                         info!("Resized to {:?}", new_size);
-                        render_state.resize(new_size);
+                        renderer.resize(new_size);
                     }
                     WindowEvent::ScaleFactorChanged { .. } => {
-                        let new_size = render_state.window.inner_size();
+                        let scale = base_pixel_scale(renderer.window()) * current_zoom;
+                        renderer.set_pixel_scale(scale);
+                        let new_size = renderer.window().inner_size();
                         info!("Resized to {:?}", new_size);
-                        render_state.resize(new_size);
+                        renderer.resize(new_size);
                     }
 
                     // Detect shift keys for shift state
@@ -134,65 +391,305 @@ where
                         shift_state.update(modifiers.state());
                     }
 
+                    // Track the mouse for the mouse state
+                    WindowEvent::CursorMoved { position, .. } => {
+                        mouse_state.position = renderer.char_at(position);
+                        selection.drag(mouse_state.position);
+                    }
+                    WindowEvent::CursorLeft { .. } => mouse_state.position = None,
+
+                    // The left button selects text instead while selecting is on.
+                    WindowEvent::MouseInput {
+                        state,
+                        button: MouseButton::Left,
+                        ..
+                    } if selection.is_active() => {
+                        if state == ElementState::Pressed {
+                            selection.press(mouse_state.position);
+                        } else if let Some(rect) = selection.release() {
+                            let text = Selection::text(renderer.screen(), rect);
+                            #[cfg(target_arch = "wasm32")]
+                            web::copy_to_clipboard(&text);
+                            #[cfg(all(
+                                feature = "clipboard",
+                                not(any(
+                                    target_arch = "wasm32",
+                                    target_os = "android",
+                                    target_os = "ios"
+                                ))
+                            ))]
+                            clipboard.copy(&text);
+                            app.selection_copied(text);
+                        }
+                    }
+                    WindowEvent::MouseInput { state, button, .. } => {
+                        mouse_state.update_button(button, state == ElementState::Pressed);
+                    }
+
+                    // The first finger on a touch screen acts as the left mouse button, so
+                    // taps work as clicks.
+                    WindowEvent::Touch(Touch {
+                        phase,
+                        location,
+                        id,
+                        ..
+                    }) if touch_id.is_none_or(|touch_id| touch_id == id) => {
+                        mouse_state.position = renderer.char_at(location);
+                        match phase {
+                            TouchPhase::Started => {
+                                touch_id = Some(id);
+                                mouse_state.update_button(MouseButton::Left, true);
+                            }
+                            TouchPhase::Ended | TouchPhase::Cancelled => {
+                                touch_id = None;
+                                mouse_state.update_button(MouseButton::Left, false);
+                            }
+                            TouchPhase::Moved => {}
+                        }
+                    }
+                    WindowEvent::MouseWheel { delta, .. } => match delta {
+                        MouseScrollDelta::LineDelta(_, lines) => {
+                            mouse_state.wheel += lines.round() as i32;
+                        }
+                        MouseScrollDelta::PixelDelta(position) => {
+                            // Scroll by a line for each char height of movement.
+                            wheel_pixels += position.y;
+                            let char_height = renderer.char_height() as f64;
+                            let lines = (wheel_pixels / char_height).trunc();
+                            wheel_pixels -= lines * char_height;
+                            mouse_state.wheel += lines as i32;
+                        }
+                    },
+
+                    WindowEvent::RedrawRequested if skip_frame => profile::end_frame(),
                     WindowEvent::RedrawRequested => {
-                        if present(&mut app, &mut render_state) == PresentResult::Changed {
-                            match render_state.render() {
-                                Ok(_) => {}
-                                Err(SurfaceError::Lost) => {
-                                    info!("Surface lost, recreating");
-                                    render_state.resize(render_state.window.inner_size());
-                                }
-                                Err(SurfaceError::OutOfMemory) => {
-                                    error!("Out of memory, exiting");
-                                    ev_loop.exit();
+                        let selection_changed = selection.take_changed();
+                        if present(&mut app, &mut renderer, animation) == PresentResult::Changed
+                            || renderer.is_frame_pending()
+                            || selection_changed
+                        {
+                            // The selection is only highlighted while it is drawn, so
+                            // the app never sees it.
+                            selection.toggle_highlight(renderer.screen());
+                            let render_result = renderer.render();
+                            selection.toggle_highlight(renderer.screen());
+                            match render_result {
+                                Ok(_) => render_failures = 0,
+                                Err(error) => {
+                                    render_failures += 1;
+                                    let fatal = matches!(
+                                        error,
+                                        MageError::RenderError(SurfaceError::OutOfMemory)
+                                            | MageError::RenderThreadStopped
+                                    );
+                                    match &error {
+                                        MageError::RenderError(
+                                            SurfaceError::Lost | SurfaceError::Outdated,
+                                        ) => {
+                                            info!("Surface lost, recreating");
+                                            renderer.resize(renderer.window().inner_size());
+                                        }
+                                        MageError::RenderError(SurfaceError::OutOfMemory) => {
+                                            error!("Out of memory, exiting")
+                                        }
+                                        _ => error!("Error: {}", error),
+                                    }
+
+                                    // Give the app a chance to save before it is shut down, or
+                                    // to give up once drawing keeps failing.
+                                    if fatal || render_failures >= RENDER_FAILURE_LIMIT {
+                                        let result = app.render_failed(&error, render_failures);
+                                        if fatal {
+                                            *run_error.borrow_mut() = Some(error);
+                                            ev_loop.exit();
+                                        } else if result == TickResult::Quit {
+                                            ev_loop.exit();
+                                        }
+                                    }
                                 }
-                                Err(e) => error!("Error: {:?}", e),
                             }
                         }
+                        profile::end_frame();
                     }
 
                     _ => (),
                 }
             }
+
+            // Some platforms, such as Android, take the window's surface away while
+            // the app is in the background.
+            Event::Suspended => renderer.suspend(),
+            Event::Resumed => match renderer.resume() {
+                #[cfg(feature = "raw-window-handle")]
+                Ok(true) => app.window_created(WindowHandles::new(renderer.window())),
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Unable to recreate surface: {}", e);
+                    *run_error.borrow_mut() = Some(e);
+                    ev_loop.exit();
+                }
+            },
             Event::AboutToWait => {
                 let new_time = Local::now();
                 let dt = new_time - current_time;
                 current_time = new_time;
 
-                if tick(&mut app, &mut render_state, dt) == TickResult::Quit {
-                    ev_loop.exit();
+                // Tell the app when the window is minimised or restored, before it
+                // is next ticked.
+                if renderer.is_hidden() != window_hidden {
+                    window_hidden = renderer.is_hidden();
+                    app.window_hidden(window_hidden);
                 }
-                render_state.window.request_redraw();
+
+                // Apply any zoom asked for by the player or the app before the
+                // app is next ticked.
+                if let Some(new_zoom) = zoom::take_request() {
+                    if new_zoom != current_zoom {
+                        current_zoom = new_zoom;
+                        set_zoom(&mut app, &mut renderer, new_zoom);
+                        // The chars have moved under the mouse pointer.
+                        mouse_state.position = None;
+                    }
+                }
+
+                #[cfg(target_arch = "wasm32")]
+                web::poll_gamepads(&mut gamepads);
+                #[cfg(all(feature = "gilrs", not(target_arch = "wasm32")))]
+                gilrs_gamepads.poll(&mut gamepads);
+
+                // Frames that took too long are split into several ticks, and
+                // may skip being drawn to give the ticks time to catch up.
+                let (tick_dt, ticks) = frame_policy.ticks(dt);
+                for _ in 0..ticks {
+                    animation.advance(tick_dt);
+                    let (width, height) = renderer.size_in_chars();
+                    let tick_input = TickInput {
+                        dt: tick_dt,
+                        width,
+                        height,
+                        mouse: mouse_state,
+                        keys: &keys,
+                        text: &typed_text,
+                        composition: composition.as_ref(),
+                        gamepads: &gamepads,
+                        memory: renderer.memory_stats(),
+                        zoom: current_zoom,
+                        animation,
+                        context: &context,
+                    };
+                    let mut tick_result = tick(&mut app, tick_input);
+                    if apply_context(&context, &mut renderer) == TickResult::Quit {
+                        tick_result = TickResult::Quit;
+                    }
+                    mouse_state.end_tick();
+                    keys.clear();
+                    typed_text.clear();
+                    gamepads.iter_mut().for_each(Gamepad::end_tick);
+                    if tick_result == TickResult::Quit {
+                        ev_loop.exit();
+                        break;
+                    }
+                }
+                skip_frame =
+                    frame_policy.is_behind(dt) && skipped_frames < frame_policy.max_frame_skip;
+                if skip_frame {
+                    skipped_frames += 1;
+                } else {
+                    skipped_frames = 0;
+                }
+                // Browsers only call back again once a redraw is requested,
+                // so skipped frames still request one.
+                renderer.window().request_redraw();
             }
             _ => (),
         }
-    });
+    };
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        use winit::platform::web::EventLoopExtWebSys;
+        event_loop.spawn(event_handler);
+    }
+    #[cfg(target_os = "android")]
+    android::run(event_loop, event_handler);
+    #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+    event_loop.run(event_handler)?;
 
-    Ok(())
+    match loop_error.take() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
 }
 
-fn tick<A>(app: &mut A, state: &mut RenderState, dt: Duration) -> TickResult
+fn tick<A>(app: &mut A, tick_input: TickInput) -> TickResult
 where
     A: App,
 {
-    let (width, height) = state.size_in_chars();
-    let tick_input = TickInput { dt, width, height };
+    profile::profile_scope!("tick");
     app.tick(tick_input)
 }
 
-fn present<A>(app: &mut A, state: &mut RenderState) -> PresentResult
+/// Makes the changes to the window that the app asked for through its
+/// context during a tick.
+///
+/// # Returns
+///
+/// [`TickResult::Quit`] if the app asked to quit.
+///
+/// [`TickResult::Quit`]: enum.TickResult.html#variant.Quit
+///
+fn apply_context(context: &MageContext, state: &mut Renderer) -> TickResult {
+    let mut result = TickResult::Continue;
+    for request in context.take_requests() {
+        match request {
+            ContextRequest::SetTitle(title) => state.window().set_title(&title),
+            ContextRequest::ToggleFullscreen => state.window().toggle_fullscreen(),
+            ContextRequest::ResizeCells(width, height) => {
+                let (char_width, char_height) = state.char_size();
+                let size = PhysicalSize::new(
+                    width.saturating_mul(char_width),
+                    height.saturating_mul(char_height),
+                );
+                // Some platforms resize straight away, and others send a
+                // resized event later.
+                if let Some(size) = state.window().request_inner_size(size) {
+                    info!("Resized to {:?}", size);
+                    state.resize(size);
+                }
+            }
+            ContextRequest::SetCursorVisible(visible) => state.window().set_cursor_visible(visible),
+            ContextRequest::SetZoom(zoom) => zoom::set_zoom(zoom),
+            ContextRequest::Quit => result = TickResult::Quit,
+        }
+    }
+    result
+}
+
+/// Zooms the font, recomputing the grid, and tells the app.
+fn set_zoom<A>(app: &mut A, state: &mut Renderer, zoom: u32)
 where
     A: App,
 {
-    let (width, height) = state.size_in_chars();
-    let (fore_image, back_image, text_image) = state.images();
+    info!("Zoomed to {}", zoom);
+    state.set_pixel_scale(base_pixel_scale(state.window()) * zoom);
+    app.zoom_changed(zoom);
+}
 
+/// Returns how many screen pixels wide and high each pixel of the font is
+/// drawn before it is zoomed.  Only iOS scales the font for its display.
+#[cfg(not(target_os = "ios"))]
+fn base_pixel_scale(_window: &winit::window::Window) -> u32 {
+    1
+}
+
+fn present<A>(app: &mut A, state: &mut Renderer, animation: AnimationPhase) -> PresentResult
+where
+    A: App,
+{
+    profile::profile_scope!("present");
     let present_input = PresentInput {
-        width,
-        height,
-        fore_image,
-        back_image,
-        text_image,
+        screen: state.screen(),
+        animation,
     };
 
     app.present(present_input)
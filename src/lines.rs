@@ -0,0 +1,355 @@
+//! Drawing horizontal and vertical lines with the box drawing glyphs of code
+//! page 437.
+//!
+//! Lines can optionally be merged with line glyphs already drawn on an image,
+//! so that crossings and T-junctions are drawn with the correct junction glyph
+//! (e.g. `├`, `┼` or `╦`).
+
+use crate::{
+    colour::Colour,
+    image::{Char, Image, Point, Rect},
+};
+
+/// The style of line drawn by [`Image::draw_hline`] and [`Image::draw_vline`].
+///
+/// [`Image::draw_hline`]: ../image/struct.Image.html#method.draw_hline
+/// [`Image::draw_vline`]: ../image/struct.Image.html#method.draw_vline
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineStyle {
+    /// A single line, e.g. `─` and `│`.
+    Single,
+
+    /// A double line, e.g. `═` and `║`.
+    Double,
+}
+
+impl LineStyle {
+    /// Returns the weight of the line used when describing connections.
+    fn weight(self) -> u8 {
+        match self {
+            LineStyle::Single => 1,
+            LineStyle::Double => 2,
+        }
+    }
+}
+
+/// The lines leaving the centre of a cell in each direction.  Each weight is 0
+/// for no line, 1 for a single line and 2 for a double line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Connections {
+    up: u8,
+    down: u8,
+    left: u8,
+    right: u8,
+}
+
+impl Connections {
+    const fn new(up: u8, down: u8, left: u8, right: u8) -> Self {
+        Self {
+            up,
+            down,
+            left,
+            right,
+        }
+    }
+
+    /// Returns the connections of the given glyph if it is a box drawing
+    /// glyph.
+    fn from_glyph(glyph: u32) -> Option<Self> {
+        BOX_GLYPHS
+            .iter()
+            .find(|(g, _)| *g as u32 == glyph)
+            .map(|(_, c)| *c)
+    }
+
+    /// Returns the box drawing glyph with exactly these connections, if there
+    /// is one.
+    fn to_glyph(self) -> Option<u8> {
+        BOX_GLYPHS.iter().find(|(_, c)| *c == self).map(|(g, _)| *g)
+    }
+
+    /// Adds the connections in `other` to these connections.  Where both have
+    /// a line in the same direction, the weight in `other` wins.
+    fn merge(self, other: Self) -> Self {
+        let pick = |a: u8, b: u8| if b != 0 { b } else { a };
+        Self {
+            up: pick(self.up, other.up),
+            down: pick(self.down, other.down),
+            left: pick(self.left, other.left),
+            right: pick(self.right, other.right),
+        }
+    }
+
+    /// Returns these connections with every line set to the given weight.
+    fn with_weight(self, weight: u8) -> Self {
+        let set = |w: u8| if w != 0 { weight } else { 0 };
+        Self {
+            up: set(self.up),
+            down: set(self.down),
+            left: set(self.left),
+            right: set(self.right),
+        }
+    }
+}
+
+/// Every box drawing glyph in code page 437 and its connections.
+const BOX_GLYPHS: [(u8, Connections); 40] = [
+    (0xb3, Connections::new(1, 1, 0, 0)),
+    (0xb4, Connections::new(1, 1, 1, 0)),
+    (0xb5, Connections::new(1, 1, 2, 0)),
+    (0xb6, Connections::new(2, 2, 1, 0)),
+    (0xb7, Connections::new(0, 2, 1, 0)),
+    (0xb8, Connections::new(0, 1, 2, 0)),
+    (0xb9, Connections::new(2, 2, 2, 0)),
+    (0xba, Connections::new(2, 2, 0, 0)),
+    (0xbb, Connections::new(0, 2, 2, 0)),
+    (0xbc, Connections::new(2, 0, 2, 0)),
+    (0xbd, Connections::new(2, 0, 1, 0)),
+    (0xbe, Connections::new(1, 0, 2, 0)),
+    (0xbf, Connections::new(0, 1, 1, 0)),
+    (0xc0, Connections::new(1, 0, 0, 1)),
+    (0xc1, Connections::new(1, 0, 1, 1)),
+    (0xc2, Connections::new(0, 1, 1, 1)),
+    (0xc3, Connections::new(1, 1, 0, 1)),
+    (0xc4, Connections::new(0, 0, 1, 1)),
+    (0xc5, Connections::new(1, 1, 1, 1)),
+    (0xc6, Connections::new(1, 1, 0, 2)),
+    (0xc7, Connections::new(2, 2, 0, 1)),
+    (0xc8, Connections::new(2, 0, 0, 2)),
+    (0xc9, Connections::new(0, 2, 0, 2)),
+    (0xca, Connections::new(2, 0, 2, 2)),
+    (0xcb, Connections::new(0, 2, 2, 2)),
+    (0xcc, Connections::new(2, 2, 0, 2)),
+    (0xcd, Connections::new(0, 0, 2, 2)),
+    (0xce, Connections::new(2, 2, 2, 2)),
+    (0xcf, Connections::new(1, 0, 2, 2)),
+    (0xd0, Connections::new(2, 0, 1, 1)),
+    (0xd1, Connections::new(0, 1, 2, 2)),
+    (0xd2, Connections::new(0, 2, 1, 1)),
+    (0xd3, Connections::new(2, 0, 0, 1)),
+    (0xd4, Connections::new(1, 0, 0, 2)),
+    (0xd5, Connections::new(0, 1, 0, 2)),
+    (0xd6, Connections::new(0, 2, 0, 1)),
+    (0xd7, Connections::new(2, 2, 1, 1)),
+    (0xd8, Connections::new(1, 1, 2, 2)),
+    (0xd9, Connections::new(1, 0, 1, 0)),
+    (0xda, Connections::new(0, 1, 0, 1)),
+];
+
+/// Returns the box drawing glyph with lines leaving its centre in the given
+/// directions.  Where there is no such glyph, such as at the end of a line,
+/// a straight line is returned along the axis of the directions given.
+pub(crate) fn line_glyph(up: bool, down: bool, left: bool, right: bool, style: LineStyle) -> u8 {
+    let w = style.weight();
+    let weight = |line: bool| if line { w } else { 0 };
+    let connections = Connections::new(weight(up), weight(down), weight(left), weight(right));
+    let straight = if up || down {
+        Connections::new(w, w, 0, 0)
+    } else {
+        Connections::new(0, 0, w, w)
+    };
+    connections
+        .to_glyph()
+        .or_else(|| straight.to_glyph())
+        .unwrap_or(b' ')
+}
+
+impl Image {
+    /// Draws a horizontal line of box drawing glyphs.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The coordinates of the left end of the line.
+    /// * `length` - The length of the line in chars.
+    /// * `style` - The style of line to draw.
+    /// * `ink` - The foreground colour of the line.
+    /// * `paper` - The background colour of the line.
+    /// * `merge` - If true, the line is merged with any line glyphs it crosses
+    ///   or touches, drawing the correct junction glyph.
+    ///
+    /// # Notes
+    ///
+    /// If the coordinates are out of bounds, the line is clipped.
+    ///
+    pub fn draw_hline(
+        &mut self,
+        p: Point,
+        length: u32,
+        style: LineStyle,
+        ink: Colour,
+        paper: Colour,
+        merge: bool,
+    ) {
+        let w = style.weight();
+        for i in 0..length {
+            let left = if i > 0 { w } else { 0 };
+            let right = if i + 1 < length { w } else { 0 };
+            self.draw_line_cell(
+                Point::new(p.x + i as i32, p.y),
+                Connections::new(0, 0, left, right),
+                Connections::new(0, 0, w, w),
+                ink,
+                paper,
+                merge,
+            );
+        }
+    }
+
+    /// Draws a vertical line of box drawing glyphs.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The coordinates of the top end of the line.
+    /// * `length` - The length of the line in chars.
+    /// * `style` - The style of line to draw.
+    /// * `ink` - The foreground colour of the line.
+    /// * `paper` - The background colour of the line.
+    /// * `merge` - If true, the line is merged with any line glyphs it crosses
+    ///   or touches, drawing the correct junction glyph.
+    ///
+    /// # Notes
+    ///
+    /// If the coordinates are out of bounds, the line is clipped.
+    ///
+    pub fn draw_vline(
+        &mut self,
+        p: Point,
+        length: u32,
+        style: LineStyle,
+        ink: Colour,
+        paper: Colour,
+        merge: bool,
+    ) {
+        let w = style.weight();
+        for i in 0..length {
+            let up = if i > 0 { w } else { 0 };
+            let down = if i + 1 < length { w } else { 0 };
+            self.draw_line_cell(
+                Point::new(p.x, p.y + i as i32),
+                Connections::new(up, down, 0, 0),
+                Connections::new(w, w, 0, 0),
+                ink,
+                paper,
+                merge,
+            );
+        }
+    }
+
+    /// Draws the border of a rectangle with box drawing glyphs, leaving the
+    /// inside untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The rectangle whose edge cells are drawn.
+    /// * `style` - The style of line to draw.
+    /// * `ink` - The foreground colour of the border.
+    /// * `paper` - The background colour of the border.
+    ///
+    /// # Notes
+    ///
+    /// Unlike the lines drawn by [`draw_hline`] and [`draw_vline`], the border
+    /// is never merged with the glyphs already on the image, so it can be
+    /// drawn over other lines as a popup or window frame.  If the rectangle is
+    /// only one char wide or high, a plain line is drawn.  If the coordinates
+    /// are out of bounds, the border is clipped.
+    ///
+    /// [`draw_hline`]: struct.Image.html#method.draw_hline
+    /// [`draw_vline`]: struct.Image.html#method.draw_vline
+    ///
+    pub fn draw_box(&mut self, rect: Rect, style: LineStyle, ink: Colour, paper: Colour) {
+        let p = Point::new(rect.x, rect.y);
+        if rect.width == 0 || rect.height == 0 {
+            return;
+        }
+        if rect.height == 1 {
+            self.draw_hline(p, rect.width, style, ink, paper, false);
+            return;
+        }
+        if rect.width == 1 {
+            self.draw_vline(p, rect.height, style, ink, paper, false);
+            return;
+        }
+
+        let w = style.weight();
+        let right = rect.width as i32 - 1;
+        let bottom = rect.height as i32 - 1;
+        let mut draw = |x: i32, y: i32, connections: Connections| {
+            let glyph = connections.to_glyph().unwrap_or(b' ');
+            self.draw_char(
+                Point::new(rect.x + x, rect.y + y),
+                Char::new(glyph, ink, paper),
+            );
+        };
+        for x in 1..right {
+            draw(x, 0, Connections::new(0, 0, w, w));
+            draw(x, bottom, Connections::new(0, 0, w, w));
+        }
+        for y in 1..bottom {
+            draw(0, y, Connections::new(w, w, 0, 0));
+            draw(right, y, Connections::new(w, w, 0, 0));
+        }
+        draw(0, 0, Connections::new(0, w, 0, w));
+        draw(right, 0, Connections::new(0, w, w, 0));
+        draw(0, bottom, Connections::new(w, 0, 0, w));
+        draw(right, bottom, Connections::new(w, 0, w, 0));
+    }
+
+    /// Draws a single cell of a line.
+    ///
+    /// `connections` are the lines leaving the cell that belong to the line
+    /// being drawn, and `plain` is the connections of a plain line glyph to
+    /// fall back on if no better glyph exists.
+    fn draw_line_cell(
+        &mut self,
+        p: Point,
+        connections: Connections,
+        plain: Connections,
+        ink: Colour,
+        paper: Colour,
+        merge: bool,
+    ) {
+        let Some(existing) = self.get_glyph(p) else {
+            return;
+        };
+
+        let weight = plain.up.max(plain.left);
+        let glyph = if merge {
+            let existing = Connections::from_glyph(existing)
+                .map(|existing| self.joined_connections(p, existing))
+                .unwrap_or_default();
+            let merged = existing.merge(connections);
+            merged
+                .to_glyph()
+                .or_else(|| merged.with_weight(weight).to_glyph())
+        } else {
+            None
+        };
+        let glyph = glyph.or_else(|| plain.to_glyph()).unwrap_or(b' ');
+
+        self.draw_char(p, Char::new(glyph, ink, paper));
+    }
+
+    /// Removes any connections from a cell that are not met by a line in the
+    /// neighbouring cell.
+    ///
+    /// The end of a line is drawn with a plain line glyph as code page 437 has
+    /// no glyphs for half lines.  Without this, a line starting at the end of
+    /// another would be drawn as a T-junction rather than a corner.
+    fn joined_connections(&self, p: Point, connections: Connections) -> Connections {
+        let neighbour = |dx: i32, dy: i32| {
+            self.get_glyph(Point::new(p.x + dx, p.y + dy))
+                .and_then(Connections::from_glyph)
+                .unwrap_or_default()
+        };
+        let keep = |weight: u8, joined: bool| if joined { weight } else { 0 };
+
+        Connections {
+            up: keep(connections.up, neighbour(0, -1).down != 0),
+            down: keep(connections.down, neighbour(0, 1).up != 0),
+            left: keep(connections.left, neighbour(-1, 0).right != 0),
+            right: keep(connections.right, neighbour(1, 0).left != 0),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Masks controlling which cells of an image are drawn to.
+
+use crate::image::{BlitOptions, Image, Point, Rect};
+
+/// A grid of flags, one per cell, used to restrict drawing to an irregular
+/// area such as a circular vision area or a torn-edge panel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mask {
+    /// The width of the mask in cells.
+    width: u32,
+
+    /// The height of the mask in cells.
+    height: u32,
+
+    /// Whether each cell is set, row by row.
+    cells: Vec<bool>,
+}
+
+impl Mask {
+    /// Creates a new mask with no cells set.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![false; (width * height) as usize],
+        }
+    }
+
+    /// Creates a new mask the same size as an image, with cells set wherever
+    /// the image has a non-zero glyph.
+    pub fn from_image(image: &Image) -> Self {
+        Self {
+            width: image.width,
+            height: image.height,
+            cells: image.text_image.iter().map(|&ch| ch != 0).collect(),
+        }
+    }
+
+    /// Returns the width of the mask in cells.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the mask in cells.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the index of the cell at the given coordinates, or `None` if
+    /// the coordinates are out of bounds.
+    fn index(&self, p: Point) -> Option<usize> {
+        (p.x >= 0 && p.y >= 0 && (p.x as u32) < self.width && (p.y as u32) < self.height)
+            .then(|| p.y as usize * self.width as usize + p.x as usize)
+    }
+
+    /// Returns true if the cell at the given coordinates is set.  Cells out of
+    /// bounds are never set.
+    pub fn get(&self, p: Point) -> bool {
+        self.index(p).is_some_and(|i| self.cells[i])
+    }
+
+    /// Sets or clears the cell at the given coordinates.  Coordinates out of
+    /// bounds are ignored.
+    pub fn set(&mut self, p: Point, value: bool) {
+        if let Some(i) = self.index(p) {
+            self.cells[i] = value;
+        }
+    }
+
+    /// Sets or clears every cell.
+    pub fn fill(&mut self, value: bool) {
+        self.cells.fill(value);
+    }
+
+    /// Sets or clears every cell within a rectangle, clipped to the mask.
+    pub fn fill_rect(&mut self, rect: Rect, value: bool) {
+        if let Some(rect) = rect.intersect(Rect::new(0, 0, self.width, self.height)) {
+            for p in rect.points() {
+                self.set(p, value);
+            }
+        }
+    }
+
+    /// Sets or clears every cell within a circle, clipped to the mask.
+    ///
+    /// # Arguments
+    ///
+    /// * `centre` - The cell at the centre of the circle.
+    /// * `radius` - The radius of the circle in cells.
+    /// * `value` - Whether to set or clear the cells.
+    ///
+    pub fn fill_circle(&mut self, centre: Point, radius: u32, value: bool) {
+        let r = radius as i32;
+        let r2 = (radius as i64 * radius as i64) + radius as i64;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if (dx as i64 * dx as i64) + (dy as i64 * dy as i64) <= r2 {
+                    self.set(Point::new(centre.x + dx, centre.y + dy), value);
+                }
+            }
+        }
+    }
+
+    /// Sets every cell that is clear and clears every cell that is set.
+    pub fn invert(&mut self) {
+        self.cells.iter_mut().for_each(|cell| *cell = !*cell);
+    }
+}
+
+impl Image {
+    /// Blits an area of another image on to this image, only writing to the
+    /// cells that are set in a mask.
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - The coordinates on this image to blit the top-left corner of
+    ///   the source area to.
+    /// * `src_image` - The image to blit from.
+    /// * `src_rect` - The area of the source image to blit.
+    /// * `mask` - The mask controlling which cells are written.  The mask uses
+    ///   the same coordinates as this image, and cells outside the mask are
+    ///   never written.
+    /// * `options` - Controls which source chars are considered transparent.
+    ///
+    /// # Notes
+    ///
+    /// The source area is clipped to the source image, and the destination is
+    /// clipped to this image.
+    ///
+    pub fn blit_masked(
+        &mut self,
+        dst: Point,
+        src_image: &Image,
+        src_rect: Rect,
+        mask: &Mask,
+        options: BlitOptions,
+    ) {
+        let width = self.width as usize;
+        for (d, s) in self.blit_cells(dst, src_image, src_rect) {
+            let p = Point::new((d % width) as i32, (d / width) as i32);
+            if !mask.get(p)
+                || options.is_transparent(src_image.text_image[s], src_image.back_image[s])
+            {
+                continue;
+            }
+            self.fore_image[d] = src_image.fore_image[s];
+            self.back_image[d] = src_image.back_image[s];
+            self.text_image[d] = src_image.text_image[s];
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Small overviews of large images or worlds, such as corner-of-screen maps.
+
+use crate::{
+    colour::Colour,
+    image::{BlitOptions, Char, Image, Point, Rect},
+    mask::Mask,
+};
+
+/// The glyph of a full block, `█`.
+const FULL_GLYPH: u8 = 0xdb;
+
+/// The glyph of a dark shade, `▓`, which is mostly ink.
+const DARK_SHADE_GLYPH: u8 = 0xb2;
+
+/// The glyph of a medium shade, `▒`, which is half ink and half paper.
+const MEDIUM_SHADE_GLYPH: u8 = 0xb1;
+
+/// The glyph of the upper half block, `▀`.
+const UPPER_HALF_GLYPH: u8 = 0xdf;
+
+/// How each cell of a [`Minimap`] shows the area of the world it covers.
+///
+/// [`Minimap`]: struct.Minimap.html
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MinimapStyle {
+    /// Each cell covers one block of the world, drawn with a shade glyph
+    /// mixing the two most common colours in it.
+    #[default]
+    Shade,
+
+    /// Each cell covers two blocks of the world, one above the other, drawn
+    /// with a half block glyph in the most common colour of each.  As cells
+    /// are about twice as tall as they are wide, this gives square blocks.
+    HalfBlocks,
+}
+
+/// A small overview of a large image or world.
+///
+/// The world is split into blocks of cells, and each block is shown by the
+/// colours most common in it.  The minimap only redraws the parts of the
+/// world that have been marked as changed with [`invalidate`], so it is cheap
+/// to update every frame.
+///
+/// ```
+/// use mage_core::{image::{Image, Point}, minimap::Minimap};
+///
+/// let world = Image::new(200, 100);
+/// let mut minimap = Minimap::new(200, 100, 20, 10);
+/// minimap.update(&world);
+///
+/// assert_eq!(minimap.image().width, 20);
+/// assert_eq!(minimap.world_to_map(Point::new(105, 52)), Point::new(10, 5));
+/// ```
+///
+/// [`invalidate`]: struct.Minimap.html#method.invalidate
+///
+#[derive(Clone, Debug)]
+pub struct Minimap {
+    /// The width of the world in cells.
+    world_width: u32,
+
+    /// The height of the world in cells.
+    world_height: u32,
+
+    /// The largest width of the minimap in cells.
+    max_width: u32,
+
+    /// The largest height of the minimap in cells.
+    max_height: u32,
+
+    /// How each cell shows the world.
+    style: MinimapStyle,
+
+    /// The width of a block in world cells.
+    block_width: u32,
+
+    /// The height of a block in world cells.
+    block_height: u32,
+
+    /// The minimap drawn so far.
+    image: Image,
+
+    /// The cells of the minimap that need to be redrawn.
+    dirty: Mask,
+}
+
+impl Minimap {
+    /// Creates a new minimap of a world, which needs to be drawn in full by
+    /// the first update.
+    ///
+    /// # Arguments
+    ///
+    /// * `world_width` - The width of the world in cells.
+    /// * `world_height` - The height of the world in cells.
+    /// * `max_width` - The largest width the minimap can be in cells.
+    /// * `max_height` - The largest height the minimap can be in cells.
+    ///
+    /// # Notes
+    ///
+    /// Blocks are as small as they can be while fitting the whole world in
+    /// the minimap, so the minimap may be smaller than the largest size.
+    ///
+    pub fn new(world_width: u32, world_height: u32, max_width: u32, max_height: u32) -> Self {
+        let mut minimap = Self {
+            world_width,
+            world_height,
+            max_width,
+            max_height,
+            style: MinimapStyle::default(),
+            block_width: 1,
+            block_height: 1,
+            image: Image::new(0, 0),
+            dirty: Mask::new(0, 0),
+        };
+        minimap.layout();
+        minimap
+    }
+
+    /// Sets how each cell of the minimap shows the world.
+    pub fn with_style(mut self, style: MinimapStyle) -> Self {
+        self.style = style;
+        self.layout();
+        self
+    }
+
+    /// Works out the size of the blocks and the minimap, and marks the whole
+    /// minimap as needing to be redrawn.
+    fn layout(&mut self) {
+        let blocks_per_cell = self.blocks_per_cell();
+        self.block_width = self.world_width.div_ceil(self.max_width.max(1)).max(1);
+        self.block_height = self
+            .world_height
+            .div_ceil(self.max_height.max(1) * blocks_per_cell)
+            .max(1);
+        let width = self.world_width.div_ceil(self.block_width);
+        let height = self
+            .world_height
+            .div_ceil(self.block_height * blocks_per_cell);
+        self.image = Image::new(width, height);
+        self.dirty = Mask::new(width, height);
+        self.dirty.fill(true);
+    }
+
+    /// Returns the number of blocks stacked in each cell of the minimap.
+    fn blocks_per_cell(&self) -> u32 {
+        match self.style {
+            MinimapStyle::Shade => 1,
+            MinimapStyle::HalfBlocks => 2,
+        }
+    }
+
+    /// Returns the size of the world in cells.
+    pub fn world_size(&self) -> (u32, u32) {
+        (self.world_width, self.world_height)
+    }
+
+    /// Changes the size of the world, which redraws the whole minimap on the
+    /// next update.
+    pub fn set_world_size(&mut self, world_width: u32, world_height: u32) {
+        self.world_width = world_width;
+        self.world_height = world_height;
+        self.layout();
+    }
+
+    /// Returns the size of a block in world cells.
+    pub fn block_size(&self) -> (u32, u32) {
+        (self.block_width, self.block_height)
+    }
+
+    /// Returns the minimap as drawn by the last update.
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// Marks an area of the world as changed, so the parts of the minimap
+    /// covering it are redrawn on the next update.
+    pub fn invalidate(&mut self, world_rect: Rect) {
+        if world_rect.width == 0 || world_rect.height == 0 {
+            return;
+        }
+        let top_left = self.world_to_map(Point::new(world_rect.x, world_rect.y));
+        let bottom_right = self.world_to_map(Point::new(
+            world_rect.x.saturating_add(world_rect.width as i32 - 1),
+            world_rect.y.saturating_add(world_rect.height as i32 - 1),
+        ));
+        // Clip to the minimap, so huge areas don't take long to mark.
+        let (x0, y0) = (top_left.x.max(0), top_left.y.max(0));
+        let x1 = bottom_right.x.min(self.image.width as i32 - 1);
+        let y1 = bottom_right.y.min(self.image.height as i32 - 1);
+        if x0 <= x1 && y0 <= y1 {
+            let rect = Rect::new(x0, y0, (x1 - x0 + 1) as u32, (y1 - y0 + 1) as u32);
+            self.dirty.fill_rect(rect, true);
+        }
+    }
+
+    /// Marks the whole world as changed, so the whole minimap is redrawn on
+    /// the next update.
+    pub fn invalidate_all(&mut self) {
+        self.dirty.fill(true);
+    }
+
+    /// Redraws the parts of the minimap covering changed areas of a world
+    /// image.
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - The image of the world.  Each cell counts as its ink
+    ///   colour, or its paper colour if its glyph is blank.  Cells outside
+    ///   the image count as black.
+    ///
+    pub fn update(&mut self, world: &Image) {
+        self.update_with(|p| cell_colour(world, p));
+    }
+
+    /// Redraws the parts of the minimap covering changed areas of a world
+    /// that isn't an image, such as a grid of tiles.
+    ///
+    /// # Arguments
+    ///
+    /// * `colour_at` - Returns the colour of a cell of the world.  It is only
+    ///   called for cells within the world.
+    ///
+    pub fn update_with(&mut self, mut colour_at: impl FnMut(Point) -> Colour) {
+        let blocks_per_cell = self.blocks_per_cell();
+        for y in 0..self.image.height as i32 {
+            for x in 0..self.image.width as i32 {
+                let p = Point::new(x, y);
+                if !self.dirty.get(p) {
+                    continue;
+                }
+                let block_y = y * blocks_per_cell as i32;
+                let ch = match self.style {
+                    MinimapStyle::Shade => {
+                        let [(ink, ink_count), (paper, paper_count)] =
+                            self.common_colours(x, block_y, &mut colour_at);
+                        let share = ink_count as f32 / (ink_count + paper_count).max(1) as f32;
+                        let glyph = match share {
+                            share if share >= 0.875 => FULL_GLYPH,
+                            share if share >= 0.625 => DARK_SHADE_GLYPH,
+                            _ => MEDIUM_SHADE_GLYPH,
+                        };
+                        Char::new(glyph, ink, paper)
+                    }
+                    MinimapStyle::HalfBlocks => {
+                        let [(top, _), _] = self.common_colours(x, block_y, &mut colour_at);
+                        let [(bottom, _), _] = self.common_colours(x, block_y + 1, &mut colour_at);
+                        Char::new(UPPER_HALF_GLYPH, top, bottom)
+                    }
+                };
+                self.image.draw_char(p, ch);
+                self.dirty.set(p, false);
+            }
+        }
+    }
+
+    /// Returns the two most common colours in a block of the world and how
+    /// many cells have them.  If the block has only one colour, it is given
+    /// twice, and if it is outside the world, black is.
+    fn common_colours(
+        &self,
+        block_x: i32,
+        block_y: i32,
+        colour_at: &mut impl FnMut(Point) -> Colour,
+    ) -> [(Colour, usize); 2] {
+        let (bw, bh) = (self.block_width as i32, self.block_height as i32);
+        let (x0, y0) = (block_x * bw, block_y * bh);
+        let x1 = (x0 + bw).min(self.world_width as i32);
+        let y1 = (y0 + bh).min(self.world_height as i32);
+
+        // Blocks are small, so a list is quicker than a map.
+        let mut counts: Vec<(u32, usize)> = vec![];
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let colour: u32 = colour_at(Point::new(x, y)).into();
+                match counts.iter_mut().find(|(c, _)| *c == colour) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((colour, 1)),
+                }
+            }
+        }
+        counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+
+        let first = counts.first().copied().unwrap_or((Colour::Black.into(), 0));
+        let second = counts.get(1).copied().unwrap_or((first.0, 0));
+        [(first.0.into(), first.1), (second.0.into(), second.1)]
+    }
+
+    /// Converts world coordinates to the coordinates of the minimap cell
+    /// covering them.
+    pub fn world_to_map(&self, p: Point) -> Point {
+        Point::new(
+            p.x.div_euclid(self.block_width as i32),
+            p.y.div_euclid((self.block_height * self.blocks_per_cell()) as i32),
+        )
+    }
+
+    /// Converts the coordinates of a minimap cell to the world coordinates
+    /// at the centre of the area it covers, such as to move the camera there
+    /// when the minimap is clicked.
+    pub fn map_to_world(&self, p: Point) -> Point {
+        let cell_height = (self.block_height * self.blocks_per_cell()) as i32;
+        Point::new(
+            p.x * self.block_width as i32 + self.block_width as i32 / 2,
+            p.y * cell_height + cell_height / 2,
+        )
+    }
+
+    /// Draws the minimap on to an image.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image to draw on.
+    /// * `p` - The coordinates of the top-left corner of the minimap.
+    ///
+    /// # Notes
+    ///
+    /// The minimap is drawn as it was at the last update.  If it is out of
+    /// bounds, it is clipped.
+    ///
+    pub fn draw(&self, image: &mut Image, p: Point) {
+        image.blit(p, &self.image, self.image.rect(), BlitOptions::default());
+    }
+}
+
+/// Returns the colour a cell of an image shows most: its ink colour, or its
+/// paper colour if its glyph is blank.
+fn cell_colour(image: &Image, p: Point) -> Colour {
+    let Some(i) = image.point_to_index(p) else {
+        return Colour::Black;
+    };
+    match image.text_image[i] {
+        0 | 0x20 | 0xff => image.back_image[i].into(),
+        _ => image.fore_image[i].into(),
+    }
+}
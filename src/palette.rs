@@ -0,0 +1,698 @@
+//! Standard colour palettes.
+//!
+//! These palettes allow colours to be specified by index, matching the
+//! colours used by terminals and other software, or give a game the authentic
+//! look of a classic home computer.
+
+use std::{collections::HashMap, ops::Index};
+
+use crate::{colour::Colour, error::MageError, image::Image};
+
+/// The standard 256 colour palette used by xterm and most modern terminals.
+///
+/// Entries 0 to 15 are the 16 system colours, 16 to 231 are a 6×6×6 colour
+/// cube and 232 to 255 are a ramp of greys from dark to light.
+///
+pub const XTERM_256: [Colour; 256] = xterm_256();
+
+/// The 16 system colours at the start of the xterm palette.
+const XTERM_SYSTEM: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (128, 0, 0),
+    (0, 128, 0),
+    (128, 128, 0),
+    (0, 0, 128),
+    (128, 0, 128),
+    (0, 128, 128),
+    (192, 192, 192),
+    (128, 128, 128),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (0, 0, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The intensity of each step along an axis of the xterm colour cube.
+const XTERM_CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The 16 colours of the IBM PC's CGA adapter in text mode, in attribute
+/// order.  Colour 6 is the brown produced by real CGA monitors rather than the
+/// dark yellow of the raw RGBI signal.
+///
+pub const CGA: [Colour; 16] = [
+    hex(0x000000),
+    hex(0x0000aa),
+    hex(0x00aa00),
+    hex(0x00aaaa),
+    hex(0xaa0000),
+    hex(0xaa00aa),
+    hex(0xaa5500),
+    hex(0xaaaaaa),
+    hex(0x555555),
+    hex(0x5555ff),
+    hex(0x55ff55),
+    hex(0x55ffff),
+    hex(0xff5555),
+    hex(0xff55ff),
+    hex(0xffff55),
+    hex(0xffffff),
+];
+
+/// The high intensity cyan, magenta and white palette of the CGA 320×200
+/// graphics mode, with black as the background colour.
+///
+pub const CGA_CYAN_MAGENTA: [Colour; 4] =
+    [hex(0x000000), hex(0x55ffff), hex(0xff55ff), hex(0xffffff)];
+
+/// The full 64 colour palette of the IBM PC's EGA adapter, indexed by the
+/// 6-bit `rgbRGB` colour value.
+///
+/// The EGA's default 16 colours are the same as the [`CGA`] palette.
+///
+/// [`CGA`]: constant.CGA.html
+///
+pub const EGA: [Colour; 64] = ega();
+
+/// The 16 colours of the Commodore 64, as measured by Philip "Pepto"
+/// Timmermann.
+///
+pub const C64: [Colour; 16] = [
+    hex(0x000000),
+    hex(0xffffff),
+    hex(0x68372b),
+    hex(0x70a4b2),
+    hex(0x6f3d86),
+    hex(0x588d43),
+    hex(0x352879),
+    hex(0xb8c76f),
+    hex(0x6f4f25),
+    hex(0x433900),
+    hex(0x9a6759),
+    hex(0x444444),
+    hex(0x6c6c6c),
+    hex(0x9ad284),
+    hex(0x6c5eb5),
+    hex(0x959595),
+];
+
+/// The colours of the Sinclair ZX Spectrum.  Entries 0 to 7 are the normal
+/// colours and 8 to 15 are their bright versions, in the Spectrum's order of
+/// black, blue, red, magenta, green, cyan, yellow and white.
+///
+pub const ZX_SPECTRUM: [Colour; 16] = [
+    hex(0x000000),
+    hex(0x0000d7),
+    hex(0xd70000),
+    hex(0xd700d7),
+    hex(0x00d700),
+    hex(0x00d7d7),
+    hex(0xd7d700),
+    hex(0xd7d7d7),
+    hex(0x000000),
+    hex(0x0000ff),
+    hex(0xff0000),
+    hex(0xff00ff),
+    hex(0x00ff00),
+    hex(0x00ffff),
+    hex(0xffff00),
+    hex(0xffffff),
+];
+
+/// The 148 named colours of CSS and HTML, sorted by name.
+///
+/// Names are in lower case with no spaces, and both the `gray` and `grey`
+/// spellings are included.  Use [`Colour::from_css_name`] to look up a colour
+/// by name.
+///
+/// [`Colour::from_css_name`]: ../colour/enum.Colour.html#method.from_css_name
+///
+pub const CSS_COLOURS: [(&str, Colour); 148] = [
+    ("aliceblue", hex(0xf0f8ff)),
+    ("antiquewhite", hex(0xfaebd7)),
+    ("aqua", hex(0x00ffff)),
+    ("aquamarine", hex(0x7fffd4)),
+    ("azure", hex(0xf0ffff)),
+    ("beige", hex(0xf5f5dc)),
+    ("bisque", hex(0xffe4c4)),
+    ("black", hex(0x000000)),
+    ("blanchedalmond", hex(0xffebcd)),
+    ("blue", hex(0x0000ff)),
+    ("blueviolet", hex(0x8a2be2)),
+    ("brown", hex(0xa52a2a)),
+    ("burlywood", hex(0xdeb887)),
+    ("cadetblue", hex(0x5f9ea0)),
+    ("chartreuse", hex(0x7fff00)),
+    ("chocolate", hex(0xd2691e)),
+    ("coral", hex(0xff7f50)),
+    ("cornflowerblue", hex(0x6495ed)),
+    ("cornsilk", hex(0xfff8dc)),
+    ("crimson", hex(0xdc143c)),
+    ("cyan", hex(0x00ffff)),
+    ("darkblue", hex(0x00008b)),
+    ("darkcyan", hex(0x008b8b)),
+    ("darkgoldenrod", hex(0xb8860b)),
+    ("darkgray", hex(0xa9a9a9)),
+    ("darkgreen", hex(0x006400)),
+    ("darkgrey", hex(0xa9a9a9)),
+    ("darkkhaki", hex(0xbdb76b)),
+    ("darkmagenta", hex(0x8b008b)),
+    ("darkolivegreen", hex(0x556b2f)),
+    ("darkorange", hex(0xff8c00)),
+    ("darkorchid", hex(0x9932cc)),
+    ("darkred", hex(0x8b0000)),
+    ("darksalmon", hex(0xe9967a)),
+    ("darkseagreen", hex(0x8fbc8f)),
+    ("darkslateblue", hex(0x483d8b)),
+    ("darkslategray", hex(0x2f4f4f)),
+    ("darkslategrey", hex(0x2f4f4f)),
+    ("darkturquoise", hex(0x00ced1)),
+    ("darkviolet", hex(0x9400d3)),
+    ("deeppink", hex(0xff1493)),
+    ("deepskyblue", hex(0x00bfff)),
+    ("dimgray", hex(0x696969)),
+    ("dimgrey", hex(0x696969)),
+    ("dodgerblue", hex(0x1e90ff)),
+    ("firebrick", hex(0xb22222)),
+    ("floralwhite", hex(0xfffaf0)),
+    ("forestgreen", hex(0x228b22)),
+    ("fuchsia", hex(0xff00ff)),
+    ("gainsboro", hex(0xdcdcdc)),
+    ("ghostwhite", hex(0xf8f8ff)),
+    ("gold", hex(0xffd700)),
+    ("goldenrod", hex(0xdaa520)),
+    ("gray", hex(0x808080)),
+    ("green", hex(0x008000)),
+    ("greenyellow", hex(0xadff2f)),
+    ("grey", hex(0x808080)),
+    ("honeydew", hex(0xf0fff0)),
+    ("hotpink", hex(0xff69b4)),
+    ("indianred", hex(0xcd5c5c)),
+    ("indigo", hex(0x4b0082)),
+    ("ivory", hex(0xfffff0)),
+    ("khaki", hex(0xf0e68c)),
+    ("lavender", hex(0xe6e6fa)),
+    ("lavenderblush", hex(0xfff0f5)),
+    ("lawngreen", hex(0x7cfc00)),
+    ("lemonchiffon", hex(0xfffacd)),
+    ("lightblue", hex(0xadd8e6)),
+    ("lightcoral", hex(0xf08080)),
+    ("lightcyan", hex(0xe0ffff)),
+    ("lightgoldenrodyellow", hex(0xfafad2)),
+    ("lightgray", hex(0xd3d3d3)),
+    ("lightgreen", hex(0x90ee90)),
+    ("lightgrey", hex(0xd3d3d3)),
+    ("lightpink", hex(0xffb6c1)),
+    ("lightsalmon", hex(0xffa07a)),
+    ("lightseagreen", hex(0x20b2aa)),
+    ("lightskyblue", hex(0x87cefa)),
+    ("lightslategray", hex(0x778899)),
+    ("lightslategrey", hex(0x778899)),
+    ("lightsteelblue", hex(0xb0c4de)),
+    ("lightyellow", hex(0xffffe0)),
+    ("lime", hex(0x00ff00)),
+    ("limegreen", hex(0x32cd32)),
+    ("linen", hex(0xfaf0e6)),
+    ("magenta", hex(0xff00ff)),
+    ("maroon", hex(0x800000)),
+    ("mediumaquamarine", hex(0x66cdaa)),
+    ("mediumblue", hex(0x0000cd)),
+    ("mediumorchid", hex(0xba55d3)),
+    ("mediumpurple", hex(0x9370db)),
+    ("mediumseagreen", hex(0x3cb371)),
+    ("mediumslateblue", hex(0x7b68ee)),
+    ("mediumspringgreen", hex(0x00fa9a)),
+    ("mediumturquoise", hex(0x48d1cc)),
+    ("mediumvioletred", hex(0xc71585)),
+    ("midnightblue", hex(0x191970)),
+    ("mintcream", hex(0xf5fffa)),
+    ("mistyrose", hex(0xffe4e1)),
+    ("moccasin", hex(0xffe4b5)),
+    ("navajowhite", hex(0xffdead)),
+    ("navy", hex(0x000080)),
+    ("oldlace", hex(0xfdf5e6)),
+    ("olive", hex(0x808000)),
+    ("olivedrab", hex(0x6b8e23)),
+    ("orange", hex(0xffa500)),
+    ("orangered", hex(0xff4500)),
+    ("orchid", hex(0xda70d6)),
+    ("palegoldenrod", hex(0xeee8aa)),
+    ("palegreen", hex(0x98fb98)),
+    ("paleturquoise", hex(0xafeeee)),
+    ("palevioletred", hex(0xdb7093)),
+    ("papayawhip", hex(0xffefd5)),
+    ("peachpuff", hex(0xffdab9)),
+    ("peru", hex(0xcd853f)),
+    ("pink", hex(0xffc0cb)),
+    ("plum", hex(0xdda0dd)),
+    ("powderblue", hex(0xb0e0e6)),
+    ("purple", hex(0x800080)),
+    ("rebeccapurple", hex(0x663399)),
+    ("red", hex(0xff0000)),
+    ("rosybrown", hex(0xbc8f8f)),
+    ("royalblue", hex(0x4169e1)),
+    ("saddlebrown", hex(0x8b4513)),
+    ("salmon", hex(0xfa8072)),
+    ("sandybrown", hex(0xf4a460)),
+    ("seagreen", hex(0x2e8b57)),
+    ("seashell", hex(0xfff5ee)),
+    ("sienna", hex(0xa0522d)),
+    ("silver", hex(0xc0c0c0)),
+    ("skyblue", hex(0x87ceeb)),
+    ("slateblue", hex(0x6a5acd)),
+    ("slategray", hex(0x708090)),
+    ("slategrey", hex(0x708090)),
+    ("snow", hex(0xfffafa)),
+    ("springgreen", hex(0x00ff7f)),
+    ("steelblue", hex(0x4682b4)),
+    ("tan", hex(0xd2b48c)),
+    ("teal", hex(0x008080)),
+    ("thistle", hex(0xd8bfd8)),
+    ("tomato", hex(0xff6347)),
+    ("turquoise", hex(0x40e0d0)),
+    ("violet", hex(0xee82ee)),
+    ("wheat", hex(0xf5deb3)),
+    ("white", hex(0xffffff)),
+    ("whitesmoke", hex(0xf5f5f5)),
+    ("yellow", hex(0xffff00)),
+    ("yellowgreen", hex(0x9acd32)),
+];
+
+/// Creates a colour from a `0xRRGGBB` value.
+const fn hex(rgb: u32) -> Colour {
+    Colour::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+}
+
+/// Builds the EGA palette.
+const fn ega() -> [Colour; 64] {
+    let mut palette = [Colour::Black; 64];
+    let mut i = 0;
+    while i < 64 {
+        palette[i] = Colour::Rgb(
+            ega_channel(i, 2, 5),
+            ega_channel(i, 1, 4),
+            ega_channel(i, 0, 3),
+        );
+        i += 1;
+    }
+    palette
+}
+
+/// Returns the intensity of one channel of an EGA colour value.  Each channel
+/// has a 2/3 intensity bit in the low 3 bits and a 1/3 intensity bit in the
+/// high 3 bits.
+const fn ega_channel(value: usize, primary: usize, secondary: usize) -> u8 {
+    0xaa * ((value >> primary) & 1) as u8 + 0x55 * ((value >> secondary) & 1) as u8
+}
+
+/// Builds the xterm palette.
+const fn xterm_256() -> [Colour; 256] {
+    let mut palette = [Colour::Black; 256];
+    let mut i = 0;
+    while i < 256 {
+        palette[i] = if i < 16 {
+            let (r, g, b) = XTERM_SYSTEM[i];
+            Colour::Rgb(r, g, b)
+        } else if i < 232 {
+            let n = i - 16;
+            Colour::Rgb(
+                XTERM_CUBE_LEVELS[n / 36],
+                XTERM_CUBE_LEVELS[(n / 6) % 6],
+                XTERM_CUBE_LEVELS[n % 6],
+            )
+        } else {
+            let level = 8 + 10 * (i - 232) as u8;
+            Colour::Rgb(level, level, level)
+        };
+        i += 1;
+    }
+    palette
+}
+
+impl Colour {
+    /// Returns the CSS colour with the given name, or `None` if there is no
+    /// such colour.
+    ///
+    /// The name is matched ignoring case, spaces, hyphens and underscores, so
+    /// `"CornflowerBlue"` and `"cornflower blue"` both give the same colour.
+    /// See [`CSS_COLOURS`].
+    ///
+    /// [`CSS_COLOURS`]: ../palette/constant.CSS_COLOURS.html
+    ///
+    pub fn from_css_name(name: &str) -> Option<Colour> {
+        let name = name
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        CSS_COLOURS
+            .binary_search_by(|(css_name, _)| (*css_name).cmp(name.as_str()))
+            .ok()
+            .map(|index| CSS_COLOURS[index].1)
+    }
+
+    /// Returns the colour at the given index of the xterm 256 colour palette.
+    ///
+    /// This is the colour selected by the ANSI escape sequences `ESC[38;5;nm`
+    /// and `ESC[48;5;nm`.
+    ///
+    pub fn from_xterm(index: u8) -> Colour {
+        XTERM_256[index as usize]
+    }
+}
+
+/// A list of colours that can be looked up by index or by name.
+///
+/// Drawing with colours taken from a palette allows the whole look of a game
+/// to be changed at once, for themes or day and night cycles, by swapping
+/// in another palette with [`Image::swap_palette`].
+///
+/// [`Image::swap_palette`]: ../image/struct.Image.html#method.swap_palette
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Palette {
+    /// The colours in index order.
+    colours: Vec<Colour>,
+
+    /// The index of each named colour.
+    #[cfg_attr(feature = "serde", serde(default))]
+    names: HashMap<String, usize>,
+}
+
+impl Palette {
+    /// Creates a new empty palette.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a colour to the end of the palette.
+    ///
+    /// # Returns
+    ///
+    /// The index of the new colour.
+    ///
+    pub fn push(&mut self, colour: Colour) -> usize {
+        self.colours.push(colour);
+        self.colours.len() - 1
+    }
+
+    /// Adds a named colour to the end of the palette.  If the name is already
+    /// used, it now refers to the new colour.
+    ///
+    /// # Returns
+    ///
+    /// The index of the new colour.
+    ///
+    pub fn push_named(&mut self, name: impl Into<String>, colour: Colour) -> usize {
+        let index = self.push(colour);
+        self.names.insert(name.into(), index);
+        index
+    }
+
+    /// Returns the colour at the given index, or `None` if the index is out of
+    /// range.
+    pub fn get(&self, index: usize) -> Option<Colour> {
+        self.colours.get(index).copied()
+    }
+
+    /// Returns the colour with the given name, or `None` if there is no such
+    /// colour.
+    pub fn get_named(&self, name: &str) -> Option<Colour> {
+        self.index_of(name).and_then(|index| self.get(index))
+    }
+
+    /// Returns the index of the colour with the given name, or `None` if there
+    /// is no such colour.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    /// Replaces the colour at the given index.
+    ///
+    /// # Returns
+    ///
+    /// True if the index is in range and the colour was replaced.
+    ///
+    pub fn set(&mut self, index: usize, colour: Colour) -> bool {
+        match self.colours.get_mut(index) {
+            Some(entry) => {
+                *entry = colour;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of colours in the palette.
+    pub fn len(&self) -> usize {
+        self.colours.len()
+    }
+
+    /// Returns true if the palette has no colours.
+    pub fn is_empty(&self) -> bool {
+        self.colours.is_empty()
+    }
+
+    /// Returns the colours of the palette in index order.
+    pub fn colours(&self) -> &[Colour] {
+        &self.colours
+    }
+
+    /// Returns the index of the palette colour that looks most like the given
+    /// colour, or `None` if the palette is empty.
+    ///
+    /// Colours are compared with [`Colour::distance`].  If several palette
+    /// colours are equally close, the first is chosen.
+    ///
+    /// [`Colour::distance`]: ../colour/enum.Colour.html#method.distance
+    ///
+    pub fn nearest_index(&self, colour: Colour) -> Option<usize> {
+        self.colours
+            .iter()
+            .map(|entry| entry.distance(colour))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| index)
+    }
+
+    /// Returns the palette colour that looks most like the given colour, or
+    /// `None` if the palette is empty.
+    pub fn nearest(&self, colour: Colour) -> Option<Colour> {
+        self.nearest_index(colour).map(|index| self.colours[index])
+    }
+
+    /// Parses a palette file, in either the GIMP `.gpl` format or as a list
+    /// of hex colours such as the `.hex` files from Lospec.
+    ///
+    /// Each line is a colour, either as red, green and blue values from 0 to
+    /// 255 or as a hex string such as `#ff8000` or `ff8000`.  Any text after
+    /// the colour is its name.  Blank lines, comments starting with `#` or
+    /// `;`, and the GIMP header lines are skipped.
+    ///
+    /// A line starting with `#` is a comment unless its first word is a hex
+    /// colour.  As short words such as `#add` look like 3 digit colours, a 3
+    /// digit colour followed by more text is also a comment, so
+    /// `#add more below` is skipped.  Named colours need all 6 or 8 digits.
+    ///
+    /// # Returns
+    ///
+    /// The palette, or [`MageError::InvalidColour`] with the first line that
+    /// is not a colour.
+    ///
+    /// [`MageError::InvalidColour`]: ../error/enum.MageError.html#variant.InvalidColour
+    ///
+    pub fn parse(text: &str) -> Result<Palette, MageError> {
+        let mut palette = Palette::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty()
+                || line == "GIMP Palette"
+                || line.starts_with("Name:")
+                || line.starts_with("Columns:")
+                || line.starts_with(';')
+            {
+                continue;
+            }
+
+            let (first, rest) = line
+                .split_once(char::is_whitespace)
+                .map_or((line, ""), |(first, rest)| (first, rest.trim()));
+            // Short hex colours need the `#`, as they look like GIMP's
+            // decimal values.
+            let (hex, lengths) = match first.strip_prefix('#') {
+                Some(hex) => (hex, &[3, 6, 8][..]),
+                None => (first, &[6, 8][..]),
+            };
+            let is_hex = lengths.contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit());
+            if line.starts_with('#') && (!is_hex || (hex.len() == 3 && !rest.is_empty())) {
+                continue;
+            }
+            let (colour, name) = if is_hex {
+                (format!("#{hex}").parse::<Colour>()?, rest.to_string())
+            } else {
+                let mut parts = line.split_whitespace();
+                let mut channel = || -> Result<u8, MageError> {
+                    parts
+                        .next()
+                        .and_then(|part| part.parse().ok())
+                        .ok_or_else(|| MageError::InvalidColour(line.to_string()))
+                };
+                let colour = Colour::Rgb(channel()?, channel()?, channel()?);
+                (colour, parts.collect::<Vec<_>>().join(" "))
+            };
+
+            // GIMP names colours it has no name for "Untitled".
+            if name.is_empty() || name == "Untitled" {
+                palette.push(colour);
+            } else {
+                palette.push_named(name, colour);
+            }
+        }
+        Ok(palette)
+    }
+}
+
+impl Index<usize> for Palette {
+    type Output = Colour;
+
+    fn index(&self, index: usize) -> &Colour {
+        &self.colours[index]
+    }
+}
+
+impl From<&[Colour]> for Palette {
+    fn from(colours: &[Colour]) -> Self {
+        Self {
+            colours: colours.to_vec(),
+            names: HashMap::new(),
+        }
+    }
+}
+
+impl<const N: usize> From<[Colour; N]> for Palette {
+    fn from(colours: [Colour; N]) -> Self {
+        Self::from(&colours[..])
+    }
+}
+
+impl FromIterator<Colour> for Palette {
+    fn from_iter<I: IntoIterator<Item = Colour>>(iter: I) -> Self {
+        Self {
+            colours: iter.into_iter().collect(),
+            names: HashMap::new(),
+        }
+    }
+}
+
+impl Image {
+    /// Replaces the colours of one palette with the colours of another.
+    ///
+    /// Every ink and paper colour in the image that matches a colour in `old`
+    /// is replaced with the colour at the same index in `new`.  Colours not in
+    /// `old`, or whose index is beyond the end of `new`, are left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - The palette the image was drawn with.
+    /// * `new` - The palette to redraw the image with.
+    ///
+    /// # Notes
+    ///
+    /// If a colour appears more than once in `old`, its first index is used.
+    ///
+    pub fn swap_palette(&mut self, old: &Palette, new: &Palette) {
+        let mut map = HashMap::new();
+        for (old, new) in old.colours.iter().zip(&new.colours) {
+            map.entry(old.colour()).or_insert(new.colour());
+        }
+
+        for colour in self.fore_image.iter_mut().chain(self.back_image.iter_mut()) {
+            if let Some(&new) = map.get(colour) {
+                *colour = new;
+            }
+        }
+    }
+
+    /// Replaces every ink and paper colour in the image with the nearest
+    /// colour in a palette.
+    ///
+    /// This allows true colour art to be converted for a limited palette, such
+    /// as the 16 colours of [`CGA`], or for export to terminals with few
+    /// colours.
+    ///
+    /// # Arguments
+    ///
+    /// * `palette` - The colours to convert the image to.
+    ///
+    /// # Notes
+    ///
+    /// The alpha of each colour is kept, so transparent paper stays
+    /// transparent.  If the palette is empty, the image is unchanged.
+    ///
+    /// [`CGA`]: ../palette/constant.CGA.html
+    ///
+    pub fn quantize(&mut self, palette: &Palette) {
+        let mut cache = HashMap::new();
+        for colour in self.fore_image.iter_mut().chain(self.back_image.iter_mut()) {
+            let rgb = *colour & 0x00ffffff;
+            let nearest = *cache.entry(rgb).or_insert_with(|| {
+                palette
+                    .nearest(Colour::from(rgb))
+                    .map(|nearest| nearest.colour() & 0x00ffffff)
+                    .unwrap_or(rgb)
+            });
+            *colour = (*colour & 0xff000000) | nearest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_palettes_skip_comment_lines() {
+        let text = "\
+# PICO-8 colours
+#add more below
+#
+#not a colour
+#ff0000 Red
+#0f0
+00ff00
+#0000ffff Blue
+#fab and more
+";
+        let palette = Palette::parse(text).unwrap();
+        assert_eq!(
+            palette.colours(),
+            [
+                Colour::Rgb(0xff, 0, 0),
+                Colour::Rgb(0, 0xff, 0),
+                Colour::Rgb(0, 0xff, 0),
+                Colour::Rgba(0, 0, 0xff, 0xff),
+            ]
+        );
+        assert_eq!(palette.get_named("Red"), Some(Colour::Rgb(0xff, 0, 0)));
+        assert_eq!(palette.index_of("Blue"), Some(3));
+        assert_eq!(palette.index_of("more below"), None);
+    }
+
+    #[test]
+    fn gimp_palettes_read_names_and_skip_headers() {
+        let text = "\
+GIMP Palette
+Name: Test
+Columns: 2
+# A comment
+255 128   0 Orange
+  0   0   0 Untitled
+";
+        let palette = Palette::parse(text).unwrap();
+        assert_eq!(palette.len(), 2);
+        assert_eq!(palette.get_named("Orange"), Some(Colour::Rgb(255, 128, 0)));
+        assert!(Palette::parse("12 34").is_err());
+    }
+}
@@ -0,0 +1,304 @@
+//! Paths through a list of points, drawn with line and arrow glyphs.
+
+use chrono::Duration;
+
+use crate::{
+    colour::Colour,
+    image::{Char, Image, Point},
+    lines::{line_glyph, LineStyle},
+    sprite::LoopMode,
+};
+
+/// The glyph drawn at dots along a path, `·`.
+const DOT_GLYPH: u8 = 0xfa;
+
+/// The glyphs of arrows pointing right, left, up and down, `→←↑↓`.
+const ARROW_GLYPHS: [u8; 4] = [0x1a, 0x1b, 0x18, 0x19];
+
+/// The glyphs of arrowheads pointing right, left, up and down, `►◄▲▼`.
+const ARROWHEAD_GLYPHS: [u8; 4] = [0x10, 0x11, 0x1e, 0x1f];
+
+/// How a [`Path`] is drawn.
+///
+/// Code page 437 has no diagonal lines or arrows, so diagonal steps are drawn
+/// with `/` and `\` in every style other than dots.
+///
+/// [`Path`]: struct.Path.html
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathStyle {
+    /// Box drawing lines, with corners where the path turns.
+    Line(LineStyle),
+
+    /// A dot in every cell.
+    Dots,
+
+    /// An arrow in every cell pointing to the next cell.
+    Arrows,
+}
+
+/// A path through a list of waypoints, joined by straight lines.
+///
+/// Paths are useful for showing targeting lines, the routes of patrolling
+/// monsters and directions in tutorials.  A [`PathMarker`] moves along a path
+/// over time.
+///
+/// ```
+/// use mage_core::{image::Point, path::Path};
+///
+/// let path = Path::new([Point::new(0, 0), Point::new(3, 0), Point::new(3, 2)]);
+/// assert_eq!(path.len(), 6);
+/// assert_eq!(path.cells()[4], Point::new(3, 1));
+/// ```
+///
+/// [`PathMarker`]: struct.PathMarker.html
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Path {
+    /// The points the path goes through, in order.
+    waypoints: Vec<Point>,
+
+    /// Every cell along the path, in order, without repeats where the lines
+    /// meet.
+    cells: Vec<Point>,
+}
+
+impl Path {
+    /// Creates a new path through the given points, joined by straight lines.
+    pub fn new(waypoints: impl IntoIterator<Item = Point>) -> Self {
+        let waypoints: Vec<Point> = waypoints.into_iter().collect();
+        let mut cells: Vec<Point> = waypoints.first().copied().into_iter().collect();
+        for pair in waypoints.windows(2) {
+            cells.extend(line_cells(pair[0], pair[1]).into_iter().skip(1));
+        }
+        Self { waypoints, cells }
+    }
+
+    /// Returns the points the path goes through.
+    pub fn waypoints(&self) -> &[Point] {
+        &self.waypoints
+    }
+
+    /// Returns every cell along the path, in order.
+    pub fn cells(&self) -> &[Point] {
+        &self.cells
+    }
+
+    /// Returns the number of cells along the path.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns true if the path has no waypoints.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Draws the path on to an image.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image to draw on.
+    /// * `style` - How each cell of the path is drawn.
+    /// * `ink` - The foreground colour of the path.
+    /// * `paper` - The background colour of the path.
+    /// * `arrowhead` - If true, the last cell is drawn as an arrowhead
+    ///   pointing the way the path ends.
+    ///
+    /// # Notes
+    ///
+    /// If the coordinates are out of bounds, the path is clipped.
+    ///
+    pub fn draw(
+        &self,
+        image: &mut Image,
+        style: PathStyle,
+        ink: Colour,
+        paper: Colour,
+        arrowhead: bool,
+    ) {
+        let last = self.cells.len().saturating_sub(1);
+        for (i, &p) in self.cells.iter().enumerate() {
+            let prev = i.checked_sub(1).map(|i| step(p, self.cells[i]));
+            let next = self.cells.get(i + 1).map(|&q| step(p, q));
+            let glyph = if arrowhead && i == last && i > 0 {
+                // Point the way the path was going as it reached the end.
+                let (dx, dy) = prev.map_or((0, 0), |(dx, dy)| (-dx, -dy));
+                ARROWHEAD_GLYPHS[direction(dx, dy)]
+            } else {
+                glyph(style, prev, next)
+            };
+            image.draw_char(p, Char::new(glyph, ink, paper));
+        }
+    }
+}
+
+/// Moves a point along a [`Path`] at a steady speed.
+///
+/// The marker is advanced with the time since the last tick and gives the
+/// cell it is in, so a glyph can be drawn there to show movement along the
+/// path.
+///
+/// [`Path`]: struct.Path.html
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathMarker {
+    /// The cells of the path.
+    cells: Vec<Point>,
+
+    /// How many cells the marker moves each second.
+    speed: f64,
+
+    /// What to do when the end of the path is reached.
+    mode: LoopMode,
+
+    /// How long the marker has moved for in microseconds.
+    elapsed: i64,
+}
+
+impl PathMarker {
+    /// Creates a new marker at the start of a path, which stops when it
+    /// reaches the end.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to move along.
+    /// * `speed` - How many cells the marker moves each second.
+    ///
+    pub fn new(path: &Path, speed: f32) -> Self {
+        Self {
+            cells: path.cells.clone(),
+            speed: speed.max(0.0) as f64,
+            mode: LoopMode::Once,
+            elapsed: 0,
+        }
+    }
+
+    /// Sets what happens when the marker reaches the end of the path.
+    pub fn with_loop_mode(mut self, mode: LoopMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Moves the marker along the path by the distance covered in the given
+    /// time.
+    pub fn advance(&mut self, dt: Duration) {
+        let dt = dt.num_microseconds().unwrap_or(i64::MAX).max(0);
+        self.elapsed = self.elapsed.saturating_add(dt);
+    }
+
+    /// Returns the index of the cell of the path the marker is in.
+    pub fn index(&self) -> usize {
+        let last = self.cells.len().saturating_sub(1);
+        if last == 0 {
+            return 0;
+        }
+        let moved = (self.elapsed as f64 / 1_000_000.0 * self.speed) as usize;
+        match self.mode {
+            LoopMode::Once => moved.min(last),
+            LoopMode::Loop => moved % (last + 1),
+            LoopMode::PingPong => {
+                let moved = moved % (last * 2);
+                if moved > last {
+                    last * 2 - moved
+                } else {
+                    moved
+                }
+            }
+        }
+    }
+
+    /// Returns the cell the marker is in, or `None` if the path is empty.
+    pub fn position(&self) -> Option<Point> {
+        self.cells.get(self.index()).copied()
+    }
+
+    /// Returns true if a marker that doesn't loop has reached the end of the
+    /// path.
+    pub fn is_finished(&self) -> bool {
+        self.mode == LoopMode::Once && self.index() + 1 >= self.cells.len()
+    }
+
+    /// Moves the marker back to the start of the path.
+    pub fn reset(&mut self) {
+        self.elapsed = 0;
+    }
+
+    /// Draws a char at the marker's position.
+    pub fn draw(&self, image: &mut Image, ch: Char) {
+        if let Some(p) = self.position() {
+            image.draw_char(p, ch);
+        }
+    }
+}
+
+/// Returns the cells of a straight line between two points, including both
+/// ends, using Bresenham's algorithm.
+fn line_cells(from: Point, to: Point) -> Vec<Point> {
+    let (dx, dy) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+    let (sx, sy) = ((to.x - from.x).signum(), (to.y - from.y).signum());
+    let mut error = dx + dy;
+    let mut p = from;
+    let mut cells = vec![p];
+    while p != to {
+        let e2 = 2 * error;
+        if e2 >= dy {
+            error += dy;
+            p.x += sx;
+        }
+        if e2 <= dx {
+            error += dx;
+            p.y += sy;
+        }
+        cells.push(p);
+    }
+    cells
+}
+
+/// Returns the step from one cell to a neighbouring cell.
+fn step(from: Point, to: Point) -> (i32, i32) {
+    ((to.x - from.x).signum(), (to.y - from.y).signum())
+}
+
+/// Returns the index into the arrow glyphs of a step: right, left, up or
+/// down.  Diagonal steps use their horizontal direction.
+fn direction(dx: i32, dy: i32) -> usize {
+    match (dx, dy) {
+        (1, _) => 0,
+        (-1, _) => 1,
+        (_, -1) => 2,
+        _ => 3,
+    }
+}
+
+/// Returns the glyph of a cell of a path, given the steps to the previous and
+/// next cells.
+fn glyph(style: PathStyle, prev: Option<(i32, i32)>, next: Option<(i32, i32)>) -> u8 {
+    if style == PathStyle::Dots {
+        return DOT_GLYPH;
+    }
+    // Steps are diagonal if they move on both axes.
+    let diagonal = [next, prev]
+        .into_iter()
+        .flatten()
+        .find(|&(dx, dy)| dx != 0 && dy != 0);
+    if let Some((dx, dy)) = diagonal {
+        return if dx == dy { b'\\' } else { b'/' };
+    }
+    match style {
+        PathStyle::Line(line_style) => {
+            let has = |step| prev == Some(step) || next == Some(step);
+            line_glyph(
+                has((0, -1)),
+                has((0, 1)),
+                has((-1, 0)),
+                has((1, 0)),
+                line_style,
+            )
+        }
+        _ => {
+            let (dx, dy) = next.or(prev.map(|(dx, dy)| (-dx, -dy))).unwrap_or((1, 0));
+            ARROW_GLYPHS[direction(dx, dy)]
+        }
+    }
+}
@@ -1,25 +1,46 @@
+use std::ops::Range;
+
 use crate::{
-    image::{Image, Rect},
-    PresentInput,
+    canvas::Canvas,
+    colour::Colour,
+    image::{BlitOptions, Image, Rect},
+    simd, PresentInput,
 };
 
 impl<'t> PresentInput<'t> {
+    /// Returns the width of the screen in chars.
+    pub fn width(&self) -> u32 {
+        self.screen.width
+    }
+
+    /// Returns the height of the screen in chars.
+    pub fn height(&self) -> u32 {
+        self.screen.height
+    }
+
     pub fn rect(&self) -> Rect {
-        Rect {
-            x: 0,
-            y: 0,
-            width: self.width,
-            height: self.height,
-        }
+        self.screen.rect()
     }
 
     pub fn new_image(&self) -> Image {
-        Image::new(self.width, self.height)
+        Image::new(self.screen.width, self.screen.height)
+    }
+
+    /// Returns a canvas for drawing within an area of the screen.  See
+    /// [`Canvas`].
+    ///
+    /// [`Canvas`]: canvas/struct.Canvas.html
+    ///
+    pub fn canvas(&mut self, rect: Rect) -> Canvas<'_> {
+        self.screen.canvas(rect)
     }
 
     /// Blit the an area of the source image to the screen.
     ///
-    /// The source rectangle is clipped to the source image.
+    /// The destination rectangle is clipped to the screen.  Any part of the
+    /// destination rectangle that falls outside the source rectangle or the
+    /// source image is cleared to the paper colour.  Transparent chars in the
+    /// source image leave the screen untouched.
     ///
     /// # Arguments
     ///
@@ -29,104 +50,122 @@ impl<'t> PresentInput<'t> {
     /// * `paper` - The paper colour to use outside the source image.  This will
     ///   also be used as the ink colour.
     ///
-    pub fn blit(&mut self, mut dst_rect: Rect, src_rect: Rect, src_image: &Image, paper: u32) {
-        assert_eq!(dst_rect.width, src_rect.width);
-        assert_eq!(dst_rect.height, src_rect.height);
-        assert!(dst_rect.x >= 0 && dst_rect.y >= 0);
-        assert!(dst_rect.x + dst_rect.width as i32 <= self.width as i32);
-        assert!(dst_rect.y + dst_rect.height as i32 <= self.height as i32);
-
-        // Clip the source rectangle to the source image and adjust the
-        // destination rectangle accordingly.
-        let (src_rect, src_offset) = src_rect.clip_within(src_image.width, src_image.height);
-        dst_rect.width = src_rect.width;
-        dst_rect.height = src_rect.height;
-
-        if src_rect.width == 0 || src_rect.height == 0 {
-            // Nothing to blit, so clear it
-            self.clear(dst_rect, paper);
-        } else {
-            // Clear the top-left corner of the destination rectangle according
-            // to any offset in the source rectangle.
-            self.clear(
-                Rect {
-                    x: dst_rect.x,
-                    y: dst_rect.y,
-                    width: src_offset.x as u32,
-                    height: src_offset.y as u32,
-                },
-                paper,
-            );
+    pub fn blit(&mut self, dst_rect: Rect, src_rect: Rect, src_image: &Image, paper: Colour) {
+        let paper = paper.colour();
+        let Some((x0, x1, y0, y1)) = self.clip(dst_rect) else {
+            return;
+        };
 
-            // Clear the top-right corner of the destination rectangle according
-            // to any offset in the source rectangle.
-            self.clear(
-                Rect {
-                    x: dst_rect.x + src_offset.x,
-                    y: dst_rect.y,
-                    width: dst_rect.width - src_offset.x as u32,
-                    height: src_offset.y as u32,
-                },
-                paper,
-            );
+        // The range of screen columns that have a source char, as the source
+        // rectangle's left edge, right edge and the source image's edges.
+        let shift = dst_rect.x as i64 - src_rect.x as i64;
+        let src_x0 = (dst_rect.x as i64).max(shift).clamp(x0, x1);
+        let src_x1 = (dst_rect.x as i64 + src_rect.width as i64)
+            .min(shift + src_image.width as i64)
+            .clamp(src_x0, x1);
 
-            // Clear the bottom-left corner of the destination rectangle according
-            // to any offset in the source rectangle.
-            self.clear(
-                Rect {
-                    x: dst_rect.x,
-                    y: dst_rect.y + src_offset.y,
-                    width: src_offset.x as u32,
-                    height: dst_rect.height - src_offset.y as u32,
-                },
-                paper,
-            );
+        let width = self.screen.width as usize;
+        for y in y0..y1 {
+            let row = y as usize * width;
+            let offset_y = y - dst_rect.y as i64;
+            let src_y = src_rect.y as i64 + offset_y;
+            let has_source =
+                offset_y < src_rect.height as i64 && (0..src_image.height as i64).contains(&src_y);
 
-            // Blit the image to the screen.
-            self.blit_internal(
-                Rect {
-                    x: dst_rect.x + src_offset.x,
-                    y: dst_rect.y + src_offset.y,
-                    width: dst_rect.width - src_offset.x as u32,
-                    height: dst_rect.height - src_offset.y as u32,
-                },
-                src_rect,
-                src_image,
-            );
+            if has_source && src_x0 < src_x1 {
+                self.clear_span(row + x0 as usize..row + src_x0 as usize, paper);
+                self.clear_span(row + src_x1 as usize..row + x1 as usize, paper);
+                self.blit_internal(
+                    Rect::new(src_x0 as i32, y as i32, (src_x1 - src_x0) as u32, 1),
+                    Rect::new(
+                        (src_x0 - shift) as i32,
+                        src_y as i32,
+                        (src_x1 - src_x0) as u32,
+                        1,
+                    ),
+                    src_image,
+                );
+            } else {
+                self.clear_span(row + x0 as usize..row + x1 as usize, paper);
+            }
         }
     }
 
-    pub fn clear(&mut self, rect: Rect, paper: u32) {
-        assert!(rect.x >= 0 && rect.y >= 0);
-        assert!(rect.x + rect.width as i32 <= self.width as i32);
-        assert!(rect.y + rect.height as i32 <= self.height as i32);
-
-        let mut i = rect.y as usize * self.width as usize + rect.x as usize;
-        for _ in 0..rect.height {
-            self.fore_image[i..i + rect.width as usize].fill(paper);
-            self.back_image[i..i + rect.width as usize].fill(paper);
-            self.text_image[i..i + rect.width as usize].fill(0);
-            i += self.width as usize;
+    /// Clears an area of the screen to the paper colour.
+    ///
+    /// The rectangle is clipped to the screen.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area of the screen to clear.
+    /// * `paper` - The colour to clear to.  This will also be used as the ink
+    ///   colour.
+    ///
+    pub fn clear(&mut self, rect: Rect, paper: Colour) {
+        let paper = paper.colour();
+        let Some((x0, x1, y0, y1)) = self.clip(rect) else {
+            return;
+        };
+
+        let width = self.screen.width as usize;
+        for y in y0..y1 {
+            let row = y as usize * width;
+            self.clear_span(row + x0 as usize..row + x1 as usize, paper);
         }
     }
 
-    pub fn blit_internal(&mut self, dst_rect: Rect, src_rect: Rect, src_image: &Image) {
-        assert_eq!(dst_rect.width, src_rect.width);
-        assert_eq!(dst_rect.height, src_rect.height);
-        assert!(dst_rect.x >= 0 && dst_rect.y >= 0);
-        assert!(dst_rect.x + dst_rect.width as i32 <= self.width as i32);
-        assert!(dst_rect.y + dst_rect.height as i32 <= self.height as i32);
+    /// Clips a rectangle to the screen, returning the start and end columns
+    /// and rows, or `None` if the rectangle is entirely off screen.
+    fn clip(&self, rect: Rect) -> Option<(i64, i64, i64, i64)> {
+        let x0 = (rect.x as i64).max(0);
+        let y0 = (rect.y as i64).max(0);
+        let x1 = (rect.x as i64 + rect.width as i64).min(self.screen.width as i64);
+        let y1 = (rect.y as i64 + rect.height as i64).min(self.screen.height as i64);
+        (x0 < x1 && y0 < y1).then_some((x0, x1, y0, y1))
+    }
 
-        let mut dst_i = dst_rect.y as usize * self.width as usize + dst_rect.x as usize;
+    /// Clears a range of cells to the paper colour.
+    fn clear_span(&mut self, span: Range<usize>, paper: u32) {
+        self.screen.fore_image[span.clone()].fill(paper);
+        self.screen.back_image[span.clone()].fill(paper);
+        self.screen.text_image[span].fill(0);
+    }
+
+    /// Copies the source rectangle to the destination rectangle, which must
+    /// already be clipped to both the screen and the source image by
+    /// [`blit`].
+    ///
+    /// [`blit`]: struct.PresentInput.html#method.blit
+    ///
+    fn blit_internal(&mut self, dst_rect: Rect, src_rect: Rect, src_image: &Image) {
+        debug_assert_eq!(dst_rect.width, src_rect.width);
+        debug_assert_eq!(dst_rect.height, src_rect.height);
+        debug_assert!(dst_rect.x >= 0 && dst_rect.y >= 0);
+        debug_assert!(dst_rect.x + dst_rect.width as i32 <= self.screen.width as i32);
+        debug_assert!(dst_rect.y + dst_rect.height as i32 <= self.screen.height as i32);
+
+        let mut dst_i = dst_rect.y as usize * self.screen.width as usize + dst_rect.x as usize;
         let mut src_i = src_rect.y as usize * src_image.width as usize + src_rect.x as usize;
+        let w = dst_rect.width as usize;
         for _ in 0..dst_rect.height {
-            self.fore_image[dst_i..dst_i + dst_rect.width as usize]
-                .copy_from_slice(&src_image.fore_image[src_i..src_i + dst_rect.width as usize]);
-            self.back_image[dst_i..dst_i + dst_rect.width as usize]
-                .copy_from_slice(&src_image.back_image[src_i..src_i + dst_rect.width as usize]);
-            self.text_image[dst_i..dst_i + dst_rect.width as usize]
-                .copy_from_slice(&src_image.text_image[src_i..src_i + dst_rect.width as usize]);
-            dst_i += self.width as usize;
+            // Transparent chars leave the screen untouched.
+            let src = src_i..src_i + w;
+            simd::copy_opaque(
+                [
+                    &mut self.screen.fore_image[dst_i..dst_i + w],
+                    &mut self.screen.back_image[dst_i..dst_i + w],
+                    &mut self.screen.text_image[dst_i..dst_i + w],
+                ],
+                [
+                    &src_image.fore_image[src.clone()],
+                    &src_image.back_image[src.clone()],
+                    &src_image.text_image[src.clone()],
+                ],
+                &src_image.text_image[src.clone()],
+                &src_image.back_image[src],
+                BlitOptions::default(),
+            );
+            dst_i += self.screen.width as usize;
             src_i += src_image.width as usize;
         }
     }
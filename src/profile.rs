@@ -0,0 +1,38 @@
+//! Spans around the stages of a frame, so that where the frame time goes can
+//! be seen with a profiler.
+//!
+//! Each stage is wrapped in a `tracing` span at the info level, so any
+//! `tracing` subscriber that records spans (such as `tracing-tracy` or
+//! `tracing-chrome`) will show them.  With the `puffin` feature, each stage is
+//! also a puffin scope and a puffin frame ends after every frame drawn.
+//!
+//! The spans are:
+//!
+//! * `tick` - The app's `tick` method.
+//! * `present` - The app's `present` method.
+//! * `render` - Drawing a frame on the GPU, which is made up of:
+//!   * `acquire_frame` - Waiting for the window's next surface texture.
+//!   * `upload_textures` - Copying the character and colour images to the GPU.
+//!   * `submit` - Encoding and submitting the render pass, and presenting the
+//!     frame.
+//!
+//! Headless runs have the `tick` and `present` spans, and end a puffin frame
+//! after each step.
+
+/// Enters a span, and a puffin scope with the `puffin` feature, that lasts
+/// until the end of the enclosing block.
+macro_rules! profile_scope {
+    ($name:literal) => {
+        let _span = tracing::info_span!($name).entered();
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!($name);
+    };
+}
+
+pub(crate) use profile_scope;
+
+/// Marks the end of a frame for the profiler.
+pub(crate) fn end_frame() {
+    #[cfg(feature = "puffin")]
+    puffin::GlobalProfiler::lock().new_frame();
+}
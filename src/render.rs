@@ -1,45 +1,506 @@
-use std::iter::once;
+use std::{
+    env, fmt,
+    iter::once,
+    sync::{Arc, Mutex},
+};
 
 use bytemuck::{cast_slice, Pod, Zeroable};
+use tracing::{info, warn};
 use wgpu::{
     include_wgsl,
-    util::{BufferInitDescriptor, DeviceExt},
-    Backends, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
-    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, BlendState,
-    BufferBindingType, BufferUsages, Color, ColorTargetState, ColorWrites,
-    CommandEncoderDescriptor, CompositeAlphaMode, Device, DeviceDescriptor, Extent3d, Features,
-    FragmentState, FrontFace, ImageCopyTexture, ImageDataLayout, Instance, InstanceDescriptor,
-    Limits, LoadOp, MemoryHints, MultisampleState, Operations, Origin3d,
-    PipelineCompilationOptions, PipelineLayoutDescriptor, PolygonMode, PowerPreference,
-    PresentMode, PrimitiveState, PrimitiveTopology, Queue, RenderPassColorAttachment,
-    RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, RequestAdapterOptions,
-    ShaderStages, StoreOp, Surface, SurfaceConfiguration, SurfaceError, TextureAspect,
-    TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages,
-    TextureViewDescriptor, TextureViewDimension, VertexState,
+    util::{BufferInitDescriptor, DeviceExt, StagingBelt},
+    Adapter, AdapterInfo, Backends, BindGroup, BindGroupDescriptor, BindGroupEntry,
+    BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType,
+    BlendState, Buffer, BufferBindingType, BufferDescriptor, BufferSize, BufferUsages, Color,
+    ColorTargetState, ColorWrites, CommandEncoder, CommandEncoderDescriptor, CompositeAlphaMode,
+    Device, DeviceDescriptor, Extent3d, Features, FragmentState, FrontFace, ImageCopyBuffer,
+    ImageCopyTexture, ImageDataLayout, Instance, InstanceDescriptor, Limits, LoadOp, MemoryHints,
+    MultisampleState, Operations, Origin3d, PipelineCompilationOptions, PipelineLayoutDescriptor,
+    PolygonMode, PowerPreference, PresentMode, PrimitiveState, PrimitiveTopology, Queue,
+    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor,
+    RequestAdapterOptions, ShaderStages, StoreOp, Surface, SurfaceConfiguration, SurfaceError,
+    TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType,
+    TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension, VertexState,
+    COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    window::Window,
 };
-use winit::{dpi::PhysicalSize, window::Window};
 
-use crate::{error::MageError, FontData};
+#[cfg(feature = "frame-texture")]
+use crate::frame_texture::{FrameSink, SharedOutput};
+use crate::{
+    colour::ColourVision,
+    error::MageError,
+    image::{Image, Point},
+    profile::profile_scope,
+    stats::MemoryStats,
+    FontData,
+};
+
+/// The environment variable that chooses the GPU backends, as a
+/// comma-separated list such as `vulkan,gl`.
+const BACKEND_VAR: &str = "MAGE_BACKEND";
+
+/// The environment variable that chooses the GPU adapter by part of its name.
+const ADAPTER_VAR: &str = "MAGE_ADAPTER";
+
+/// The environment variable that turns vertical sync off, or on if it is `0`.
+const NO_VSYNC_VAR: &str = "MAGE_NO_VSYNC";
+
+/// The size of each chunk of memory that textures are uploaded through.
+/// Larger uploads get a chunk of their own size.
+const STAGING_CHUNK_SIZE: u64 = 1 << 20;
+
+/// What is known about the GPU being drawn with, once the renderer has
+/// started.
+static GPU_INFO: Mutex<Option<GpuInfo>> = Mutex::new(None);
+
+/// A GPU context created outside of Mage, so that Mage can share the GPU
+/// with another renderer in the same process.
+///
+/// Set it as [`Config::gpu`] and Mage will draw with this device instead of
+/// creating its own.  The adapter must be able to draw to Mage's window, so
+/// create it with a `compatible_surface` or check it with
+/// `Adapter::is_surface_supported` first.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use mage_core::{render::GpuContext, Config};
+///
+/// # async fn example() -> Option<()> {
+/// let instance = wgpu::Instance::default();
+/// let adapter = instance
+///     .request_adapter(&wgpu::RequestAdapterOptions::default())
+///     .await?;
+/// let (device, queue) = adapter
+///     .request_device(&wgpu::DeviceDescriptor::default(), None)
+///     .await
+///     .ok()?;
+/// let gpu = GpuContext::new(
+///     Arc::new(instance),
+///     Arc::new(adapter),
+///     Arc::new(device),
+///     Arc::new(queue),
+/// );
+///
+/// // Keep a clone of the context to draw something else with the same device.
+/// let config = Config {
+///     gpu: Some(gpu.clone()),
+///     ..Default::default()
+/// };
+/// # Some(())
+/// # }
+/// ```
+///
+/// [`Config::gpu`]: ../struct.Config.html#structfield.gpu
+///
+#[derive(Clone, Debug)]
+pub struct GpuContext {
+    /// The instance that the adapter was requested from, which Mage's window
+    /// surface is created with.
+    pub instance: Arc<Instance>,
+
+    /// The adapter that the device was requested from.
+    pub adapter: Arc<Adapter>,
+
+    /// The device that Mage creates its textures and pipeline with.
+    pub device: Arc<Device>,
+
+    /// The queue that Mage submits its render commands to.
+    pub queue: Arc<Queue>,
+}
+
+impl GpuContext {
+    /// Creates a GPU context from its parts.
+    ///
+    /// # Arguments
+    ///
+    /// * `instance` - The instance that the adapter was requested from.
+    /// * `adapter` - The adapter that the device was requested from.
+    /// * `device` - The device to draw with.
+    /// * `queue` - The device's queue.
+    ///
+    pub fn new(
+        instance: Arc<Instance>,
+        adapter: Arc<Adapter>,
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+    ) -> Self {
+        Self {
+            instance,
+            adapter,
+            device,
+            queue,
+        }
+    }
+
+    /// Creates the GPU context that Mage uses when none is given.  Each set
+    /// of backends in turn is tried until an adapter and device can be
+    /// created that draw to the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The window that will be drawn to.
+    ///
+    /// # Returns
+    ///
+    /// The GPU context and a surface for the window, or
+    /// [`MageError::NoAdapter`] listing everything that was tried.
+    ///
+    /// [`MageError::NoAdapter`]: ../error/enum.MageError.html#variant.NoAdapter
+    ///
+    // GPU objects can't be sent between threads in a browser, but the context
+    // still uses `Arc` so it can be shared between threads elsewhere.
+    #[cfg_attr(target_arch = "wasm32", allow(clippy::arc_with_non_send_sync))]
+    async fn for_window(
+        window: &Arc<Window>,
+        overrides: &GpuOverrides,
+    ) -> Result<(Self, Surface<'static>), MageError> {
+        let mut tried = Vec::new();
+        for backends in backend_chain(overrides.backends) {
+            let instance = Instance::new(InstanceDescriptor {
+                backends,
+                ..Default::default()
+            });
+            let surface = match instance.create_surface(window.clone()) {
+                Ok(surface) => surface,
+                Err(e) => {
+                    tried.push(format!("{:?}: unable to create surface ({})", backends, e));
+                    continue;
+                }
+            };
+
+            let Some(adapter) =
+                request_adapter(&instance, backends, &surface, overrides, &mut tried).await
+            else {
+                continue;
+            };
+            let info = adapter.get_info();
+            info!("Using GPU adapter: {} ({:?})", info.name, info.backend);
+
+            let device = adapter
+                .request_device(
+                    &DeviceDescriptor {
+                        label: Some("Main device"),
+                        // Breaking change means that you have to say "required" limits and features
+                        required_features: Features::empty(),
+                        required_limits: required_limits(&adapter),
+                        memory_hints: MemoryHints::Performance,
+                    },
+                    None,
+                )
+                .await;
+            let (device, queue) = match device {
+                Ok(device) => device,
+                Err(e) => {
+                    tried.push(format!("{}: unable to create device ({})", info.name, e));
+                    continue;
+                }
+            };
+
+            let context = Self::new(
+                Arc::new(instance),
+                Arc::new(adapter),
+                Arc::new(device),
+                Arc::new(queue),
+            );
+            return Ok((context, surface));
+        }
+
+        Err(MageError::NoAdapter(tried))
+    }
+}
+
+/// Returns the sets of backends to try in turn until one can draw to the
+/// window.
+///
+/// # Arguments
+///
+/// * `chosen` - The backends chosen with `MAGE_BACKEND`, which are the only
+///   ones tried if set.
+///
+fn backend_chain(chosen: Option<Backends>) -> Vec<Backends> {
+    if let Some(backends) = chosen {
+        return vec![backends];
+    }
+    if cfg!(target_arch = "wasm32") {
+        // Browsers without WebGPU can still draw with WebGL if the `webgl`
+        // feature is enabled.
+        vec![Backends::BROWSER_WEBGPU | Backends::GL]
+    } else {
+        vec![Backends::PRIMARY, Backends::SECONDARY]
+    }
+}
+
+/// Requests an adapter that can draw to the surface, trying the adapter
+/// named with `MAGE_ADAPTER`, then a high performance adapter, then the
+/// fallback adapter, which draws on the CPU.
+///
+/// # Arguments
+///
+/// * `instance` - The instance to request the adapter from.
+/// * `backends` - The backends that the instance was created with.
+/// * `surface` - The surface that the adapter must be able to draw to.
+/// * `overrides` - The GPU settings read from the environment.
+/// * `tried` - What has been tried so far, which each failure is added to.
+///
+/// # Returns
+///
+/// The adapter, or `None` if none could be found.
+///
+async fn request_adapter(
+    instance: &Instance,
+    backends: Backends,
+    surface: &Surface<'_>,
+    overrides: &GpuOverrides,
+    tried: &mut Vec<String>,
+) -> Option<Adapter> {
+    if let Some(name) = overrides.adapter.as_deref() {
+        match find_adapter(instance, backends, surface, name) {
+            Some(adapter) => return Some(adapter),
+            None => tried.push(format!("{:?}: no adapter named \"{}\"", backends, name)),
+        }
+    }
+
+    for (force_fallback_adapter, kind) in [(false, "high performance"), (true, "fallback")] {
+        let adapter = instance
+            .request_adapter(&RequestAdapterOptions {
+                power_preference: PowerPreference::HighPerformance,
+                force_fallback_adapter,
+                compatible_surface: Some(surface),
+            })
+            .await;
+        match adapter {
+            Some(adapter) => return Some(adapter),
+            None => tried.push(format!("{:?}: no {} adapter", backends, kind)),
+        }
+    }
+    None
+}
+
+/// Details of the GPU being drawn with, for bug reports from players on
+/// unusual hardware.  Its `Display` form is a short summary, which is also
+/// logged when the renderer starts.
+#[derive(Clone, Debug)]
+pub struct GpuInfo {
+    /// The adapter's name, vendor, driver and backend.
+    pub adapter: AdapterInfo,
+
+    /// The limits of the device.
+    pub limits: Limits,
+
+    /// The format of the window's surface.
+    pub surface_format: TextureFormat,
+
+    /// The present mode asked for when setting up the window's surface.  The
+    /// automatic modes are turned into one of `present_modes` by wgpu.
+    pub present_mode: PresentMode,
+
+    /// The present modes that the window's surface supports.
+    pub present_modes: Vec<PresentMode>,
+}
+
+impl fmt::Display for GpuInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let adapter = &self.adapter;
+        writeln!(
+            f,
+            "GPU: {} ({:?}, {:?} backend)",
+            adapter.name, adapter.device_type, adapter.backend
+        )?;
+        writeln!(
+            f,
+            "Vendor: {:#06x}, device: {:#06x}",
+            adapter.vendor, adapter.device
+        )?;
+        writeln!(f, "Driver: {} {}", adapter.driver, adapter.driver_info)?;
+        writeln!(f, "Surface format: {:?}", self.surface_format)?;
+        writeln!(
+            f,
+            "Present mode: {:?} (supported: {:?})",
+            self.present_mode, self.present_modes
+        )?;
+        writeln!(
+            f,
+            "Largest texture: {}x{}",
+            self.limits.max_texture_dimension_2d, self.limits.max_texture_dimension_2d
+        )?;
+        let (columns, rows) = self.max_grid_size();
+        write!(f, "Largest grid: {}x{} chars", columns, rows)
+    }
+}
+
+impl GpuInfo {
+    /// Returns the largest grid of chars, in columns and rows, that the GPU
+    /// can draw.  A window with room for more chars than this draws only this
+    /// many, with the rest of the window left black.
+    pub fn max_grid_size(&self) -> (u32, u32) {
+        max_grid_size(
+            self.limits.max_texture_dimension_2d,
+            self.limits.max_buffer_size,
+            u32::MAX,
+        )
+    }
+}
+
+/// Returns details of the GPU being drawn with.
+///
+/// # Returns
+///
+/// The details, or `None` if the renderer hasn't started yet.  If `run` has
+/// been called more than once, the details are of the last renderer started.
+///
+pub fn gpu_info() -> Option<GpuInfo> {
+    GPU_INFO.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Overrides for the GPU settings, read from environment variables at
+/// startup so that players can work around driver problems.
+#[derive(Default)]
+struct GpuOverrides {
+    /// The backends to choose an adapter from, from `MAGE_BACKEND`.
+    backends: Option<Backends>,
+
+    /// Part of the name of the adapter to use, from `MAGE_ADAPTER`.
+    adapter: Option<String>,
+
+    /// Whether to wait for vertical sync, from `MAGE_NO_VSYNC`.
+    vsync: Option<bool>,
+}
+
+impl GpuOverrides {
+    /// Reads the overrides from the environment.
+    fn from_env() -> Self {
+        let var = |name: &str| {
+            let value = env::var(name).ok()?;
+            info!("GPU setting overridden: {}={}", name, value);
+            Some(value)
+        };
+
+        Self {
+            backends: var(BACKEND_VAR).and_then(|value| parse_backends(&value)),
+            adapter: var(ADAPTER_VAR).map(|value| value.to_lowercase()),
+            vsync: var(NO_VSYNC_VAR).map(|value| {
+                matches!(
+                    value.trim().to_lowercase().as_str(),
+                    "0" | "false" | "no" | "off"
+                )
+            }),
+        }
+    }
+}
+
+/// Parses a comma-separated list of backend names, such as `vulkan,gl`.
+///
+/// # Returns
+///
+/// The backends, or `None` if no names were recognised.
+///
+fn parse_backends(text: &str) -> Option<Backends> {
+    let mut backends = Backends::empty();
+    for name in text.split(',').map(|name| name.trim().to_lowercase()) {
+        backends |= match name.as_str() {
+            "vulkan" | "vk" => Backends::VULKAN,
+            "metal" | "mtl" => Backends::METAL,
+            "dx12" | "d3d12" => Backends::DX12,
+            "gl" | "gles" | "opengl" => Backends::GL,
+            "webgpu" => Backends::BROWSER_WEBGPU,
+            "primary" => Backends::PRIMARY,
+            "secondary" => Backends::SECONDARY,
+            "all" => Backends::all(),
+            _ => {
+                warn!("Unknown GPU backend in {}: {}", BACKEND_VAR, name);
+                Backends::empty()
+            }
+        };
+    }
+    (!backends.is_empty()).then_some(backends)
+}
+
+/// Finds the first adapter whose name contains the given text and that can
+/// draw to the surface.
+///
+/// # Arguments
+///
+/// * `instance` - The instance to find the adapter with.
+/// * `backends` - The backends to look for adapters in.
+/// * `surface` - The surface that the adapter must be able to draw to.
+/// * `name` - The text to look for in the adapter's name, in lowercase.
+///
+/// # Returns
+///
+/// The adapter, or `None` if there is none, in which case the usual adapter
+/// is used instead.
+///
+#[cfg(not(target_arch = "wasm32"))]
+fn find_adapter(
+    instance: &Instance,
+    backends: Backends,
+    surface: &Surface,
+    name: &str,
+) -> Option<Adapter> {
+    let adapters = instance.enumerate_adapters(backends);
+    let found = adapters.iter().position(|adapter| {
+        adapter.get_info().name.to_lowercase().contains(name)
+            && adapter.is_surface_supported(surface)
+    });
+    if found.is_none() {
+        let names = adapters
+            .iter()
+            .map(|adapter| adapter.get_info().name)
+            .collect::<Vec<_>>();
+        warn!(
+            "No GPU adapter matching {}={} can draw to the window, found: {:?}",
+            ADAPTER_VAR, name, names
+        );
+    }
+    found.map(|index| adapters.into_iter().nth(index).unwrap())
+}
+
+/// Adapters can't be listed in a browser, so the usual one is always used.
+#[cfg(target_arch = "wasm32")]
+fn find_adapter(
+    _instance: &Instance,
+    _backends: Backends,
+    _surface: &Surface,
+    _name: &str,
+) -> Option<Adapter> {
+    warn!("{} is ignored in a web page", ADAPTER_VAR);
+    None
+}
+
+pub(crate) struct RenderState {
+    /// The GPU instance, kept to recreate the surface after it is lost.
+    instance: Arc<Instance>,
 
-pub(crate) struct RenderState<'a> {
-    /// The surface that we'll render to.
-    surface: Surface<'a>,
+    /// The surface that we'll render to, or `None` while the app is suspended.
+    surface: Option<Surface<'static>>,
 
     /// Various configuration options for the surface.
     surface_config: SurfaceConfiguration,
 
+    /// The ways the surface's textures can be used, which decides whether
+    /// frames can be copied from it to share them.
+    #[cfg(feature = "frame-texture")]
+    surface_usages: TextureUsages,
+
     /// The GPU device that will create and manage our resources.
-    device: Device,
+    device: Arc<Device>,
 
     /// The queue that we'll submit render commands to.
-    queue: Queue,
+    queue: Arc<Queue>,
 
     /// The render pipeline for drawing the game.
     render_pipeline: RenderPipeline,
 
-    /// The window that we'll draw to.
-    // Added lifetime and made it a reference because God (the compiler) said so
-    pub(crate) window: &'a Window,
+    /// The window that we'll draw to, which is shared so that the surface
+    /// can outlive the borrow of the window in `run`.
+    pub(crate) window: Arc<Window>,
 
     /// The texture that contains the foreground color data.
     fg_texture: Texture,
@@ -62,80 +523,120 @@ pub(crate) struct RenderState<'a> {
     /// The bind group for the uniform data.
     uniform_bind_group: BindGroup,
 
-    /// The size of each character in the font texture.
-    font_char_size: (u32, u32),
+    /// The buffer holding the uniform data.
+    uniform_buffer: Buffer,
+
+    /// The uniform data, kept so that parts of it can be changed.
+    uniforms: RenderUniforms,
 
     /// The size of the surface in characters.
-    surface_char_size: (u32, u32),
-}
+    grid: CharGrid,
 
-impl<'a> RenderState<'a> {
-    pub(crate) async fn new(window: &'a Window, font: FontData) -> Result<Self, MageError> {
-        let window_size = window.inner_size();
+    /// The chars that the app presents to, which are uploaded to the
+    /// character and colour textures each frame.
+    screen: Image,
 
-        let instance = Instance::new(InstanceDescriptor {
-            backends: Backends::PRIMARY,
-            ..Default::default()
-        });
+    /// The memory that the character and colour textures are uploaded
+    /// through, which is reused from frame to frame.
+    staging_belt: StagingBelt,
 
-        // Unsafe function no longer needed
+    /// The texture that each frame is also drawn into for capture tools.
+    #[cfg(feature = "frame-texture")]
+    shared_output: Option<SharedOutput>,
+}
 
-        let surface = instance.create_surface(window).ok();
+impl RenderState {
+    pub(crate) async fn new(
+        window: Arc<Window>,
+        font: FontData,
+        gpu: Option<GpuContext>,
+        vsync: bool,
+        present_mode: Option<PresentMode>,
+    ) -> Result<Self, MageError> {
+        let window_size = window.inner_size();
+        let overrides = GpuOverrides::from_env();
 
-        let adapter = instance
-            .request_adapter(&RequestAdapterOptions {
-                power_preference: PowerPreference::HighPerformance,
-                force_fallback_adapter: false,
-                compatible_surface: surface.as_ref(),
-            })
-            .await
-            .ok_or(MageError::BadAdapter)?;
-
-        let (device, queue) = adapter
-            .request_device(
-                &DeviceDescriptor {
-                    label: Some("Main device"),
-                    // Breaking change means that you have to say "required" limits and features
-                    required_features: Features::empty(),
-                    required_limits: Limits::default(),
-                    memory_hints: MemoryHints::Performance,
-                },
-                None,
-            )
-            .await?;
+        let (gpu, surface) = match gpu {
+            Some(gpu) => {
+                let surface = gpu.instance.create_surface(window.clone())?;
+                if !gpu.adapter.is_surface_supported(&surface) {
+                    return Err(MageError::BadAdapter);
+                }
+                (gpu, surface)
+            }
+            None => GpuContext::for_window(&window, &overrides).await?,
+        };
+        let GpuContext {
+            instance,
+            adapter,
+            device,
+            queue,
+        } = gpu;
 
-        let surface_expected = surface.expect("No surface Found");
-        let surface_format = surface_expected
-            .get_capabilities(&adapter)
+        let capabilities = surface.get_capabilities(&adapter);
+        let surface_format = capabilities
             .formats
             .iter()
             .copied()
             .find(|format| !format.is_srgb())
-            .expect("Could not find the surface format");
+            .ok_or_else(|| MageError::NoSurfaceFormat {
+                available: capabilities.formats.clone(),
+            })?;
+
+        // The environment variable wins over an exact present mode, so that
+        // players can still turn vsync on or off.
+        let present_mode = match (overrides.vsync, present_mode) {
+            (None, Some(requested)) => {
+                if !capabilities.present_modes.contains(&requested) {
+                    return Err(MageError::UnsupportedPresentMode {
+                        requested,
+                        supported: capabilities.present_modes,
+                    });
+                }
+                requested
+            }
+            (vsync_override, _) if vsync_override.unwrap_or(vsync) => PresentMode::AutoVsync,
+            _ => PresentMode::AutoNoVsync,
+        };
+
+        let font_size = (16 * font.char_width, 16 * font.char_height);
+        let limits = device.limits();
+        check_texture_size("font", font_size, limits.max_texture_dimension_2d)?;
+        let grid = CharGrid::new((font.char_width, font.char_height), window_size, &limits);
+        let surface_size = grid.size();
+        warn_if_clamped(&grid);
+
+        let (surface_width, surface_height) =
+            clamp_surface_size(window_size, limits.max_texture_dimension_2d);
         let surface_config = SurfaceConfiguration {
             usage: TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
-            width: window_size.width,
-            height: window_size.height,
-            present_mode: PresentMode::AutoNoVsync,
+            width: surface_width,
+            height: surface_height,
+            present_mode,
             desired_maximum_frame_latency: 2,
             alpha_mode: CompositeAlphaMode::Auto,
             view_formats: vec![],
         };
-        surface_expected.configure(&device, &surface_config);
+        if !grid.is_hidden() {
+            surface.configure(&device, &surface_config);
+        }
+
+        let gpu_info = GpuInfo {
+            adapter: adapter.get_info(),
+            limits: device.limits(),
+            surface_format,
+            present_mode,
+            present_modes: capabilities.present_modes,
+        };
+        info!("{}", gpu_info);
+        *GPU_INFO.lock().unwrap_or_else(|e| e.into_inner()) = Some(gpu_info);
 
-        let font_size = (16 * font.char_width, 16 * font.char_height);
-        let surface_size = (
-            window_size.width / font.char_width,
-            window_size.height / font.char_height,
-        );
         let fg_texture = Texture::new(&device, surface_size);
         let bg_texture = Texture::new(&device, surface_size);
         let chars_texture = Texture::new(&device, surface_size);
-        let mut font_texture = Texture::new(&device, font_size);
-
-        font_texture.storage.copy_from_slice(font.data.as_slice());
-        font_texture.update(&queue);
+        let font_texture = Texture::new(&device, font_size);
+        font_texture.update(&queue, &font.data);
 
         let texture_bind_group_layout =
             device.create_bind_group_layout(&BindGroupLayoutDescriptor {
@@ -195,7 +696,9 @@ impl<'a> RenderState<'a> {
         let uniforms = RenderUniforms {
             font_width: font.char_width,
             font_height: font.char_height,
-            _padding: [0; 2],
+            colour_filter: 0,
+            pixel_scale: 1,
+            colour_matrix: [[0.0; 4]; 3],
         };
         let uniform_buffer = device.create_buffer_init(&BufferInitDescriptor {
             label: Some("Uniform Buffer for Render"),
@@ -225,12 +728,6 @@ impl<'a> RenderState<'a> {
             }],
         });
 
-        let font_char_size = (font.char_width, font.char_height);
-        let surface_char_size = (
-            window_size.width / font.char_width,
-            window_size.height / font.char_height,
-        );
-
         let shader = device.create_shader_module(include_wgsl!("shader.wgsl"));
         let render_pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
@@ -280,8 +777,11 @@ impl<'a> RenderState<'a> {
         });
 
         Ok(Self {
-            surface: surface_expected,
+            instance,
+            surface: Some(surface),
             surface_config,
+            #[cfg(feature = "frame-texture")]
+            surface_usages: capabilities.usages,
             device,
             queue,
             render_pipeline,
@@ -293,46 +793,96 @@ impl<'a> RenderState<'a> {
             texture_bind_group_layout,
             texture_bind_group,
             uniform_bind_group,
-            font_char_size,
-            surface_char_size,
+            uniform_buffer,
+            uniforms,
+            grid,
+            screen: Image::new(surface_size.0, surface_size.1),
+            staging_belt: StagingBelt::new(STAGING_CHUNK_SIZE),
+            #[cfg(feature = "frame-texture")]
+            shared_output: None,
         })
     }
 
+    /// Resizes the surface to the window's new size.  A window with no area,
+    /// such as a minimised one, keeps its old surface and characters, and
+    /// isn't drawn until it has an area again.
     pub(crate) fn resize(&mut self, new_size: PhysicalSize<u32>) {
-        if new_size.width > 0 && new_size.height > 0 {
-            self.surface_config.width = new_size.width;
-            self.surface_config.height = new_size.height;
-            self.surface.configure(&self.device, &self.surface_config);
-
-            let chars_size = (
-                new_size.width / self.font_char_size.0,
-                new_size.height / self.font_char_size.1,
+        let grid_changed = self.grid.resize(new_size);
+        if self.grid.is_hidden() {
+            return;
+        }
+
+        let surface_size =
+            clamp_surface_size(new_size, self.device.limits().max_texture_dimension_2d);
+        (self.surface_config.width, self.surface_config.height) = surface_size;
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.surface_config);
+        }
+        #[cfg(feature = "frame-texture")]
+        if let Some(shared) = &mut self.shared_output {
+            shared.resize(&self.device, surface_size);
+        }
+
+        if grid_changed {
+            warn_if_clamped(&self.grid);
+            let chars_size = self.grid.size();
+            self.screen.resize(chars_size.0, chars_size.1);
+            self.fg_texture.resize(&self.device, chars_size);
+            self.bg_texture.resize(&self.device, chars_size);
+            self.chars_texture.resize(&self.device, chars_size);
+
+            self.texture_bind_group = create_texture_bind_group(
+                &self.device,
+                &self.texture_bind_group_layout,
+                &self.fg_texture,
+                &self.bg_texture,
+                &self.chars_texture,
+                &self.font_texture,
             );
+        }
+    }
 
-            if chars_size != self.surface_char_size {
-                self.surface_char_size = chars_size;
-                self.fg_texture = Texture::new(&self.device, chars_size);
-                self.bg_texture = Texture::new(&self.device, chars_size);
-                self.chars_texture = Texture::new(&self.device, chars_size);
-
-                self.texture_bind_group = create_texture_bind_group(
-                    &self.device,
-                    &self.texture_bind_group_layout,
-                    &self.fg_texture,
-                    &self.bg_texture,
-                    &self.chars_texture,
-                    &self.font_texture,
-                );
-            }
+    /// Returns true while the window has no area to draw to, such as when it
+    /// is minimised.
+    pub(crate) fn is_hidden(&self) -> bool {
+        self.grid.is_hidden()
+    }
+
+    /// Drops the surface when the app is suspended.  Some platforms, such as
+    /// Android, destroy the window's surface while the app is in the
+    /// background.
+    pub(crate) fn suspend(&mut self) {
+        self.surface = None;
+    }
+
+    /// Recreates the surface when the app is resumed, if it was dropped.
+    ///
+    /// # Returns
+    ///
+    /// True if the surface was recreated.
+    ///
+    pub(crate) fn resume(&mut self) -> Result<bool, MageError> {
+        if self.surface.is_some() {
+            return Ok(false);
         }
+        self.surface = Some(self.instance.create_surface(self.window.clone())?);
+        self.resize(self.window.inner_size());
+        Ok(true)
     }
 
     pub(crate) fn render(&mut self) -> Result<(), SurfaceError> {
-        self.fg_texture.update(&self.queue);
-        self.bg_texture.update(&self.queue);
-        self.chars_texture.update(&self.queue);
+        profile_scope!("render");
+
+        // There is nothing to draw to while the app is suspended or the
+        // window is minimised.
+        let Some(surface) = self.surface.as_ref().filter(|_| !self.grid.is_hidden()) else {
+            return Ok(());
+        };
 
-        let frame = self.surface.get_current_texture()?;
+        let frame = {
+            profile_scope!("acquire_frame");
+            surface.get_current_texture()?
+        };
         let view = frame.texture.create_view(&TextureViewDescriptor::default());
 
         let mut encoder = self
@@ -340,53 +890,412 @@ impl<'a> RenderState<'a> {
             .create_command_encoder(&CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
-
         {
-            let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: Operations {
-                        load: LoadOp::Clear(Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
+            profile_scope!("upload_textures");
+            for (texture, data) in [
+                (&self.fg_texture, &self.screen.fore_image),
+                (&self.bg_texture, &self.screen.back_image),
+                (&self.chars_texture, &self.screen.text_image),
+            ] {
+                texture.upload(data, &mut self.staging_belt, &mut encoder, &self.device);
+            }
+            self.staging_belt.finish();
+        }
 
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_bind_group(0, &self.texture_bind_group, &[]);
-            render_pass.set_bind_group(1, &self.uniform_bind_group, &[]);
-            render_pass.draw(0..4, 0..1);
+        profile_scope!("submit");
+        self.draw(&mut encoder, &view);
+        #[cfg(feature = "frame-texture")]
+        if let Some(shared) = &self.shared_output {
+            if self.surface_config.usage.contains(TextureUsages::COPY_SRC) {
+                shared.copy_frame(&mut encoder, &frame.texture);
+            } else {
+                self.draw(&mut encoder, &shared.view);
+            }
         }
 
         self.queue.submit(once(encoder.finish()));
+        self.staging_belt.recall();
+        #[cfg(feature = "frame-texture")]
+        if let Some(shared) = &mut self.shared_output {
+            shared.publish(&self.device, &self.queue);
+        }
         frame.present();
 
         Ok(())
     }
 
+    /// Records a render pass that draws the screen to a texture.
+    fn draw(&self, encoder: &mut CommandEncoder, view: &TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color {
+                        r: 0.1,
+                        g: 0.2,
+                        b: 0.3,
+                        a: 1.0,
+                    }),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.texture_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.uniform_bind_group, &[]);
+        render_pass.draw(0..4, 0..1);
+    }
+
+    /// Publishes every frame drawn to a sink, as a texture.
+    ///
+    /// Each finished frame is copied from the surface into the shared
+    /// texture.  Only if the surface can't be copied from is the frame drawn
+    /// a second time instead.
+    #[cfg(feature = "frame-texture")]
+    pub(crate) fn set_frame_sink(&mut self, sink: Box<dyn FrameSink>) {
+        self.shared_output = Some(SharedOutput::new(
+            sink,
+            &self.device,
+            self.surface_config.format,
+            (self.surface_config.width, self.surface_config.height),
+        ));
+
+        if self.surface_usages.contains(TextureUsages::COPY_SRC) {
+            self.surface_config.usage |= TextureUsages::COPY_SRC;
+            if let Some(surface) = self.surface.as_ref().filter(|_| !self.grid.is_hidden()) {
+                surface.configure(&self.device, &self.surface_config);
+            }
+        } else {
+            warn!("The window's surface can't be copied from, so shared frames are drawn twice");
+        }
+    }
+
+    /// Sets the colour vision simulated when rendering.
+    pub(crate) fn set_colour_vision(&mut self, vision: ColourVision) {
+        match vision.matrix() {
+            Some(m) => {
+                self.uniforms.colour_filter = 1;
+                // WGSL matrices are stored as columns padded to 4 floats.
+                for (column, values) in self.uniforms.colour_matrix.iter_mut().enumerate() {
+                    *values = [m[0][column], m[1][column], m[2][column], 0.0];
+                }
+            }
+            None => self.uniforms.colour_filter = 0,
+        }
+        self.queue
+            .write_buffer(&self.uniform_buffer, 0, cast_slice(&[self.uniforms]));
+    }
+
+    /// Returns the coordinates of the char at the given pixel position on the
+    /// surface, or `None` if there is no char there.
+    pub(crate) fn char_at(&self, position: PhysicalPosition<f64>) -> Option<Point> {
+        self.grid.char_at(position)
+    }
+
+    /// Returns the height of a char in pixels.
+    pub(crate) fn char_height(&self) -> u32 {
+        self.grid.char_height()
+    }
+
+    /// Returns the size of a char in pixels.
+    pub(crate) fn char_size(&self) -> (u32, u32) {
+        self.grid.char_size()
+    }
+
+    /// Sets how many screen pixels wide and high each pixel of the font is
+    /// drawn, so that text stays readable on high density displays.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The pixel scale, which is at least 1.
+    ///
+    pub(crate) fn set_pixel_scale(&mut self, scale: u32) {
+        if !self.grid.set_pixel_scale(scale) {
+            return;
+        }
+        self.uniforms.pixel_scale = self.grid.pixel_scale();
+        self.queue
+            .write_buffer(&self.uniform_buffer, 0, cast_slice(&[self.uniforms]));
+        self.resize(self.window.inner_size());
+    }
+
     pub(crate) fn size_in_chars(&self) -> (u32, u32) {
-        self.surface_char_size
+        self.grid.size()
+    }
+
+    /// Returns the size of the surface in characters, which can be used to
+    /// find chars without the render state.
+    // Only the render thread needs a copy of the grid.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    pub(crate) fn grid(&self) -> CharGrid {
+        self.grid
+    }
+
+    /// Returns how much memory the screen and the textures use.
+    pub(crate) fn memory_stats(&self) -> MemoryStats {
+        self.grid.memory_stats(&self.screen, 1)
+    }
+
+    /// Swaps the screen with the one given, so a frame drawn elsewhere can be
+    /// rendered without copying it.
+    ///
+    /// # Arguments
+    ///
+    /// * `screen` - The chars to draw, which must be the size of the surface
+    ///   in characters.
+    ///
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    pub(crate) fn swap_screen(&mut self, screen: &mut Image) {
+        std::mem::swap(&mut self.screen, screen);
+    }
+
+    /// Returns the chars that the app presents to.
+    pub(crate) fn screen(&mut self) -> &mut Image {
+        &mut self.screen
+    }
+}
+
+/// The size of the window in characters, and where each character is on it.
+/// A window smaller than a character still shows part of one, so the grid is
+/// at least 1x1.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CharGrid {
+    /// The size of each character in the font texture.
+    font_char_size: (u32, u32),
+
+    /// How many screen pixels wide and high each pixel of the font is drawn.
+    pixel_scale: u32,
+
+    /// The size of the window in characters.
+    size: (u32, u32),
+
+    /// True while the window has no area to draw to, such as when it is
+    /// minimised.
+    hidden: bool,
+
+    /// True if the window has room for more characters than the GPU can
+    /// draw, so the grid is smaller than the window.
+    clamped: bool,
+
+    /// The largest width or height of a texture that the GPU allows.
+    max_texture_size: u32,
+
+    /// The largest buffer that the GPU allows, which limits the size of the
+    /// buffers that the textures are uploaded through.
+    max_buffer_size: u64,
+}
+
+impl CharGrid {
+    /// Creates a grid for a window.
+    ///
+    /// # Arguments
+    ///
+    /// * `font_char_size` - The size of each character in the font.
+    /// * `window_size` - The size of the window in pixels.
+    /// * `limits` - The limits of the GPU, which the grid is kept within.
+    ///
+    pub(crate) fn new(
+        font_char_size: (u32, u32),
+        window_size: PhysicalSize<u32>,
+        limits: &Limits,
+    ) -> Self {
+        let mut grid = Self {
+            font_char_size,
+            pixel_scale: 1,
+            size: (1, 1),
+            hidden: false,
+            clamped: false,
+            max_texture_size: limits.max_texture_dimension_2d,
+            max_buffer_size: limits.max_buffer_size,
+        };
+        grid.resize(window_size);
+        grid
+    }
+
+    /// Fits the grid to the window's new size.  A window with no area keeps
+    /// its old size, and one with room for more characters than the GPU can
+    /// draw is given as many as it can.
+    ///
+    /// # Returns
+    ///
+    /// True if the size in characters changed.
+    ///
+    pub(crate) fn resize(&mut self, window_size: PhysicalSize<u32>) -> bool {
+        self.hidden = window_size.width == 0 || window_size.height == 0;
+        if self.hidden {
+            return false;
+        }
+        let (char_width, char_height) = self.char_size();
+        let wanted = (
+            (window_size.width / char_width).max(1),
+            (window_size.height / char_height).max(1),
+        );
+        let (max_columns, max_rows) =
+            max_grid_size(self.max_texture_size, self.max_buffer_size, wanted.0);
+        let size = (wanted.0.min(max_columns), wanted.1.min(max_rows));
+        self.clamped = size != wanted;
+        let changed = size != self.size;
+        self.size = size;
+        changed
+    }
+
+    /// Sets the pixel scale, which is at least 1.  The grid must be resized
+    /// afterwards to fit the window again.
+    ///
+    /// # Returns
+    ///
+    /// True if the pixel scale changed.
+    ///
+    pub(crate) fn set_pixel_scale(&mut self, scale: u32) -> bool {
+        let scale = scale.max(1);
+        let changed = scale != self.pixel_scale;
+        self.pixel_scale = scale;
+        changed
+    }
+
+    pub(crate) fn pixel_scale(&self) -> u32 {
+        self.pixel_scale
+    }
+
+    pub(crate) fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub(crate) fn is_hidden(&self) -> bool {
+        self.hidden
     }
 
-    pub(crate) fn images(&mut self) -> (&mut [u32], &mut [u32], &mut [u32]) {
+    /// Returns true if the window has room for more characters than the GPU
+    /// can draw.
+    pub(crate) fn is_clamped(&self) -> bool {
+        self.clamped
+    }
+
+    /// Returns how much memory the screen images and the textures use for a
+    /// grid of this size.
+    ///
+    /// # Arguments
+    ///
+    /// * `screen` - One of the screen images, which are all the same size.
+    /// * `screens` - The number of screen images.
+    ///
+    pub(crate) fn memory_stats(&self, screen: &Image, screens: u32) -> MemoryStats {
+        let (char_width, char_height) = self.font_char_size;
+        MemoryStats {
+            screen_bytes: screen.memory_bytes() * screens as usize,
+            screens,
+            texture_bytes: 3 * texture_bytes(self.size),
+            font_bytes: texture_bytes((16 * char_width, 16 * char_height)),
+        }
+    }
+
+    /// Returns the coordinates of the char at the given pixel position on the
+    /// window, or `None` if there is no char there.
+    pub(crate) fn char_at(&self, position: PhysicalPosition<f64>) -> Option<Point> {
+        if position.x < 0.0 || position.y < 0.0 {
+            return None;
+        }
+        let (char_width, char_height) = self.char_size();
+        let x = position.x as u32 / char_width;
+        let y = position.y as u32 / char_height;
+        (x < self.size.0 && y < self.size.1).then(|| Point::new(x as i32, y as i32))
+    }
+
+    /// Returns the height of a char in pixels.
+    pub(crate) fn char_height(&self) -> u32 {
+        self.char_size().1
+    }
+
+    /// Returns the size of a char on the window in pixels, which is the size
+    /// of a char in the font scaled by the pixel scale.
+    pub(crate) fn char_size(&self) -> (u32, u32) {
         (
-            &mut self.fg_texture.storage,
-            &mut self.bg_texture.storage,
-            &mut self.chars_texture.storage,
+            self.font_char_size.0 * self.pixel_scale,
+            self.font_char_size.1 * self.pixel_scale,
         )
     }
 }
 
+/// Returns the limits the GPU device must support.  Web browsers may only
+/// offer WebGL 2 and phones may only offer older GPUs, so the limits are
+/// lowered to what those support there, while still allowing textures as
+/// large as the adapter can handle.
+/// Checks that a texture fits within the GPU's limit.
+///
+/// # Arguments
+///
+/// * `what` - What the texture holds, for the error.
+/// * `size` - The size of the texture in pixels.
+/// * `max` - The largest width or height of a texture that the GPU allows.
+///
+fn check_texture_size(what: &'static str, size: (u32, u32), max: u32) -> Result<(), MageError> {
+    let (width, height) = size;
+    if width > max || height > max {
+        return Err(MageError::TextureTooLarge {
+            what,
+            width,
+            height,
+            max,
+        });
+    }
+    Ok(())
+}
+
+/// Returns the largest grid, in columns and rows, whose textures and upload
+/// buffers the GPU allows.
+///
+/// # Arguments
+///
+/// * `max_texture_size` - The largest width or height of a texture.
+/// * `max_buffer_size` - The largest buffer.
+/// * `columns` - The most columns wanted.  Narrower grids can have more
+///   rows, as their upload buffers are smaller.
+///
+fn max_grid_size(max_texture_size: u32, max_buffer_size: u64, columns: u32) -> (u32, u32) {
+    let columns = columns.clamp(1, max_texture_size.max(1));
+    let rows = (max_buffer_size / padded_bytes_per_row(columns) as u64)
+        .min(max_texture_size as u64)
+        .max(1) as u32;
+    (columns, rows)
+}
+
+/// Warns that the window is larger than the GPU can draw, if it is.
+fn warn_if_clamped(grid: &CharGrid) {
+    if grid.is_clamped() {
+        let (columns, rows) = grid.size();
+        warn!(
+            "The window has room for more characters than the GPU can draw; only \
+             {}x{} are drawn",
+            columns, rows
+        );
+    }
+}
+
+/// Returns the size of the window's surface, which is the window's size
+/// limited to the largest texture that the GPU allows.
+fn clamp_surface_size(window_size: PhysicalSize<u32>, max_texture_size: u32) -> (u32, u32) {
+    (
+        window_size.width.min(max_texture_size),
+        window_size.height.min(max_texture_size),
+    )
+}
+
+fn required_limits(adapter: &Adapter) -> Limits {
+    if cfg!(target_arch = "wasm32") {
+        Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits())
+    } else if cfg!(target_os = "android") {
+        Limits::downlevel_defaults().using_resolution(adapter.limits())
+    } else {
+        Limits::default()
+    }
+}
+
 fn create_texture_bind_group(
     device: &Device,
     texture_bind_group_layout: &BindGroupLayout,
@@ -401,35 +1310,19 @@ fn create_texture_bind_group(
         entries: &[
             BindGroupEntry {
                 binding: 0,
-                resource: BindingResource::TextureView(
-                    &fg_texture
-                        .texture
-                        .create_view(&TextureViewDescriptor::default()),
-                ),
+                resource: BindingResource::TextureView(&fg_texture.view),
             },
             BindGroupEntry {
                 binding: 1,
-                resource: BindingResource::TextureView(
-                    &bg_texture
-                        .texture
-                        .create_view(&TextureViewDescriptor::default()),
-                ),
+                resource: BindingResource::TextureView(&bg_texture.view),
             },
             BindGroupEntry {
                 binding: 2,
-                resource: BindingResource::TextureView(
-                    &chars_texture
-                        .texture
-                        .create_view(&TextureViewDescriptor::default()),
-                ),
+                resource: BindingResource::TextureView(&chars_texture.view),
             },
             BindGroupEntry {
                 binding: 3,
-                resource: BindingResource::TextureView(
-                    &font_texture
-                        .texture
-                        .create_view(&TextureViewDescriptor::default()),
-                ),
+                resource: BindingResource::TextureView(&font_texture.view),
             },
         ],
     });
@@ -440,43 +1333,38 @@ struct Texture {
     /// Size of the texture in pixels.
     pub(crate) size: (u32, u32),
 
-    /// The texture itself.
-    pub(crate) storage: Vec<u32>,
-
     /// The WGPU texture object.
     texture: wgpu::Texture,
+
+    /// The view of the texture that is bound for drawing.
+    view: TextureView,
+
+    /// The buffer that the texture's data is copied through on its way to
+    /// the texture, with each row padded as copies to textures need.
+    upload_buffer: Buffer,
 }
 
 impl Texture {
     fn new(device: &Device, size: (u32, u32)) -> Self {
-        let vec_size = (size.0 * size.1) as usize;
-        let storage = vec![0; vec_size];
-
-        let texture_size = Extent3d {
-            width: size.0,
-            height: size.1,
-            depth_or_array_layers: 1,
-        };
-
-        let texture = device.create_texture(&TextureDescriptor {
-            label: None,
-            size: texture_size,
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: TextureDimension::D2,
-            format: TextureFormat::Rgba8Unorm,
-            usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
-            view_formats: &[],
-        });
+        let (texture, view, upload_buffer) = create_gpu_texture(device, size);
 
         Self {
             size,
-            storage,
             texture,
+            view,
+            upload_buffer,
         }
     }
 
-    fn update(&mut self, queue: &Queue) {
+    /// Changes the size of the texture.
+    fn resize(&mut self, device: &Device, size: (u32, u32)) {
+        self.size = size;
+        (self.texture, self.view, self.upload_buffer) = create_gpu_texture(device, size);
+    }
+
+    /// Copies data straight to the texture.  This is for textures that
+    /// rarely change, such as the font.
+    fn update(&self, queue: &Queue, data: &[u32]) {
         let (width, height) = self.size;
         queue.write_texture(
             ImageCopyTexture {
@@ -485,7 +1373,7 @@ impl Texture {
                 origin: Origin3d::ZERO,
                 aspect: TextureAspect::All,
             },
-            cast_slice(&self.storage),
+            cast_slice(data),
             ImageDataLayout {
                 offset: 0,
                 bytes_per_row: Some(width * 4),
@@ -498,6 +1386,93 @@ impl Texture {
             },
         );
     }
+
+    /// Records a copy of data to the texture, through memory taken from the
+    /// staging belt so that uploading every frame doesn't allocate.
+    fn upload(
+        &self,
+        data: &[u32],
+        belt: &mut StagingBelt,
+        encoder: &mut CommandEncoder,
+        device: &Device,
+    ) {
+        let (width, height) = self.size;
+        let padded_row = padded_bytes_per_row(width);
+        let Some(size) = BufferSize::new(padded_row as u64 * height as u64) else {
+            return;
+        };
+        {
+            let mut staging = belt.write_buffer(encoder, &self.upload_buffer, 0, size, device);
+            for (src, dst) in data
+                .chunks_exact(width as usize)
+                .zip(staging.chunks_exact_mut(padded_row as usize))
+            {
+                dst[..src.len() * 4].copy_from_slice(cast_slice(src));
+            }
+        }
+        encoder.copy_buffer_to_texture(
+            ImageCopyBuffer {
+                buffer: &self.upload_buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
+/// Creates a texture of the given size, a view of it, and a buffer to upload
+/// to it through.
+fn create_gpu_texture(device: &Device, size: (u32, u32)) -> (wgpu::Texture, TextureView, Buffer) {
+    let texture_size = Extent3d {
+        width: size.0,
+        height: size.1,
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(&TextureDescriptor {
+        label: None,
+        size: texture_size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&TextureViewDescriptor::default());
+    let upload_buffer = device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: padded_bytes_per_row(size.0) as u64 * size.1 as u64,
+        usage: BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    (texture, view, upload_buffer)
+}
+
+/// Returns the GPU memory used by a texture of the given size and the buffer
+/// it is uploaded through.
+fn texture_bytes((width, height): (u32, u32)) -> usize {
+    (width as usize * 4 + padded_bytes_per_row(width) as usize) * height as usize
+}
+
+/// Returns the number of bytes in each row of a texture of the given width
+/// when it is copied from a buffer, which must be a multiple of 256.
+fn padded_bytes_per_row(width: u32) -> u32 {
+    (width * 4).next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT)
 }
 
 #[repr(C)]
@@ -509,6 +1484,13 @@ struct RenderUniforms {
     /// The height of a single character in pixels.
     font_height: u32,
 
-    /// Some padding.
-    _padding: [u32; 2],
+    /// Non-zero if the colour matrix is applied to the final image.
+    colour_filter: u32,
+
+    /// The width and height in screen pixels of each pixel of the font.
+    pixel_scale: u32,
+
+    /// The columns of the matrix applied to linear RGB colours when the
+    /// colour filter is enabled.
+    colour_matrix: [[f32; 4]; 3],
 }
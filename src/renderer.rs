@@ -0,0 +1,427 @@
+//! Drawing frames either on the event loop's thread or on a render thread of
+//! their own.
+//!
+//! With [`Config::render_thread`] set, the screen image the app presents to
+//! is kept on the event loop's thread.  Each frame is sent to the render
+//! thread, which uploads and draws it while the event loop carries on
+//! handling events.  There are two screen images, so at most one frame is in
+//! flight, and a frame presented while the render thread is busy is sent as
+//! soon as it is free.
+//!
+//! With [`Config::triple_buffering`] also set, there are three screen images,
+//! so a second finished frame can be sent while the first is drawn.  The
+//! render thread then skips to the newest frame it has been sent, returning
+//! the older one unseen.
+//!
+//! [`Config::render_thread`]: ../struct.Config.html#structfield.render_thread
+//! [`Config::triple_buffering`]: ../struct.Config.html#structfield.triple_buffering
+
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    sync::mpsc::{channel, Receiver, Sender, TryRecvError},
+    thread::{self, JoinHandle},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use wgpu::SurfaceError;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    window::Window,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::render::CharGrid;
+use crate::{
+    error::MageError,
+    image::{Image, Point},
+    render::RenderState,
+    stats::MemoryStats,
+};
+
+/// Draws frames for the game loop, on whichever thread was chosen.
+// There is only ever one renderer, so its size doesn't matter.
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum Renderer {
+    /// Frames are drawn on the event loop's thread as they are presented.
+    Direct(RenderState),
+
+    /// Frames are drawn on a render thread.
+    #[cfg(not(target_arch = "wasm32"))]
+    Threaded(RenderThread),
+}
+
+impl Renderer {
+    /// Creates a renderer that draws with the render state.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The render state, which is moved to a render thread if
+    ///   `render_thread` is true.
+    /// * `render_thread` - True to draw on a render thread.  This is ignored
+    ///   in a web page.
+    /// * `triple_buffering` - True to keep three screen images for the render
+    ///   thread instead of two.
+    ///
+    pub(crate) fn new(
+        state: RenderState,
+        render_thread: bool,
+        triple_buffering: bool,
+    ) -> Result<Self, MageError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if render_thread {
+            let screens = if triple_buffering { 3 } else { 2 };
+            return Ok(Renderer::Threaded(RenderThread::spawn(state, screens)?));
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = (render_thread, triple_buffering);
+        Ok(Renderer::Direct(state))
+    }
+
+    /// Returns the window that frames are drawn to.
+    pub(crate) fn window(&self) -> &Arc<Window> {
+        match self {
+            Renderer::Direct(state) => &state.window,
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => &thread.window,
+        }
+    }
+
+    pub(crate) fn size_in_chars(&self) -> (u32, u32) {
+        match self {
+            Renderer::Direct(state) => state.size_in_chars(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => thread.grid.size(),
+        }
+    }
+
+    /// Returns how much memory the screen images and the textures use.
+    pub(crate) fn memory_stats(&self) -> MemoryStats {
+        match self {
+            Renderer::Direct(state) => state.memory_stats(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => thread.grid.memory_stats(&thread.screen, thread.screens),
+        }
+    }
+
+    /// Returns the screen image that the app presents to.
+    pub(crate) fn screen(&mut self) -> &mut Image {
+        match self {
+            Renderer::Direct(state) => state.screen(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => &mut thread.screen,
+        }
+    }
+
+    /// Draws the screen image presented to.  On a render thread, the frame is only
+    /// sent to be drawn, and errors are those from earlier frames.
+    pub(crate) fn render(&mut self) -> Result<(), MageError> {
+        match self {
+            Renderer::Direct(state) => Ok(state.render()?),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => thread.render(),
+        }
+    }
+
+    /// Returns true if a frame presented earlier is still waiting for the
+    /// render thread, so it should be sent even if the app presents no
+    /// changes.
+    pub(crate) fn is_frame_pending(&self) -> bool {
+        match self {
+            Renderer::Direct(_) => false,
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => thread.pending,
+        }
+    }
+
+    pub(crate) fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        match self {
+            Renderer::Direct(state) => state.resize(new_size),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => {
+                thread.resize(new_size);
+                thread.send(Command::Resize(new_size));
+            }
+        }
+    }
+
+    pub(crate) fn is_hidden(&self) -> bool {
+        match self {
+            Renderer::Direct(state) => state.is_hidden(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => thread.grid.is_hidden(),
+        }
+    }
+
+    pub(crate) fn suspend(&mut self) {
+        match self {
+            Renderer::Direct(state) => state.suspend(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => thread.send(Command::Suspend),
+        }
+    }
+
+    /// Recreates the surface when the app is resumed, if it was dropped,
+    /// waiting for the render thread to do so.
+    ///
+    /// # Returns
+    ///
+    /// True if the surface was recreated.
+    ///
+    pub(crate) fn resume(&mut self) -> Result<bool, MageError> {
+        match self {
+            Renderer::Direct(state) => state.resume(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => {
+                let (reply, result) = channel();
+                thread.send(Command::Resume(reply));
+                result.recv().map_err(|_| MageError::RenderThreadStopped)?
+            }
+        }
+    }
+
+    pub(crate) fn char_at(&self, position: PhysicalPosition<f64>) -> Option<Point> {
+        match self {
+            Renderer::Direct(state) => state.char_at(position),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => thread.grid.char_at(position),
+        }
+    }
+
+    pub(crate) fn char_height(&self) -> u32 {
+        match self {
+            Renderer::Direct(state) => state.char_height(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => thread.grid.char_height(),
+        }
+    }
+
+    pub(crate) fn char_size(&self) -> (u32, u32) {
+        match self {
+            Renderer::Direct(state) => state.char_size(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => thread.grid.char_size(),
+        }
+    }
+
+    pub(crate) fn set_pixel_scale(&mut self, scale: u32) {
+        match self {
+            Renderer::Direct(state) => state.set_pixel_scale(scale),
+            #[cfg(not(target_arch = "wasm32"))]
+            Renderer::Threaded(thread) => {
+                if thread.grid.set_pixel_scale(scale) {
+                    let window_size = thread.window.inner_size();
+                    thread.resize(window_size);
+                    thread.send(Command::SetPixelScale(scale));
+                }
+            }
+        }
+    }
+}
+
+/// A request from the event loop to the render thread.
+#[cfg(not(target_arch = "wasm32"))]
+enum Command {
+    /// Draws a frame, and sends its screen image back to be reused.
+    Frame(Image),
+
+    /// Resizes the surface to the window's new size.
+    Resize(PhysicalSize<u32>),
+
+    /// Sets the pixel scale of the font.
+    SetPixelScale(u32),
+
+    /// Drops the surface while the app is suspended.
+    Suspend,
+
+    /// Recreates the surface, and replies with whether it was recreated.
+    Resume(Sender<Result<bool, MageError>>),
+}
+
+/// The event loop's side of a render thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) struct RenderThread {
+    /// The window that frames are drawn to.
+    window: Arc<Window>,
+
+    /// The size of the window in characters, kept in step with the render
+    /// thread's.
+    grid: CharGrid,
+
+    /// The screen image the app presents to.
+    screen: Image,
+
+    /// The number of screen images, including those on the render thread.
+    screens: u32,
+
+    /// True if a frame was presented while the render thread was busy.
+    pending: bool,
+
+    /// Requests sent to the render thread.  This is an `Option` so that it
+    /// can be dropped to stop the thread.
+    commands: Option<Sender<Command>>,
+
+    /// The screen images of frames that have been drawn, ready to be
+    /// reused.
+    free_screens: Receiver<Image>,
+
+    /// Errors from drawing frames.
+    errors: Receiver<SurfaceError>,
+
+    /// The render thread, joined when the renderer is dropped.
+    thread: Option<JoinHandle<()>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RenderThread {
+    /// Moves the render state to a new render thread, with the given number
+    /// of screen images.
+    fn spawn(mut state: RenderState, screens: u32) -> Result<Self, MageError> {
+        let window = state.window.clone();
+        let grid = state.grid();
+        let screen = state.screen().clone();
+
+        let (commands, command_receiver) = channel();
+        let (free_sender, free_screens) = channel();
+        let (error_sender, errors) = channel();
+
+        // The other screen images are drawn into while the first is in flight.
+        for _ in 1..screens {
+            free_sender
+                .send(screen.clone())
+                .map_err(|_| MageError::RenderThreadStopped)?;
+        }
+
+        let thread = thread::Builder::new()
+            .name("mage-render".to_string())
+            .spawn(move || run_render_thread(state, command_receiver, free_sender, error_sender))?;
+
+        Ok(Self {
+            window,
+            grid,
+            screen,
+            screens,
+            pending: false,
+            commands: Some(commands),
+            free_screens,
+            errors,
+            thread: Some(thread),
+        })
+    }
+
+    /// Sends the screen image presented to the render thread, if it is ready
+    /// for another frame.
+    fn render(&mut self) -> Result<(), MageError> {
+        match self.errors.try_recv() {
+            Ok(e) => return Err(e.into()),
+            Err(TryRecvError::Disconnected) => return Err(MageError::RenderThreadStopped),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        let mut next_screen = match self.free_screens.try_recv() {
+            Ok(screen) => screen,
+            Err(TryRecvError::Empty) => {
+                self.pending = true;
+                return Ok(());
+            }
+            Err(TryRecvError::Disconnected) => return Err(MageError::RenderThreadStopped),
+        };
+
+        // Apps only draw what has changed, so the next frame starts from a
+        // copy of this one.
+        next_screen.copy_from(&self.screen);
+        let frame = std::mem::replace(&mut self.screen, next_screen);
+        self.pending = false;
+        self.send(Command::Frame(frame));
+        Ok(())
+    }
+
+    /// Fits the grid and the screen image to the window's new size.  The
+    /// screen is cleared if the size in characters changes, as the textures
+    /// are.
+    fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        self.grid.resize(new_size);
+        let (width, height) = self.grid.size();
+        if (self.screen.width, self.screen.height) != (width, height) {
+            self.screen.resize(width, height);
+        }
+    }
+
+    /// Sends a request to the render thread.  If the thread has stopped, the
+    /// next frame reports it.
+    fn send(&self, command: Command) {
+        if let Some(commands) = &self.commands {
+            let _ = commands.send(command);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for RenderThread {
+    fn drop(&mut self) {
+        // Closing the channel stops the thread once it has drawn its frame.
+        self.commands = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Handles requests from the event loop until the channel is closed.
+#[cfg(not(target_arch = "wasm32"))]
+fn run_render_thread(
+    mut state: RenderState,
+    commands: Receiver<Command>,
+    free_screens: Sender<Image>,
+    errors: Sender<SurfaceError>,
+) {
+    while let Ok(command) = commands.recv() {
+        match command {
+            Command::Frame(mut screen) => {
+                // Frames that arrived while the last one was drawn are
+                // skipped for the newest.  Other requests are handled as they
+                // come, as the frames after them were presented after them.
+                loop {
+                    match commands.try_recv() {
+                        Ok(Command::Frame(newer)) => {
+                            let older = std::mem::replace(&mut screen, newer);
+                            let _ = free_screens.send(older);
+                        }
+                        Ok(command) => handle_command(&mut state, command),
+                        Err(_) => break,
+                    }
+                }
+
+                // A frame presented before a resize that the render thread
+                // has since seen no longer fits, so it is dropped.
+                if (screen.width, screen.height) == state.size_in_chars() {
+                    state.swap_screen(&mut screen);
+                    let result = state.render();
+                    state.swap_screen(&mut screen);
+                    if let Err(e) = result {
+                        if matches!(e, SurfaceError::Lost | SurfaceError::Outdated) {
+                            state.resize(state.window.inner_size());
+                        }
+                        let _ = errors.send(e);
+                    }
+                }
+                let _ = free_screens.send(screen);
+            }
+            command => handle_command(&mut state, command),
+        }
+    }
+}
+
+/// Handles a request from the event loop other than a frame.
+#[cfg(not(target_arch = "wasm32"))]
+fn handle_command(state: &mut RenderState, command: Command) {
+    match command {
+        Command::Frame(_) => unreachable!("frames are drawn by the render loop"),
+        Command::Resize(new_size) => state.resize(new_size),
+        Command::SetPixelScale(scale) => state.set_pixel_scale(scale),
+        Command::Suspend => state.suspend(),
+        Command::Resume(reply) => {
+            let _ = reply.send(state.resume());
+        }
+    }
+}
@@ -0,0 +1,710 @@
+//! Recording sessions and replaying them frame for frame, for bug reports and
+//! checking speedruns.
+//!
+//! A [`Headless`] runner started with [`Headless::with_recording`] records
+//! the input given to the app each frame, including the keys pressed and the
+//! text typed, along with a seed for the app's
+//! random numbers and a hash of the screen after each frame.  A [`Replay`]
+//! runs a new copy of the app with the same seed and input, and checks that
+//! it draws the same screens:
+//!
+//! ```
+//! use mage_core::{
+//!     headless::Headless, image::Point, input::MouseState, replay::Replay, App, Colour,
+//!     PresentInput, PresentResult, TickInput, TickResult,
+//! };
+//!
+//! struct Game {
+//!     state: u64,
+//! }
+//!
+//! impl App for Game {
+//!     fn tick(&mut self, tick_input: TickInput) -> TickResult {
+//!         if tick_input.mouse.left.pressed {
+//!             self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1);
+//!         }
+//!         TickResult::Continue
+//!     }
+//!
+//!     fn present(&mut self, mut present_input: PresentInput) -> PresentResult {
+//!         let mut image = present_input.new_image();
+//!         image.draw_string(Point::new(0, 0), &self.state.to_string(), Colour::White, Colour::Black);
+//!         present_input.blit(present_input.rect(), image.rect(), &image, Colour::Black);
+//!         PresentResult::Changed
+//!     }
+//! }
+//!
+//! let mut headless = Headless::new(Game { state: 42 }, 30, 2).with_recording(42);
+//! let mut mouse = MouseState::default();
+//! mouse.left.pressed = true;
+//! headless.run_frames(5);
+//! headless.set_mouse(mouse);
+//! headless.run_frames(5);
+//! let recording = headless.take_recording().unwrap();
+//!
+//! let data = recording.to_bytes();
+//! let recording = mage_core::replay::Recording::from_bytes(&data).unwrap();
+//! let mut replay = Replay::new(recording, |seed| Game { state: seed });
+//! replay.run_to_end();
+//! assert_eq!(replay.desync_frame(), None);
+//! assert_eq!(replay.screen(), headless.screen());
+//! ```
+//!
+//! For a replay to match, the app must only depend on the input it is given
+//! and the seed: it must take its random numbers from a generator seeded with
+//! the recording's seed, and must not read the clock or other outside state.
+//!
+//! Recordings are stored in a compact binary format, which starts with a
+//! header:
+//!
+//! | Offset | Size | Description                                  |
+//! |--------|------|----------------------------------------------|
+//! | 0      | 4    | The magic bytes `MAGR`                       |
+//! | 4      | 1    | The format version                           |
+//! | 5      | 4    | The width of the screen (little-endian)      |
+//! | 9      | 4    | The height of the screen (little-endian)     |
+//! | 13     | 4    | The frames per second (little-endian)        |
+//! | 17     | 8    | The seed (little-endian)                     |
+//! | 25     | 8    | The number of frames (little-endian)         |
+//! | 33     | 8    | The number of input changes (little-endian)  |
+//! | 41     | 8    | The number of key events (little-endian)     |
+//! | 49     | 8    | The number of texts typed (little-endian)    |
+//!
+//! This is followed by the input changes, each 31 bytes long, then the key
+//! events, each 11 bytes long, then the texts typed, and then a
+//! little-endian `u64` hash of the screen after each frame.  Each key event
+//! is the frame it was given on, the key's number and a byte of flags:
+//! pressed, repeat, shift, control and alt from the lowest bit up.  Each text
+//! is the frame it was typed on, its length in bytes as a `u32` and then its
+//! UTF-8 bytes.  Recordings from versions 1 and 2 of the format, which have
+//! no key events and no texts typed, can still be read.
+//!
+//! Text being composed with an input method isn't recorded.
+//!
+//! [`Headless`]: ../headless/struct.Headless.html
+//! [`Headless::with_recording`]: ../headless/struct.Headless.html#method.with_recording
+//! [`Replay`]: struct.Replay.html
+
+use std::{fs, path::Path};
+
+use crate::{
+    error::MageError,
+    headless::Headless,
+    image::{Image, Point},
+    input::{key_code_from_index, key_code_index, KeyEvent, MouseButtonState, MouseState},
+    App, TickInput, TickResult,
+};
+
+/// The magic bytes at the start of every recording file.
+const MAGIC: &[u8; 4] = b"MAGR";
+
+/// The current version of the recording file format.
+pub const RECORDING_FORMAT_VERSION: u8 = 3;
+
+/// The size in bytes of each input change in a recording file.
+const INPUT_SIZE: usize = 31;
+
+/// The size in bytes of each key event in a recording file.
+const KEY_SIZE: usize = 11;
+
+/// The most chars a recorded screen can have.  Recordings with larger screens
+/// are rejected rather than allocating a screen for them.
+const MAX_SCREEN_CELLS: u64 = 1 << 24;
+
+/// The input given to an app from a frame onwards, until it next changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecordedInput {
+    /// The frame the input was first given on, counting from zero.
+    pub frame: u64,
+
+    /// The width of the screen in chars.
+    pub width: u32,
+
+    /// The height of the screen in chars.
+    pub height: u32,
+
+    /// The state of the mouse.
+    pub mouse: MouseState,
+}
+
+/// A key pressed or released during a recorded session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecordedKey {
+    /// The frame the key was given on, counting from zero.
+    pub frame: u64,
+
+    /// The key pressed or released.
+    pub event: KeyEvent,
+}
+
+/// Text typed during a recorded session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedText {
+    /// The frame the text was typed on, counting from zero.
+    pub frame: u64,
+
+    /// The text typed.
+    pub text: String,
+}
+
+/// A recorded session: everything needed to run an app again exactly as it
+/// ran before.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recording {
+    /// The width of the screen in chars when the session started.
+    pub width: u32,
+
+    /// The height of the screen in chars when the session started.
+    pub height: u32,
+
+    /// The number of frames run each second.
+    pub frame_rate: u32,
+
+    /// The seed the app's random numbers were generated from.
+    pub seed: u64,
+
+    /// The input each time it changed.
+    inputs: Vec<RecordedInput>,
+
+    /// The keys pressed and released, in the order they were given.
+    keys: Vec<RecordedKey>,
+
+    /// The text typed on each frame that any was.
+    texts: Vec<RecordedText>,
+
+    /// A hash of the screen after each frame.
+    screen_hashes: Vec<u64>,
+}
+
+impl Recording {
+    /// Creates a new, empty recording.
+    pub(crate) fn new(width: u32, height: u32, frame_rate: u32, seed: u64) -> Self {
+        Self {
+            width,
+            height,
+            frame_rate,
+            seed,
+            inputs: Vec::new(),
+            keys: Vec::new(),
+            texts: Vec::new(),
+            screen_hashes: Vec::new(),
+        }
+    }
+
+    /// Records the input given to the app on the next frame, if it has
+    /// changed.
+    pub(crate) fn record_input(&mut self, tick_input: &TickInput) {
+        let frame = self.frames();
+        self.keys.extend(
+            tick_input
+                .keys
+                .iter()
+                .map(|&event| RecordedKey { frame, event }),
+        );
+        if !tick_input.text.is_empty() {
+            self.texts.push(RecordedText {
+                frame,
+                text: tick_input.text.to_string(),
+            });
+        }
+        let input = RecordedInput {
+            frame,
+            width: tick_input.width,
+            height: tick_input.height,
+            mouse: tick_input.mouse,
+        };
+        let changed = self.inputs.last().is_none_or(|last| {
+            (last.width, last.height, last.mouse) != (input.width, input.height, input.mouse)
+        });
+        if changed {
+            self.inputs.push(input);
+        }
+    }
+
+    /// Records the screen at the end of a frame.
+    pub(crate) fn record_screen(&mut self, screen: &Image) {
+        self.screen_hashes.push(screen_hash(screen));
+    }
+
+    /// Returns the number of frames recorded.
+    pub fn frames(&self) -> u64 {
+        self.screen_hashes.len() as u64
+    }
+
+    /// Returns the input each time it changed, in frame order.
+    pub fn inputs(&self) -> &[RecordedInput] {
+        &self.inputs
+    }
+
+    /// Returns the keys pressed and released, in the order they were given.
+    pub fn keys(&self) -> &[RecordedKey] {
+        &self.keys
+    }
+
+    /// Returns the text typed on each frame that any was, in frame order.
+    pub fn texts(&self) -> &[RecordedText] {
+        &self.texts
+    }
+
+    /// Returns the hash of the screen after a frame, or `None` if the frame
+    /// wasn't recorded.
+    pub fn screen_hash(&self, frame: u64) -> Option<u64> {
+        self.screen_hashes.get(frame as usize).copied()
+    }
+
+    /// Saves the recording to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), MageError> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Loads a recording from a file written by [`save`].
+    ///
+    /// [`save`]: struct.Recording.html#method.save
+    ///
+    pub fn load(path: impl AsRef<Path>) -> Result<Recording, MageError> {
+        Recording::from_bytes(&fs::read(path)?)
+    }
+
+    /// Encodes the recording into the binary recording format.  Keys that
+    /// have no number in the format are left out.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let keys: Vec<_> = self
+            .keys
+            .iter()
+            .filter_map(|key| Some((key, key_code_index(key.event.key)?)))
+            .collect();
+        let text_bytes: usize = self.texts.iter().map(|text| 12 + text.text.len()).sum();
+        let mut data = Vec::with_capacity(
+            57 + self.inputs.len() * INPUT_SIZE
+                + keys.len() * KEY_SIZE
+                + text_bytes
+                + self.screen_hashes.len() * 8,
+        );
+        data.extend_from_slice(MAGIC);
+        data.push(RECORDING_FORMAT_VERSION);
+        data.extend_from_slice(&self.width.to_le_bytes());
+        data.extend_from_slice(&self.height.to_le_bytes());
+        data.extend_from_slice(&self.frame_rate.to_le_bytes());
+        data.extend_from_slice(&self.seed.to_le_bytes());
+        data.extend_from_slice(&self.frames().to_le_bytes());
+        data.extend_from_slice(&(self.inputs.len() as u64).to_le_bytes());
+        data.extend_from_slice(&(keys.len() as u64).to_le_bytes());
+        data.extend_from_slice(&(self.texts.len() as u64).to_le_bytes());
+
+        for input in &self.inputs {
+            let mouse = &input.mouse;
+            let position = mouse.position.unwrap_or_default();
+            let buttons = [mouse.left, mouse.right, mouse.middle]
+                .iter()
+                .enumerate()
+                .fold(0u16, |bits, (i, button)| {
+                    bits | (button.down as u16) << (i * 3)
+                        | (button.pressed as u16) << (i * 3 + 1)
+                        | (button.released as u16) << (i * 3 + 2)
+                });
+            data.extend_from_slice(&input.frame.to_le_bytes());
+            data.extend_from_slice(&input.width.to_le_bytes());
+            data.extend_from_slice(&input.height.to_le_bytes());
+            data.push(mouse.position.is_some() as u8);
+            data.extend_from_slice(&position.x.to_le_bytes());
+            data.extend_from_slice(&position.y.to_le_bytes());
+            data.extend_from_slice(&buttons.to_le_bytes());
+            data.extend_from_slice(&mouse.wheel.to_le_bytes());
+        }
+        for (key, index) in keys {
+            let event = &key.event;
+            let flags = [
+                event.pressed,
+                event.repeat,
+                event.shift,
+                event.ctrl,
+                event.alt,
+            ]
+            .iter()
+            .enumerate()
+            .fold(0u8, |bits, (i, &flag)| bits | (flag as u8) << i);
+            data.extend_from_slice(&key.frame.to_le_bytes());
+            data.extend_from_slice(&index.to_le_bytes());
+            data.push(flags);
+        }
+        for text in &self.texts {
+            data.extend_from_slice(&text.frame.to_le_bytes());
+            data.extend_from_slice(&(text.text.len() as u32).to_le_bytes());
+            data.extend_from_slice(text.text.as_bytes());
+        }
+        for hash in &self.screen_hashes {
+            data.extend_from_slice(&hash.to_le_bytes());
+        }
+
+        data
+    }
+
+    /// Decodes a recording from the binary recording format.
+    ///
+    /// # Returns
+    ///
+    /// The decoded recording, or [`MageError::InvalidRecording`] if the data
+    /// is not a valid recording, including if any of its screens would have
+    /// more than 16M chars.
+    ///
+    /// [`MageError::InvalidRecording`]: ../error/enum.MageError.html#variant.InvalidRecording
+    ///
+    pub fn from_bytes(data: &[u8]) -> Result<Recording, MageError> {
+        let mut reader = Reader { data };
+        if reader.take(4)? != MAGIC {
+            return Err(MageError::InvalidRecording);
+        }
+        let version = reader.take(1)?[0];
+        if !(1..=RECORDING_FORMAT_VERSION).contains(&version) {
+            return Err(MageError::InvalidRecording);
+        }
+        let width = reader.read_u32()?;
+        let height = reader.read_u32()?;
+        check_screen_size(width, height)?;
+        let frame_rate = reader.read_u32()?;
+        let seed = reader.read_u64()?;
+        let frames = reader.read_u64()? as usize;
+        let input_count = reader.read_u64()? as usize;
+        let key_count = if version >= 2 {
+            reader.read_u64()? as usize
+        } else {
+            0
+        };
+        let text_count = if version >= 3 {
+            reader.read_u64()? as usize
+        } else {
+            0
+        };
+        // Texts are at least 12 bytes long, and the rest of their length is
+        // checked once they have been read.
+        let expected = input_count
+            .checked_mul(INPUT_SIZE)
+            .and_then(|size| size.checked_add(key_count.checked_mul(KEY_SIZE)?))
+            .and_then(|size| size.checked_add(text_count.checked_mul(12)?))
+            .and_then(|size| size.checked_add(frames.checked_mul(8)?));
+        let fits = expected.is_some_and(|expected| {
+            if text_count > 0 {
+                expected <= reader.data.len()
+            } else {
+                expected == reader.data.len()
+            }
+        });
+        if !fits {
+            return Err(MageError::InvalidRecording);
+        }
+
+        let mut inputs: Vec<RecordedInput> = Vec::with_capacity(input_count);
+        for _ in 0..input_count {
+            let frame = reader.read_u64()?;
+            let width = reader.read_u32()?;
+            let height = reader.read_u32()?;
+            check_screen_size(width, height)?;
+            let has_position = reader.take(1)?[0] != 0;
+            let x = reader.read_u32()? as i32;
+            let y = reader.read_u32()? as i32;
+            let buttons = reader.take(2)?;
+            let buttons = u16::from_le_bytes([buttons[0], buttons[1]]);
+            let wheel = reader.read_u32()? as i32;
+            if inputs.last().is_some_and(|last| last.frame >= frame) {
+                return Err(MageError::InvalidRecording);
+            }
+
+            let button = |i: usize| MouseButtonState {
+                down: buttons & (1 << (i * 3)) != 0,
+                pressed: buttons & (1 << (i * 3 + 1)) != 0,
+                released: buttons & (1 << (i * 3 + 2)) != 0,
+            };
+            inputs.push(RecordedInput {
+                frame,
+                width,
+                height,
+                mouse: MouseState {
+                    position: has_position.then_some(Point::new(x, y)),
+                    left: button(0),
+                    right: button(1),
+                    middle: button(2),
+                    wheel,
+                },
+            });
+        }
+        let mut keys: Vec<RecordedKey> = Vec::with_capacity(key_count);
+        for _ in 0..key_count {
+            let frame = reader.read_u64()?;
+            let index = reader.take(2)?;
+            let key = key_code_from_index(u16::from_le_bytes([index[0], index[1]]))
+                .ok_or(MageError::InvalidRecording)?;
+            let flags = reader.take(1)?[0];
+            if keys.last().is_some_and(|last| last.frame > frame) {
+                return Err(MageError::InvalidRecording);
+            }
+
+            let flag = |i: usize| flags & (1 << i) != 0;
+            keys.push(RecordedKey {
+                frame,
+                event: KeyEvent {
+                    key,
+                    pressed: flag(0),
+                    repeat: flag(1),
+                    shift: flag(2),
+                    ctrl: flag(3),
+                    alt: flag(4),
+                },
+            });
+        }
+        let mut texts: Vec<RecordedText> = Vec::with_capacity(text_count);
+        for _ in 0..text_count {
+            let frame = reader.read_u64()?;
+            let len = reader.read_u32()? as usize;
+            let text = std::str::from_utf8(reader.take(len)?)
+                .map_err(|_| MageError::InvalidRecording)?
+                .to_string();
+            if texts.last().is_some_and(|last| last.frame >= frame) {
+                return Err(MageError::InvalidRecording);
+            }
+            texts.push(RecordedText { frame, text });
+        }
+        if reader.data.len() != frames * 8 {
+            return Err(MageError::InvalidRecording);
+        }
+        let screen_hashes = (0..frames)
+            .map(|_| reader.read_u64())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Recording {
+            width,
+            height,
+            frame_rate,
+            seed,
+            inputs,
+            keys,
+            texts,
+            screen_hashes,
+        })
+    }
+}
+
+/// Runs an app again with the input from a recording, checking that it draws
+/// the same screens as it did when recorded.
+pub struct Replay<A: App> {
+    /// The runner the app is replayed in.
+    headless: Headless<A>,
+
+    /// The recording being replayed.
+    recording: Recording,
+
+    /// The index of the next input change to give the app.
+    next_input: usize,
+
+    /// The index of the next key to give the app.
+    next_key: usize,
+
+    /// The index of the next text to type.
+    next_text: usize,
+
+    /// The first frame whose screen didn't match the recording.
+    desync_frame: Option<u64>,
+}
+
+impl<A: App> Replay<A> {
+    /// Creates a new replay of a recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `recording` - The session to replay.
+    /// * `make_app` - Creates the app in the state it started the session
+    ///   in, given the recording's seed for its random numbers.
+    ///
+    pub fn new(recording: Recording, make_app: impl FnOnce(u64) -> A) -> Self {
+        let headless = Headless::new(make_app(recording.seed), recording.width, recording.height)
+            .with_frame_rate(recording.frame_rate);
+        Self {
+            headless,
+            recording,
+            next_input: 0,
+            next_key: 0,
+            next_text: 0,
+            desync_frame: None,
+        }
+    }
+
+    /// Runs the next recorded frame.
+    ///
+    /// # Returns
+    ///
+    /// The result of the app's tick, or [`TickResult::Quit`] once all the
+    /// recorded frames have been run.
+    ///
+    /// [`TickResult::Quit`]: ../enum.TickResult.html#variant.Quit
+    ///
+    pub fn step(&mut self) -> TickResult {
+        let frame = self.headless.frame_count();
+        if frame >= self.recording.frames() {
+            return TickResult::Quit;
+        }
+
+        if let Some(input) = self
+            .recording
+            .inputs
+            .get(self.next_input)
+            .filter(|input| input.frame == frame)
+        {
+            let screen = self.headless.screen();
+            if (screen.width, screen.height) != (input.width, input.height) {
+                self.headless.resize(input.width, input.height);
+            }
+            self.headless.set_mouse(input.mouse);
+            self.next_input += 1;
+        } else if let Some(input) = self.recording.inputs[..self.next_input].last() {
+            // The input hasn't changed, but the mouse's pressed and released
+            // flags are cleared after each tick, so give it again.
+            self.headless.set_mouse(input.mouse);
+        }
+        while let Some(key) = self
+            .recording
+            .keys
+            .get(self.next_key)
+            .filter(|key| key.frame == frame)
+        {
+            self.headless.send_key(key.event);
+            self.next_key += 1;
+        }
+        if let Some(text) = self
+            .recording
+            .texts
+            .get(self.next_text)
+            .filter(|text| text.frame == frame)
+        {
+            self.headless.type_text(&text.text);
+            self.next_text += 1;
+        }
+
+        let result = self.headless.step();
+        if self.desync_frame.is_none()
+            && self.recording.screen_hash(frame) != Some(screen_hash(self.headless.screen()))
+        {
+            self.desync_frame = Some(frame);
+        }
+        result
+    }
+
+    /// Runs all the recorded frames that are left, stopping early if the app
+    /// asks to quit.
+    pub fn run_to_end(&mut self) {
+        while self.step() == TickResult::Continue {}
+    }
+
+    /// Returns the first frame whose screen didn't match the recording, or
+    /// `None` if all the frames run so far have matched.
+    pub fn desync_frame(&self) -> Option<u64> {
+        self.desync_frame
+    }
+
+    /// Returns true once all the recorded frames have been run, or the app
+    /// has asked to quit.
+    pub fn is_finished(&self) -> bool {
+        self.headless.is_finished() || self.headless.frame_count() >= self.recording.frames()
+    }
+
+    /// Returns the number of frames run so far.
+    pub fn frame_count(&self) -> u64 {
+        self.headless.frame_count()
+    }
+
+    /// Returns the cells the app has presented so far.
+    pub fn screen(&self) -> &Image {
+        self.headless.screen()
+    }
+
+    /// Returns the recording being replayed.
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// Returns the app being replayed.
+    pub fn app(&self) -> &A {
+        self.headless.app()
+    }
+
+    /// Stops replaying and returns the app.
+    pub fn into_app(self) -> A {
+        self.headless.into_app()
+    }
+}
+
+/// Returns a hash of the size and cells of a screen, using 64-bit FNV-1a so
+/// that it is the same on every platform.
+fn screen_hash(screen: &Image) -> u64 {
+    let words = [screen.width, screen.height]
+        .into_iter()
+        .chain(screen.fore_image.iter().copied())
+        .chain(screen.back_image.iter().copied())
+        .chain(screen.text_image.iter().copied());
+    words
+        .flat_map(u32::to_le_bytes)
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+/// Checks that a recorded screen size can be allocated.
+fn check_screen_size(width: u32, height: u32) -> Result<(), MageError> {
+    if width as u64 * height as u64 > MAX_SCREEN_CELLS {
+        return Err(MageError::InvalidRecording);
+    }
+    Ok(())
+}
+
+/// Reads values from the front of a byte slice.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MageError> {
+        if self.data.len() < len {
+            return Err(MageError::InvalidRecording);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, MageError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, MageError> {
+        let bytes = self.take(8)?;
+        let mut value = [0; 8];
+        value.copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_an_empty_recording() {
+        let recording = Recording::new(80, 25, 60, 42);
+        let decoded = Recording::from_bytes(&recording.to_bytes()).unwrap();
+        assert_eq!((decoded.width, decoded.height), (80, 25));
+        assert_eq!((decoded.frame_rate, decoded.seed), (60, 42));
+    }
+
+    #[test]
+    fn rejects_huge_screens() {
+        for (width, height) in [(u32::MAX, u32::MAX), (70_000, 70_000), (1 << 24, 2)] {
+            let mut data = Recording::new(80, 25, 60, 42).to_bytes();
+            data[5..9].copy_from_slice(&width.to_le_bytes());
+            data[9..13].copy_from_slice(&height.to_le_bytes());
+            assert!(matches!(
+                Recording::from_bytes(&data),
+                Err(MageError::InvalidRecording)
+            ));
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Loading of images created with [REXPaint](https://www.gridsagegames.com/rexpaint/).
+//!
+//! REXPaint stores its images as gzip-compressed `.xp` files containing one or
+//! more layers.  The layers are flattened into a single [`Image`], with upper
+//! layers drawn over lower ones.  Cells with REXPaint's transparent background
+//! colour (magenta, `#ff00ff`) are skipped on upper layers, and on the bottom
+//! layer are given a paper colour with an alpha of zero so that they can be
+//! treated as transparent when blitting.
+//!
+//! [`Image`]: ../image/struct.Image.html
+
+use std::{fs, io::Read, path::Path};
+
+use flate2::read::GzDecoder;
+
+use crate::{error::MageError, image::Image};
+
+/// The size of each cell in a layer: a glyph and two RGB colours.
+const XP_CELL_SIZE: usize = 10;
+
+impl Image {
+    /// Loads an image from a REXPaint `.xp` file.
+    pub fn load_xp(path: impl AsRef<Path>) -> Result<Image, MageError> {
+        Image::from_xp_bytes(&fs::read(path)?)
+    }
+
+    /// Decodes an image from the contents of a REXPaint `.xp` file, such as
+    /// data embedded with `include_bytes!`.
+    ///
+    /// # Returns
+    ///
+    /// The flattened image, or [`MageError::InvalidImageData`] if the data is
+    /// not a valid REXPaint file.
+    ///
+    /// [`MageError::InvalidImageData`]: ../error/enum.MageError.html#variant.InvalidImageData
+    ///
+    pub fn from_xp_bytes(data: &[u8]) -> Result<Image, MageError> {
+        let mut bytes = Vec::new();
+        GzDecoder::new(data)
+            .read_to_end(&mut bytes)
+            .map_err(|_| MageError::InvalidImageData)?;
+        let mut data = bytes.as_slice();
+
+        let read_i32 = |data: &mut &[u8]| -> Result<i32, MageError> {
+            let (value, rest) = data
+                .split_first_chunk::<4>()
+                .ok_or(MageError::InvalidImageData)?;
+            *data = rest;
+            Ok(i32::from_le_bytes(*value))
+        };
+
+        let _version = read_i32(&mut data)?;
+        let layers = read_i32(&mut data)?;
+        if layers < 1 {
+            return Err(MageError::InvalidImageData);
+        }
+
+        let mut image: Option<Image> = None;
+        for layer in 0..layers {
+            let width = read_i32(&mut data)?;
+            let height = read_i32(&mut data)?;
+
+            // Check the layer fits in the remaining data before allocating
+            // anything, so a corrupt size can't overflow or exhaust memory.
+            let (Ok(width), Ok(height)) = (u32::try_from(width), u32::try_from(height)) else {
+                return Err(MageError::InvalidImageData);
+            };
+            let layer_size = (width as usize)
+                .checked_mul(height as usize)
+                .and_then(|cells| cells.checked_mul(XP_CELL_SIZE))
+                .ok_or(MageError::InvalidImageData)?;
+            if layer_size > data.len() {
+                return Err(MageError::InvalidImageData);
+            }
+
+            let image = image.get_or_insert_with(|| Image::new(width, height));
+            if (width, height) != (image.width, image.height) {
+                return Err(MageError::InvalidImageData);
+            }
+
+            // Cells are stored in column-major order.
+            for x in 0..width as usize {
+                for y in 0..height as usize {
+                    let glyph = read_i32(&mut data)? as u32;
+                    let (colours, rest) = data
+                        .split_first_chunk::<6>()
+                        .ok_or(MageError::InvalidImageData)?;
+                    data = rest;
+
+                    let ink = rgb(colours[0], colours[1], colours[2]);
+                    let paper = rgb(colours[3], colours[4], colours[5]);
+                    let transparent = colours[3..] == [0xff, 0x00, 0xff];
+                    if transparent && layer > 0 {
+                        continue;
+                    }
+
+                    let i = y * image.width as usize + x;
+                    image.text_image[i] = glyph;
+                    image.fore_image[i] = ink;
+                    image.back_image[i] = if transparent {
+                        paper & 0x00ffffff
+                    } else {
+                        paper
+                    };
+                }
+            }
+        }
+
+        image.ok_or(MageError::InvalidImageData)
+    }
+}
+
+/// Packs red, green and blue components into an opaque colour.
+fn rgb(r: u8, g: u8, b: u8) -> u32 {
+    0xff000000 | ((b as u32) << 16) | ((g as u32) << 8) | r as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    fn xp(values: &[i32], cells: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        for value in values {
+            encoder.write_all(&value.to_le_bytes()).unwrap();
+        }
+        encoder.write_all(cells).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn loads_a_layer() {
+        let mut cell = (b'@' as i32).to_le_bytes().to_vec();
+        cell.extend_from_slice(&[0xff, 0x00, 0x00, 0x00, 0x00, 0xff]);
+        let image = Image::from_xp_bytes(&xp(&[-1, 1, 1, 1], &cell)).unwrap();
+        assert_eq!((image.width, image.height), (1, 1));
+        assert_eq!(image.text_image[0], b'@' as u32);
+        assert_eq!(image.fore_image[0], 0xff0000ff);
+        assert_eq!(image.back_image[0], 0xffff0000);
+    }
+
+    #[test]
+    fn rejects_negative_sizes() {
+        assert!(Image::from_xp_bytes(&xp(&[-1, 1, -1, 1], &[])).is_err());
+        assert!(Image::from_xp_bytes(&xp(&[-1, 1, 1, -1], &[])).is_err());
+    }
+
+    #[test]
+    fn rejects_sizes_larger_than_the_data() {
+        assert!(Image::from_xp_bytes(&xp(&[-1, 1, i32::MAX, i32::MAX], &[])).is_err());
+        assert!(Image::from_xp_bytes(&xp(&[-1, 1, 100_000, 100_000], &[0; 100])).is_err());
+    }
+}
@@ -0,0 +1,600 @@
+//! Running games written as [Rhai] scripts, for modding and quick prototypes
+//! that don't need recompiling.
+//!
+//! This is only available with the `scripting` feature.
+//!
+//! A [`ScriptApp`] runs a script as an [`App`].  The script can define these
+//! functions, all of which are optional:
+//!
+//! * `init()` - Returns the game's state, usually an object map.  Without
+//!   it, the state is an empty map.
+//! * `tick(input)` - Updates the game.  Call `quit()` to end it.
+//! * `present(screen)` - Draws the game to the screen image.
+//!
+//! The state is `this` in every function, so it lasts from frame to frame:
+//!
+//! ```
+//! use mage_core::{headless::Headless, script::ScriptApp};
+//!
+//! let script = r#"
+//!     fn init() {
+//!         #{ clicks: 0 }
+//!     }
+//!
+//!     fn tick(input) {
+//!         if input.left_pressed {
+//!             this.clicks += 1;
+//!         }
+//!         if input.time > 1.0 {
+//!             quit();
+//!         }
+//!     }
+//!
+//!     fn present(screen) {
+//!         screen.clear(Colour::White, Colour::Blue);
+//!         screen.draw_string(1, 1, `Clicks: ${this.clicks}`, Colour::Yellow, Colour::Blue);
+//!     }
+//! "#;
+//!
+//! let app = ScriptApp::new(script).unwrap();
+//! let mut headless = Headless::new(app, 20, 3);
+//! headless.run_frames(1);
+//! assert!(headless.screen().to_plain_text().contains("Clicks: 0"));
+//! headless.run_frames(100);
+//! assert!(headless.is_finished());
+//! ```
+//!
+//! Scripts have these types and functions as well as Rhai's own:
+//!
+//! * `Image` - `new_image(width, height)`, which raises an error for images
+//!   of more than 16M chars, the `width` and `height` properties, `clear(ink, paper)`, `draw_char(x, y, char, ink, paper)`,
+//!   `draw_string(x, y, text, ink, paper)`,
+//!   `fill_rect(x, y, width, height, char, ink, paper)`, `glyph(x, y)` and
+//!   `blit(x, y, image)`.
+//! * `Colour` - The 16 named colours, such as `Colour::LightBlue`, and
+//!   `Colour::rgb(r, g, b)`.
+//! * `Input` - The `dt` and `time` properties in seconds, `width` and
+//!   `height` in chars, `mouse_x` and `mouse_y` (-1 when the mouse is outside
+//!   the window), `left_down`, `left_pressed`, `left_released` and the same
+//!   for `right` and `middle`, `wheel`, and `keys_pressed` and
+//!   `keys_released`, which are arrays of the names of the keys pressed and
+//!   released since the last tick, such as `"ArrowUp"` and `"KeyW"`.  Keys
+//!   held down long enough to repeat aren't pressed again.  `text` is the
+//!   text typed since the last tick.
+//! * Timers - `after(seconds, "name")` calls the script function `name` once
+//!   after a delay, and `every(seconds, "name")` calls it repeatedly, at
+//!   most once a millisecond.  Both return an id that can be given to
+//!   `cancel(id)`.
+//! * `quit()` - Ends the game at the end of the tick.
+//!
+//! [Rhai]: https://rhai.rs
+//! [`ScriptApp`]: struct.ScriptApp.html
+//! [`App`]: ../trait.App.html
+
+use std::{cell::RefCell, fs, path::Path, rc::Rc};
+
+use chrono::Duration;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Module, Scope, AST, INT};
+use tracing::error;
+
+use crate::{
+    error::MageError,
+    image::{Char, Image, Point, Rect},
+    input::{KeyEvent, MouseButtonState},
+    App, Colour, PresentInput, PresentResult, TickInput, TickResult,
+};
+
+/// The most chars an image created by a script can have, so that a script
+/// can't crash the game by asking for an image too large to allocate.
+const MAX_IMAGE_CELLS: INT = 1 << 24;
+
+/// Runs a Rhai script as a game.
+pub struct ScriptApp {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+
+    /// The game's state, which is `this` in the script's functions.
+    state: Dynamic,
+
+    /// The screen the script draws to, kept between frames as the window's
+    /// buffers are.
+    screen: ScriptImage,
+
+    /// The state shared with the functions the script calls.
+    shared: Rc<RefCell<Shared>>,
+
+    /// The time the game has been running for, in seconds.
+    time: f64,
+
+    /// The error that stopped the script, if any.
+    error: Option<String>,
+}
+
+/// The state changed by the functions that scripts call.
+#[derive(Default)]
+struct Shared {
+    /// The timers waiting to go off.
+    timers: Vec<Timer>,
+
+    /// The id given to the next timer.
+    next_timer: INT,
+
+    /// True once the script has called `quit()`.
+    quit: bool,
+
+    /// The time the game has been running for, in seconds.
+    time: f64,
+}
+
+/// The shortest time between calls of a repeating timer, in seconds.
+/// Shorter intervals are lengthened to this, so a timer repeating with no
+/// delay is called at most once a millisecond rather than forever.
+const MIN_TIMER_INTERVAL: f64 = 0.001;
+
+/// A script function to call after a delay.
+struct Timer {
+    id: INT,
+
+    /// The name of the script function to call.
+    name: String,
+
+    /// The time to call it at, in seconds.
+    due: f64,
+
+    /// The time between calls if it repeats.
+    repeat: Option<f64>,
+}
+
+/// An image that a script can draw to, shared so that drawing to it in the
+/// script changes the image the engine sees.
+#[derive(Clone)]
+struct ScriptImage(Rc<RefCell<Image>>);
+
+/// The input a script is given each tick.
+#[derive(Clone)]
+struct ScriptInput {
+    dt: f64,
+    time: f64,
+    width: INT,
+    height: INT,
+    mouse_x: INT,
+    mouse_y: INT,
+    left: MouseButtonState,
+    right: MouseButtonState,
+    middle: MouseButtonState,
+    wheel: INT,
+    keys_pressed: Array,
+    keys_released: Array,
+    text: String,
+}
+
+impl ScriptApp {
+    /// Compiles a script and runs its `init` function.
+    ///
+    /// # Returns
+    ///
+    /// The app, or [`MageError::ScriptError`] if the script fails to compile
+    /// or `init` fails.
+    ///
+    /// [`MageError::ScriptError`]: ../error/enum.MageError.html#variant.ScriptError
+    ///
+    pub fn new(source: &str) -> Result<Self, MageError> {
+        let shared = Rc::new(RefCell::new(Shared::default()));
+        let engine = new_engine(&shared);
+        let ast = engine
+            .compile(source)
+            .map_err(|e| MageError::ScriptError(e.to_string()))?;
+
+        let mut app = Self {
+            engine,
+            ast,
+            scope: Scope::new(),
+            state: Dynamic::from_map(Map::new()),
+            screen: ScriptImage(Rc::new(RefCell::new(Image::new(0, 0)))),
+            shared,
+            time: 0.0,
+            error: None,
+        };
+        if app.has_fn("init", 0) {
+            app.state = app
+                .call("init", ())
+                .map_err(|e| MageError::ScriptError(e.to_string()))?;
+        }
+        Ok(app)
+    }
+
+    /// Loads a script from a file and compiles it, as [`new`] does.
+    ///
+    /// [`new`]: struct.ScriptApp.html#method.new
+    ///
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, MageError> {
+        Self::new(&fs::read_to_string(path)?)
+    }
+
+    /// Returns the error that stopped the script, if it failed while
+    /// running.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns the game's state, as returned by the script's `init`
+    /// function and changed since.
+    pub fn state(&self) -> &Dynamic {
+        &self.state
+    }
+
+    /// Returns true if the script defines a function.
+    fn has_fn(&self, name: &str, params: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == name && f.params.len() == params)
+    }
+
+    /// Calls a script function with the state as `this`.
+    fn call(&mut self, name: &str, args: impl rhai::FuncArgs) -> Result<Dynamic, String> {
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+        self.engine
+            .call_fn_with_options(options, &mut self.scope, &self.ast, name, args)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Stops the script after an error.
+    fn fail(&mut self, e: String) -> TickResult {
+        error!("Script error: {}", e);
+        self.error = Some(e);
+        TickResult::Quit
+    }
+}
+
+impl App for ScriptApp {
+    fn tick(&mut self, tick_input: TickInput) -> TickResult {
+        let dt = duration_secs(tick_input.dt);
+        self.time += dt;
+        self.shared.borrow_mut().time = self.time;
+
+        // Call the timers that are due, in the order they are due.
+        loop {
+            let due = {
+                let mut shared = self.shared.borrow_mut();
+                let next = shared
+                    .timers
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, timer)| timer.due <= self.time)
+                    .min_by(|(_, a), (_, b)| a.due.total_cmp(&b.due))
+                    .map(|(i, _)| i);
+                next.map(|i| {
+                    let timer = &mut shared.timers[i];
+                    let name = timer.name.clone();
+                    match timer.repeat {
+                        Some(repeat) => timer.due += repeat,
+                        None => {
+                            shared.timers.remove(i);
+                        }
+                    }
+                    name
+                })
+            };
+            let Some(name) = due else {
+                break;
+            };
+            if let Err(e) = self.call(&name, ()) {
+                return self.fail(e);
+            }
+        }
+
+        if self.has_fn("tick", 1) {
+            let mouse = tick_input.mouse;
+            let position = mouse.position.unwrap_or(Point::new(-1, -1));
+            let input = ScriptInput {
+                dt,
+                time: self.time,
+                width: tick_input.width as INT,
+                height: tick_input.height as INT,
+                mouse_x: position.x as INT,
+                mouse_y: position.y as INT,
+                left: mouse.left,
+                right: mouse.right,
+                middle: mouse.middle,
+                wheel: mouse.wheel as INT,
+                keys_pressed: key_names(tick_input.keys, true),
+                keys_released: key_names(tick_input.keys, false),
+                text: tick_input.text.to_string(),
+            };
+            if let Err(e) = self.call("tick", (input,)) {
+                return self.fail(e);
+            }
+        }
+
+        if self.shared.borrow().quit {
+            TickResult::Quit
+        } else {
+            TickResult::Continue
+        }
+    }
+
+    fn present(&mut self, present_input: PresentInput) -> PresentResult {
+        if !self.has_fn("present", 1) {
+            return PresentResult::NoChanges;
+        }
+
+        {
+            let mut screen = self.screen.0.borrow_mut();
+            if (screen.width, screen.height) != (present_input.width(), present_input.height()) {
+                *screen = Image::new(present_input.width(), present_input.height());
+            }
+        }
+        let screen = self.screen.clone();
+        if let Err(e) = self.call("present", (screen,)) {
+            self.fail(e);
+            self.shared.borrow_mut().quit = true;
+        }
+
+        let screen = self.screen.0.borrow();
+        present_input.screen.copy_from(&screen);
+        PresentResult::Changed
+    }
+}
+
+/// Creates a script engine with Mage's types and functions registered.
+fn new_engine(shared: &Rc<RefCell<Shared>>) -> Engine {
+    let mut engine = Engine::new();
+
+    let mut colours = Module::new();
+    for (name, colour) in [
+        ("Black", Colour::Black),
+        ("Blue", Colour::Blue),
+        ("Green", Colour::Green),
+        ("Cyan", Colour::Cyan),
+        ("Red", Colour::Red),
+        ("Magenta", Colour::Magenta),
+        ("Brown", Colour::Brown),
+        ("LightGray", Colour::LightGray),
+        ("DarkGray", Colour::DarkGray),
+        ("LightBlue", Colour::LightBlue),
+        ("LightGreen", Colour::LightGreen),
+        ("LightCyan", Colour::LightCyan),
+        ("LightRed", Colour::LightRed),
+        ("LightMagenta", Colour::LightMagenta),
+        ("Yellow", Colour::Yellow),
+        ("White", Colour::White),
+    ] {
+        colours.set_var(name, colour);
+    }
+    colours.set_native_fn("rgb", |r: INT, g: INT, b: INT| {
+        Ok(Colour::Rgb(channel(r), channel(g), channel(b)))
+    });
+    engine
+        .register_type_with_name::<Colour>("Colour")
+        .register_static_module("Colour", colours.into());
+
+    engine
+        .register_type_with_name::<ScriptImage>("Image")
+        .register_fn(
+            "new_image",
+            |width: INT, height: INT| -> Result<ScriptImage, Box<EvalAltResult>> {
+                let (width, height) = (width.max(0), height.max(0));
+                if width.max(height) > MAX_IMAGE_CELLS
+                    || width.saturating_mul(height) > MAX_IMAGE_CELLS
+                {
+                    return Err(format!("Image of {width}x{height} chars is too large").into());
+                }
+                Ok(ScriptImage(Rc::new(RefCell::new(Image::new(
+                    width as u32,
+                    height as u32,
+                )))))
+            },
+        )
+        .register_get("width", |image: &mut ScriptImage| {
+            image.0.borrow().width as INT
+        })
+        .register_get("height", |image: &mut ScriptImage| {
+            image.0.borrow().height as INT
+        })
+        .register_fn(
+            "clear",
+            |image: &mut ScriptImage, ink: Colour, paper: Colour| {
+                image.0.borrow_mut().clear(ink, paper);
+            },
+        )
+        .register_fn(
+            "draw_char",
+            |image: &mut ScriptImage, x: INT, y: INT, ch: char, ink: Colour, paper: Colour| {
+                image
+                    .0
+                    .borrow_mut()
+                    .draw_char(point(x, y), Char::new_char(ch, ink, paper));
+            },
+        )
+        .register_fn(
+            "draw_string",
+            |image: &mut ScriptImage, x: INT, y: INT, text: &str, ink: Colour, paper: Colour| {
+                image
+                    .0
+                    .borrow_mut()
+                    .draw_string(point(x, y), text, ink, paper);
+            },
+        )
+        .register_fn(
+            "fill_rect",
+            |image: &mut ScriptImage,
+             x: INT,
+             y: INT,
+             width: INT,
+             height: INT,
+             ch: char,
+             ink: Colour,
+             paper: Colour| {
+                let p = point(x, y);
+                let rect = Rect::new(
+                    p.x,
+                    p.y,
+                    width.clamp(0, u32::MAX as INT) as u32,
+                    height.clamp(0, u32::MAX as INT) as u32,
+                );
+                image
+                    .0
+                    .borrow_mut()
+                    .draw_filled_rect(rect, Char::new_char(ch, ink, paper));
+            },
+        )
+        .register_fn("glyph", |image: &mut ScriptImage, x: INT, y: INT| {
+            image
+                .0
+                .borrow()
+                .get_glyph(point(x, y))
+                .map_or(-1, |glyph| glyph as INT)
+        })
+        .register_fn(
+            "blit",
+            |image: &mut ScriptImage, x: INT, y: INT, src: ScriptImage| {
+                // Copy the source first, as it may be the image drawn to.
+                let src = src.0.borrow().clone();
+                let src_rect = src.rect();
+                image
+                    .0
+                    .borrow_mut()
+                    .blit(point(x, y), &src, src_rect, Default::default());
+            },
+        );
+
+    engine
+        .register_type_with_name::<ScriptInput>("Input")
+        .register_get("dt", |input: &mut ScriptInput| input.dt)
+        .register_get("time", |input: &mut ScriptInput| input.time)
+        .register_get("width", |input: &mut ScriptInput| input.width)
+        .register_get("height", |input: &mut ScriptInput| input.height)
+        .register_get("mouse_x", |input: &mut ScriptInput| input.mouse_x)
+        .register_get("mouse_y", |input: &mut ScriptInput| input.mouse_y)
+        .register_get("left_down", |input: &mut ScriptInput| input.left.down)
+        .register_get("left_pressed", |input: &mut ScriptInput| input.left.pressed)
+        .register_get("left_released", |input: &mut ScriptInput| {
+            input.left.released
+        })
+        .register_get("right_down", |input: &mut ScriptInput| input.right.down)
+        .register_get("right_pressed", |input: &mut ScriptInput| {
+            input.right.pressed
+        })
+        .register_get("right_released", |input: &mut ScriptInput| {
+            input.right.released
+        })
+        .register_get("middle_down", |input: &mut ScriptInput| input.middle.down)
+        .register_get("middle_pressed", |input: &mut ScriptInput| {
+            input.middle.pressed
+        })
+        .register_get("middle_released", |input: &mut ScriptInput| {
+            input.middle.released
+        })
+        .register_get("wheel", |input: &mut ScriptInput| input.wheel)
+        .register_get("keys_pressed", |input: &mut ScriptInput| {
+            input.keys_pressed.clone()
+        })
+        .register_get("keys_released", |input: &mut ScriptInput| {
+            input.keys_released.clone()
+        })
+        .register_get("text", |input: &mut ScriptInput| input.text.clone());
+
+    let add_timer = |shared: Rc<RefCell<Shared>>, repeat: bool| {
+        move |seconds: f64, name: &str| {
+            let mut shared = shared.borrow_mut();
+            let id = shared.next_timer;
+            shared.next_timer += 1;
+            let seconds = seconds.max(0.0);
+            let due = shared.time + seconds;
+            shared.timers.push(Timer {
+                id,
+                name: name.to_string(),
+                due,
+                repeat: repeat.then_some(seconds.max(MIN_TIMER_INTERVAL)),
+            });
+            id
+        }
+    };
+    engine.register_fn("after", add_timer(shared.clone(), false));
+    engine.register_fn("every", add_timer(shared.clone(), true));
+    let timers = shared.clone();
+    engine.register_fn("cancel", move |id: INT| {
+        timers.borrow_mut().timers.retain(|timer| timer.id != id);
+    });
+    let quit = shared.clone();
+    engine.register_fn("quit", move || quit.borrow_mut().quit = true);
+
+    engine
+}
+
+/// Converts script coordinates to a point, saturating at the limits.
+fn point(x: INT, y: INT) -> Point {
+    Point::new(
+        x.clamp(i32::MIN as INT, i32::MAX as INT) as i32,
+        y.clamp(i32::MIN as INT, i32::MAX as INT) as i32,
+    )
+}
+
+/// Converts a script colour channel to a byte, saturating at the limits.
+fn channel(value: INT) -> u8 {
+    value.clamp(0, 255) as u8
+}
+
+/// Returns a duration in seconds.
+fn duration_secs(duration: Duration) -> f64 {
+    duration.num_microseconds().unwrap_or(i64::MAX) as f64 / 1_000_000.0
+}
+
+/// Returns the names of the keys pressed, or released, for a script, such as
+/// `"ArrowUp"`.  Repeats are left out of the keys pressed.
+fn key_names(keys: &[KeyEvent], pressed: bool) -> Array {
+    keys.iter()
+        .filter(|key| key.pressed == pressed && !key.repeat)
+        .map(|key| format!("{:?}", key.key).into())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::Headless;
+
+    #[test]
+    fn new_image_rejects_huge_sizes() {
+        let engine = new_engine(&Rc::default());
+        assert!(engine
+            .eval::<ScriptImage>("new_image(70000, 70000)")
+            .is_err());
+        assert!(engine.eval::<ScriptImage>("new_image(1 << 40, 0)").is_err());
+        let image = engine.eval::<ScriptImage>("new_image(-5, 3)").unwrap();
+        assert_eq!(image.0.borrow().width, 0);
+    }
+
+    #[test]
+    fn repeating_timers_with_no_delay_end_the_tick() {
+        // Started after 2 seconds, where adding the smallest f64 to the time
+        // no longer changes it.
+        let script = r#"
+            fn init() {
+                #{ started: false, calls: 0 }
+            }
+
+            fn tick(input) {
+                if input.time > 2.5 && !this.started {
+                    this.started = true;
+                    every(0.0, "count");
+                }
+            }
+
+            fn count() {
+                this.calls += 1;
+            }
+        "#;
+        let app = ScriptApp::new(script).unwrap();
+        let mut headless = Headless::new(app, 10, 2);
+        headless.run_frames(180);
+
+        let app = headless.into_app();
+        assert!(app.error().is_none());
+        let calls = app.state().clone_cast::<Map>()["calls"].as_int().unwrap();
+        assert!(calls > 0 && calls <= 1000, "{} calls", calls);
+    }
+}
@@ -0,0 +1,104 @@
+//! Selecting an area of the screen with the mouse and copying its text, when
+//! [`Config::selection_key`] is set.
+//!
+//! The selection is highlighted by inverting the screen's colours just while
+//! a frame is drawn, so the screen the app presents to is never changed.
+//!
+//! [`Config::selection_key`]: ../struct.Config.html#structfield.selection_key
+
+use crate::image::{Image, Point, Rect};
+
+/// The state of selecting an area of the screen.
+#[derive(Debug, Default)]
+pub(crate) struct Selection {
+    /// True while selecting is switched on.
+    active: bool,
+
+    /// The char that a drag started on, while the left button is held down.
+    anchor: Option<Point>,
+
+    /// The area selected, which is highlighted.
+    rect: Option<Rect>,
+
+    /// True if the highlight has changed since the last frame was drawn.
+    changed: bool,
+}
+
+impl Selection {
+    /// Returns true while selecting is switched on.
+    pub(crate) fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Switches selecting on or off.  Switching it off removes the
+    /// highlight.
+    pub(crate) fn toggle(&mut self) {
+        self.active = !self.active;
+        if !self.active {
+            self.anchor = None;
+            self.set_rect(None);
+        }
+    }
+
+    /// Starts a drag when the left button is pressed over a char.
+    pub(crate) fn press(&mut self, position: Option<Point>) {
+        self.anchor = position;
+        self.set_rect(position.map(|p| Rect::new(p.x, p.y, 1, 1)));
+    }
+
+    /// Selects the area from the start of the drag to the char under the
+    /// mouse pointer, while the left button is held down.
+    pub(crate) fn drag(&mut self, position: Option<Point>) {
+        if let (Some(anchor), Some(p)) = (self.anchor, position) {
+            // Both chars are in the area selected.
+            let (x, y) = (anchor.x.min(p.x), anchor.y.min(p.y));
+            let width = anchor.x.abs_diff(p.x) + 1;
+            let height = anchor.y.abs_diff(p.y) + 1;
+            self.set_rect(Some(Rect::new(x, y, width, height)));
+        }
+    }
+
+    /// Ends a drag when the left button is released.
+    ///
+    /// # Returns
+    ///
+    /// The area selected, or `None` if no drag was started.
+    ///
+    pub(crate) fn release(&mut self) -> Option<Rect> {
+        self.anchor.take().and(self.rect)
+    }
+
+    /// Returns the text of the area selected on the screen, one line per row
+    /// with the spaces at the end of each line removed.
+    pub(crate) fn text(screen: &Image, rect: Rect) -> String {
+        screen
+            .region_to_plain_text(rect)
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Inverts the colours of the area selected on the screen.  Doing so
+    /// again puts them back, so the screen can be highlighted just while it
+    /// is drawn.
+    pub(crate) fn toggle_highlight(&self, screen: &mut Image) {
+        if let Some(rect) = self.rect {
+            screen.invert(rect);
+        }
+    }
+
+    /// Returns true, once, if the highlight has changed since this was last
+    /// called, so the screen must be drawn again.
+    pub(crate) fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Changes the area selected.
+    fn set_rect(&mut self, rect: Option<Rect>) {
+        if rect != self.rect {
+            self.rect = rect;
+            self.changed = true;
+        }
+    }
+}
@@ -12,6 +12,9 @@ struct VertexOutput {
 struct Uniforms {
     font_width: u32,
     font_height: u32,
+    colour_filter: u32,
+    pixel_scale: u32,
+    colour_matrix: mat3x3<f32>,
 }
 
 @group(1) @binding(0) var<uniform> uniforms: Uniforms;
@@ -48,13 +51,20 @@ fn vs_main(
 fn fs_main(
     @builtin(position) pos: vec4<f32>,
 ) -> @location(0) vec4<f32> {
-    // Calculate the pixel coords
-    let p = vec2<f32>(pos.x - 0.5, pos.y - 0.5);
+    // Calculate the pixel coords, in font pixels
+    let p = vec2<f32>(pos.x - 0.5, pos.y - 0.5) / f32(uniforms.pixel_scale);
 
     // Calculate the char coords and the local coords inside a character block
     let cp = vec2(i32(p.x) / i32(uniforms.font_width), i32(p.y) / i32(uniforms.font_height));
     let lp = vec2(i32(p.x) % i32(uniforms.font_width), i32(p.y) % i32(uniforms.font_height));
 
+    // Chars beyond the grid, such as when the window is larger than the GPU
+    // can draw, are left black
+    let grid_size = vec2<i32>(textureDimensions(t_fore));
+    if cp.x >= grid_size.x || cp.y >= grid_size.y {
+        return vec4(0.0, 0.0, 0.0, 1.0);
+    }
+
     // Look up the textures
     let fore = textureLoad(t_fore, cp, 0);
     let back = textureLoad(t_back, cp, 0);
@@ -75,9 +85,17 @@ fn fs_main(
     // Fetch the pixel in the font texture
     let font_pixel = textureLoad(t_font, vec2<i32>(lx, ly), 0);
 
+    var colour = fore;
     if font_pixel.r < 0.5 {
-        return back;
-    } else {
-        return fore;
+        colour = back;
     }
+
+    // Apply the colour filter in linear space
+    if uniforms.colour_filter != 0u {
+        let linear = pow(colour.rgb, vec3(2.2));
+        let filtered = clamp(uniforms.colour_matrix * linear, vec3(0.0), vec3(1.0));
+        colour = vec4(pow(filtered, vec3(1.0 / 2.2)), colour.a);
+    }
+
+    return colour;
 }
\ No newline at end of file
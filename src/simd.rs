@@ -0,0 +1,116 @@
+//! Row operations on the image planes, written so that the compiler turns
+//! them into SIMD instructions without any unsafe code.
+//!
+//! Rows are worked on in chunks of [`LANES`] cells.  Which cells of a chunk a
+//! blit copies is worked out without branching as a mask for each cell.
+//! Chunks that are wholly copied or wholly skipped are handled with a single
+//! copy or not at all, and the rest are merged with the masks, which avoids a
+//! branch for every cell of a sprite with holes in it.
+
+use crate::image::{BlitOptions, TRANSPARENT_GLYPH};
+
+/// The number of cells worked on at once.  This is several vectors' worth,
+/// so that the cost of checking each chunk is spread over many cells.
+const LANES: usize = 64;
+
+/// Which source cells a blit copies, tested without branching.
+#[derive(Clone, Copy)]
+struct Opacity {
+    /// The glyph that is skipped as well as [`TRANSPARENT_GLYPH`].
+    transparent_char: u32,
+
+    /// The bits of the paper colour that must not all be zero, which are the
+    /// alpha channel if transparent papers are skipped and none otherwise.
+    alpha_mask: u32,
+}
+
+impl Opacity {
+    fn new(options: BlitOptions) -> Self {
+        Self {
+            transparent_char: options.transparent_char.unwrap_or(TRANSPARENT_GLYPH),
+            alpha_mask: if options.transparent_paper {
+                0xff00_0000
+            } else {
+                0
+            },
+        }
+    }
+
+    /// Returns true if a source char with the given glyph and paper colour is
+    /// copied.  This gives the opposite of [`BlitOptions::is_transparent`].
+    #[inline(always)]
+    fn is_opaque(self, ch: u32, paper: u32) -> bool {
+        (ch != TRANSPARENT_GLYPH)
+            & (ch != self.transparent_char)
+            & ((paper | !self.alpha_mask) >> 24 != 0)
+    }
+}
+
+/// Copies a row of cells from the planes of a source image to those of a
+/// destination, skipping the source chars that the options make transparent.
+///
+/// # Arguments
+///
+/// * `dst` - The planes of the destination row.
+/// * `src` - The matching planes of the source row, which are the same
+///   length as the destination's.
+/// * `src_text` - The source row's glyphs, which decide which cells are
+///   transparent.
+/// * `src_back` - The source row's paper colours.
+/// * `options` - Controls which source chars are considered transparent.
+///
+pub(crate) fn copy_opaque<const N: usize>(
+    mut dst: [&mut [u32]; N],
+    src: [&[u32]; N],
+    src_text: &[u32],
+    src_back: &[u32],
+    options: BlitOptions,
+) {
+    let opacity = Opacity::new(options);
+    let len = src_text.len();
+
+    // Most rows are solid, and are copied whole.
+    if src_text
+        .iter()
+        .zip(src_back)
+        .fold(true, |all, (&ch, &paper)| {
+            all & opacity.is_opaque(ch, paper)
+        })
+    {
+        for (dst, src) in dst.iter_mut().zip(src) {
+            dst.copy_from_slice(src);
+        }
+        return;
+    }
+
+    for start in (0..len).step_by(LANES) {
+        let end = (start + LANES).min(len);
+        let mut masks = [0u32; LANES];
+        for (mask, (&ch, &paper)) in masks
+            .iter_mut()
+            .zip(src_text[start..end].iter().zip(&src_back[start..end]))
+        {
+            *mask = (opacity.is_opaque(ch, paper) as u32).wrapping_neg();
+        }
+        let masks = &masks[..end - start];
+
+        if masks.iter().fold(!0, |all, &mask| all & mask) != 0 {
+            for (dst, src) in dst.iter_mut().zip(src) {
+                dst[start..end].copy_from_slice(&src[start..end]);
+            }
+        } else if masks.iter().fold(0, |any, &mask| any | mask) != 0 {
+            for (dst, src) in dst.iter_mut().zip(src) {
+                select(&mut dst[start..end], &src[start..end], masks);
+            }
+        }
+    }
+}
+
+/// Replaces the destination cells whose mask is all ones with the source
+/// cells, leaving those whose mask is zero.
+#[inline(always)]
+fn select(dst: &mut [u32], src: &[u32], masks: &[u32]) {
+    for ((dst, &src), &mask) in dst.iter_mut().zip(src).zip(masks) {
+        *dst = (src & mask) | (*dst & !mask);
+    }
+}
@@ -0,0 +1,442 @@
+//! Sprite sheets and animations.
+//!
+//! A [`SpriteSheet`] is a single image holding many named sprites, and an
+//! [`Animation`] steps through a sequence of frames over time.
+//!
+//! [`SpriteSheet`]: struct.SpriteSheet.html
+//! [`Animation`]: struct.Animation.html
+
+use std::{collections::HashMap, path::Path};
+
+use chrono::Duration;
+
+use crate::{
+    error::MageError,
+    image::{BlitOptions, Image, Point, Rect},
+};
+
+/// An image containing many sprites, each identified by name and occupying a
+/// rectangle of the image.
+///
+/// Sprite sheets can be loaded from the Mage image format or from REXPaint
+/// `.xp` files, and frames are added by name either individually or by
+/// dividing the sheet into a grid.
+///
+pub struct SpriteSheet {
+    /// The image containing all the sprites.
+    image: Image,
+
+    /// The area of the image occupied by each named frame.
+    frames: HashMap<String, Rect>,
+}
+
+/// A view of a single frame of a [`SpriteSheet`], ready for blitting.
+///
+/// [`SpriteSheet`]: struct.SpriteSheet.html
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Frame<'sheet> {
+    /// The image containing the frame.
+    pub image: &'sheet Image,
+
+    /// The area of the image occupied by the frame.
+    pub rect: Rect,
+}
+
+impl SpriteSheet {
+    /// Creates a new sprite sheet from an image with no frames.
+    pub fn new(image: Image) -> Self {
+        Self {
+            image,
+            frames: HashMap::new(),
+        }
+    }
+
+    /// Loads a sprite sheet with no frames from a file in the Mage image
+    /// format.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MageError> {
+        Ok(Self::new(Image::load(path)?))
+    }
+
+    /// Loads a sprite sheet with no frames from a REXPaint `.xp` file.
+    pub fn load_xp(path: impl AsRef<Path>) -> Result<Self, MageError> {
+        Ok(Self::new(Image::load_xp(path)?))
+    }
+
+    /// Creates a new sprite sheet by dividing an image into a grid of equally
+    /// sized frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image containing the sprites.
+    /// * `cell_width` - The width of each frame in chars.
+    /// * `cell_height` - The height of each frame in chars.
+    /// * `names` - The names of the frames, in order from left to right and
+    ///   then top to bottom.  Names beyond the number of cells in the grid are
+    ///   ignored.
+    ///
+    pub fn from_grid(image: Image, cell_width: u32, cell_height: u32, names: &[&str]) -> Self {
+        let mut sheet = Self::new(image);
+        if cell_width == 0 || cell_height == 0 {
+            return sheet;
+        }
+
+        let columns = sheet.image.width / cell_width;
+        let rows = sheet.image.height / cell_height;
+        let cells = (0..rows).flat_map(|y| (0..columns).map(move |x| (x, y)));
+        for (name, (x, y)) in names.iter().zip(cells) {
+            sheet.add_frame(
+                *name,
+                Rect::new(
+                    (x * cell_width) as i32,
+                    (y * cell_height) as i32,
+                    cell_width,
+                    cell_height,
+                ),
+            );
+        }
+        sheet
+    }
+
+    /// Adds a named frame to the sprite sheet, replacing any existing frame
+    /// with the same name.
+    pub fn add_frame(&mut self, name: impl Into<String>, rect: Rect) {
+        self.frames.insert(name.into(), rect);
+    }
+
+    /// Returns the frame with the given name, or `None` if there is no such
+    /// frame.
+    pub fn frame(&self, name: &str) -> Option<Frame<'_>> {
+        self.frames.get(name).map(|&rect| Frame {
+            image: &self.image,
+            rect,
+        })
+    }
+
+    /// Returns an iterator over the names of all frames in the sprite sheet.
+    pub fn frame_names(&self) -> impl Iterator<Item = &str> {
+        self.frames.keys().map(String::as_str)
+    }
+
+    /// Returns the image containing all the sprites.
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+}
+
+impl<'sheet> Frame<'sheet> {
+    /// Returns the width of the frame in chars.
+    pub fn width(&self) -> u32 {
+        self.rect.width
+    }
+
+    /// Returns the height of the frame in chars.
+    pub fn height(&self) -> u32 {
+        self.rect.height
+    }
+
+    /// Copies the frame into a new image of its own.
+    pub fn to_image(&self) -> Image {
+        let mut image = Image::new(self.rect.width, self.rect.height);
+        image.blit(
+            Point::default(),
+            self.image,
+            self.rect,
+            BlitOptions::default(),
+        );
+        image
+    }
+}
+
+impl Image {
+    /// Blits a sprite sheet frame on to this image.
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - The coordinates on this image to blit the top-left corner of
+    ///   the frame to.
+    /// * `frame` - The frame to blit.
+    /// * `options` - Controls which source chars are considered transparent.
+    ///
+    pub fn blit_frame(&mut self, dst: Point, frame: Frame, options: BlitOptions) {
+        self.blit(dst, frame.image, frame.rect, options);
+    }
+}
+
+/// Determines what an [`Animation`] does when it reaches its last frame.
+///
+/// [`Animation`]: struct.Animation.html
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoopMode {
+    /// Stop on the last frame.
+    Once,
+
+    /// Start again from the first frame.
+    Loop,
+
+    /// Play the frames backwards to the first frame, and then forwards again.
+    PingPong,
+}
+
+/// A sequence of frames, each shown for a given duration.
+///
+/// The animation is advanced with the time since the last frame (such as
+/// [`TickInput::dt`]) and yields the current frame.  Frames can be anything,
+/// but are typically the names of frames in a [`SpriteSheet`].
+///
+/// [`TickInput::dt`]: ../struct.TickInput.html#structfield.dt
+/// [`SpriteSheet`]: struct.SpriteSheet.html
+///
+#[derive(Clone, Debug)]
+pub struct Animation<T> {
+    /// The frames and how long each is shown for in microseconds.
+    frames: Vec<(T, i64)>,
+
+    /// What to do when the last frame is reached.
+    mode: LoopMode,
+
+    /// The index of the current frame.
+    current: usize,
+
+    /// How long the current frame has been shown for in microseconds.
+    elapsed: i64,
+
+    /// True if the frames are currently being played backwards.
+    reversed: bool,
+
+    /// True if a [`LoopMode::Once`] animation has reached its end.
+    finished: bool,
+}
+
+impl<T> Animation<T> {
+    /// Creates a new animation with no frames.
+    pub fn new(mode: LoopMode) -> Self {
+        Self {
+            frames: Vec::new(),
+            mode,
+            current: 0,
+            elapsed: 0,
+            reversed: false,
+            finished: false,
+        }
+    }
+
+    /// Creates a new animation where every frame is shown for the same
+    /// duration.
+    pub fn from_frames(
+        frames: impl IntoIterator<Item = T>,
+        frame_duration: Duration,
+        mode: LoopMode,
+    ) -> Self {
+        let mut animation = Self::new(mode);
+        frames
+            .into_iter()
+            .for_each(|frame| animation.add_frame(frame, frame_duration));
+        animation
+    }
+
+    /// Adds a frame to the end of the animation.
+    pub fn add_frame(&mut self, frame: T, duration: Duration) {
+        let duration = duration.num_microseconds().unwrap_or(i64::MAX).max(0);
+        self.frames.push((frame, duration));
+    }
+
+    /// Advances the animation by the given time.
+    pub fn advance(&mut self, dt: Duration) {
+        let total: i64 = self.frames.iter().map(|(_, d)| d).sum();
+        if self.finished || total == 0 {
+            return;
+        }
+
+        self.elapsed = self
+            .elapsed
+            .saturating_add(dt.num_microseconds().unwrap_or(i64::MAX).max(0));
+
+        // Skip any whole cycles so large time steps don't step through every
+        // frame.  A cycle always returns to the same frame and direction.
+        let cycle = match self.mode {
+            LoopMode::Once => 0,
+            LoopMode::Loop => total,
+            LoopMode::PingPong if self.frames.len() == 1 => total,
+            LoopMode::PingPong => {
+                2 * total - self.frames[0].1 - self.frames[self.frames.len() - 1].1
+            }
+        };
+        if cycle > 0 {
+            self.elapsed %= cycle;
+        }
+
+        while !self.finished && self.elapsed >= self.frames[self.current].1 {
+            self.elapsed -= self.frames[self.current].1;
+            self.step();
+        }
+    }
+
+    /// Moves on to the next frame according to the loop mode.
+    fn step(&mut self) {
+        let last = self.frames.len() - 1;
+        match self.mode {
+            LoopMode::Once if self.current == last => self.finished = true,
+            LoopMode::Once => self.current += 1,
+            LoopMode::Loop => {
+                self.current = if self.current == last {
+                    0
+                } else {
+                    self.current + 1
+                }
+            }
+            LoopMode::PingPong if last == 0 => {}
+            LoopMode::PingPong => {
+                if (self.reversed && self.current == 0) || (!self.reversed && self.current == last)
+                {
+                    self.reversed = !self.reversed;
+                }
+                if self.reversed {
+                    self.current -= 1;
+                } else {
+                    self.current += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the current frame, or `None` if the animation has no frames.
+    pub fn current(&self) -> Option<&T> {
+        self.frames.get(self.current).map(|(frame, _)| frame)
+    }
+
+    /// Returns the index of the current frame.
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    /// Returns true if a [`LoopMode::Once`] animation has shown its last frame
+    /// for its full duration.
+    ///
+    /// [`LoopMode::Once`]: enum.LoopMode.html#variant.Once
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Restarts the animation from the first frame.
+    pub fn reset(&mut self) {
+        self.current = 0;
+        self.elapsed = 0;
+        self.reversed = false;
+        self.finished = false;
+    }
+}
+
+impl<T: AsRef<str>> Animation<T> {
+    /// Looks up the current frame by name in a sprite sheet.
+    ///
+    /// # Returns
+    ///
+    /// The current frame, or `None` if the animation has no frames or the
+    /// sprite sheet has no frame with the current name.
+    ///
+    pub fn current_frame<'sheet>(&self, sheet: &'sheet SpriteSheet) -> Option<Frame<'sheet>> {
+        self.current().and_then(|name| sheet.frame(name.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(animation: &mut Animation<char>, steps: usize, dt: Duration) -> String {
+        (0..steps)
+            .map(|_| {
+                let frame = *animation.current().unwrap();
+                animation.advance(dt);
+                frame
+            })
+            .collect()
+    }
+
+    #[test]
+    fn once_stops_on_the_last_frame() {
+        let mut animation =
+            Animation::from_frames("abc".chars(), Duration::milliseconds(10), LoopMode::Once);
+        assert_eq!(
+            frames(&mut animation, 5, Duration::milliseconds(10)),
+            "abccc"
+        );
+        assert!(animation.is_finished());
+    }
+
+    #[test]
+    fn loop_starts_again() {
+        let mut animation =
+            Animation::from_frames("abc".chars(), Duration::milliseconds(10), LoopMode::Loop);
+        assert_eq!(
+            frames(&mut animation, 7, Duration::milliseconds(10)),
+            "abcabca"
+        );
+    }
+
+    #[test]
+    fn ping_pong_turns_at_both_ends() {
+        let mut animation = Animation::from_frames(
+            "abc".chars(),
+            Duration::milliseconds(10),
+            LoopMode::PingPong,
+        );
+        assert_eq!(
+            frames(&mut animation, 9, Duration::milliseconds(10)),
+            "abcbabcba"
+        );
+
+        let mut animation =
+            Animation::from_frames("ab".chars(), Duration::milliseconds(10), LoopMode::PingPong);
+        assert_eq!(
+            frames(&mut animation, 5, Duration::milliseconds(10)),
+            "ababa"
+        );
+    }
+
+    #[test]
+    fn ping_pong_with_one_frame_stays_put() {
+        let mut animation =
+            Animation::from_frames("a".chars(), Duration::milliseconds(10), LoopMode::PingPong);
+        assert_eq!(frames(&mut animation, 3, Duration::milliseconds(25)), "aaa");
+    }
+
+    #[test]
+    fn ping_pong_large_steps_match_small_steps() {
+        let mut animation = Animation::new(LoopMode::PingPong);
+        for (frame, ms) in [('a', 10), ('b', 20), ('c', 30), ('d', 40)] {
+            animation.add_frame(frame, Duration::milliseconds(ms));
+        }
+
+        for ms in [0, 5, 10, 45, 100, 139, 140, 141, 1000, 12_345] {
+            let mut stepped = animation.clone();
+            for _ in 0..ms {
+                stepped.advance(Duration::milliseconds(1));
+            }
+            let mut jumped = animation.clone();
+            jumped.advance(Duration::milliseconds(ms));
+
+            assert_eq!(
+                jumped.current_index(),
+                stepped.current_index(),
+                "after {ms}ms"
+            );
+            assert_eq!(jumped.reversed, stepped.reversed, "after {ms}ms");
+            assert_eq!(jumped.elapsed, stepped.elapsed, "after {ms}ms");
+        }
+    }
+
+    #[test]
+    fn ping_pong_survives_huge_steps() {
+        let mut animation = Animation::from_frames(
+            "abc".chars(),
+            Duration::milliseconds(10),
+            LoopMode::PingPong,
+        );
+        animation.advance(Duration::MAX);
+        assert!(animation.current().is_some());
+    }
+}
@@ -0,0 +1,59 @@
+//! How much memory the engine is using, for debug overlays and for catching
+//! leaks in apps with large screens or many images.
+//!
+//! Each tick is given the engine's [`MemoryStats`] in [`TickInput::memory`].
+//! They cover what the engine holds for drawing the window: the screen images
+//! the app presents to and the GPU textures and buffers they are uploaded
+//! through.  Images the app creates itself can be measured with
+//! [`Image::memory_bytes`].
+//!
+//! ```
+//! use mage_core::{image::Image, stats::MemoryStats};
+//!
+//! fn memory_line(stats: &MemoryStats, sprites: &[Image]) -> String {
+//!     let sprite_bytes: usize = sprites.iter().map(Image::memory_bytes).sum();
+//!     format!(
+//!         "engine {} KiB, sprites {} KiB",
+//!         stats.total_bytes() / 1024,
+//!         sprite_bytes / 1024
+//!     )
+//! }
+//!
+//! let sprites = vec![Image::new(32, 32); 4];
+//! assert_eq!(memory_line(&MemoryStats::default(), &sprites), "engine 0 KiB, sprites 48 KiB");
+//! ```
+//!
+//! [`MemoryStats`]: struct.MemoryStats.html
+//! [`TickInput::memory`]: ../struct.TickInput.html#structfield.memory
+//! [`Image::memory_bytes`]: ../image/struct.Image.html#method.memory_bytes
+
+/// The memory used by the engine to draw the window, in bytes.
+///
+/// GPU memory is worked out from the sizes of the textures and buffers that
+/// were asked for, so drivers may use a little more.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// The memory used by the screen images the app presents to, including
+    /// the spare ones kept for a render thread.
+    pub screen_bytes: usize,
+
+    /// The number of screen images, which is one without a render thread and
+    /// two or three with one.
+    pub screens: u32,
+
+    /// The GPU memory used by the textures the screen is uploaded to, and the
+    /// buffers it is uploaded through.
+    pub texture_bytes: usize,
+
+    /// The GPU memory used by the font texture and the buffer it is uploaded
+    /// through.
+    pub font_bytes: usize,
+}
+
+impl MemoryStats {
+    /// Returns the memory used by the engine on both the CPU and the GPU.
+    pub fn total_bytes(&self) -> usize {
+        self.screen_bytes + self.texture_bytes + self.font_bytes
+    }
+}
@@ -0,0 +1,215 @@
+//! Drawing tables of text with box drawing borders.
+
+use crate::{
+    codepage::unicode_to_cp437,
+    colour::Colour,
+    image::{Char, Image, Point, Rect, DEFAULT_FALLBACK_GLYPH},
+    lines::LineStyle,
+};
+
+/// How text is positioned within a table column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Alignment {
+    /// Text starts at the left edge of the column.
+    #[default]
+    Left,
+
+    /// Text is centred within the column.
+    Centre,
+
+    /// Text ends at the right edge of the column.
+    Right,
+}
+
+/// A single column of a [`Table`].
+///
+/// [`Table`]: struct.Table.html
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Column {
+    /// The text shown in the header row.
+    pub header: String,
+
+    /// The width of the column in chars, not including the borders.
+    pub width: u32,
+
+    /// How text is positioned within the column.
+    pub alignment: Alignment,
+}
+
+impl Column {
+    /// Creates a new column with the given header, width and alignment.
+    pub fn new(header: impl Into<String>, width: u32, alignment: Alignment) -> Self {
+        Self {
+            header: header.into(),
+            width,
+            alignment,
+        }
+    }
+}
+
+/// Describes the layout and colours of a table drawn with
+/// [`Image::draw_table`].
+///
+/// If every column has an empty header, no header row is drawn.
+///
+/// [`Image::draw_table`]: ../image/struct.Image.html#method.draw_table
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Table {
+    /// The columns of the table from left to right.
+    pub columns: Vec<Column>,
+
+    /// The style of the border and separator lines.
+    pub style: LineStyle,
+
+    /// The foreground colour of the borders and cell text.
+    pub ink: Colour,
+
+    /// The foreground colour of the header text.
+    pub header_ink: Colour,
+
+    /// The background colour of the whole table.
+    pub paper: Colour,
+
+    /// The glyph drawn in place of the last visible character of text that is
+    /// too wide for its column, or `None` to simply clip the text.
+    pub ellipsis: Option<u8>,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            style: LineStyle::Single,
+            ink: Colour::Rgb(0xc0, 0xc0, 0xc0),
+            header_ink: Colour::White,
+            paper: Colour::Black,
+            ellipsis: Some(b'~'),
+        }
+    }
+}
+
+impl Table {
+    /// Returns true if the table has a header row.
+    fn has_header(&self) -> bool {
+        self.columns.iter().any(|column| !column.header.is_empty())
+    }
+
+    /// Returns the size of the table in chars, including borders, when drawn
+    /// with the given number of rows.
+    pub fn size(&self, rows: usize) -> (u32, u32) {
+        let width = self.columns.iter().map(|c| c.width + 1).sum::<u32>() + 1;
+        let header = if self.has_header() { 2 } else { 0 };
+        (width, rows as u32 + header + 2)
+    }
+}
+
+impl Image {
+    /// Draws a table of text with borders between every column.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The coordinates of the top-left corner of the table.
+    /// * `table` - The columns, line style and colours of the table.
+    /// * `rows` - The text of each cell, row by row.  Cells beyond the number
+    ///   of columns are ignored, and missing cells are left empty.
+    ///
+    /// # Returns
+    ///
+    /// The area of the image covered by the table.
+    ///
+    /// # Notes
+    ///
+    /// Text is mapped to code page 437 glyphs as in [`draw_string_unicode`].
+    /// Text too wide for its column is clipped and ends with the table's
+    /// ellipsis glyph.  If the coordinates are out of bounds, the table is
+    /// clipped.
+    ///
+    /// [`draw_string_unicode`]: struct.Image.html#method.draw_string_unicode
+    ///
+    pub fn draw_table<R, S>(&mut self, p: Point, table: &Table, rows: &[R]) -> Rect
+    where
+        R: AsRef<[S]>,
+        S: AsRef<str>,
+    {
+        let (width, height) = table.size(rows.len());
+        let rect = Rect::from_point_and_size(p, width, height);
+        self.draw_filled_rect(rect, Char::new(b' ', table.ink, table.paper));
+
+        // Horizontal lines first, so that the vertical lines merge with them
+        // to form the junctions.
+        let mut text_y = p.y + 1;
+        self.draw_hline(p, width, table.style, table.ink, table.paper, false);
+        if table.has_header() {
+            let mut x = p.x + 1;
+            for column in &table.columns {
+                self.draw_cell_text(
+                    Point::new(x, text_y),
+                    column,
+                    &column.header,
+                    table.header_ink,
+                    table,
+                );
+                x += column.width as i32 + 1;
+            }
+            text_y += 2;
+            let separator = Point::new(p.x, p.y + 2);
+            self.draw_hline(separator, width, table.style, table.ink, table.paper, false);
+        }
+        let bottom = Point::new(p.x, p.y + height as i32 - 1);
+        self.draw_hline(bottom, width, table.style, table.ink, table.paper, false);
+
+        let mut x = p.x;
+        self.draw_vline(p, height, table.style, table.ink, table.paper, true);
+        for column in &table.columns {
+            x += column.width as i32 + 1;
+            let top = Point::new(x, p.y);
+            self.draw_vline(top, height, table.style, table.ink, table.paper, true);
+        }
+
+        for (row, y) in rows.iter().zip(text_y..) {
+            let mut x = p.x + 1;
+            for (column, text) in table.columns.iter().zip(row.as_ref()) {
+                self.draw_cell_text(Point::new(x, y), column, text.as_ref(), table.ink, table);
+                x += column.width as i32 + 1;
+            }
+        }
+
+        rect
+    }
+
+    /// Draws the text of a single table cell, aligned and clipped to the
+    /// column.
+    fn draw_cell_text(
+        &mut self,
+        p: Point,
+        column: &Column,
+        text: &str,
+        ink: Colour,
+        table: &Table,
+    ) {
+        let width = column.width as usize;
+        let mut glyphs = text
+            .chars()
+            .map(|ch| unicode_to_cp437(ch).unwrap_or(DEFAULT_FALLBACK_GLYPH))
+            .collect::<Vec<_>>();
+        if glyphs.len() > width {
+            glyphs.truncate(width);
+            if let (Some(last), Some(ellipsis)) = (glyphs.last_mut(), table.ellipsis) {
+                *last = ellipsis;
+            }
+        }
+
+        let space = (width - glyphs.len()) as i32;
+        let offset = match column.alignment {
+            Alignment::Left => 0,
+            Alignment::Centre => space / 2,
+            Alignment::Right => space,
+        };
+        for (i, glyph) in glyphs.into_iter().enumerate() {
+            let p = Point::new(p.x + offset + i as i32, p.y);
+            self.draw_char(p, Char::new(glyph, ink, table.paper));
+        }
+    }
+}
@@ -0,0 +1,556 @@
+//! Serving games to telnet clients, in the style of public roguelike servers.
+//!
+//! A [`TelnetServer`] runs a new copy of an app for each client that
+//! connects, without a window or GPU, and streams its screen to the client
+//! as text with ANSI colour codes.  The keys the client types are decoded
+//! into [`KeyPress`]es, which the app reads from its [`Session`]:
+//!
+//! ```no_run
+//! use mage_core::{
+//!     telnet::{Session, TelnetServer},
+//!     ui::{Key, UiInput},
+//!     App, PresentInput, PresentResult, TickInput, TickResult,
+//! };
+//!
+//! struct Game {
+//!     session: Session,
+//! }
+//!
+//! impl App for Game {
+//!     fn tick(&mut self, tick_input: TickInput) -> TickResult {
+//!         let mut input = UiInput::from(&tick_input);
+//!         input.keys = self.session.take_keys();
+//!         if input.keys.iter().any(|key| key.key == Key::Char('q')) {
+//!             return TickResult::Quit;
+//!         }
+//!         TickResult::Continue
+//!     }
+//!
+//!     fn present(&mut self, _present_input: PresentInput) -> PresentResult {
+//!         PresentResult::Changed
+//!     }
+//! }
+//!
+//! let server = TelnetServer::bind("0.0.0.0:2323").unwrap();
+//! server.run(|session| Game { session }).unwrap();
+//! ```
+//!
+//! Clients are asked for their window size, and the app's screen follows it
+//! as it changes.  True colour escape codes are used, which most modern
+//! terminals support.
+//!
+//! [`TelnetServer`]: struct.TelnetServer.html
+//! [`KeyPress`]: ../ui/struct.KeyPress.html
+//! [`Session`]: struct.Session.html
+
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use tracing::{info, warn};
+
+use crate::{
+    headless::Headless,
+    image::Image,
+    ui::{Key, KeyPress},
+    App, TickResult,
+};
+
+/// Interpret as command, which starts each telnet command.
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+/// Subnegotiation begin.
+const SB: u8 = 250;
+/// Subnegotiation end.
+const SE: u8 = 240;
+
+/// The option for the server echoing typed characters.
+const ECHO: u8 = 1;
+/// The option for sending characters as they are typed, not a line at a time.
+const SUPPRESS_GO_AHEAD: u8 = 3;
+/// The option for the client telling the server its window size.
+const NAWS: u8 = 31;
+
+/// The size of the screen until the client says what its window size is.
+const DEFAULT_SIZE: (u32, u32) = (80, 24);
+
+/// The most bytes of a subnegotiation that are kept.  Longer ones are
+/// dropped, so a client can't use one to exhaust the server's memory.
+const MAX_SUBNEGOTIATION_LEN: usize = 64;
+
+/// The largest screen a client can ask for.  Window sizes are clamped to it,
+/// as each client's screen is allocated on the server.
+const MAX_SIZE: (u32, u32) = (1000, 1000);
+
+/// Serves a game to telnet clients, running a copy of it for each.
+pub struct TelnetServer {
+    listener: TcpListener,
+
+    /// The number of frames each game runs each second.
+    frame_rate: u32,
+}
+
+impl TelnetServer {
+    /// Creates a new server listening for clients at the given address.
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            frame_rate: 30,
+        })
+    }
+
+    /// Sets the number of frames each game runs each second.  The default is
+    /// 30, as frames are sent over the network.
+    pub fn with_frame_rate(mut self, frames_per_second: u32) -> Self {
+        self.frame_rate = frames_per_second.max(1);
+        self
+    }
+
+    /// Returns the address the server is listening at.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts clients until the listener fails, running a game for each
+    /// on its own thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `make_app` - Creates the game for a new client.  The game reads the
+    ///   client's keys from the session it is given.
+    ///
+    pub fn run<A, F>(self, make_app: F) -> io::Result<()>
+    where
+        A: App,
+        F: Fn(Session) -> A + Send + Sync + 'static,
+    {
+        let make_app = Arc::new(make_app);
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            let make_app = make_app.clone();
+            let frame_rate = self.frame_rate;
+            thread::spawn(move || {
+                let peer = stream.peer_addr().ok();
+                info!("Telnet client {:?} connected", peer);
+                if let Err(e) = serve(stream, frame_rate, make_app.as_ref()) {
+                    warn!("Telnet client {:?} failed: {}", peer, e);
+                }
+                info!("Telnet client {:?} disconnected", peer);
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A client's connection, given to the game running for it.
+#[derive(Clone, Debug)]
+pub struct Session {
+    /// The keys typed since they were last taken.
+    keys: Arc<Mutex<Vec<KeyPress>>>,
+
+    /// The address of the client.
+    peer_addr: Option<SocketAddr>,
+}
+
+impl Session {
+    /// Returns the keys the client has typed since they were last taken, in
+    /// the order they were typed.
+    pub fn take_keys(&self) -> Vec<KeyPress> {
+        self.keys
+            .lock()
+            .map(|mut keys| std::mem::take(&mut *keys))
+            .unwrap_or_default()
+    }
+
+    /// Returns the network address of the client.
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+}
+
+/// Runs a game for a client until either quits.
+fn serve<A: App>(
+    mut stream: TcpStream,
+    frame_rate: u32,
+    make_app: &dyn Fn(Session) -> A,
+) -> io::Result<()> {
+    let session = Session {
+        keys: Arc::default(),
+        peer_addr: stream.peer_addr().ok(),
+    };
+    let mut headless = Headless::new(make_app(session.clone()), DEFAULT_SIZE.0, DEFAULT_SIZE.1)
+        .with_frame_rate(frame_rate);
+    let mut input = InputDecoder::default();
+    let mut sent: Option<Image> = None;
+
+    // Ask for characters as they are typed, without local echo, and for the
+    // window size.  Then hide the cursor.
+    stream.write_all(&[
+        IAC,
+        WILL,
+        ECHO,
+        IAC,
+        WILL,
+        SUPPRESS_GO_AHEAD,
+        IAC,
+        DO,
+        SUPPRESS_GO_AHEAD,
+        IAC,
+        DO,
+        NAWS,
+    ])?;
+    stream.write_all(b"\x1b[?25l")?;
+
+    let frame_time = Duration::from_secs(1) / frame_rate.max(1);
+    let mut next_frame = Instant::now();
+    let mut buffer = [0; 1024];
+    loop {
+        // Read what the client sends until it is time for the next frame.
+        loop {
+            let now = Instant::now();
+            if now >= next_frame {
+                break;
+            }
+            stream.set_read_timeout(Some(next_frame - now))?;
+            match stream.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(n) => input.decode(&buffer[..n]),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        next_frame = (next_frame + frame_time).max(Instant::now());
+
+        if let Some((width, height)) = input.size.take() {
+            headless.resize(width, height);
+            sent = None;
+        }
+        if let Ok(mut keys) = session.keys.lock() {
+            keys.append(&mut input.keys);
+        }
+
+        if headless.step() == TickResult::Quit {
+            stream.write_all(b"\x1b[0m\x1b[2J\x1b[H\x1b[?25h")?;
+            return Ok(());
+        }
+        let update = screen_update(headless.screen(), sent.as_ref());
+        if !update.is_empty() {
+            stream.write_all(update.as_bytes())?;
+            sent = Some(headless.screen().clone());
+        }
+    }
+}
+
+/// Returns the text that redraws the rows of a screen that have changed
+/// since it was last sent, or the whole screen if it hasn't been sent at that
+/// size.
+fn screen_update(screen: &Image, sent: Option<&Image>) -> String {
+    let sent = sent.filter(|sent| sent.width == screen.width && sent.height == screen.height);
+    let mut text = String::new();
+    if sent.is_none() {
+        text.push_str("\x1b[0m\x1b[2J");
+    }
+    let w = screen.width as usize;
+    for y in 0..screen.height as usize {
+        let row = y * w..(y + 1) * w;
+        let changed = sent.is_none_or(|sent| {
+            sent.text_image[row.clone()] != screen.text_image[row.clone()]
+                || sent.fore_image[row.clone()] != screen.fore_image[row.clone()]
+                || sent.back_image[row.clone()] != screen.back_image[row.clone()]
+        });
+        if changed {
+            text.push_str(&format!("\x1b[{};1H", y + 1));
+            screen.write_ansi_row(&mut text, y);
+        }
+    }
+    text
+}
+
+/// Turns the bytes sent by a telnet client into key presses and window
+/// sizes, handling the telnet commands mixed in with them.
+#[derive(Debug, Default)]
+struct InputDecoder {
+    /// The keys decoded so far.
+    keys: Vec<KeyPress>,
+
+    /// The latest window size the client has sent, if it has changed.
+    size: Option<(u32, u32)>,
+
+    /// The bytes of keys typed that haven't been decoded yet, as escape
+    /// sequences and UTF-8 characters can be split between reads.
+    pending: Vec<u8>,
+
+    /// The state of the telnet command being read.
+    command: Command,
+
+    /// The last byte read if it was a carriage return, so that the line feed
+    /// or null that follows it isn't read as another key.
+    after_cr: bool,
+}
+
+/// The state of reading a telnet command.
+#[derive(Debug, Default)]
+enum Command {
+    /// Not reading a command.
+    #[default]
+    None,
+
+    /// After IAC.
+    Start,
+
+    /// After IAC and a verb that takes an option, such as WILL.
+    Option,
+
+    /// In a subnegotiation, collecting its bytes.
+    Sub(Vec<u8>),
+
+    /// After IAC in a subnegotiation.
+    SubIac(Vec<u8>),
+}
+
+impl InputDecoder {
+    /// Decodes bytes sent by the client.  Replies to the options the server
+    /// asked for are ignored, as the server works whatever the client
+    /// agrees to.
+    fn decode(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.command = match (std::mem::take(&mut self.command), byte) {
+                (Command::None, IAC) => Command::Start,
+                (Command::None, byte) => {
+                    self.data(byte);
+                    Command::None
+                }
+                (Command::Start, IAC) => {
+                    self.data(IAC);
+                    Command::None
+                }
+                (Command::Start, WILL | WONT | DO | DONT) => Command::Option,
+                (Command::Start, SB) => Command::Sub(Vec::new()),
+                (Command::Start | Command::Option, _) => Command::None,
+                (Command::Sub(sub), IAC) => Command::SubIac(sub),
+                (Command::Sub(mut sub), byte) => {
+                    push_sub(&mut sub, byte);
+                    Command::Sub(sub)
+                }
+                (Command::SubIac(mut sub), IAC) => {
+                    push_sub(&mut sub, IAC);
+                    Command::Sub(sub)
+                }
+                (Command::SubIac(sub), SE) => {
+                    if sub.len() <= MAX_SUBNEGOTIATION_LEN {
+                        self.subnegotiation(&sub);
+                    }
+                    Command::None
+                }
+                (Command::SubIac(_), _) => Command::None,
+            };
+        }
+        self.decode_keys(true);
+    }
+
+    /// Handles a subnegotiation, which is how the client sends its window
+    /// size.  Window sizes are clamped to [`MAX_SIZE`].
+    fn subnegotiation(&mut self, sub: &[u8]) {
+        if let [NAWS, w0, w1, h0, h1] = *sub {
+            let width = u16::from_be_bytes([w0, w1]) as u32;
+            let height = u16::from_be_bytes([h0, h1]) as u32;
+            if width > 0 && height > 0 {
+                self.size = Some((width.min(MAX_SIZE.0), height.min(MAX_SIZE.1)));
+            }
+        }
+    }
+
+    /// Handles a byte of typed data.
+    fn data(&mut self, byte: u8) {
+        let after_cr = std::mem::replace(&mut self.after_cr, byte == b'\r');
+        if after_cr && matches!(byte, b'\n' | 0) {
+            return;
+        }
+        self.pending.push(byte);
+        self.decode_keys(false);
+    }
+
+    /// Decodes as many keys as possible from the pending bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `all` - If true, the end of the bytes read so far has been reached,
+    ///   so a lone escape is the escape key rather than the start of a
+    ///   sequence.
+    ///
+    fn decode_keys(&mut self, all: bool) {
+        let mut start = 0;
+        while start < self.pending.len() {
+            match decode_key(&self.pending[start..], all) {
+                Decoded::Key(key, len) => {
+                    self.keys.extend(key);
+                    start += len;
+                }
+                Decoded::Incomplete => break,
+            }
+        }
+        self.pending.drain(..start);
+    }
+}
+
+/// Adds a byte to a subnegotiation.  Bytes past the limit are dropped, but
+/// one more is kept so that an overflowing subnegotiation can be told apart
+/// and ignored.
+fn push_sub(sub: &mut Vec<u8>, byte: u8) {
+    if sub.len() <= MAX_SUBNEGOTIATION_LEN {
+        sub.push(byte);
+    }
+}
+
+/// The result of decoding a key from typed bytes.
+enum Decoded {
+    /// A key, or `None` for bytes that are ignored, and the number of bytes
+    /// it used.
+    Key(Option<KeyPress>, usize),
+
+    /// More bytes are needed.
+    Incomplete,
+}
+
+/// Decodes the key at the start of some typed bytes.
+///
+/// # Arguments
+///
+/// * `bytes` - The typed bytes, which aren't empty.
+/// * `all` - True if no more bytes have been read yet.
+///
+fn decode_key(bytes: &[u8], all: bool) -> Decoded {
+    let key = |key: Key| Some(KeyPress::new(key));
+    match bytes[0] {
+        0x1b => {
+            let Some(&next) = bytes.get(1) else {
+                return if all {
+                    Decoded::Key(key(Key::Escape), 1)
+                } else {
+                    Decoded::Incomplete
+                };
+            };
+            match next {
+                b'[' | b'O' => decode_sequence(bytes, all),
+                0x1b => Decoded::Key(key(Key::Escape), 1),
+                _ => match decode_key(&bytes[1..], all) {
+                    Decoded::Key(pressed, len) => {
+                        Decoded::Key(pressed.map(KeyPress::with_alt), len + 1)
+                    }
+                    Decoded::Incomplete => Decoded::Incomplete,
+                },
+            }
+        }
+        b'\r' | b'\n' => Decoded::Key(key(Key::Enter), 1),
+        b'\t' => Decoded::Key(key(Key::Tab), 1),
+        0x7f | 0x08 => Decoded::Key(key(Key::Backspace), 1),
+        // Control and a letter send the letter's control code.
+        byte @ 0x01..=0x1a => {
+            let ch = char::from(byte + b'a' - 1);
+            Decoded::Key(Some(KeyPress::new(Key::Char(ch)).with_ctrl()), 1)
+        }
+        byte if byte < 0x20 => Decoded::Key(None, 1),
+        byte => {
+            let len = match byte {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            };
+            if bytes.len() < len {
+                return if all {
+                    Decoded::Key(None, bytes.len())
+                } else {
+                    Decoded::Incomplete
+                };
+            }
+            let ch = std::str::from_utf8(&bytes[..len])
+                .ok()
+                .and_then(|text| text.chars().next());
+            Decoded::Key(ch.map(|ch| KeyPress::new(Key::Char(ch))), len)
+        }
+    }
+}
+
+/// Decodes an escape sequence for a key, such as `ESC [ A` for the up arrow.
+fn decode_sequence(bytes: &[u8], all: bool) -> Decoded {
+    // The sequence ends with a byte from `@` to `~`, after any parameters.
+    let Some(end) = bytes[2..]
+        .iter()
+        .position(|byte| (0x40..=0x7e).contains(byte))
+        .map(|i| i + 2)
+    else {
+        return if all {
+            Decoded::Key(None, bytes.len())
+        } else {
+            Decoded::Incomplete
+        };
+    };
+    let params = &bytes[2..end];
+    let key = match (bytes[end], params) {
+        (b'A', _) => Some(Key::Up),
+        (b'B', _) => Some(Key::Down),
+        (b'C', _) => Some(Key::Right),
+        (b'D', _) => Some(Key::Left),
+        (b'H', _) | (b'~', b"1" | b"7") => Some(Key::Home),
+        (b'F', _) | (b'~', b"4" | b"8") => Some(Key::End),
+        (b'~', b"3") => Some(Key::Delete),
+        (b'~', b"5") => Some(Key::PageUp),
+        (b'~', b"6") => Some(Key::PageDown),
+        (b'Z', _) => return Decoded::Key(Some(KeyPress::new(Key::Tab).with_shift()), end + 1),
+        _ => None,
+    };
+    Decoded::Key(key.map(KeyPress::new), end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_window_size() {
+        let mut input = InputDecoder::default();
+        input.decode(&[IAC, SB, NAWS, 0, 100, 0, 40, IAC, SE]);
+        assert_eq!(input.size, Some((100, 40)));
+    }
+
+    #[test]
+    fn clamps_huge_window_sizes() {
+        // 65535 by 65535, with each 255 byte escaped as IAC IAC.
+        let mut input = InputDecoder::default();
+        input.decode(&[
+            IAC, SB, NAWS, IAC, IAC, IAC, IAC, IAC, IAC, IAC, IAC, IAC, SE,
+        ]);
+        assert_eq!(input.size, Some(MAX_SIZE));
+    }
+
+    #[test]
+    fn bounds_subnegotiations() {
+        let mut input = InputDecoder::default();
+        input.decode(&[IAC, SB, NAWS]);
+        for _ in 0..1000 {
+            input.decode(&[0; 1000]);
+        }
+        match &input.command {
+            Command::Sub(sub) => assert!(sub.len() <= MAX_SUBNEGOTIATION_LEN + 1),
+            command => panic!("unexpected state {command:?}"),
+        }
+
+        // The overflowing subnegotiation is dropped and reading carries on.
+        input.decode(&[IAC, SE, b'x']);
+        assert_eq!(input.size, None);
+        assert_eq!(input.keys, vec![KeyPress::new(Key::Char('x'))]);
+    }
+}
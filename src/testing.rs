@@ -0,0 +1,437 @@
+//! Testing apps frame by frame, with scripted input and assertions on the
+//! screen.
+//!
+//! A [`TestDriver`] runs an app without a window, as [`Headless`] does, but
+//! lets each frame's input be chosen: how long the frame took and what the
+//! mouse and keyboard did.  Input can be given a step at a time or as a script of
+//! [`Step`]s, and after each frame the cells the app presented can be checked
+//! with [`assert_cell`] and [`assert_region_text`].  This makes gameplay and
+//! UI logic testable with `cargo test`.
+//!
+//! ```
+//! use mage_core::{
+//!     image::{Char, Point, Rect},
+//!     testing::{Step, TestDriver},
+//!     App, Colour, PresentInput, PresentResult, TickInput, TickResult,
+//! };
+//!
+//! #[derive(Default)]
+//! struct Clicks(u32);
+//!
+//! impl App for Clicks {
+//!     fn tick(&mut self, tick_input: TickInput) -> TickResult {
+//!         if tick_input.mouse.left.pressed {
+//!             self.0 += 1;
+//!         }
+//!         TickResult::Continue
+//!     }
+//!
+//!     fn present(&mut self, mut present_input: PresentInput) -> PresentResult {
+//!         let mut image = present_input.new_image();
+//!         image.clear(Colour::White, Colour::Black);
+//!         image.draw_string(Point::new(0, 0), &format!("Clicks: {}", self.0), Colour::White, Colour::Black);
+//!         present_input.blit(present_input.rect(), image.rect(), &image, Colour::Black);
+//!         PresentResult::Changed
+//!     }
+//! }
+//!
+//! let mut driver = TestDriver::new(Clicks::default(), 20, 3);
+//! driver.run_script(&[
+//!     Step::Click(Point::new(5, 1)),
+//!     Step::Frames(2),
+//!     Step::Click(Point::new(6, 1)),
+//! ]);
+//!
+//! driver.assert_region_text(Rect::new(0, 0, 9, 1), "Clicks: 2");
+//! driver.assert_cell(Point::new(8, 0), Char::new(b'2', Colour::White, Colour::Black));
+//! assert_eq!(driver.app().0, 2);
+//! ```
+//!
+//! [`TestDriver`]: struct.TestDriver.html
+//! [`Headless`]: ../headless/struct.Headless.html
+//! [`Step`]: enum.Step.html
+//! [`assert_cell`]: struct.TestDriver.html#method.assert_cell
+//! [`assert_region_text`]: struct.TestDriver.html#method.assert_region_text
+
+use chrono::Duration;
+use winit::{event::MouseButton, keyboard::KeyCode};
+
+use crate::{
+    gamepad::Gamepad,
+    headless::Headless,
+    image::{Char, Image, Point, Rect},
+    input::{KeyEvent, MouseState},
+    App, TickResult,
+};
+
+/// One step of a script run by [`TestDriver::run_script`].
+///
+/// Steps that change the mouse or send keys take effect on the next frame
+/// run.
+///
+/// [`TestDriver::run_script`]: struct.TestDriver.html#method.run_script
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// Runs a number of frames at the frame rate.
+    Frames(u64),
+
+    /// Runs a single frame that took the given time.
+    Wait(Duration),
+
+    /// Moves the mouse pointer over a char.
+    MoveMouse(Point),
+
+    /// Moves the mouse pointer out of the window.
+    LeaveWindow,
+
+    /// Presses a mouse button and holds it down.
+    Press(MouseButton),
+
+    /// Releases a mouse button.
+    Release(MouseButton),
+
+    /// Moves the mouse pointer over a char and clicks the left button on it,
+    /// pressing it for one frame and releasing it on the next.
+    Click(Point),
+
+    /// Scrolls the mouse wheel by a number of lines.  Positive values scroll
+    /// up, away from the user.
+    Scroll(i32),
+
+    /// Sends a key pressed or released.
+    Key(KeyEvent),
+
+    /// Presses a key for one frame and releases it on the next, with no
+    /// modifier keys held.
+    TapKey(KeyCode),
+
+    /// Types text, as the player's keyboard layout or input method would.
+    Type(&'static str),
+}
+
+/// Runs an app a frame at a time with scripted input, for testing.
+///
+/// The app is ticked and presented as it would be in a window of the chosen
+/// size, and the cells it presents are kept between frames in a screen image
+/// that can be checked after each frame.
+///
+pub struct TestDriver<A: App> {
+    /// Runs the app's frames.
+    headless: Headless<A>,
+
+    /// The time the app has seen pass.
+    elapsed: Duration,
+}
+
+impl<A: App> TestDriver<A> {
+    /// Creates a driver for an app with a screen of the given size in chars,
+    /// running at 60 frames per second.
+    pub fn new(app: A, width: u32, height: u32) -> Self {
+        Self {
+            headless: Headless::new(app, width, height),
+            elapsed: Duration::zero(),
+        }
+    }
+
+    /// Sets the number of frames run each second, which is the time passed to
+    /// each tick unless a frame is run with [`frame_with_dt`].
+    ///
+    /// [`frame_with_dt`]: struct.TestDriver.html#method.frame_with_dt
+    ///
+    pub fn with_frame_rate(mut self, frames_per_second: u32) -> Self {
+        self.headless = self.headless.with_frame_rate(frames_per_second);
+        self
+    }
+
+    /// Runs a single frame at the frame rate.
+    ///
+    /// # Returns
+    ///
+    /// The result of the tick, which is always [`TickResult::Quit`] once the
+    /// app has asked to quit.
+    ///
+    /// [`TickResult::Quit`]: ../enum.TickResult.html#variant.Quit
+    ///
+    pub fn frame(&mut self) -> TickResult {
+        let frame_time = self.frame_time();
+        self.frame_with_dt(frame_time)
+    }
+
+    /// Runs a single frame that took the given time, such as to test what an
+    /// app does after a long pause.
+    ///
+    /// # Returns
+    ///
+    /// The result of the tick.
+    ///
+    pub fn frame_with_dt(&mut self, dt: Duration) -> TickResult {
+        if !self.headless.is_finished() {
+            self.elapsed += dt;
+        }
+        self.headless.step_with_dt(dt)
+    }
+
+    /// Runs a number of frames at the frame rate, stopping early if the app
+    /// asks to quit.
+    ///
+    /// # Returns
+    ///
+    /// The result of the last tick.
+    ///
+    pub fn frames(&mut self, frames: u64) -> TickResult {
+        let mut result = TickResult::Continue;
+        for _ in 0..frames {
+            result = self.frame();
+            if result == TickResult::Quit {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Runs a script of input and frames, stopping early if the app asks to
+    /// quit.
+    ///
+    /// # Returns
+    ///
+    /// The result of the last tick, or [`TickResult::Continue`] if the script
+    /// ran no frames.
+    ///
+    /// [`TickResult::Continue`]: ../enum.TickResult.html#variant.Continue
+    ///
+    pub fn run_script(&mut self, script: &[Step]) -> TickResult {
+        let mut result = TickResult::Continue;
+        for &step in script {
+            result = match step {
+                Step::Frames(frames) => self.frames(frames),
+                Step::Wait(dt) => self.frame_with_dt(dt),
+                Step::MoveMouse(p) => {
+                    self.move_mouse(p);
+                    continue;
+                }
+                Step::LeaveWindow => {
+                    self.leave_window();
+                    continue;
+                }
+                Step::Press(button) => {
+                    self.press(button);
+                    continue;
+                }
+                Step::Release(button) => {
+                    self.release(button);
+                    continue;
+                }
+                Step::Click(p) => self.click(p),
+                Step::Scroll(lines) => {
+                    self.scroll(lines);
+                    continue;
+                }
+                Step::Key(event) => {
+                    self.send_key(event);
+                    continue;
+                }
+                Step::TapKey(key) => self.tap_key(key),
+                Step::Type(text) => {
+                    self.type_text(text);
+                    continue;
+                }
+            };
+            if result == TickResult::Quit {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Moves the mouse pointer over a char, from the next frame.
+    pub fn move_mouse(&mut self, p: Point) {
+        self.update_mouse(|mouse| mouse.position = Some(p));
+    }
+
+    /// Moves the mouse pointer out of the window, from the next frame.
+    pub fn leave_window(&mut self) {
+        self.update_mouse(|mouse| mouse.position = None);
+    }
+
+    /// Presses a mouse button and holds it down, from the next frame.
+    pub fn press(&mut self, button: MouseButton) {
+        self.update_mouse(|mouse| mouse.update_button(button, true));
+    }
+
+    /// Releases a mouse button, from the next frame.
+    pub fn release(&mut self, button: MouseButton) {
+        self.update_mouse(|mouse| mouse.update_button(button, false));
+    }
+
+    /// Scrolls the mouse wheel by a number of lines on the next frame.
+    pub fn scroll(&mut self, lines: i32) {
+        self.update_mouse(|mouse| mouse.wheel += lines);
+    }
+
+    /// Moves the mouse pointer over a char and clicks the left button,
+    /// running one frame with it pressed and one with it released.
+    ///
+    /// # Returns
+    ///
+    /// The result of the last tick.
+    ///
+    pub fn click(&mut self, p: Point) -> TickResult {
+        self.move_mouse(p);
+        self.press(MouseButton::Left);
+        if self.frame() == TickResult::Quit {
+            return TickResult::Quit;
+        }
+        self.release(MouseButton::Left);
+        self.frame()
+    }
+
+    /// Sends a key pressed or released to the app on the next frame.
+    pub fn send_key(&mut self, event: KeyEvent) {
+        self.headless.send_key(event);
+    }
+
+    /// Presses a key with no modifier keys held, running one frame with it
+    /// pressed and one with it released.
+    ///
+    /// # Returns
+    ///
+    /// The result of the last tick.
+    ///
+    pub fn tap_key(&mut self, key: KeyCode) -> TickResult {
+        self.send_key(KeyEvent::press(key));
+        if self.frame() == TickResult::Quit {
+            return TickResult::Quit;
+        }
+        self.send_key(KeyEvent::release(key));
+        self.frame()
+    }
+
+    /// Types text on the next frame.
+    pub fn type_text(&mut self, text: &str) {
+        self.headless.type_text(text);
+    }
+
+    /// Connects a controller, given to the app from the next frame.  See
+    /// [`Headless::connect_gamepad`].
+    ///
+    /// [`Headless::connect_gamepad`]: ../headless/struct.Headless.html#method.connect_gamepad
+    ///
+    pub fn connect_gamepad(&mut self, gamepad: Gamepad) {
+        self.headless.connect_gamepad(gamepad);
+    }
+
+    /// Disconnects the controller with the given id, from the next frame.
+    pub fn disconnect_gamepad(&mut self, id: u32) {
+        self.headless.disconnect_gamepad(id);
+    }
+
+    /// Returns the connected controller with the given id, so its buttons
+    /// and sticks can be changed for the next frame.
+    pub fn gamepad_mut(&mut self, id: u32) -> Option<&mut Gamepad> {
+        self.headless.gamepad_mut(id)
+    }
+
+    /// Returns the cells the app has presented so far.
+    pub fn screen(&self) -> &Image {
+        self.headless.screen()
+    }
+
+    /// Returns the text of an area of the screen, one line per row with each
+    /// line ending in a newline.  The area is clipped to the screen.
+    pub fn region_text(&self, rect: Rect) -> String {
+        self.screen().region_to_plain_text(rect)
+    }
+
+    /// Checks the char, ink and paper of a cell of the screen.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the screen's text if the cell is different or is off the
+    /// screen.
+    ///
+    #[track_caller]
+    pub fn assert_cell(&self, p: Point, expected: Char) {
+        let actual = self.screen().get_char(p);
+        assert!(
+            actual == Some(expected),
+            "cell ({}, {}) is {:?}, expected {:?}\nscreen:\n{}",
+            p.x,
+            p.y,
+            actual,
+            expected,
+            self.screen().to_plain_text(),
+        );
+    }
+
+    /// Checks the text of an area of the screen.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area of the screen to check, which is clipped to the
+    ///   screen.
+    /// * `expected` - The expected text, one line per row.  A newline at the
+    ///   end is optional.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the area's text if it is different.
+    ///
+    #[track_caller]
+    pub fn assert_region_text(&self, rect: Rect, expected: &str) {
+        let actual = self.region_text(rect);
+        assert!(
+            actual.strip_suffix('\n') == Some(expected.strip_suffix('\n').unwrap_or(expected)),
+            "text at ({}, {}) size {}x{} is:\n{}expected:\n{}",
+            rect.x,
+            rect.y,
+            rect.width,
+            rect.height,
+            actual,
+            expected,
+        );
+    }
+
+    /// Returns the number of frames run so far.
+    pub fn frame_count(&self) -> u64 {
+        self.headless.frame_count()
+    }
+
+    /// Returns the time the app has seen pass, which is the sum of the time
+    /// of every frame run.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns true if the app has asked to quit.
+    pub fn is_finished(&self) -> bool {
+        self.headless.is_finished()
+    }
+
+    /// Returns the app being run.
+    pub fn app(&self) -> &A {
+        self.headless.app()
+    }
+
+    /// Returns the app being run, so its state can be changed.
+    pub fn app_mut(&mut self) -> &mut A {
+        self.headless.app_mut()
+    }
+
+    /// Stops running the app and returns it.
+    pub fn into_app(self) -> A {
+        self.headless.into_app()
+    }
+
+    /// Returns the time of one frame at the frame rate.
+    fn frame_time(&self) -> Duration {
+        self.headless.frame_time()
+    }
+
+    /// Changes the state of the mouse given to the app on the next frame.
+    fn update_mouse(&mut self, f: impl FnOnce(&mut MouseState)) {
+        let mut mouse = self.headless.mouse();
+        f(&mut mouse);
+        self.headless.set_mouse(mouse);
+    }
+}
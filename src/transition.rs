@@ -0,0 +1,195 @@
+//! Effects that change one image into another, such as between screens.
+
+use crate::{
+    colour::Colour,
+    image::{Image, Point},
+};
+
+/// The direction a [`Transition::Wipe`] moves across the image.
+///
+/// [`Transition::Wipe`]: enum.Transition.html#variant.Wipe
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WipeDirection {
+    /// The new image appears from the left edge.
+    LeftToRight,
+
+    /// The new image appears from the right edge.
+    RightToLeft,
+
+    /// The new image appears from the top edge.
+    TopToBottom,
+
+    /// The new image appears from the bottom edge.
+    BottomToTop,
+}
+
+/// A way of changing from one image to another.
+///
+/// A transition gives the frame part of the way through the change with
+/// [`frame`], so it can be driven by a timer or a [`Tween`]:
+///
+/// ```
+/// use mage_core::{
+///     image::Image,
+///     transition::{Transition, WipeDirection},
+/// };
+///
+/// let (old_screen, new_screen) = (Image::new(80, 25), Image::new(80, 25));
+/// let wipe = Transition::Wipe(WipeDirection::LeftToRight);
+/// let halfway = wipe.frame(&old_screen, &new_screen, 0.5);
+/// ```
+///
+/// [`frame`]: enum.Transition.html#method.frame
+/// [`Tween`]: ../tween/struct.Tween.html
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transition {
+    /// The colours fade smoothly from the old image to the new one.  The
+    /// glyphs of the old image fade into the background before those of the
+    /// new image fade in.
+    Crossfade,
+
+    /// The cells change to the new image one by one in a random order, set
+    /// by a seed.
+    Dissolve(u64),
+
+    /// The new image sweeps across the old one from an edge.
+    Wipe(WipeDirection),
+
+    /// The new image appears in a circle growing from the centre.
+    IrisOpen,
+
+    /// The old image shrinks into a circle at the centre, with the new image
+    /// around it.
+    IrisClose,
+}
+
+impl Transition {
+    /// Returns the frame part of the way through changing from one image to
+    /// another.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The image to change from.
+    /// * `to` - The image to change to.  Cells outside it are taken from the
+    ///   old image.
+    /// * `t` - How far through the change the frame is, from 0.0 for the old
+    ///   image to 1.0 for the new image.  Values outside this range are
+    ///   clamped.
+    ///
+    /// # Returns
+    ///
+    /// A new image the size of the old image.
+    ///
+    pub fn frame(&self, from: &Image, to: &Image, t: f32) -> Image {
+        let mut image = from.clone();
+        self.frame_into(&mut image, from, to, t);
+        image
+    }
+
+    /// Draws the frame part of the way through changing from one image to
+    /// another on to an image, as [`frame`] but without making a new image.
+    /// Cells outside the old image are left untouched.
+    ///
+    /// [`frame`]: enum.Transition.html#method.frame
+    ///
+    pub fn frame_into(&self, image: &mut Image, from: &Image, to: &Image, t: f32) {
+        let t = t.clamp(0.0, 1.0);
+        let (width, height) = (from.width, from.height);
+        for y in 0..height.min(image.height) as i32 {
+            for x in 0..width.min(image.width) as i32 {
+                let p = Point::new(x, y);
+                let (Some(dst), Some(old)) = (image.point_to_index(p), from.point_to_index(p))
+                else {
+                    continue;
+                };
+                let (src, i) = match (self, to.point_to_index(p)) {
+                    (_, None) => (from, old),
+                    (Transition::Crossfade, Some(new)) => {
+                        crossfade(image, dst, from, old, to, new, t);
+                        continue;
+                    }
+                    (_, Some(new)) if self.shows_new(p, width, height, t) => (to, new),
+                    _ => (from, old),
+                };
+                image.fore_image[dst] = src.fore_image[i];
+                image.back_image[dst] = src.back_image[i];
+                image.text_image[dst] = src.text_image[i];
+            }
+        }
+    }
+
+    /// Returns true if a cell shows the new image part of the way through a
+    /// transition that moves cells from one image to the other.
+    fn shows_new(&self, p: Point, width: u32, height: u32, t: f32) -> bool {
+        // How far through the image a cell is along an axis, from just above
+        // 0.0 to 1.0, so that no cells change at 0.0 and all at 1.0.
+        let along = |i: i32, size: u32| (i + 1) as f32 / size.max(1) as f32;
+        match *self {
+            Transition::Crossfade => t >= 0.5,
+            Transition::Dissolve(seed) => threshold(p, seed) < t,
+            Transition::Wipe(direction) => {
+                let position = match direction {
+                    WipeDirection::LeftToRight => along(p.x, width),
+                    WipeDirection::RightToLeft => along(width as i32 - 1 - p.x, width),
+                    WipeDirection::TopToBottom => along(p.y, height),
+                    WipeDirection::BottomToTop => along(height as i32 - 1 - p.y, height),
+                };
+                position <= t
+            }
+            Transition::IrisOpen => iris_distance(p, width, height) <= t,
+            Transition::IrisClose => iris_distance(p, width, height) > 1.0 - t,
+        }
+    }
+}
+
+/// Mixes a cell of two images part of the way through a crossfade.
+fn crossfade(
+    image: &mut Image,
+    dst: usize,
+    from: &Image,
+    old: usize,
+    to: &Image,
+    new: usize,
+    t: f32,
+) {
+    let colour = |image: &Image, i: usize| -> (Colour, Colour) {
+        (image.fore_image[i].into(), image.back_image[i].into())
+    };
+    let (old_ink, old_paper) = colour(from, old);
+    let (new_ink, new_paper) = colour(to, new);
+    let paper = Colour::lerp(old_paper, new_paper, t);
+    let (glyph, ink) = if t < 0.5 {
+        (from.text_image[old], Colour::lerp(old_ink, paper, t * 2.0))
+    } else {
+        (
+            to.text_image[new],
+            Colour::lerp(paper, new_ink, t * 2.0 - 1.0),
+        )
+    };
+    image.fore_image[dst] = ink.into();
+    image.back_image[dst] = paper.into();
+    image.text_image[dst] = glyph;
+}
+
+/// Returns a number from 0.0 up to but not including 1.0 that is fixed for a
+/// cell and seed, but looks random.
+fn threshold(p: Point, seed: u64) -> f32 {
+    let mut hash = seed ^ ((p.x as u32 as u64) << 32 | p.y as u32 as u64);
+    // The finaliser of SplitMix64, which mixes every bit of the input.
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^= hash >> 31;
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Returns how far a cell is from the centre of an image, from 0.0 at the
+/// centre to 1.0 at the corners.  Cells are about twice as tall as they are
+/// wide, so rows count double to make the iris round.
+fn iris_distance(p: Point, width: u32, height: u32) -> f32 {
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let (dx, dy) = (p.x as f32 + 0.5 - cx, (p.y as f32 + 0.5 - cy) * 2.0);
+    let corner = (cx * cx + cy * cy * 4.0).sqrt().max(f32::EPSILON);
+    (dx * dx + dy * dy).sqrt() / corner
+}
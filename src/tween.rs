@@ -0,0 +1,511 @@
+//! Values that change smoothly over time.
+//!
+//! A [`Tween`] moves a value, such as a number, [`Point`] or [`Colour`],
+//! through a chain of steps, each to a new value over a duration with an
+//! [`Easing`] curve.  It is advanced with the time since the last tick, so UI
+//! slides and colour fades don't need their own timers:
+//!
+//! ```
+//! use chrono::Duration;
+//! use mage_core::{
+//!     colour::Colour,
+//!     tween::{Easing, Tween},
+//! };
+//!
+//! let mut fade = Tween::new(Colour::Black)
+//!     .to(Colour::White, Duration::milliseconds(500), Easing::Linear)
+//!     .to(Colour::Red, Duration::milliseconds(500), Easing::QuadOut);
+//!
+//! fade.advance(Duration::milliseconds(500));
+//! assert_eq!(fade.value(), Colour::Rgba(255, 255, 255, 255));
+//! fade.advance(Duration::seconds(1));
+//! assert!(fade.is_finished());
+//! ```
+//!
+//! [`Tween`]: struct.Tween.html
+//! [`Point`]: ../image/struct.Point.html
+//! [`Colour`]: ../colour/enum.Colour.html
+//! [`Easing`]: enum.Easing.html
+
+use std::f32::consts::PI;
+
+use chrono::Duration;
+
+use crate::{colour::Colour, image::Point, sprite::LoopMode};
+
+/// A curve controlling how a value moves between two others over time.
+///
+/// Curves ending in `In` start slowly, those ending in `Out` end slowly, and
+/// those ending in `InOut` do both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Easing {
+    /// Moves at a constant speed.
+    #[default]
+    Linear,
+
+    /// Accelerates from zero speed.
+    QuadIn,
+
+    /// Decelerates to zero speed.
+    QuadOut,
+
+    /// Accelerates to halfway, then decelerates.
+    QuadInOut,
+
+    /// Accelerates from zero speed, more sharply than [`QuadIn`].
+    ///
+    /// [`QuadIn`]: enum.Easing.html#variant.QuadIn
+    ///
+    CubicIn,
+
+    /// Decelerates to zero speed, more sharply than [`QuadOut`].
+    ///
+    /// [`QuadOut`]: enum.Easing.html#variant.QuadOut
+    ///
+    CubicOut,
+
+    /// Accelerates to halfway, then decelerates, more sharply than
+    /// [`QuadInOut`].
+    ///
+    /// [`QuadInOut`]: enum.Easing.html#variant.QuadInOut
+    ///
+    CubicInOut,
+
+    /// Accelerates gently along a sine curve.
+    SineIn,
+
+    /// Decelerates gently along a sine curve.
+    SineOut,
+
+    /// Accelerates and decelerates gently along a sine curve.
+    SineInOut,
+
+    /// Pulls back a little before moving.
+    BackIn,
+
+    /// Overshoots the end a little before settling.
+    BackOut,
+
+    /// Overshoots the end and springs back and forth before settling.
+    ElasticOut,
+
+    /// Bounces off the end like a dropped ball.
+    BounceOut,
+
+    /// Stays at the start until the end of the step, then jumps to the end.
+    Step,
+}
+
+impl Easing {
+    /// Returns how far along the curve a value is at a point in time.
+    ///
+    /// # Arguments
+    ///
+    /// * `t` - How far through the step the time is, from 0.0 to 1.0.  Values
+    ///   outside this range are clamped.
+    ///
+    /// # Returns
+    ///
+    /// How far the value has moved, where 0.0 is the start and 1.0 the end.
+    /// Curves that overshoot, such as [`BackOut`], return values outside this
+    /// range.
+    ///
+    /// [`BackOut`]: enum.Easing.html#variant.BackOut
+    ///
+    pub fn apply(self, t: f32) -> f32 {
+        // The overshoot of the back curves.
+        const BACK: f32 = 1.70158;
+
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Easing::QuadInOut => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::CubicInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Easing::SineOut => (t * PI / 2.0).sin(),
+            Easing::SineInOut => -((t * PI).cos() - 1.0) / 2.0,
+            Easing::BackIn => (BACK + 1.0) * t * t * t - BACK * t * t,
+            Easing::BackOut => {
+                let t = t - 1.0;
+                1.0 + (BACK + 1.0) * t * t * t + BACK * t * t
+            }
+            Easing::ElasticOut if t == 0.0 || t == 1.0 => t,
+            Easing::ElasticOut => {
+                2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+            }
+            Easing::BounceOut => bounce_out(t),
+            Easing::Step if t < 1.0 => 0.0,
+            Easing::Step => 1.0,
+        }
+    }
+}
+
+/// A value that can be moved smoothly between two others by a [`Tween`].
+///
+/// [`Tween`]: struct.Tween.html
+///
+pub trait Tweenable: Clone {
+    /// Returns a value part of the way between this value and another.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The value to move towards.
+    /// * `t` - How far to move, where 0.0 gives this value and 1.0 gives
+    ///   `other`.  Easing curves that overshoot pass values outside this
+    ///   range.
+    ///
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Tweenable for f64 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t as f64
+    }
+}
+
+impl Tweenable for i32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        (*self as f32).interpolate(&(*other as f32), t).round() as i32
+    }
+}
+
+impl Tweenable for u32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        (*self as f32).interpolate(&(*other as f32), t).round() as u32
+    }
+}
+
+impl Tweenable for u8 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        (*self as f32).interpolate(&(*other as f32), t).round() as u8
+    }
+}
+
+impl Tweenable for Point {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Point::new(
+            self.x.interpolate(&other.x, t),
+            self.y.interpolate(&other.y, t),
+        )
+    }
+}
+
+impl Tweenable for Colour {
+    /// Mixes the colours as [`Colour::lerp`], so curves that overshoot stop
+    /// at either colour.
+    ///
+    /// [`Colour::lerp`]: ../colour/enum.Colour.html#method.lerp
+    ///
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Colour::lerp(*self, *other, t)
+    }
+}
+
+/// A step of a [`Tween`], moving to a value over a duration.
+///
+/// [`Tween`]: struct.Tween.html
+///
+#[derive(Clone, Debug, PartialEq)]
+struct Step<T> {
+    /// The value at the end of the step.
+    target: T,
+
+    /// How long the step takes in microseconds.
+    duration: i64,
+
+    /// How the value moves during the step.
+    easing: Easing,
+}
+
+/// Something that happens at a point in the time of a tween.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Event {
+    /// A step finished.
+    Step(usize),
+
+    /// The tween reached its end, or its start if playing backwards.
+    Complete,
+}
+
+/// A value moved through a chain of steps over time.
+///
+/// Each step moves the value from where the last step left it to a new value
+/// over a duration, along an easing curve.  Functions can be called as each
+/// step finishes and when the tween reaches its end.
+pub struct Tween<T> {
+    /// The value before the first step.
+    start: T,
+
+    /// The steps, in order.
+    steps: Vec<Step<T>>,
+
+    /// What to do when the end of the last step is reached.
+    mode: LoopMode,
+
+    /// How long the tween has run for in microseconds.
+    elapsed: i64,
+
+    /// Called with the index of each step as it finishes.
+    on_step: Option<Box<dyn FnMut(usize)>>,
+
+    /// Called when the tween reaches its end.
+    on_complete: Option<Box<dyn FnMut()>>,
+}
+
+impl<T: Tweenable> Tween<T> {
+    /// Creates a new tween that holds a value, with no steps.  Add steps with
+    /// [`to`] and [`wait`].
+    ///
+    /// [`to`]: struct.Tween.html#method.to
+    /// [`wait`]: struct.Tween.html#method.wait
+    ///
+    pub fn new(start: T) -> Self {
+        Self {
+            start,
+            steps: Vec::new(),
+            mode: LoopMode::Once,
+            elapsed: 0,
+            on_step: None,
+            on_complete: None,
+        }
+    }
+
+    /// Creates a new tween with a single step from one value to another.
+    pub fn between(start: T, end: T, duration: Duration, easing: Easing) -> Self {
+        Self::new(start).to(end, duration, easing)
+    }
+
+    /// Adds a step that moves the value to a target over a duration.
+    pub fn to(mut self, target: T, duration: Duration, easing: Easing) -> Self {
+        let duration = duration.num_microseconds().unwrap_or(i64::MAX).max(0);
+        self.steps.push(Step {
+            target,
+            duration,
+            easing,
+        });
+        self
+    }
+
+    /// Adds a step that holds the value where it is for a duration.
+    pub fn wait(self, duration: Duration) -> Self {
+        let target = self.end();
+        self.to(target, duration, Easing::Linear)
+    }
+
+    /// Sets what happens when the end of the last step is reached.  By
+    /// default, the tween stops there.
+    pub fn with_loop_mode(mut self, mode: LoopMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets a function called with the index of each step as it finishes.
+    /// When a [`LoopMode::PingPong`] tween is playing backwards, a step
+    /// finishes when its start is reached.
+    ///
+    /// [`LoopMode::PingPong`]: ../sprite/enum.LoopMode.html#variant.PingPong
+    ///
+    pub fn on_step(mut self, f: impl FnMut(usize) + 'static) -> Self {
+        self.on_step = Some(Box::new(f));
+        self
+    }
+
+    /// Sets a function called when the tween reaches its end.  Looping
+    /// tweens call it each time they reach their end, and ping-pong tweens
+    /// each time they reach either end.
+    pub fn on_complete(mut self, f: impl FnMut() + 'static) -> Self {
+        self.on_complete = Some(Box::new(f));
+        self
+    }
+
+    /// Advances the tween by the given time, calling the step and complete
+    /// functions for the points in time passed.  When more than a whole
+    /// cycle of a looping tween passes, the functions are not called for the
+    /// cycles skipped.
+    pub fn advance(&mut self, dt: Duration) {
+        let total = self.total();
+        let old = self.elapsed;
+        let mut new = old.saturating_add(dt.num_microseconds().unwrap_or(i64::MAX).max(0));
+        if self.mode == LoopMode::Once {
+            new = new.min(total);
+        }
+        if new == old {
+            return;
+        }
+
+        // Fire the events of each cycle between the old and new times.
+        let period = if self.mode == LoopMode::PingPong {
+            total.saturating_mul(2)
+        } else {
+            total
+        };
+        let events = self.events();
+        if period > 0 {
+            let mut base = old / period * period;
+            if new - old > period {
+                base = (new - period) / period * period;
+            }
+            let from = old.max(base);
+            while base < new {
+                for &(time, event) in &events {
+                    let time = base.saturating_add(time);
+                    if from < time && time <= new {
+                        self.fire(event);
+                    }
+                }
+                base = base.saturating_add(period);
+            }
+        }
+
+        // Keep the time within a cycle so it never overflows.
+        self.elapsed = match self.mode {
+            LoopMode::Once => new,
+            _ if period > 0 => match new % period {
+                0 => period,
+                time => time,
+            },
+            _ => 0,
+        };
+    }
+
+    /// Returns the value at the current time.
+    pub fn value(&self) -> T {
+        let mut time = self.position();
+        let mut from = &self.start;
+        for step in &self.steps {
+            if time < step.duration {
+                let t = time as f32 / step.duration as f32;
+                return from.interpolate(&step.target, step.easing.apply(t));
+            }
+            time -= step.duration;
+            from = &step.target;
+        }
+        from.clone()
+    }
+
+    /// Returns true if a tween that doesn't loop has reached its end.
+    pub fn is_finished(&self) -> bool {
+        self.mode == LoopMode::Once && self.elapsed >= self.total()
+    }
+
+    /// Returns the value at the end of the last step.
+    pub fn end(&self) -> T {
+        self.steps
+            .last()
+            .map_or_else(|| self.start.clone(), |step| step.target.clone())
+    }
+
+    /// Returns how long the steps take, from the start to the end.
+    pub fn duration(&self) -> Duration {
+        Duration::microseconds(self.total())
+    }
+
+    /// Returns how far through the tween the time is, from 0.0 at the start
+    /// to 1.0 at the end.  Tweens with no duration are always at their end.
+    pub fn progress(&self) -> f32 {
+        match self.total() {
+            0 => 1.0,
+            total => self.position() as f32 / total as f32,
+        }
+    }
+
+    /// Starts the tween again from its first step.
+    pub fn reset(&mut self) {
+        self.elapsed = 0;
+    }
+
+    /// Returns how far through the steps the time is in microseconds, which
+    /// goes back down when a ping-pong tween plays backwards.
+    fn position(&self) -> i64 {
+        let total = self.total();
+        match self.mode {
+            LoopMode::Once => self.elapsed.min(total),
+            // A looping tween that has just reached its end shows it, rather
+            // than jumping back to its start.
+            LoopMode::Loop if self.elapsed == total => total,
+            LoopMode::Loop if total > 0 => self.elapsed % total,
+            LoopMode::PingPong if total > 0 => {
+                let time = self.elapsed % (total * 2);
+                if time > total {
+                    total * 2 - time
+                } else {
+                    time
+                }
+            }
+            _ => 0,
+        }
+    }
+
+    /// Returns the total duration of the steps in microseconds.
+    fn total(&self) -> i64 {
+        self.steps
+            .iter()
+            .fold(0i64, |total, step| total.saturating_add(step.duration))
+    }
+
+    /// Returns the times within a cycle that events happen at, in order.
+    fn events(&self) -> Vec<(i64, Event)> {
+        let total = self.total();
+        let mut events = Vec::new();
+        let mut time = 0i64;
+        for (index, step) in self.steps.iter().enumerate() {
+            time = time.saturating_add(step.duration);
+            events.push((time, Event::Step(index)));
+        }
+        events.push((total, Event::Complete));
+        if self.mode == LoopMode::PingPong {
+            for (index, step) in self.steps.iter().enumerate().rev() {
+                time = time.saturating_sub(step.duration);
+                events.push((total * 2 - time, Event::Step(index)));
+            }
+            events.push((total * 2, Event::Complete));
+        }
+        events
+    }
+
+    /// Calls the function for an event.
+    fn fire(&mut self, event: Event) {
+        match event {
+            Event::Step(index) => {
+                if let Some(f) = self.on_step.as_mut() {
+                    f(index);
+                }
+            }
+            Event::Complete => {
+                if let Some(f) = self.on_complete.as_mut() {
+                    f();
+                }
+            }
+        }
+    }
+}
+
+/// The curve of a ball bouncing to rest at 1.0.
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
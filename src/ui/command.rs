@@ -0,0 +1,327 @@
+//! Named commands typed by the user, such as those run from a [`Console`].
+//!
+//! A [`CommandRegistry`] holds [`Command`]s, each with a name, help text and a
+//! list of arguments.  A line of text is split into words, the first naming
+//! the command and the rest its arguments, which are checked against the
+//! command's arguments before the command's function turns them into a
+//! message.  Words can be quoted with `"` to include spaces.
+//!
+//! ```
+//! use mage_core::ui::{Command, CommandRegistry};
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Message {
+//!     Spawn(String, u32),
+//! }
+//!
+//! let mut commands = CommandRegistry::new();
+//! commands.add(
+//!     Command::new("spawn", "Spawns monsters next to the player", |args| {
+//!         Ok(Message::Spawn(args.get("kind")?, args.get_or("count", 1)?))
+//!     })
+//!     .arg("kind", "The kind of monster")
+//!     .optional_arg("count", "How many to spawn"),
+//! );
+//!
+//! let message = commands.execute("spawn \"cave troll\" 3");
+//! assert_eq!(message, Ok(Some(Message::Spawn("cave troll".to_string(), 3))));
+//! assert!(commands.execute("spawn troll many").is_err());
+//! ```
+//!
+//! [`Console`]: struct.Console.html
+//! [`CommandRegistry`]: struct.CommandRegistry.html
+//! [`Command`]: struct.Command.html
+
+use std::str::FromStr;
+
+/// Turns the arguments of a command into a message, or an error to show the
+/// user.
+type RunFn<M> = Box<dyn Fn(&Args) -> Result<M, String>>;
+
+/// How many words an argument takes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArgKind {
+    /// Exactly one word.
+    Required,
+
+    /// One word, or none if there are no more words.
+    Optional,
+
+    /// Every remaining word, joined with spaces.
+    Rest,
+}
+
+/// An argument of a command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ArgSpec {
+    name: String,
+    help: String,
+    kind: ArgKind,
+}
+
+/// A command that can be typed by the user.
+pub struct Command<M> {
+    name: String,
+    help: String,
+    args: Vec<ArgSpec>,
+    run: RunFn<M>,
+}
+
+impl<M> Command<M> {
+    /// Creates a new command with no arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The word the user types to run the command.
+    /// * `help` - A short description of what the command does.
+    /// * `run` - Creates the message produced when the command is run from
+    ///   its arguments, or returns an error to show the user.
+    ///
+    pub fn new(
+        name: impl Into<String>,
+        help: impl Into<String>,
+        run: impl Fn(&Args) -> Result<M, String> + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            help: help.into(),
+            args: Vec::new(),
+            run: Box::new(run),
+        }
+    }
+
+    /// Adds an argument that must be given.
+    pub fn arg(self, name: impl Into<String>, help: impl Into<String>) -> Self {
+        self.with_arg(name.into(), help.into(), ArgKind::Required)
+    }
+
+    /// Adds an argument that can be left out.  Optional arguments must come
+    /// after the arguments that must be given.
+    pub fn optional_arg(self, name: impl Into<String>, help: impl Into<String>) -> Self {
+        self.with_arg(name.into(), help.into(), ArgKind::Optional)
+    }
+
+    /// Adds a last argument that takes the rest of the words, joined with
+    /// spaces, such as the text of a message.
+    pub fn rest_arg(self, name: impl Into<String>, help: impl Into<String>) -> Self {
+        self.with_arg(name.into(), help.into(), ArgKind::Rest)
+    }
+
+    /// Returns the name of the command.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the description of the command.
+    pub fn help(&self) -> &str {
+        &self.help
+    }
+
+    /// Returns how the command is typed, such as `spawn <kind> [count]`.
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.clone();
+        for arg in &self.args {
+            let word = match arg.kind {
+                ArgKind::Required => format!(" <{}>", arg.name),
+                ArgKind::Optional => format!(" [{}]", arg.name),
+                ArgKind::Rest => format!(" [{}...]", arg.name),
+            };
+            usage.push_str(&word);
+        }
+        usage
+    }
+
+    /// Returns the help shown for the command by the `help` command: its
+    /// usage, description and the description of each argument.
+    pub fn help_lines(&self) -> Vec<String> {
+        let mut lines = vec![self.usage(), format!("  {}", self.help)];
+        lines.extend(
+            self.args
+                .iter()
+                .map(|arg| format!("  {} - {}", arg.name, arg.help)),
+        );
+        lines
+    }
+
+    /// Adds an argument.
+    fn with_arg(mut self, name: String, help: String, kind: ArgKind) -> Self {
+        self.args.push(ArgSpec { name, help, kind });
+        self
+    }
+
+    /// Matches words to the command's arguments and runs the command.
+    fn run(&self, words: &[String]) -> Result<M, String> {
+        let mut values = Vec::new();
+        let mut words = words.iter();
+        for arg in &self.args {
+            let value = match arg.kind {
+                ArgKind::Rest => {
+                    let rest: Vec<&str> = words.by_ref().map(String::as_str).collect();
+                    (!rest.is_empty()).then(|| rest.join(" "))
+                }
+                _ => words.next().cloned(),
+            };
+            match value {
+                Some(value) => values.push((arg.name.clone(), value)),
+                None if arg.kind == ArgKind::Required => {
+                    return Err(format!("Missing <{}>.  Usage: {}", arg.name, self.usage()));
+                }
+                None => {}
+            }
+        }
+        if words.next().is_some() {
+            return Err(format!("Too many arguments.  Usage: {}", self.usage()));
+        }
+        (self.run)(&Args { values })
+    }
+}
+
+/// The arguments given to a command.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    values: Vec<(String, String)>,
+}
+
+impl Args {
+    /// Returns the text of an argument, or `None` if it wasn't given.
+    pub fn raw(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(arg, _)| arg == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns true if an argument was given.
+    pub fn has(&self, name: &str) -> bool {
+        self.raw(name).is_some()
+    }
+
+    /// Parses an argument.
+    ///
+    /// # Returns
+    ///
+    /// The parsed value, or an error to show the user if the argument wasn't
+    /// given or couldn't be parsed.
+    ///
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<T, String> {
+        let value = self
+            .raw(name)
+            .ok_or_else(|| format!("Missing <{}>", name))?;
+        value
+            .parse()
+            .map_err(|_| format!("Invalid {}: '{}'", name, value))
+    }
+
+    /// Parses an argument, or returns a default if it wasn't given.
+    ///
+    /// # Returns
+    ///
+    /// The parsed value or the default, or an error to show the user if the
+    /// argument couldn't be parsed.
+    ///
+    pub fn get_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        if self.has(name) {
+            self.get(name)
+        } else {
+            Ok(default)
+        }
+    }
+}
+
+/// A set of commands that lines of text are run against.
+pub struct CommandRegistry<M> {
+    commands: Vec<Command<M>>,
+}
+
+impl<M> CommandRegistry<M> {
+    /// Creates a new registry with no commands.
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+
+    /// Adds a command, replacing any command with the same name.
+    pub fn add(&mut self, command: Command<M>) {
+        self.commands.retain(|other| other.name != command.name);
+        self.commands.push(command);
+    }
+
+    /// Removes a command, returning true if it was there.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.commands.len();
+        self.commands.retain(|command| command.name != name);
+        self.commands.len() != len
+    }
+
+    /// Returns a command.
+    pub fn get(&self, name: &str) -> Option<&Command<M>> {
+        self.commands.iter().find(|command| command.name == name)
+    }
+
+    /// Returns the commands, in the order they were added.
+    pub fn commands(&self) -> impl Iterator<Item = &Command<M>> + '_ {
+        self.commands.iter()
+    }
+
+    /// Returns the names of the commands starting with some text, in
+    /// alphabetical order.
+    pub fn complete(&self, prefix: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .commands
+            .iter()
+            .map(|command| command.name.as_str())
+            .filter(|name| name.starts_with(prefix))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Runs a line of text.
+    ///
+    /// # Returns
+    ///
+    /// The message produced by the command, `None` if the line is blank, or
+    /// an error to show the user if there is no such command or its
+    /// arguments are wrong.
+    ///
+    pub fn execute(&self, line: &str) -> Result<Option<M>, String> {
+        let words = split_words(line)?;
+        let Some((name, args)) = words.split_first() else {
+            return Ok(None);
+        };
+        let command = self
+            .get(name)
+            .ok_or_else(|| format!("Unknown command '{}'", name))?;
+        command.run(args).map(Some)
+    }
+}
+
+impl<M> Default for CommandRegistry<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Splits a line into words separated by whitespace.  Text within double
+/// quotes is a single word.
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quoted = false;
+    for ch in line.chars() {
+        match ch {
+            '"' => {
+                quoted = !quoted;
+                word.get_or_insert_with(String::new);
+            }
+            ch if ch.is_whitespace() && !quoted => words.extend(word.take()),
+            ch => word.get_or_insert_with(String::new).push(ch),
+        }
+    }
+    if quoted {
+        return Err("Missing closing '\"'".to_string());
+    }
+    words.extend(word);
+    Ok(words)
+}
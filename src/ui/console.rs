@@ -0,0 +1,669 @@
+//! A drop-down console showing the app's log output and running commands.
+//!
+//! The [`Console`] collects the events logged with the [`tracing`] crate
+//! through a [`LogCapture`], a tracing-subscriber layer.  It is opened and
+//! closed with a key, and while open it covers the top of the screen, where
+//! the log can be scrolled, searched and copied, and where the app's
+//! [`Command`]s can be typed.  Output from the `log` crate can be captured
+//! too by forwarding it to tracing with the `tracing-log` crate.
+//!
+//! ```no_run
+//! use mage_core::{image::Rect, ui::{Command, Console}};
+//! use tracing_subscriber::prelude::*;
+//!
+//! enum Message {
+//!     Copy(String),
+//!     SetSpeed(f32),
+//! }
+//!
+//! let console = Console::new(Rect::new(0, 0, 80, 25))
+//!     .on_copy(Message::Copy)
+//!     .with_command(
+//!         Command::new("speed", "Sets the game speed", |args| {
+//!             Ok(Message::SetSpeed(args.get("factor")?))
+//!         })
+//!         .arg("factor", "1.0 is normal speed"),
+//!     );
+//! tracing_subscriber::registry().with(console.capture()).init();
+//! ```
+//!
+//! [`Console`]: struct.Console.html
+//! [`LogCapture`]: struct.LogCapture.html
+//! [`Command`]: struct.Command.html
+//! [`tracing`]: https://docs.rs/tracing
+
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::{
+    colour::Colour,
+    image::{Char, Image, Point, Rect},
+    table::Alignment,
+};
+
+use super::{draw_text, Command, CommandRegistry, Key, KeyPress, LogView, Theme, UiInput, Widget};
+
+/// The glyph of the line along the bottom of the console, `─`.
+const EDGE_GLYPH: u8 = 0xc4;
+
+/// The most commands remembered for recalling with the up key.
+const HISTORY_SIZE: usize = 100;
+
+/// An event logged with the `tracing` crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogRecord {
+    /// The level the event was logged at.
+    pub level: Level,
+
+    /// The module path or target the event was logged from.
+    pub target: String,
+
+    /// The event's message, followed by its other fields as `name=value`.
+    pub message: String,
+}
+
+impl LogRecord {
+    /// Returns the record as a line of text, as shown in the console.
+    pub fn to_line(&self) -> String {
+        format!("{:>5} {}: {}", self.level, self.target, self.message)
+    }
+
+    /// Returns the colour the record is shown in.
+    fn ink(&self) -> Colour {
+        match self.level {
+            Level::ERROR => Colour::LightRed,
+            Level::WARN => Colour::Yellow,
+            Level::INFO => Colour::LightGray,
+            Level::DEBUG => Colour::Cyan,
+            Level::TRACE => Colour::DarkGray,
+        }
+    }
+}
+
+/// A tracing-subscriber layer that collects events for a [`Console`].
+///
+/// Get one with [`Console::capture`] and add it to the app's subscriber.  It
+/// can be cloned and shared between threads, and keeps the newest events up
+/// to the console's capacity until the console next updates.
+///
+/// [`Console`]: struct.Console.html
+/// [`Console::capture`]: struct.Console.html#method.capture
+///
+#[derive(Clone, Debug)]
+pub struct LogCapture {
+    records: Arc<Mutex<VecDeque<LogRecord>>>,
+    capacity: usize,
+}
+
+impl LogCapture {
+    /// Removes and returns every record collected so far.
+    fn take(&self) -> Vec<LogRecord> {
+        match self.records.lock() {
+            Ok(mut records) => records.drain(..).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogCapture {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        let record = LogRecord {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+        };
+        if let Ok(mut records) = self.records.lock() {
+            records.push_back(record);
+            while records.len() > self.capacity {
+                records.pop_front();
+            }
+        }
+    }
+}
+
+/// Formats the fields of an event into a single line.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl MessageVisitor {
+    /// Adds a field to the message.  The `message` field goes first, without
+    /// its name.
+    fn push(&mut self, field: &Field, value: fmt::Arguments) {
+        if field.name() == "message" {
+            self.message.insert_str(0, &value.to_string());
+        } else {
+            let _ = write!(self.message, " {}={}", field.name(), value);
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(field, format_args!("{value:?}"));
+    }
+}
+
+/// A line shown in a console.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Entry {
+    /// An event captured from tracing.
+    Log(LogRecord),
+
+    /// A command typed by the user, or its output.
+    Text(String, Colour),
+}
+
+impl Entry {
+    /// Returns the text of the line.
+    fn line(&self) -> String {
+        match self {
+            Entry::Log(record) => record.to_line(),
+            Entry::Text(text, _) => text.clone(),
+        }
+    }
+
+    /// Returns the colour of the line.
+    fn ink(&self) -> Colour {
+        match self {
+            Entry::Log(record) => record.ink(),
+            Entry::Text(_, ink) => *ink,
+        }
+    }
+}
+
+/// A Quake-style console that drops down from the top of the screen, showing
+/// the app's log and running commands typed by the user.
+///
+/// The console is opened and closed with a key, the back-quote by default,
+/// whichever widget has the focus.  While open, it captures all input:
+///
+/// * Typing enters a command, which is run with enter.  Tab completes the
+///   name of a command, and the up and down keys recall earlier commands.
+///   The `help` command lists the commands, and `clear` clears the log.
+/// * Page up, page down, home, end and the mouse wheel scroll the log.
+/// * Control+F starts a search.  Only lines containing the search text,
+///   ignoring case, are shown.  Enter finishes typing the search, and escape
+///   clears it.
+/// * Control+C copies the lines shown.
+/// * Escape clears the command, and closes the console when there's no
+///   command or search.
+pub struct Console<M> {
+    rect: Rect,
+    height: Option<u32>,
+    open: bool,
+    toggle_key: KeyPress,
+    capture: LogCapture,
+    entries: VecDeque<Entry>,
+    view: LogView,
+    search: String,
+    searching: bool,
+    commands: CommandRegistry<M>,
+    input: String,
+    history: VecDeque<String>,
+    recalled: Option<usize>,
+    on_copy: Option<Box<dyn Fn(String) -> M>>,
+}
+
+impl<M> Console<M> {
+    /// Creates a new closed console that keeps the newest 1000 records.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area the console drops down over, usually the whole
+    ///   screen.  By default, the console covers the top half of it.
+    ///
+    pub fn new(rect: Rect) -> Self {
+        let mut console = Self {
+            rect,
+            height: None,
+            open: false,
+            toggle_key: KeyPress::new(Key::Char('`')),
+            capture: LogCapture {
+                records: Arc::new(Mutex::new(VecDeque::new())),
+                capacity: 1000,
+            },
+            entries: VecDeque::new(),
+            view: LogView::new(Rect::default(), 1000),
+            search: String::new(),
+            searching: false,
+            commands: CommandRegistry::new(),
+            input: String::new(),
+            history: VecDeque::new(),
+            recalled: None,
+            on_copy: None,
+        };
+        console.refresh();
+        console
+    }
+
+    /// Sets the number of lines kept before the oldest are dropped.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capture.capacity = capacity;
+        self.refresh();
+        self
+    }
+
+    /// Sets the number of rows the console covers when open, including its
+    /// bottom edge.
+    pub fn with_height(mut self, height: u32) -> Self {
+        self.height = Some(height);
+        self.refresh();
+        self
+    }
+
+    /// Adds a command that can be typed into the console.
+    pub fn with_command(mut self, command: Command<M>) -> Self {
+        self.commands.add(command);
+        self
+    }
+
+    /// Sets the key that opens and closes the console.
+    pub fn with_toggle_key(mut self, key: impl Into<KeyPress>) -> Self {
+        self.toggle_key = key.into();
+        self
+    }
+
+    /// Sets the function that creates the message produced when control+C is
+    /// pressed.  It is passed the text of the lines shown, so that the app
+    /// can put it on the clipboard.
+    pub fn on_copy(mut self, f: impl Fn(String) -> M + 'static) -> Self {
+        self.on_copy = Some(Box::new(f));
+        self
+    }
+
+    /// Returns the layer to add to the app's tracing subscriber, so that the
+    /// console shows the events it logs.
+    pub fn capture(&self) -> LogCapture {
+        self.capture.clone()
+    }
+
+    /// Returns true if the console is open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Opens the console.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Closes the console.
+    pub fn close(&mut self) {
+        self.open = false;
+        self.searching = false;
+    }
+
+    /// Opens the console if it is closed, or closes it if it is open.
+    pub fn toggle(&mut self) {
+        if self.open {
+            self.close();
+        } else {
+            self.open();
+        }
+    }
+
+    /// Returns the commands that can be typed into the console.
+    pub fn commands(&self) -> &CommandRegistry<M> {
+        &self.commands
+    }
+
+    /// Returns the commands that can be typed into the console, so that
+    /// commands can be added and removed.
+    pub fn commands_mut(&mut self) -> &mut CommandRegistry<M> {
+        &mut self.commands
+    }
+
+    /// Adds a line of text to the log, such as the reply to a command.
+    pub fn print(&mut self, text: impl Into<String>) {
+        self.push(Entry::Text(text.into(), Colour::White));
+    }
+
+    /// Adds a line of text to the log in the given colour.
+    pub fn print_coloured(&mut self, text: impl Into<String>, ink: Colour) {
+        self.push(Entry::Text(text.into(), ink));
+    }
+
+    /// Runs a command as if the user had typed it, adding it and any error to
+    /// the log.
+    ///
+    /// # Returns
+    ///
+    /// The message produced by the command, or `None` if the line was blank,
+    /// was a built-in command or failed.
+    ///
+    pub fn execute(&mut self, line: &str) -> Option<M> {
+        if line.trim().is_empty() {
+            return None;
+        }
+        self.print_coloured(format!("> {}", line), Colour::LightGreen);
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("clear"), None) => {
+                self.clear();
+                return None;
+            }
+            (Some("help"), name) if words.next().is_none() => {
+                self.help(name);
+                return None;
+            }
+            _ => {}
+        }
+        match self.commands.execute(line) {
+            Ok(message) => message,
+            Err(error) => {
+                self.print_coloured(error, Colour::LightRed);
+                None
+            }
+        }
+    }
+
+    /// Returns the records captured from tracing that are kept, oldest first.
+    pub fn records(&self) -> impl Iterator<Item = &LogRecord> + '_ {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Log(record) => Some(record),
+            Entry::Text(..) => None,
+        })
+    }
+
+    /// Removes every line from the log.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.view.clear();
+    }
+
+    /// Returns the search text.  Only records containing it are shown.
+    pub fn search(&self) -> &str {
+        &self.search
+    }
+
+    /// Changes the search text.  An empty search shows every record.
+    pub fn set_search(&mut self, search: impl Into<String>) {
+        self.search = search.into();
+        self.refresh();
+    }
+
+    /// Returns the text of the lines shown, one per line.
+    pub fn text(&self) -> String {
+        self.view.lines().collect::<Vec<_>>().join("\n")
+    }
+
+    /// Returns the number of rows the console covers when open.
+    fn open_height(&self) -> u32 {
+        self.height
+            .unwrap_or(self.rect.height / 2)
+            .min(self.rect.height)
+    }
+
+    /// Returns the area the console covers when open.
+    fn open_rect(&self) -> Rect {
+        Rect::new(
+            self.rect.x,
+            self.rect.y,
+            self.rect.width,
+            self.open_height(),
+        )
+    }
+
+    /// Returns true if a line is shown with the current search.
+    fn matches(&self, entry: &Entry) -> bool {
+        self.search.is_empty()
+            || entry
+                .line()
+                .to_lowercase()
+                .contains(&self.search.to_lowercase())
+    }
+
+    /// Adds a line, dropping the oldest line if the console is full.
+    fn push(&mut self, entry: Entry) {
+        if self.matches(&entry) {
+            self.view.push_coloured(entry.line(), entry.ink());
+        }
+        self.entries.push_back(entry);
+        if self.entries.len() > self.capture.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Rebuilds the view after the search, size or capacity has changed.
+    fn refresh(&mut self) {
+        while self.entries.len() > self.capture.capacity {
+            self.entries.pop_front();
+        }
+        let open = self.open_rect();
+        let rows = Rect::new(open.x, open.y, open.width, open.height.saturating_sub(2));
+        let mut view = LogView::new(rows, self.capture.capacity);
+        for entry in self.entries.iter().filter(|entry| self.matches(entry)) {
+            view.push_coloured(entry.line(), entry.ink());
+        }
+        self.view = view;
+    }
+
+    /// Prints the list of commands, or the help for one command.
+    fn help(&mut self, name: Option<&str>) {
+        let lines = match name {
+            Some(name) => match self.commands.get(name) {
+                Some(command) => command.help_lines(),
+                None => {
+                    self.print_coloured(format!("Unknown command '{}'", name), Colour::LightRed);
+                    return;
+                }
+            },
+            None => {
+                let mut lines = vec![
+                    "clear - Clears the log".to_string(),
+                    "help [command] - Lists the commands, or describes one".to_string(),
+                ];
+                lines.extend(
+                    self.commands
+                        .commands()
+                        .map(|command| format!("{} - {}", command.usage(), command.help())),
+                );
+                lines
+            }
+        };
+        for line in lines {
+            self.print(line);
+        }
+    }
+
+    /// Completes the name of the command being typed.  If several commands
+    /// match, they are listed and the name is completed as far as they agree.
+    fn complete(&mut self) {
+        if self.input.contains(char::is_whitespace) {
+            return;
+        }
+        let names: Vec<String> = self
+            .commands
+            .complete(&self.input)
+            .into_iter()
+            .map(String::from)
+            .collect();
+        match names.as_slice() {
+            [] => {}
+            [name] => self.input = format!("{} ", name),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.len(), |len, name| {
+                    first
+                        .chars()
+                        .zip(name.chars())
+                        .take_while(|(a, b)| a == b)
+                        .map(|(a, _)| a.len_utf8())
+                        .sum::<usize>()
+                        .min(len)
+                });
+                self.input = first[..common].to_string();
+                self.print(names.join("  "));
+            }
+        }
+    }
+
+    /// Replaces the command being typed with an earlier command.  `back` is
+    /// true to go further back in the history.
+    fn recall(&mut self, back: bool) {
+        let len = self.history.len();
+        self.recalled = match (self.recalled, back) {
+            (None, true) if len > 0 => Some(len - 1),
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < len => Some(i + 1),
+            _ => None,
+        };
+        self.input = self
+            .recalled
+            .map(|i| self.history[i].clone())
+            .unwrap_or_default();
+    }
+
+    /// Runs the command being typed and remembers it in the history.
+    fn submit(&mut self, messages: &mut Vec<M>) {
+        let line = std::mem::take(&mut self.input);
+        self.recalled = None;
+        if line.trim().is_empty() {
+            return;
+        }
+        if self.history.back() != Some(&line) {
+            self.history.push_back(line.clone());
+            if self.history.len() > HISTORY_SIZE {
+                self.history.pop_front();
+            }
+        }
+        self.view.scroll_to_bottom();
+        messages.extend(self.execute(&line));
+    }
+
+    /// Handles a key while the console is open.  Returns false if the key
+    /// should be passed on to the view.
+    fn handle_key(&mut self, key: &KeyPress, messages: &mut Vec<M>) -> bool {
+        match key.key {
+            _ if *key == self.toggle_key && !self.searching => self.close(),
+            Key::Char('f') if key.ctrl => self.searching = true,
+            Key::Char('c') if key.ctrl => {
+                messages.extend(self.on_copy.as_ref().map(|f| f(self.text())));
+            }
+            Key::Escape if self.searching || !self.search.is_empty() => {
+                self.searching = false;
+                self.set_search("");
+            }
+            Key::Escape if !self.input.is_empty() => {
+                self.input.clear();
+                self.recalled = None;
+            }
+            Key::Escape => self.close(),
+            Key::Enter if self.searching => self.searching = false,
+            Key::Enter => self.submit(messages),
+            Key::Backspace if self.searching => {
+                self.search.pop();
+                self.refresh();
+            }
+            Key::Backspace => {
+                self.input.pop();
+            }
+            Key::Char(ch) if self.searching && !key.ctrl && !key.alt => {
+                self.search.push(ch);
+                self.refresh();
+            }
+            Key::Char(ch) if !key.ctrl && !key.alt => self.input.push(ch),
+            Key::Tab if !self.searching => self.complete(),
+            Key::Up if !self.searching => self.recall(true),
+            Key::Down if !self.searching => self.recall(false),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl<M: 'static> Widget<M> for Console<M> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.refresh();
+    }
+
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>) {
+        for record in self.capture.take() {
+            self.push(Entry::Log(record));
+        }
+
+        if !self.open {
+            if input.keys.contains(&self.toggle_key) {
+                self.open();
+            }
+            return;
+        }
+
+        let mut keys = Vec::new();
+        for key in &input.keys {
+            if !self.open {
+                break;
+            }
+            if !self.handle_key(key, messages) {
+                keys.push(*key);
+            }
+        }
+        if self.open {
+            let input = UiInput {
+                keys,
+                ..input.clone()
+            };
+            self.view.handle_input(&input, messages);
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        if !self.open {
+            return;
+        }
+        let rect = self.open_rect();
+        if rect.height == 0 {
+            return;
+        }
+        image.draw_filled_rect(rect, Char::new(b' ', theme.ink, theme.paper));
+        Widget::<M>::draw(&self.view, image, theme);
+
+        let bottom = rect.y + rect.height as i32 - 1;
+        let edge = Rect::new(rect.x, bottom, rect.width, 1);
+        image.draw_filled_rect(edge, Char::new(EDGE_GLYPH, theme.ink, theme.paper));
+        if rect.height >= 2 {
+            let p = Point::new(rect.x, bottom - 1);
+            let (text, ink, paper) = if self.searching {
+                let text = format!("Search: {}_", self.search);
+                (text, theme.active_ink, theme.active_paper)
+            } else if !self.search.is_empty() {
+                let text = format!("Search: {} (Esc to clear)", self.search);
+                (text, theme.accent, theme.paper)
+            } else {
+                let text = format!("> {}_", self.input);
+                (text, theme.ink, theme.paper)
+            };
+            draw_text(image, p, rect.width, &text, Alignment::Left, ink, paper);
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.open
+    }
+
+    fn is_shortcut(&self, key: &KeyPress) -> bool {
+        *key == self.toggle_key
+    }
+}
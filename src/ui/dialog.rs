@@ -0,0 +1,405 @@
+//! Modal message boxes, confirmations and text prompts.
+//!
+//! Dialogs are shown one at a time by a [`DialogQueue`].  While a dialog is
+//! shown, the rest of the UI is dimmed and receives no input, and when it is
+//! closed the dialog's result is returned to the app as a message.
+//!
+//! [`DialogQueue`]: struct.DialogQueue.html
+
+use std::collections::VecDeque;
+
+use crate::{
+    image::{Char, Image, Point, Rect},
+    table::Alignment,
+};
+
+use super::{draw_text, wrap_text, ClickState, Key, Theme, UiInput, Widget};
+
+/// The widest a line of a dialog's text can be before it is wrapped.
+const MAX_TEXT_WIDTH: u32 = 50;
+
+/// The width of the text field of a prompt.
+const PROMPT_WIDTH: u32 = 30;
+
+/// The number of chars between buttons.
+const BUTTON_GAP: u32 = 2;
+
+/// A modal dialog with a message, a row of buttons and optionally a text
+/// field, added to a [`DialogQueue`].
+///
+/// [`DialogQueue`]: struct.DialogQueue.html
+///
+pub struct Dialog<M> {
+    title: String,
+    text: String,
+    buttons: Vec<String>,
+    input: Option<String>,
+    selected: usize,
+    clicks: Vec<ClickState>,
+
+    on_result: ResultFn<M>,
+}
+
+/// Creates the result message of a dialog from the index of the chosen
+/// button, or `None` if the dialog was cancelled, and the text of the text
+/// field.
+type ResultFn<M> = Box<dyn Fn(Option<usize>, &str) -> M>;
+
+impl<M> Dialog<M> {
+    /// Creates a message box with an OK button.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title shown on the border of the dialog.
+    /// * `text` - The message, which is wrapped to fit.
+    /// * `on_close` - Creates the message produced when the dialog is closed,
+    ///   either with the button or by pressing escape.
+    ///
+    pub fn message(
+        title: impl Into<String>,
+        text: impl Into<String>,
+        on_close: impl Fn() -> M + 'static,
+    ) -> Self {
+        Self::new(title, text, &["OK"], None, move |_, _| on_close())
+    }
+
+    /// Creates a dialog asking a question with Yes and No buttons.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title shown on the border of the dialog.
+    /// * `text` - The question, which is wrapped to fit.
+    /// * `on_result` - Creates the message produced when the dialog is closed.
+    ///   It is passed true if Yes was chosen, and false if No was chosen or
+    ///   escape was pressed.
+    ///
+    pub fn confirm(
+        title: impl Into<String>,
+        text: impl Into<String>,
+        on_result: impl Fn(bool) -> M + 'static,
+    ) -> Self {
+        Self::new(title, text, &["Yes", "No"], None, move |button, _| {
+            on_result(button == Some(0))
+        })
+    }
+
+    /// Creates a dialog asking for a line of text with OK and Cancel buttons.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title shown on the border of the dialog.
+    /// * `text` - The question, which is wrapped to fit.
+    /// * `initial` - The text initially in the text field.
+    /// * `on_result` - Creates the message produced when the dialog is closed.
+    ///   It is passed the text if OK was chosen, and `None` if Cancel was
+    ///   chosen or escape was pressed.
+    ///
+    pub fn prompt(
+        title: impl Into<String>,
+        text: impl Into<String>,
+        initial: impl Into<String>,
+        on_result: impl Fn(Option<String>) -> M + 'static,
+    ) -> Self {
+        let input = Some(initial.into());
+        Self::new(
+            title,
+            text,
+            &["OK", "Cancel"],
+            input,
+            move |button, text| on_result((button == Some(0)).then(|| text.to_string())),
+        )
+    }
+
+    /// Creates a dialog with any buttons.
+    fn new(
+        title: impl Into<String>,
+        text: impl Into<String>,
+        buttons: &[&str],
+        input: Option<String>,
+        on_result: impl Fn(Option<usize>, &str) -> M + 'static,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            text: text.into(),
+            buttons: buttons.iter().map(|button| button.to_string()).collect(),
+            input,
+            selected: 0,
+            clicks: vec![ClickState::default(); buttons.len()],
+            on_result: Box::new(on_result),
+        }
+    }
+
+    /// Returns the lines of the message wrapped to fit within a width.
+    fn lines(&self, max_width: u32) -> Vec<String> {
+        wrap_text(&self.text, MAX_TEXT_WIDTH.min(max_width) as usize)
+    }
+
+    /// Returns the total width of the row of buttons.
+    fn buttons_width(&self) -> u32 {
+        let gaps = self.buttons.len().saturating_sub(1) as u32 * BUTTON_GAP;
+        self.buttons.iter().map(|b| button_width(b)).sum::<u32>() + gaps
+    }
+
+    /// Returns the area of the dialog centred within an area.
+    fn rect(&self, area: Rect) -> Rect {
+        let lines = self.lines(area.width.saturating_sub(4));
+        let text_width = lines.iter().map(|line| line.chars().count() as u32).max();
+        let input_width = self.input.as_ref().map(|_| PROMPT_WIDTH);
+        let inner = [
+            text_width,
+            input_width,
+            Some(self.buttons_width()),
+            Some(self.title.chars().count() as u32 + 2),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(0);
+        let input_height = if self.input.is_some() { 2 } else { 0 };
+
+        let width = (inner + 4).min(area.width);
+        let height = (lines.len() as u32 + input_height + 6).min(area.height);
+        let x = area.x + (area.width - width) as i32 / 2;
+        let y = area.y + (area.height - height) as i32 / 2;
+        Rect::new(x, y, width, height)
+    }
+
+    /// Returns the area of each button when the dialog is drawn within a
+    /// rectangle.
+    fn button_rects(&self, rect: Rect) -> Vec<Rect> {
+        let y = rect.y + rect.height as i32 - 3;
+        let mut x = rect.x + (rect.width as i32 - self.buttons_width() as i32).max(0) / 2;
+        self.buttons
+            .iter()
+            .map(|button| {
+                let button = Rect::new(x, y, button_width(button), 1);
+                x += (button.width + BUTTON_GAP) as i32;
+                button
+            })
+            .collect()
+    }
+
+    /// Updates the dialog from the input for this tick.
+    ///
+    /// # Returns
+    ///
+    /// The result message if the dialog was closed.
+    ///
+    fn handle_input(&mut self, input: &UiInput, rect: Rect) -> Option<M> {
+        for (i, button) in self.button_rects(rect).into_iter().enumerate() {
+            if self.clicks[i].update(button, &input.mouse) {
+                return Some(self.close(Some(i)));
+            }
+        }
+
+        let count = self.buttons.len().max(1);
+        for key in &input.keys {
+            match key.key {
+                Key::Left => self.selected = (self.selected + count - 1) % count,
+                Key::Right | Key::Tab => self.selected = (self.selected + 1) % count,
+                Key::Enter => return Some(self.close(Some(self.selected))),
+                Key::Char(' ') if self.input.is_none() => {
+                    return Some(self.close(Some(self.selected)))
+                }
+                Key::Escape => return Some(self.close(None)),
+                Key::Backspace => {
+                    if let Some(text) = &mut self.input {
+                        text.pop();
+                    }
+                }
+                Key::Char(ch) if !key.ctrl && !key.alt => {
+                    if let Some(text) = &mut self.input {
+                        text.push(ch);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Creates the result message for the chosen button.
+    fn close(&self, button: Option<usize>) -> M {
+        (self.on_result)(button, self.input.as_deref().unwrap_or(""))
+    }
+
+    /// Draws the dialog within a rectangle.
+    fn draw(&self, image: &mut Image, rect: Rect, theme: &Theme) {
+        image.draw_filled_rect(rect, Char::new(b' ', theme.ink, theme.paper));
+        image.draw_box(rect, theme.active_border, theme.ink, theme.paper);
+        if !self.title.is_empty() {
+            let title = format!(" {} ", self.title);
+            let width = rect.width.saturating_sub(4);
+            let title_width = (title.chars().count() as u32).min(width);
+            let p = Point::new(rect.x + 2 + (width - title_width) as i32 / 2, rect.y);
+            draw_text(
+                image,
+                p,
+                title_width,
+                &title,
+                Alignment::Left,
+                theme.accent,
+                theme.paper,
+            );
+        }
+
+        let inner = rect.width.saturating_sub(4);
+        let lines = self.lines(inner);
+        let mut y = rect.y + 2;
+        for line in &lines {
+            let p = Point::new(rect.x + 2, y);
+            draw_text(
+                image,
+                p,
+                inner,
+                line,
+                Alignment::Left,
+                theme.ink,
+                theme.paper,
+            );
+            y += 1;
+        }
+
+        if let Some(text) = &self.input {
+            // Show the end of the text if it is too long for the field, with a
+            // cursor after it.
+            let field = format!("{}_", text);
+            let skip = (field.chars().count() as u32).saturating_sub(inner);
+            let field: String = field.chars().skip(skip as usize).collect();
+            let p = Point::new(rect.x + 2, y + 1);
+            let (ink, paper) = (theme.active_ink, theme.active_paper);
+            draw_text(image, p, inner, &field, Alignment::Left, ink, paper);
+        }
+
+        for (i, button) in self.button_rects(rect).into_iter().enumerate() {
+            let hovered = self.clicks[i].hovered;
+            let active = i == self.selected || (hovered && self.clicks[i].pressed);
+            let (ink, paper) = theme.colours(hovered, active, false);
+            let text = format!("< {} >", self.buttons[i]);
+            draw_text(
+                image,
+                Point::new(button.x, button.y),
+                button.width,
+                &text,
+                Alignment::Left,
+                ink,
+                paper,
+            );
+        }
+    }
+}
+
+/// Returns the width of a button drawn as `< Text >`.
+fn button_width(text: &str) -> u32 {
+    text.chars().count() as u32 + 4
+}
+
+/// Shows dialogs one at a time, in the order they were added, centred within
+/// an area.
+///
+/// While a dialog is shown, the queue captures all input and the area behind
+/// the dialog is dimmed.
+pub struct DialogQueue<M> {
+    rect: Rect,
+    dialogs: VecDeque<Dialog<M>>,
+    dim: f32,
+}
+
+impl<M> DialogQueue<M> {
+    /// Creates a new empty dialog queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area dimmed while a dialog is shown, usually the whole
+    ///   screen.  Dialogs are centred within it.
+    ///
+    pub fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            dialogs: VecDeque::new(),
+            dim: 0.5,
+        }
+    }
+
+    /// Sets how much the area behind a dialog is darkened, from 0.0 (not at
+    /// all) to 1.0 (black).
+    pub fn with_dim(mut self, dim: f32) -> Self {
+        self.dim = dim;
+        self
+    }
+
+    /// Adds a dialog to the end of the queue.  It is shown once every dialog
+    /// before it has closed.
+    pub fn push(&mut self, dialog: Dialog<M>) {
+        self.dialogs.push_back(dialog);
+    }
+
+    /// Returns true if a dialog is shown.
+    pub fn is_open(&self) -> bool {
+        !self.dialogs.is_empty()
+    }
+
+    /// Returns the number of dialogs in the queue, including the one shown.
+    pub fn len(&self) -> usize {
+        self.dialogs.len()
+    }
+
+    /// Returns true if there are no dialogs in the queue.
+    pub fn is_empty(&self) -> bool {
+        self.dialogs.is_empty()
+    }
+
+    /// Removes every dialog without producing any messages.
+    pub fn clear(&mut self) {
+        self.dialogs.clear();
+    }
+}
+
+impl<M: 'static> Widget<M> for DialogQueue<M> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>) {
+        let Some(dialog) = self.dialogs.front_mut() else {
+            return;
+        };
+        let rect = dialog.rect(self.rect);
+        if let Some(message) = dialog.handle_input(input, rect) {
+            messages.push(message);
+            self.dialogs.pop_front();
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        let Some(dialog) = self.dialogs.front() else {
+            return;
+        };
+        let dim = self.dim;
+        image.map_cells(self.rect, |_, mut cell| {
+            cell.ink = cell.ink.darken(dim);
+            cell.paper = cell.paper.darken(dim);
+            cell
+        });
+        dialog.draw(image, dialog.rect(self.rect), theme);
+    }
+
+    fn captures_input(&self) -> bool {
+        self.is_open()
+    }
+}
+
+impl<M> Default for DialogQueue<M> {
+    /// Creates an empty dialog queue with an empty area.  Set the area with
+    /// [`Widget::set_rect`] before adding dialogs.
+    ///
+    /// [`Widget::set_rect`]: trait.Widget.html#tymethod.set_rect
+    ///
+    fn default() -> Self {
+        Self::new(Rect::default())
+    }
+}
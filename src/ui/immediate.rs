@@ -0,0 +1,530 @@
+//! Controls that are drawn and checked for input in a single call.
+//!
+//! Rather than keeping widgets in a [`Ui`], an [`ImmediateUi`] draws each
+//! control when a function such as [`Frame::button`] is called, and returns
+//! what the user did to it at the same time.  Controls exist only for as long
+//! as the code calling them runs, so there are no messages or IDs to keep
+//! track of:
+//!
+//! ```
+//! use mage_core::{image::{Image, Rect}, ui::{ImmediateUi, UiInput}};
+//!
+//! let mut ui = ImmediateUi::new();
+//! let (mut sound, mut volume) = (true, 0.5);
+//!
+//! // Every tick:
+//! ui.add_input(&UiInput::default());
+//!
+//! // Every frame:
+//! let mut image = Image::new(40, 10);
+//! let mut frame = ui.frame(&mut image, Rect::new(1, 1, 30, 8));
+//! frame.label("Options");
+//! frame.checkbox("Sound", &mut sound);
+//! frame.slider(&mut volume, 0.0, 1.0);
+//! if frame.button("OK") {
+//!     // Close the options.
+//! }
+//! ```
+//!
+//! The input of every tick is added with [`ImmediateUi::add_input`] and used
+//! by the next frame, so clicks and key presses are seen once however many
+//! ticks and frames there are.  Tab and shift+tab move the focus between
+//! controls, and the focused control is used with the keyboard as in a
+//! [`Ui`].
+//!
+//! [`Ui`]: struct.Ui.html
+//! [`ImmediateUi`]: struct.ImmediateUi.html
+//! [`ImmediateUi::add_input`]: struct.ImmediateUi.html#method.add_input
+//! [`Frame::button`]: struct.Frame.html#method.button
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use chrono::Duration;
+
+use crate::{
+    image::{Char, Image, Point, Rect},
+    table::Alignment,
+};
+
+use super::{
+    contains, draw_text, is_activate_key,
+    widgets::{SELECTED_GLYPH, THUMB_GLYPH, TICK_GLYPH, TRACK_GLYPH},
+    Key, KeyPress, Theme, UiInput,
+};
+
+/// The state kept between the frames of immediate mode controls.
+pub struct ImmediateUi {
+    /// The colours, borders and spacing used to draw the controls.
+    theme: Theme,
+
+    /// The input of the ticks since the last frame.
+    input: UiInput,
+
+    /// The control the left mouse button was pressed on, while it is held.
+    active: Option<u64>,
+
+    /// The control with the keyboard focus.
+    focus: Option<u64>,
+
+    /// The controls in the order they were drawn in the last frame.
+    order: Vec<u64>,
+}
+
+impl ImmediateUi {
+    /// Creates a new immediate mode UI with the default theme.
+    pub fn new() -> Self {
+        Self::with_theme(Theme::default())
+    }
+
+    /// Creates a new immediate mode UI with the given theme.
+    pub fn with_theme(theme: Theme) -> Self {
+        Self {
+            theme,
+            input: UiInput::default(),
+            active: None,
+            focus: None,
+            order: Vec::new(),
+        }
+    }
+
+    /// Returns the theme used to draw the controls.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Changes the theme used to draw the controls.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Adds the input of a tick, to be used by the next frame.  Call this
+    /// every tick.
+    pub fn add_input(&mut self, input: &UiInput) {
+        let (old, new) = (&mut self.input.mouse, &input.mouse);
+        for (old, new) in [
+            (&mut old.left, &new.left),
+            (&mut old.right, &new.right),
+            (&mut old.middle, &new.middle),
+        ] {
+            old.down = new.down;
+            old.pressed |= new.pressed;
+            old.released |= new.released;
+        }
+        old.position = new.position;
+        old.wheel += new.wheel;
+        self.input.dt += input.dt;
+        self.input.keys.extend_from_slice(&input.keys);
+        self.input.width = input.width;
+        self.input.height = input.height;
+    }
+
+    /// Starts drawing controls on to an image.  Controls are placed one per
+    /// row from the top-left of an area, and are drawn as they are added to
+    /// the frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image to draw the controls on.
+    /// * `rect` - The area the controls are placed in.  Controls below it are
+    ///   not drawn.
+    ///
+    pub fn frame<'a>(&'a mut self, image: &'a mut Image, rect: Rect) -> Frame<'a> {
+        // Tab and shift+tab move the focus through the controls of the last
+        // frame, and the other keys go to the focused control.
+        let mut keys = Vec::new();
+        for key in std::mem::take(&mut self.input.keys) {
+            if key.key == Key::Tab && !key.ctrl && !key.alt {
+                self.focus_step(!key.shift);
+            } else {
+                keys.push(key);
+            }
+        }
+        if self.focus.is_some_and(|id| !self.order.contains(&id)) {
+            self.focus = None;
+        }
+
+        Frame {
+            ui: self,
+            image,
+            rect,
+            cursor: Point::new(rect.x, rect.y),
+            last: None,
+            same_line: false,
+            keys,
+            order: Vec::new(),
+        }
+    }
+
+    /// Moves the focus to the next or previous control.
+    fn focus_step(&mut self, forwards: bool) {
+        let len = self.order.len();
+        if len == 0 {
+            return;
+        }
+        let index = self
+            .focus
+            .and_then(|id| self.order.iter().position(|&other| other == id));
+        let next = match (index, forwards) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(i), true) => (i + 1) % len,
+            (Some(i), false) => (i + len - 1) % len,
+        };
+        self.focus = Some(self.order[next]);
+    }
+}
+
+impl Default for ImmediateUi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What the user did to a control in a frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Response {
+    /// True if the mouse is over the control.
+    hovered: bool,
+
+    /// True if the left mouse button was pressed on the control and is held.
+    active: bool,
+
+    /// True if the control has the keyboard focus.
+    focused: bool,
+
+    /// The number of times the control was clicked, or pressed with enter or
+    /// space while it has the focus.
+    clicks: usize,
+}
+
+/// The controls drawn on to an image in one frame of an [`ImmediateUi`].
+///
+/// The input used by the frame is cleared when the frame is dropped.
+///
+/// [`ImmediateUi`]: struct.ImmediateUi.html
+///
+pub struct Frame<'a> {
+    ui: &'a mut ImmediateUi,
+    image: &'a mut Image,
+
+    /// The area the controls are placed in.
+    rect: Rect,
+
+    /// Where the next control goes.
+    cursor: Point,
+
+    /// The area of the last control.
+    last: Option<Rect>,
+
+    /// True if the next control goes to the right of the last one.
+    same_line: bool,
+
+    /// The keys for the focused control.
+    keys: Vec<KeyPress>,
+
+    /// The IDs of the controls drawn so far.
+    order: Vec<u64>,
+}
+
+impl Frame<'_> {
+    /// Returns the theme used to draw the controls.
+    pub fn theme(&self) -> &Theme {
+        &self.ui.theme
+    }
+
+    /// Puts the next control to the right of the last one, on the same row,
+    /// rather than below it.
+    pub fn same_line(&mut self) {
+        self.same_line = true;
+    }
+
+    /// Leaves a blank row.
+    pub fn space(&mut self) {
+        self.place(0);
+    }
+
+    /// Draws a line of text.
+    pub fn label(&mut self, text: &str) {
+        let rect = self.place(text.chars().count() as u32);
+        let theme = self.ui.theme;
+        if self.visible(rect) {
+            let p = Point::new(rect.x, rect.y);
+            draw_text(
+                self.image,
+                p,
+                rect.width,
+                text,
+                Alignment::Left,
+                theme.ink,
+                theme.paper,
+            );
+        }
+    }
+
+    /// Draws a horizontal line across the area.
+    pub fn separator(&mut self) {
+        let rect = self.place(u32::MAX);
+        let theme = self.ui.theme;
+        if self.visible(rect) {
+            let ch = Char::new(TRACK_GLYPH, theme.ink, theme.paper);
+            self.image.draw_filled_rect(rect, ch);
+        }
+    }
+
+    /// Draws a button, with a column of space either side of its text.
+    ///
+    /// # Returns
+    ///
+    /// True if the button was clicked, or pressed with enter or space while
+    /// it has the focus.
+    ///
+    pub fn button(&mut self, text: &str) -> bool {
+        let rect = self.place(text.chars().count() as u32 + 2);
+        let response = self.interact(text, rect);
+        let theme = self.ui.theme;
+        let (ink, paper) = theme.colours(response.hovered, response.active, response.focused);
+        if self.visible(rect) {
+            let p = Point::new(rect.x, rect.y);
+            draw_text(
+                self.image,
+                p,
+                rect.width,
+                text,
+                Alignment::Centre,
+                ink,
+                paper,
+            );
+        }
+        response.clicks > 0
+    }
+
+    /// Draws a checkbox, drawn as `[√] Text`, that is ticked and unticked by
+    /// clicking it.
+    ///
+    /// # Returns
+    ///
+    /// True if the checkbox was changed.
+    ///
+    pub fn checkbox(&mut self, text: &str, checked: &mut bool) -> bool {
+        let line = format!("[ ] {}", text);
+        let rect = self.place(line.chars().count() as u32);
+        let response = self.interact(text, rect);
+        if response.clicks % 2 == 1 {
+            *checked = !*checked;
+        }
+
+        let theme = self.ui.theme;
+        let (ink, paper) = theme.colours(response.hovered, response.active, response.focused);
+        if self.visible(rect) {
+            let p = Point::new(rect.x, rect.y);
+            draw_text(
+                self.image,
+                p,
+                rect.width,
+                &line,
+                Alignment::Left,
+                ink,
+                paper,
+            );
+            if *checked && rect.width > 1 {
+                let mark = Char::new(TICK_GLYPH, theme.accent, paper);
+                self.image.draw_char(Point::new(p.x + 1, p.y), mark);
+            }
+        }
+        response.clicks % 2 == 1
+    }
+
+    /// Draws a radio option, drawn as `(•) Text`.  A set of options is made by
+    /// drawing one for each value:
+    ///
+    /// ```
+    /// # use mage_core::{image::{Image, Rect}, ui::ImmediateUi};
+    /// # let mut ui = ImmediateUi::new();
+    /// # let mut image = Image::new(20, 5);
+    /// # let mut frame = ui.frame(&mut image, Rect::new(0, 0, 20, 5));
+    /// let mut difficulty = 1;
+    /// for (value, name) in ["Easy", "Normal", "Hard"].into_iter().enumerate() {
+    ///     if frame.radio(name, difficulty == value) {
+    ///         difficulty = value;
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// True if the option was clicked.
+    ///
+    pub fn radio(&mut self, text: &str, selected: bool) -> bool {
+        let line = format!("( ) {}", text);
+        let rect = self.place(line.chars().count() as u32);
+        let response = self.interact(text, rect);
+
+        let theme = self.ui.theme;
+        let (ink, paper) = theme.colours(response.hovered, response.active, response.focused);
+        if self.visible(rect) {
+            let p = Point::new(rect.x, rect.y);
+            draw_text(
+                self.image,
+                p,
+                rect.width,
+                &line,
+                Alignment::Left,
+                ink,
+                paper,
+            );
+            if selected && rect.width > 1 {
+                let mark = Char::new(SELECTED_GLYPH, theme.accent, paper);
+                self.image.draw_char(Point::new(p.x + 1, p.y), mark);
+            }
+        }
+        response.clicks > 0
+    }
+
+    /// Draws a slider across the rest of the row, which sets a value between
+    /// a minimum and maximum by dragging or with the mouse wheel.  While it
+    /// has the focus, the left and right keys move it by a twentieth of its
+    /// range, and home and end move it to either end.
+    ///
+    /// # Returns
+    ///
+    /// True if the value was changed.
+    ///
+    pub fn slider(&mut self, value: &mut f32, min: f32, max: f32) -> bool {
+        let rect = self.place(u32::MAX);
+        // Sliders have no text, so they are told apart by their position.
+        let name = format!("slider {} {}", rect.x, rect.y);
+        let response = self.interact(&name, rect);
+        let old_value = *value;
+
+        let mouse = self.ui.input.mouse;
+        let step = (max - min) / 20.0;
+        if let (true, Some(p)) = (response.active, mouse.position) {
+            let last = rect.width.saturating_sub(1).max(1) as f32;
+            let t = ((p.x - rect.x) as f32 / last).clamp(0.0, 1.0);
+            *value = min + t * (max - min);
+        } else if response.hovered && mouse.wheel != 0 {
+            *value += mouse.wheel as f32 * step;
+        }
+        if response.focused {
+            for key in &self.keys {
+                match key.key {
+                    Key::Left => *value -= step,
+                    Key::Right => *value += step,
+                    Key::Home => *value = min,
+                    Key::End => *value = max,
+                    _ => {}
+                }
+            }
+        }
+        *value = value.clamp(min.min(max), max.max(min));
+
+        let theme = self.ui.theme;
+        let (ink, paper) = theme.colours(response.hovered, response.active, response.focused);
+        if self.visible(rect) && rect.width > 0 {
+            self.image
+                .draw_filled_rect(rect, Char::new(TRACK_GLYPH, ink, paper));
+            let range = max - min;
+            let t = if range > 0.0 {
+                (*value - min) / range
+            } else {
+                0.0
+            };
+            let thumb = (t * rect.width.saturating_sub(1) as f32).round() as i32;
+            let p = Point::new(rect.x + thumb, rect.y);
+            self.image
+                .draw_char(p, Char::new(THUMB_GLYPH, theme.accent, paper));
+        }
+        *value != old_value
+    }
+
+    /// Returns the area of the next control, and moves on past it.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width the control wants.  It is clipped to the area of
+    ///   the frame.
+    ///
+    fn place(&mut self, width: u32) -> Rect {
+        let (x, y) = match (self.same_line, self.last) {
+            (true, Some(last)) => (last.x + last.width as i32 + 1, last.y),
+            _ => (self.rect.x, self.cursor.y),
+        };
+        self.same_line = false;
+        let room = (self.rect.x + self.rect.width as i32 - x).max(0) as u32;
+        let rect = Rect::new(x, y, width.min(room), 1);
+        self.cursor.y = self.cursor.y.max(y + 1);
+        self.last = Some(rect);
+        rect
+    }
+
+    /// Returns true if an area is within the rows of the frame.
+    fn visible(&self, rect: Rect) -> bool {
+        rect.y < self.rect.y + self.rect.height as i32
+    }
+
+    /// Works out what the user did to a control.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The text of the control.  Controls with the same text are
+    ///   told apart by the order they are drawn in.
+    /// * `rect` - The area of the control.
+    ///
+    fn interact(&mut self, name: &str, rect: Rect) -> Response {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        let mut id = hasher.finish();
+        while self.order.contains(&id) {
+            id = id.wrapping_add(1);
+        }
+        self.order.push(id);
+
+        let visible = self.visible(rect);
+        let ui = &mut *self.ui;
+        let mouse = &ui.input.mouse;
+        let hovered = visible && mouse.position.is_some_and(|p| contains(rect, p));
+        let mut clicks = 0;
+        if hovered && mouse.left.pressed {
+            ui.active = Some(id);
+            ui.focus = Some(id);
+        }
+        if ui.active == Some(id) && mouse.left.released {
+            clicks += usize::from(hovered);
+            if !mouse.left.down {
+                ui.active = None;
+            }
+        }
+
+        let focused = ui.focus == Some(id);
+        if focused {
+            clicks += self.keys.iter().filter(|key| is_activate_key(key)).count();
+        }
+        Response {
+            hovered,
+            active: ui.active == Some(id),
+            focused,
+            clicks,
+        }
+    }
+}
+
+impl Drop for Frame<'_> {
+    fn drop(&mut self) {
+        let ui = &mut *self.ui;
+        ui.order = std::mem::take(&mut self.order);
+        let input = &mut ui.input;
+        for button in [
+            &mut input.mouse.left,
+            &mut input.mouse.right,
+            &mut input.mouse.middle,
+        ] {
+            button.pressed = false;
+            button.released = false;
+        }
+        input.mouse.wheel = 0;
+        input.dt = Duration::zero();
+        if !input.mouse.left.down {
+            ui.active = None;
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! Computing the areas of widgets from the size of the screen.
+//!
+//! A [`Layout`] divides an area into rows or columns, each sized by a
+//! [`Constraint`], and each containing another layout, a widget or nothing.
+//! Solving the layout for an area gives the rectangle of every widget, so a UI
+//! can adapt to any window size.  A [`Ui`] given a layout with
+//! [`Ui::set_layout`] solves it again whenever the screen size changes.
+//!
+//! ```
+//! use mage_core::{image::Rect, ui::{Constraint, Layout}};
+//!
+//! // A menu bar across the top, a side bar on the left, and the map filling
+//! // the rest of the screen.
+//! let layout = Layout::column()
+//!     .child(Constraint::fixed(1), Layout::leaf("menu"))
+//!     .child(
+//!         Constraint::weight(1),
+//!         Layout::row()
+//!             .child(Constraint::weight(1).with_min(16).with_max(30), Layout::leaf("side"))
+//!             .child(Constraint::weight(3), Layout::leaf("map")),
+//!     );
+//!
+//! let rects = layout.solve(Rect::new(0, 0, 80, 25));
+//! assert_eq!(rects[0], ("menu", Rect::new(0, 0, 80, 1)));
+//! assert_eq!(rects[1], ("side", Rect::new(0, 1, 20, 24)));
+//! assert_eq!(rects[2], ("map", Rect::new(20, 1, 60, 24)));
+//! ```
+//!
+//! [`Layout`]: struct.Layout.html
+//! [`Constraint`]: struct.Constraint.html
+//! [`Ui`]: struct.Ui.html
+//! [`Ui::set_layout`]: struct.Ui.html#method.set_layout
+
+use crate::image::Rect;
+
+/// How the length of a row or column of a [`Layout`] is decided.
+///
+/// [`Layout`]: struct.Layout.html
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Length {
+    /// A fixed number of chars.
+    Fixed(u32),
+
+    /// A share of the space left after the fixed lengths, in proportion to
+    /// the weights of the other weighted rows or columns.
+    Weight(u32),
+}
+
+/// The length of a row or column of a [`Layout`], along with the smallest and
+/// largest it can be.
+///
+/// [`Layout`]: struct.Layout.html
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Constraint {
+    /// How the length is decided.
+    pub length: Length,
+
+    /// The smallest length allowed.
+    pub min: u32,
+
+    /// The largest length allowed.
+    pub max: u32,
+}
+
+impl Constraint {
+    /// Creates a constraint for a fixed number of chars.
+    pub fn fixed(length: u32) -> Self {
+        Self {
+            length: Length::Fixed(length),
+            min: 0,
+            max: u32::MAX,
+        }
+    }
+
+    /// Creates a constraint for a weighted share of the space left over.
+    pub fn weight(weight: u32) -> Self {
+        Self {
+            length: Length::Weight(weight),
+            min: 0,
+            max: u32::MAX,
+        }
+    }
+
+    /// Sets the smallest length allowed.
+    pub fn with_min(mut self, min: u32) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Sets the largest length allowed.
+    pub fn with_max(mut self, max: u32) -> Self {
+        self.max = max;
+        self
+    }
+
+    /// Clamps a length to the smallest and largest allowed.
+    fn clamp(&self, length: u32) -> u32 {
+        length.max(self.min).min(self.max.max(self.min))
+    }
+}
+
+/// Where a fixed size area is placed within a larger area.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Centre,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Returns the area of the given size placed within an area.  The size is
+    /// reduced to fit if necessary.
+    pub fn place(self, area: Rect, width: u32, height: u32) -> Rect {
+        let width = width.min(area.width);
+        let height = height.min(area.height);
+        let (dx, dy) = (area.width - width, area.height - height);
+        let (x, y) = match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (dx / 2, 0),
+            Anchor::TopRight => (dx, 0),
+            Anchor::Left => (0, dy / 2),
+            Anchor::Centre => (dx / 2, dy / 2),
+            Anchor::Right => (dx, dy / 2),
+            Anchor::BottomLeft => (0, dy),
+            Anchor::Bottom => (dx / 2, dy),
+            Anchor::BottomRight => (dx, dy),
+        };
+        Rect::new(area.x + x as i32, area.y + y as i32, width, height)
+    }
+}
+
+/// The direction the children of a [`Layout`] are placed in.
+///
+/// [`Layout`]: struct.Layout.html
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Children are placed left to right.
+    Row,
+
+    /// Children are placed top to bottom.
+    Column,
+}
+
+/// What a layout contains.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Node<K> {
+    /// Nothing; the area is left empty.
+    Empty,
+
+    /// An item that fills the whole area.
+    Leaf(K),
+
+    /// An item of a fixed size placed within the area.
+    Anchored {
+        key: K,
+        width: u32,
+        height: u32,
+        anchor: Anchor,
+    },
+
+    /// Children placed in a row or column.
+    Split {
+        direction: Direction,
+        children: Vec<(Constraint, Layout<K>)>,
+        gap: u32,
+        margin: u32,
+    },
+}
+
+/// A description of how an area is divided between items, usually widgets.
+///
+/// `K` is the type used to identify the items, such as a [`WidgetId`] or a
+/// name.
+///
+/// [`WidgetId`]: struct.WidgetId.html
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout<K> {
+    node: Node<K>,
+}
+
+impl<K: Clone> Layout<K> {
+    /// Creates a layout that places its children left to right.
+    pub fn row() -> Self {
+        Self::split(Direction::Row)
+    }
+
+    /// Creates a layout that places its children top to bottom.
+    pub fn column() -> Self {
+        Self::split(Direction::Column)
+    }
+
+    /// Creates a layout that places its children in the given direction.
+    pub fn split(direction: Direction) -> Self {
+        Self {
+            node: Node::Split {
+                direction,
+                children: Vec::new(),
+                gap: 0,
+                margin: 0,
+            },
+        }
+    }
+
+    /// Creates a layout that gives its whole area to an item.
+    pub fn leaf(key: K) -> Self {
+        Self {
+            node: Node::Leaf(key),
+        }
+    }
+
+    /// Creates a layout that places an item of a fixed size within its area.
+    /// The item is made smaller if the area is too small.
+    pub fn anchored(key: K, width: u32, height: u32, anchor: Anchor) -> Self {
+        Self {
+            node: Node::Anchored {
+                key,
+                width,
+                height,
+                anchor,
+            },
+        }
+    }
+
+    /// Creates a layout that leaves its area empty, for spacing.
+    pub fn empty() -> Self {
+        Self { node: Node::Empty }
+    }
+
+    /// Adds a child to the end of a row or column layout.  Does nothing to
+    /// other layouts.
+    pub fn child(mut self, constraint: Constraint, layout: Layout<K>) -> Self {
+        if let Node::Split { children, .. } = &mut self.node {
+            children.push((constraint, layout));
+        }
+        self
+    }
+
+    /// Sets the number of chars between the children of a row or column
+    /// layout.
+    pub fn gap(mut self, chars: u32) -> Self {
+        if let Node::Split { gap, .. } = &mut self.node {
+            *gap = chars;
+        }
+        self
+    }
+
+    /// Sets the number of chars left empty around the edge of a row or column
+    /// layout.
+    pub fn margin(mut self, chars: u32) -> Self {
+        if let Node::Split { margin, .. } = &mut self.node {
+            *margin = chars;
+        }
+        self
+    }
+
+    /// Computes the area of every item within an area.
+    ///
+    /// # Returns
+    ///
+    /// Each item and its area, in the order the items appear in the layout.
+    ///
+    /// # Notes
+    ///
+    /// Fixed lengths are given their space first, and the rest is shared
+    /// between the weighted lengths.  Minimum and maximum lengths are always
+    /// respected, so if the area is too small the last items are cut short or
+    /// given no space at all.
+    ///
+    pub fn solve(&self, area: Rect) -> Vec<(K, Rect)> {
+        let mut rects = Vec::new();
+        self.solve_into(area, &mut rects);
+        rects
+    }
+
+    /// Computes the area of every item within an area, adding them to a list.
+    fn solve_into(&self, area: Rect, rects: &mut Vec<(K, Rect)>) {
+        match &self.node {
+            Node::Empty => {}
+            Node::Leaf(key) => rects.push((key.clone(), area)),
+            Node::Anchored {
+                key,
+                width,
+                height,
+                anchor,
+            } => rects.push((key.clone(), anchor.place(area, *width, *height))),
+            Node::Split {
+                direction,
+                children,
+                gap,
+                margin,
+            } => {
+                let inner = Rect::new(
+                    area.x + *margin as i32,
+                    area.y + *margin as i32,
+                    area.width.saturating_sub(margin * 2),
+                    area.height.saturating_sub(margin * 2),
+                );
+                let (start, total) = match direction {
+                    Direction::Row => (inner.x, inner.width),
+                    Direction::Column => (inner.y, inner.height),
+                };
+                let constraints: Vec<_> = children.iter().map(|(c, _)| *c).collect();
+                let lengths = distribute(&constraints, total, *gap);
+
+                // Children past the end of the area are cut short.
+                let end = start as i64 + total as i64;
+                let mut pos = start as i64;
+                for ((_, child), length) in children.iter().zip(lengths) {
+                    let length = (length as i64).min(end - pos).max(0) as u32;
+                    let child_area = match direction {
+                        Direction::Row => Rect::new(pos as i32, inner.y, length, inner.height),
+                        Direction::Column => Rect::new(inner.x, pos as i32, inner.width, length),
+                    };
+                    child.solve_into(child_area, rects);
+                    pos = (pos + length as i64 + *gap as i64).min(end);
+                }
+            }
+        }
+    }
+}
+
+/// Shares a total length between constraints.
+fn distribute(constraints: &[Constraint], total: u32, gap: u32) -> Vec<u32> {
+    let gaps = constraints.len().saturating_sub(1) as u64 * gap as u64;
+    let available = (total as u64).saturating_sub(gaps);
+
+    // Fixed lengths first, then the weighted lengths share what's left.
+    // Weighted lengths that would break their limits are fixed at the limit
+    // and the rest shared again, until every share is within its limits.
+    let mut lengths: Vec<Option<u32>> = constraints
+        .iter()
+        .map(|c| match c.length {
+            Length::Fixed(length) => Some(c.clamp(length)),
+            Length::Weight(_) => None,
+        })
+        .collect();
+    loop {
+        let used: u64 = lengths.iter().flatten().map(|&l| l as u64).sum();
+        let remaining = available.saturating_sub(used);
+        let weights: u64 = constraints
+            .iter()
+            .zip(&lengths)
+            .filter(|(_, length)| length.is_none())
+            .map(|(c, _)| weight(c) as u64)
+            .sum();
+        if weights == 0 {
+            return lengths.into_iter().map(|l| l.unwrap_or(0)).collect();
+        }
+
+        let shares = share(constraints, &lengths, remaining, weights);
+        let mut changed = false;
+        for (i, c) in constraints.iter().enumerate() {
+            if lengths[i].is_none() && c.clamp(shares[i]) != shares[i] {
+                lengths[i] = Some(c.clamp(shares[i]));
+                changed = true;
+            }
+        }
+        if !changed {
+            return lengths
+                .into_iter()
+                .zip(shares)
+                .map(|(l, s)| l.unwrap_or(s))
+                .collect();
+        }
+    }
+}
+
+/// Returns the weight of a constraint, or zero if it is fixed.
+fn weight(constraint: &Constraint) -> u32 {
+    match constraint.length {
+        Length::Fixed(_) => 0,
+        Length::Weight(weight) => weight,
+    }
+}
+
+/// Shares a length between the constraints without a length yet in
+/// proportion to their weights.  Chars left over from rounding go to the
+/// shares that lost the most.
+fn share(
+    constraints: &[Constraint],
+    lengths: &[Option<u32>],
+    total: u64,
+    weights: u64,
+) -> Vec<u32> {
+    let mut shares = vec![0; constraints.len()];
+    let mut remainders = Vec::new();
+    let mut given = 0;
+    for (i, c) in constraints.iter().enumerate() {
+        if lengths[i].is_none() {
+            let exact = total * weight(c) as u64;
+            shares[i] = (exact / weights) as u32;
+            given += shares[i] as u64;
+            remainders.push((exact % weights, i));
+        }
+    }
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, i) in remainders.into_iter().take((total - given) as usize) {
+        shares[i] += 1;
+    }
+    shares
+}
@@ -0,0 +1,549 @@
+//! Scrolling lists and message logs.
+//!
+//! Both views only read and draw the rows that are visible, so they can show
+//! very large collections such as inventories and message logs cheaply.
+
+use std::{borrow::Cow, collections::VecDeque};
+
+use crate::{
+    colour::Colour,
+    image::{Char, Image, Point, Rect},
+    table::Alignment,
+};
+
+use super::{contains, draw_text, Key, KeyPress, Theme, UiInput, Widget};
+
+/// The number of rows scrolled for each notch of the mouse wheel.
+const WHEEL_ROWS: i64 = 3;
+
+/// The glyph of the track of a scroll bar, `░`.
+const TRACK_GLYPH: u8 = 0xb0;
+
+/// The glyph of the thumb of a scroll bar, `█`.
+const THUMB_GLYPH: u8 = 0xdb;
+
+/// The rows shown by a [`ListView`].
+///
+/// This is implemented for vectors and deques of strings.  Implement it for
+/// your own collections to show them without copying every row into a
+/// string.
+///
+/// [`ListView`]: struct.ListView.html
+///
+pub trait ListModel {
+    /// Returns the number of rows.
+    fn len(&self) -> usize;
+
+    /// Returns true if there are no rows.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the text of a row.  Only called for rows that are visible.
+    fn text(&self, index: usize) -> Cow<'_, str>;
+
+    /// Returns the foreground colour of a row, or `None` to use the theme's
+    /// colour.
+    fn ink(&self, _index: usize) -> Option<Colour> {
+        None
+    }
+}
+
+impl<S: AsRef<str>> ListModel for Vec<S> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn text(&self, index: usize) -> Cow<'_, str> {
+        Cow::Borrowed(self[index].as_ref())
+    }
+}
+
+impl<S: AsRef<str>> ListModel for VecDeque<S> {
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+
+    fn text(&self, index: usize) -> Cow<'_, str> {
+        Cow::Borrowed(self[index].as_ref())
+    }
+}
+
+/// The position of a view over a number of rows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Scroll {
+    /// The index of the first visible row.
+    offset: usize,
+}
+
+impl Scroll {
+    /// Returns the largest offset that still fills the view.
+    fn max_offset(len: usize, visible: usize) -> usize {
+        len.saturating_sub(visible)
+    }
+
+    /// Moves the view by a number of rows, staying within the rows.
+    fn scroll_by(&mut self, rows: i64, len: usize, visible: usize) {
+        let offset = (self.offset as i64 + rows).max(0) as usize;
+        self.offset = offset.min(Self::max_offset(len, visible));
+    }
+
+    /// Moves the view the least amount needed to show a row.
+    fn show(&mut self, index: usize, visible: usize) {
+        if index < self.offset {
+            self.offset = index;
+        } else if visible > 0 && index >= self.offset + visible {
+            self.offset = index + 1 - visible;
+        }
+    }
+
+    /// Keeps the view within the rows after they have changed.
+    fn clamp(&mut self, len: usize, visible: usize) {
+        self.offset = self.offset.min(Self::max_offset(len, visible));
+    }
+
+    /// Returns the first row and number of rows of a scroll bar's thumb.
+    fn thumb(&self, len: usize, visible: usize) -> (usize, usize) {
+        if visible == 0 {
+            return (0, 0);
+        }
+        let size = (visible * visible / len.max(1)).clamp(1, visible);
+        let max_offset = Self::max_offset(len, visible).max(1);
+        let start = self.offset.min(max_offset) * (visible - size) / max_offset;
+        (start, size)
+    }
+
+    /// Draws a scroll bar down a column, in the focus colours if the view has
+    /// the focus.
+    fn draw_bar(
+        &self,
+        image: &mut Image,
+        p: Point,
+        len: usize,
+        visible: usize,
+        theme: &Theme,
+        focused: bool,
+    ) {
+        let (ink, paper) = theme.colours(false, false, focused);
+        let (start, size) = self.thumb(len, visible);
+        for row in 0..visible {
+            let glyph = if (start..start + size).contains(&row) {
+                THUMB_GLYPH
+            } else {
+                TRACK_GLYPH
+            };
+            let ch = Char::new(glyph, ink, paper);
+            image.draw_char(Point::new(p.x, p.y + row as i32), ch);
+        }
+    }
+}
+
+/// A scrolling list of rows, one of which can be selected.
+///
+/// The selection is moved by clicking a row or, while the list has the focus,
+/// with the arrow keys, page up, page down, home and end.  Pressing enter or
+/// clicking the selected row activates it.  A scroll bar is shown on the right when there are more rows
+/// than fit, and the mouse wheel scrolls the list.
+pub struct ListView<M, T = Vec<String>> {
+    rect: Rect,
+    items: T,
+    scroll: Scroll,
+    selected: Option<usize>,
+    hovered: Option<usize>,
+    focused: bool,
+    on_select: Option<Box<dyn Fn(usize) -> M>>,
+    on_activate: Option<Box<dyn Fn(usize) -> M>>,
+}
+
+impl<M, T: ListModel> ListView<M, T> {
+    /// Creates a new list view with no row selected.
+    pub fn new(rect: Rect, items: T) -> Self {
+        Self {
+            rect,
+            items,
+            scroll: Scroll::default(),
+            selected: None,
+            hovered: None,
+            focused: false,
+            on_select: None,
+            on_activate: None,
+        }
+    }
+
+    /// Sets the function that creates the message produced when the selected
+    /// row changes.  It is passed the index of the newly selected row.
+    pub fn on_select(mut self, f: impl Fn(usize) -> M + 'static) -> Self {
+        self.on_select = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the function that creates the message produced when the selected
+    /// row is activated.  It is passed the index of the row.
+    pub fn on_activate(mut self, f: impl Fn(usize) -> M + 'static) -> Self {
+        self.on_activate = Some(Box::new(f));
+        self
+    }
+
+    /// Returns the rows of the list.
+    pub fn items(&self) -> &T {
+        &self.items
+    }
+
+    /// Returns the rows of the list so they can be changed.  The selection is
+    /// kept within the rows the next time the list is updated.
+    pub fn items_mut(&mut self) -> &mut T {
+        &mut self.items
+    }
+
+    /// Returns the index of the selected row.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Selects a row, scrolling it into view, without producing a message.
+    /// Indices past the last row are ignored.
+    pub fn set_selected(&mut self, index: Option<usize>) {
+        if index.is_none_or(|i| i < self.items.len()) {
+            self.selected = index;
+            if let Some(i) = index {
+                self.scroll.show(i, self.rect.height as usize);
+            }
+        }
+    }
+
+    /// Returns the index of the first visible row.
+    pub fn offset(&self) -> usize {
+        self.scroll.offset
+    }
+
+    /// Scrolls the list the least amount needed to show a row.
+    pub fn scroll_to(&mut self, index: usize) {
+        self.scroll.show(index, self.rect.height as usize);
+        self.scroll
+            .clamp(self.items.len(), self.rect.height as usize);
+    }
+
+    /// Returns true if the list has more rows than fit and shows a scroll
+    /// bar.
+    fn has_scroll_bar(&self) -> bool {
+        self.items.len() > self.rect.height as usize
+    }
+
+    /// Returns the area of the rows, not including the scroll bar.
+    fn rows_rect(&self) -> Rect {
+        let bar = u32::from(self.has_scroll_bar());
+        Rect::new(
+            self.rect.x,
+            self.rect.y,
+            self.rect.width.saturating_sub(bar),
+            self.rect.height,
+        )
+    }
+}
+
+impl<M: 'static, T: ListModel + 'static> Widget<M> for ListView<M, T> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn wants_key(&self, key: &KeyPress) -> bool {
+        matches!(key.key, Key::Up | Key::Down)
+    }
+
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>) {
+        let len = self.items.len();
+        let visible = self.rect.height as usize;
+        let page = visible.saturating_sub(1).max(1) as i64;
+        let mouse = &input.mouse;
+        let old_selected = self.selected;
+        self.selected = self.selected.filter(|&i| i < len);
+        self.scroll.clamp(len, visible);
+        let mut activated = None;
+
+        // Moves the selection by a number of rows, or to the first or last
+        // row if nothing is selected.
+        let step = |selected: Option<usize>, rows: i64| match selected {
+            _ if len == 0 => None,
+            Some(i) => Some((i as i64 + rows).clamp(0, len as i64 - 1) as usize),
+            None if rows > 0 => Some(0),
+            None => Some(len - 1),
+        };
+
+        let rows_rect = self.rows_rect();
+        let offset = self.scroll.offset;
+        self.hovered = mouse
+            .position
+            .filter(|&p| contains(rows_rect, p))
+            .map(|p| offset + (p.y - rows_rect.y) as usize)
+            .filter(|&i| i < len);
+        if mouse.position.is_some_and(|p| contains(self.rect, p)) && mouse.wheel != 0 {
+            self.scroll
+                .scroll_by(-mouse.wheel as i64 * WHEEL_ROWS, len, visible);
+        }
+        if let (Some(p), true) = (mouse.position, mouse.left.pressed) {
+            if let Some(i) = self.hovered {
+                if self.selected == Some(i) {
+                    activated = Some(i);
+                }
+                self.selected = Some(i);
+            } else if self.has_scroll_bar() && contains(self.rect, p) {
+                // Clicking the scroll bar above or below the thumb moves a
+                // page.
+                let (start, _) = self.scroll.thumb(len, visible);
+                let row = (p.y - self.rect.y) as usize;
+                let rows = if row < start { -page } else { page };
+                self.scroll.scroll_by(rows, len, visible);
+            }
+        }
+
+        for key in &input.keys {
+            let rows = match key.key {
+                Key::Up => -1,
+                Key::Down => 1,
+                Key::PageUp => -page,
+                Key::PageDown => page,
+                Key::Home => -(len as i64),
+                Key::End => len as i64,
+                Key::Enter => {
+                    activated = self.selected;
+                    continue;
+                }
+                _ => continue,
+            };
+            self.selected = step(self.selected, rows);
+        }
+
+        if self.selected != old_selected {
+            if let Some(i) = self.selected {
+                self.scroll.show(i, visible);
+                messages.extend(self.on_select.as_ref().map(|f| f(i)));
+            }
+        }
+        if let Some(i) = activated {
+            messages.extend(self.on_activate.as_ref().map(|f| f(i)));
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        let len = self.items.len();
+        let visible = self.rect.height as usize;
+        let rows_rect = self.rows_rect();
+        for row in 0..visible {
+            let index = self.scroll.offset + row;
+            let p = Point::new(rows_rect.x, rows_rect.y + row as i32);
+            if index >= len {
+                draw_text(
+                    image,
+                    p,
+                    rows_rect.width,
+                    "",
+                    Alignment::Left,
+                    theme.ink,
+                    theme.paper,
+                );
+                continue;
+            }
+
+            let hovered = self.hovered == Some(index);
+            let selected = self.selected == Some(index);
+            // The selected row is drawn as pressed while the list has the
+            // focus, and as hovered otherwise.
+            let (mut ink, paper) =
+                theme.colours(hovered || selected, selected && self.focused, false);
+            if !hovered && !selected {
+                ink = self.items.ink(index).unwrap_or(ink);
+            }
+            let padding = theme.padding as usize;
+            let text = format!("{:padding$}{}", "", self.items.text(index));
+            draw_text(
+                image,
+                p,
+                rows_rect.width,
+                &text,
+                Alignment::Left,
+                ink,
+                paper,
+            );
+        }
+
+        if self.has_scroll_bar() && self.rect.width > 0 {
+            let p = Point::new(rows_rect.x + rows_rect.width as i32, self.rect.y);
+            self.scroll
+                .draw_bar(image, p, len, visible, theme, self.focused);
+        }
+    }
+}
+
+/// A scrolling log of messages, such as a game's message log or debug
+/// output.
+///
+/// New lines are added to the bottom.  While the view is scrolled to the
+/// bottom it follows new lines; when scrolled up it stays where it is.  The
+/// oldest lines are dropped once the log is full.  Lines wider than the view
+/// are clipped.  While the log has the focus, the arrow keys, page up, page
+/// down, home and end scroll it.
+pub struct LogView {
+    rect: Rect,
+    lines: VecDeque<(String, Option<Colour>)>,
+    capacity: usize,
+    scroll: Scroll,
+    focused: bool,
+}
+
+impl LogView {
+    /// Creates a new empty log.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area the log is drawn in.
+    /// * `capacity` - The most lines kept before the oldest are dropped.
+    ///
+    pub fn new(rect: Rect, capacity: usize) -> Self {
+        Self {
+            rect,
+            lines: VecDeque::new(),
+            capacity,
+            scroll: Scroll::default(),
+            focused: false,
+        }
+    }
+
+    /// Adds a line to the bottom of the log in the theme's colour.
+    pub fn push(&mut self, text: impl Into<String>) {
+        self.push_line(text.into(), None);
+    }
+
+    /// Adds a line to the bottom of the log in the given colour.
+    pub fn push_coloured(&mut self, text: impl Into<String>, ink: Colour) {
+        self.push_line(text.into(), Some(ink));
+    }
+
+    /// Adds a line, dropping the oldest line if the log is full.
+    fn push_line(&mut self, text: String, ink: Option<Colour>) {
+        let visible = self.rect.height as usize;
+        let following = self.is_following();
+        self.lines.push_back((text, ink));
+        if self.lines.len() > self.capacity {
+            self.lines.pop_front();
+            self.scroll.offset = self.scroll.offset.saturating_sub(1);
+        }
+        if following {
+            self.scroll.offset = Scroll::max_offset(self.lines.len(), visible);
+        }
+    }
+
+    /// Returns true if the view is scrolled to the bottom.
+    pub fn is_following(&self) -> bool {
+        self.scroll.offset >= Scroll::max_offset(self.lines.len(), self.rect.height as usize)
+    }
+
+    /// Returns the text of each line, oldest first.
+    pub fn lines(&self) -> impl Iterator<Item = &str> + '_ {
+        self.lines.iter().map(|(text, _)| text.as_str())
+    }
+
+    /// Returns the number of lines in the log.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns true if the log has no lines.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Removes every line.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.scroll = Scroll::default();
+    }
+
+    /// Scrolls to the bottom of the log, so that new lines are followed.
+    pub fn scroll_to_bottom(&mut self) {
+        let visible = self.rect.height as usize;
+        self.scroll.offset = Scroll::max_offset(self.lines.len(), visible);
+    }
+}
+
+impl<M> Widget<M> for LogView {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        let following = self.is_following();
+        self.rect = rect;
+        if following {
+            self.scroll_to_bottom();
+        }
+        self.scroll.clamp(self.lines.len(), rect.height as usize);
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn wants_key(&self, key: &KeyPress) -> bool {
+        matches!(key.key, Key::Up | Key::Down)
+    }
+
+    fn handle_input(&mut self, input: &UiInput, _messages: &mut Vec<M>) {
+        let len = self.lines.len();
+        let visible = self.rect.height as usize;
+        let page = visible.saturating_sub(1).max(1) as i64;
+        let mouse = &input.mouse;
+        if mouse.position.is_some_and(|p| contains(self.rect, p)) {
+            self.scroll
+                .scroll_by(-mouse.wheel as i64 * WHEEL_ROWS, len, visible);
+        }
+        for key in &input.keys {
+            let rows = match key.key {
+                Key::Up => -1,
+                Key::Down => 1,
+                Key::PageUp => -page,
+                Key::PageDown => page,
+                Key::Home => -(len as i64),
+                Key::End => len as i64,
+                _ => continue,
+            };
+            self.scroll.scroll_by(rows, len, visible);
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        let len = self.lines.len();
+        let visible = self.rect.height as usize;
+        let bar = len > visible;
+        let width = self.rect.width.saturating_sub(u32::from(bar));
+        for row in 0..visible {
+            let p = Point::new(self.rect.x, self.rect.y + row as i32);
+            let (text, ink) = match self.lines.get(self.scroll.offset + row) {
+                Some((text, ink)) => (text.as_str(), ink.unwrap_or(theme.ink)),
+                None => ("", theme.ink),
+            };
+            let text = format!("{:padding$}{}", "", text, padding = theme.padding as usize);
+            draw_text(image, p, width, &text, Alignment::Left, ink, theme.paper);
+        }
+        if bar && self.rect.width > 0 {
+            let p = Point::new(self.rect.x + width as i32, self.rect.y);
+            self.scroll
+                .draw_bar(image, p, len, visible, theme, self.focused);
+        }
+    }
+}
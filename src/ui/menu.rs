@@ -0,0 +1,747 @@
+//! Menu bars, context menus and nested submenus.
+//!
+//! Menus are built from items, separators and submenus.  A `&` in the text of
+//! an item marks the following character as its mnemonic, which is
+//! highlighted and selects the item when typed while the menu is open.  Use
+//! `&&` for a literal `&`.
+
+use crate::{
+    colour::Colour,
+    image::{Char, Image, Point, Rect},
+    lines::LineStyle,
+};
+
+use super::{contains, Key, Theme, UiInput, Widget};
+
+/// The glyph drawn after an item that opens a submenu, `►`.
+const SUBMENU_GLYPH: u8 = 0x10;
+
+/// The glyphs drawn at the left and right ends of a separator in a single
+/// border, `├` and `┤`.
+const SEPARATOR_ENDS: (u8, u8) = (0xc3, 0xb4);
+
+/// The glyphs drawn at the left and right ends of a separator in a double
+/// border, `╟` and `╢`.
+const DOUBLE_SEPARATOR_ENDS: (u8, u8) = (0xc7, 0xb6);
+
+/// The text of a menu item or title with its mnemonic.
+struct MenuLabel {
+    /// The text to draw, with the `&` markers removed.
+    text: String,
+
+    /// The index of the character in the text that is the mnemonic.
+    mnemonic: Option<usize>,
+}
+
+impl MenuLabel {
+    /// Creates a label from text with the mnemonic marked by a `&`.
+    fn parse(text: &str) -> Self {
+        let mut label = Self {
+            text: String::new(),
+            mnemonic: None,
+        };
+        let mut chars = text.chars();
+        while let Some(ch) = chars.next() {
+            if ch != '&' {
+                label.text.push(ch);
+                continue;
+            }
+            match chars.next() {
+                Some('&') => label.text.push('&'),
+                Some(ch) => {
+                    if label.mnemonic.is_none() {
+                        label.mnemonic = Some(label.text.chars().count());
+                    }
+                    label.text.push(ch);
+                }
+                None => {}
+            }
+        }
+        label
+    }
+
+    /// Returns the width of the text in chars.
+    fn width(&self) -> u32 {
+        self.text.chars().count() as u32
+    }
+
+    /// Returns true if the mnemonic is the given character, ignoring case.
+    fn matches(&self, ch: char) -> bool {
+        self.mnemonic
+            .and_then(|i| self.text.chars().nth(i))
+            .is_some_and(|mnemonic| mnemonic.to_lowercase().eq(ch.to_lowercase()))
+    }
+
+    /// Draws the text with the mnemonic in the accent colour.
+    fn draw(&self, image: &mut Image, p: Point, ink: Colour, paper: Colour, accent: Colour) {
+        image.draw_string_unicode(p, &self.text, ink, paper);
+        if let Some((i, ch)) = self
+            .mnemonic
+            .and_then(|i| self.text.chars().nth(i).map(|ch| (i, ch)))
+        {
+            let p = Point::new(p.x + i as i32, p.y);
+            image.draw_string_unicode(p, ch.encode_utf8(&mut [0; 4]), accent, paper);
+        }
+    }
+}
+
+/// A single entry in a [`Menu`].
+///
+/// [`Menu`]: struct.Menu.html
+///
+enum MenuItem<M> {
+    /// An item that produces a message when chosen.
+    Action(MenuLabel, Box<dyn Fn() -> M>),
+
+    /// An item that opens another menu.
+    Submenu(MenuLabel, Menu<M>),
+
+    /// A line between groups of items.
+    Separator,
+}
+
+impl<M> MenuItem<M> {
+    /// Returns true if the item can be highlighted.
+    fn is_selectable(&self) -> bool {
+        !matches!(self, MenuItem::Separator)
+    }
+
+    /// Returns the label of the item, or `None` for a separator.
+    fn label(&self) -> Option<&MenuLabel> {
+        match self {
+            MenuItem::Action(label, _) | MenuItem::Submenu(label, _) => Some(label),
+            MenuItem::Separator => None,
+        }
+    }
+}
+
+/// A list of items shown in a popup by a [`MenuBar`] or [`ContextMenu`].
+///
+/// [`MenuBar`]: struct.MenuBar.html
+/// [`ContextMenu`]: struct.ContextMenu.html
+///
+pub struct Menu<M> {
+    items: Vec<MenuItem<M>>,
+}
+
+impl<M> Menu<M> {
+    /// Creates a new empty menu.
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    /// Adds an item to the end of the menu.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of the item, with a `&` before the mnemonic.
+    /// * `f` - Creates the message produced when the item is chosen.
+    ///
+    pub fn item(mut self, text: &str, f: impl Fn() -> M + 'static) -> Self {
+        let label = MenuLabel::parse(text);
+        self.items.push(MenuItem::Action(label, Box::new(f)));
+        self
+    }
+
+    /// Adds an item that opens a submenu to the end of the menu.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of the item, with a `&` before the mnemonic.
+    /// * `menu` - The menu opened by the item.
+    ///
+    pub fn submenu(mut self, text: &str, menu: Menu<M>) -> Self {
+        let label = MenuLabel::parse(text);
+        self.items.push(MenuItem::Submenu(label, menu));
+        self
+    }
+
+    /// Adds a separator line to the end of the menu.
+    pub fn separator(mut self) -> Self {
+        self.items.push(MenuItem::Separator);
+        self
+    }
+
+    /// Returns the number of items in the menu, including separators.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if the menu has no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the size of the menu's popup, including its border.
+    fn size(&self) -> (u32, u32) {
+        let text = self
+            .items
+            .iter()
+            .filter_map(|item| item.label())
+            .map(|label| label.width())
+            .max()
+            .unwrap_or(0);
+        let has_submenus = self
+            .items
+            .iter()
+            .any(|item| matches!(item, MenuItem::Submenu(..)));
+        let arrow = if has_submenus { 2 } else { 0 };
+        (text + arrow + 4, self.items.len() as u32 + 2)
+    }
+
+    /// Returns the index of the next item that can be highlighted after (or
+    /// before) an item, wrapping around the ends of the menu.  If `from` is
+    /// `None`, the search starts from the first (or last) item.
+    fn next_selectable(&self, from: Option<usize>, forward: bool) -> Option<usize> {
+        let n = self.items.len();
+        let start = match (from, forward) {
+            (Some(i), _) => i,
+            (None, true) => n.checked_sub(1)?,
+            (None, false) => 0,
+        };
+        (1..=n)
+            .map(|k| {
+                if forward {
+                    (start + k) % n
+                } else {
+                    (start + n - k) % n
+                }
+            })
+            .find(|&i| self.items[i].is_selectable())
+    }
+
+    /// Returns the index of the item with the given mnemonic.
+    fn find_mnemonic(&self, ch: char) -> Option<usize> {
+        self.items
+            .iter()
+            .position(|item| item.label().is_some_and(|label| label.matches(ch)))
+    }
+
+    /// Draws the menu as a popup with a border.
+    fn draw(&self, image: &mut Image, rect: Rect, highlighted: Option<usize>, theme: &Theme) {
+        image.draw_filled_rect(rect, Char::new(b' ', theme.ink, theme.paper));
+        image.draw_box(rect, theme.border, theme.ink, theme.paper);
+
+        let right = rect.x + rect.width as i32 - 1;
+        for (i, item) in self.items.iter().enumerate() {
+            let y = rect.y + 1 + i as i32;
+            match item {
+                MenuItem::Separator => {
+                    let p = Point::new(rect.x, y);
+                    let (left_end, right_end) = match theme.border {
+                        LineStyle::Single => SEPARATOR_ENDS,
+                        LineStyle::Double => DOUBLE_SEPARATOR_ENDS,
+                    };
+                    image.draw_hline(
+                        p,
+                        rect.width,
+                        LineStyle::Single,
+                        theme.ink,
+                        theme.paper,
+                        false,
+                    );
+                    image.draw_char(p, Char::new(left_end, theme.ink, theme.paper));
+                    let end = Char::new(right_end, theme.ink, theme.paper);
+                    image.draw_char(Point::new(right, y), end);
+                }
+                MenuItem::Action(label, _) | MenuItem::Submenu(label, _) => {
+                    let (ink, paper) = theme.colours(highlighted == Some(i), false, false);
+                    let row = Rect::new(rect.x + 1, y, rect.width.saturating_sub(2), 1);
+                    image.draw_filled_rect(row, Char::new(b' ', ink, paper));
+                    label.draw(image, Point::new(rect.x + 2, y), ink, paper, theme.accent);
+                    if matches!(item, MenuItem::Submenu(..)) {
+                        let arrow = Char::new(SUBMENU_GLYPH, ink, paper);
+                        image.draw_char(Point::new(right - 1, y), arrow);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<M> Default for Menu<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A single open popup of a menu and its nested submenus.
+struct Level {
+    /// The area covered by the popup, including its border.
+    rect: Rect,
+
+    /// The index of the highlighted item, if any.
+    highlighted: Option<usize>,
+}
+
+/// What happened to an open popup menu when given input.
+enum PopupResult<M> {
+    /// Nothing that the owner of the popup needs to know about.
+    Nothing,
+
+    /// An item was chosen and the popup closed.
+    Activated(M),
+
+    /// The popup was closed without choosing an item.
+    Closed,
+
+    /// The left key was pressed in the top level menu.
+    Left,
+
+    /// The right key was pressed on an item without a submenu.
+    Right,
+}
+
+/// The open popups of a menu, from the top level menu to the deepest open
+/// submenu.
+#[derive(Default)]
+struct Popup {
+    levels: Vec<Level>,
+
+    /// The position of the mouse when last given input, so that a mouse that
+    /// hasn't moved doesn't undo keyboard navigation.
+    last_mouse: Option<Point>,
+}
+
+impl Popup {
+    /// Opens the top level popup of a menu.
+    fn open<M>(&mut self, menu: &Menu<M>, p: Point, screen: (u32, u32), highlight_first: bool) {
+        let rect = place(p, menu.size(), screen, None);
+        let highlighted = highlight_first
+            .then(|| menu.next_selectable(None, true))
+            .flatten();
+        self.levels = vec![Level { rect, highlighted }];
+    }
+
+    /// Closes every level of the popup.
+    fn close(&mut self) {
+        self.levels.clear();
+    }
+
+    /// Returns true if the popup is open.
+    fn is_open(&self) -> bool {
+        !self.levels.is_empty()
+    }
+
+    /// Returns the menu shown at the given level.
+    fn menu<'a, M>(&self, root: &'a Menu<M>, depth: usize) -> &'a Menu<M> {
+        let mut menu = root;
+        for level in &self.levels[..depth] {
+            if let Some(MenuItem::Submenu(_, submenu)) =
+                level.highlighted.and_then(|i| menu.items.get(i))
+            {
+                menu = submenu;
+            }
+        }
+        menu
+    }
+
+    /// Opens the submenu of the highlighted item in the deepest level, if it
+    /// has one.
+    fn open_submenu<M>(&mut self, root: &Menu<M>, screen: (u32, u32), highlight_first: bool) {
+        let depth = self.levels.len() - 1;
+        let parent = &self.levels[depth];
+        let Some(i) = parent.highlighted else {
+            return;
+        };
+        let Some(MenuItem::Submenu(_, submenu)) = self.menu(root, depth).items.get(i) else {
+            return;
+        };
+
+        let p = Point::new(
+            parent.rect.x + parent.rect.width as i32,
+            parent.rect.y + i as i32,
+        );
+        let rect = place(p, submenu.size(), screen, Some(parent.rect));
+        let highlighted = highlight_first
+            .then(|| submenu.next_selectable(None, true))
+            .flatten();
+        self.levels.push(Level { rect, highlighted });
+    }
+
+    /// Chooses an item in the deepest level, either producing its message or
+    /// opening its submenu.
+    fn activate<M>(
+        &mut self,
+        root: &Menu<M>,
+        index: usize,
+        screen: (u32, u32),
+    ) -> Option<PopupResult<M>> {
+        let depth = self.levels.len() - 1;
+        self.levels[depth].highlighted = Some(index);
+        match self.menu(root, depth).items.get(index)? {
+            MenuItem::Action(_, f) => {
+                let message = f();
+                self.close();
+                Some(PopupResult::Activated(message))
+            }
+            MenuItem::Submenu(..) => {
+                self.open_submenu(root, screen, true);
+                None
+            }
+            MenuItem::Separator => None,
+        }
+    }
+
+    /// Updates the popup from the input for this tick.
+    fn handle_input<M>(&mut self, root: &Menu<M>, input: &UiInput) -> PopupResult<M> {
+        let screen = (input.width, input.height);
+        let mouse = &input.mouse;
+        let moved = mouse.position != self.last_mouse;
+        self.last_mouse = mouse.position;
+
+        if let Some(p) = mouse.position {
+            let depth = (0..self.levels.len())
+                .rev()
+                .find(|&depth| contains(self.levels[depth].rect, p));
+            if let Some(depth) = depth {
+                let rect = self.levels[depth].rect;
+                let menu = self.menu(root, depth);
+                let inside = p.x > rect.x && p.x < rect.x + rect.width as i32 - 1;
+                let index = usize::try_from(p.y - rect.y - 1)
+                    .ok()
+                    .filter(|&i| inside && menu.items.get(i).is_some_and(|i| i.is_selectable()));
+
+                if let (Some(i), true) = (index, moved || mouse.left.released) {
+                    if self.levels[depth].highlighted != Some(i) {
+                        self.levels.truncate(depth + 1);
+                        self.levels[depth].highlighted = Some(i);
+                    }
+                    match &menu.items[i] {
+                        MenuItem::Submenu(..) if self.levels.len() == depth + 1 => {
+                            self.open_submenu(root, screen, false);
+                        }
+                        MenuItem::Action(_, f) if mouse.left.released => {
+                            let message = f();
+                            self.close();
+                            return PopupResult::Activated(message);
+                        }
+                        _ => {}
+                    }
+                }
+            } else if mouse.left.pressed || mouse.right.pressed {
+                self.close();
+                return PopupResult::Closed;
+            }
+        }
+
+        for key in &input.keys {
+            let depth = self.levels.len() - 1;
+            let menu = self.menu(root, depth);
+            let highlighted = self.levels[depth].highlighted;
+            let result = match key.key {
+                Key::Up | Key::Down => {
+                    let forward = key.key == Key::Down;
+                    self.levels[depth].highlighted = menu.next_selectable(highlighted, forward);
+                    None
+                }
+                Key::Home | Key::End => {
+                    let forward = key.key == Key::Home;
+                    self.levels[depth].highlighted = menu.next_selectable(None, forward);
+                    None
+                }
+                Key::Right => match highlighted.and_then(|i| menu.items.get(i)) {
+                    Some(MenuItem::Submenu(..)) => {
+                        self.open_submenu(root, screen, true);
+                        None
+                    }
+                    _ => Some(PopupResult::Right),
+                },
+                Key::Left if depth > 0 => {
+                    self.levels.pop();
+                    None
+                }
+                Key::Left => Some(PopupResult::Left),
+                Key::Escape if depth > 0 => {
+                    self.levels.pop();
+                    None
+                }
+                Key::Escape => {
+                    self.close();
+                    Some(PopupResult::Closed)
+                }
+                Key::Enter | Key::Char(' ') => {
+                    highlighted.and_then(|i| self.activate(root, i, screen))
+                }
+                Key::Char(ch) if !key.ctrl => menu
+                    .find_mnemonic(ch)
+                    .and_then(|i| self.activate(root, i, screen)),
+                _ => None,
+            };
+            if let Some(result) = result {
+                return result;
+            }
+        }
+
+        PopupResult::Nothing
+    }
+
+    /// Draws every open level of the popup.
+    fn draw<M>(&self, root: &Menu<M>, image: &mut Image, theme: &Theme) {
+        for (depth, level) in self.levels.iter().enumerate() {
+            self.menu(root, depth)
+                .draw(image, level.rect, level.highlighted, theme);
+        }
+    }
+}
+
+/// Returns the area of a popup of the given size opened at a point, moved to
+/// fit on the screen.  Submenus that don't fit to the right of their parent
+/// are opened to its left.
+fn place(p: Point, size: (u32, u32), screen: (u32, u32), parent: Option<Rect>) -> Rect {
+    let (width, height) = (size.0 as i32, size.1 as i32);
+    let (screen_width, screen_height) = (screen.0 as i32, screen.1 as i32);
+    let mut x = p.x;
+    let mut y = p.y;
+    if screen_width > 0 && x + width > screen_width {
+        x = match parent {
+            Some(parent) => parent.x - width,
+            None => screen_width - width,
+        };
+    }
+    if screen_height > 0 && y + height > screen_height {
+        y = screen_height - height;
+    }
+    Rect::new(x.max(0), y.max(0), size.0, size.1)
+}
+
+/// A row of menu titles, each opening a drop-down menu.
+///
+/// Menus are opened by clicking their title or by pressing alt with the
+/// title's mnemonic.  While a menu is open, the arrow keys move between items
+/// and menus, enter chooses an item, escape closes a submenu or the menu, and
+/// typing an item's mnemonic chooses it.
+pub struct MenuBar<M> {
+    rect: Rect,
+    menus: Vec<(MenuLabel, Menu<M>)>,
+    hovered: Option<usize>,
+    open: Option<usize>,
+    popup: Popup,
+}
+
+impl<M> MenuBar<M> {
+    /// Creates a new menu bar with no menus.  Only the top row of the
+    /// rectangle is used.
+    pub fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            menus: Vec::new(),
+            hovered: None,
+            open: None,
+            popup: Popup::default(),
+        }
+    }
+
+    /// Adds a menu to the right of the existing menus.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title shown on the bar, with a `&` before the
+    ///   mnemonic.
+    /// * `menu` - The menu that drops down from the title.
+    ///
+    pub fn menu(mut self, title: &str, menu: Menu<M>) -> Self {
+        self.menus.push((MenuLabel::parse(title), menu));
+        self
+    }
+
+    /// Returns true if one of the menus is open.
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Closes the open menu, if any.
+    pub fn close(&mut self) {
+        self.open = None;
+        self.popup.close();
+    }
+
+    /// Returns the area of each title on the bar.
+    fn title_rects(&self) -> impl Iterator<Item = Rect> + '_ {
+        self.menus.iter().scan(self.rect.x + 1, |x, (label, _)| {
+            let rect = Rect::new(*x, self.rect.y, label.width() + 2, 1);
+            *x += rect.width as i32;
+            Some(rect)
+        })
+    }
+
+    /// Opens the menu at the given index.
+    fn open_menu(&mut self, index: usize, screen: (u32, u32), highlight_first: bool) {
+        let Some(title) = self.title_rects().nth(index) else {
+            return;
+        };
+        let p = Point::new(title.x, title.y + 1);
+        self.popup
+            .open(&self.menus[index].1, p, screen, highlight_first);
+        self.open = Some(index);
+    }
+}
+
+impl<M: 'static> Widget<M> for MenuBar<M> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.close();
+    }
+
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>) {
+        let screen = (input.width, input.height);
+        let mouse = &input.mouse;
+        self.hovered = mouse
+            .position
+            .and_then(|p| self.title_rects().position(|rect| contains(rect, p)));
+
+        let Some(open) = self.open else {
+            if let (Some(i), true) = (self.hovered, mouse.left.pressed) {
+                self.open_menu(i, screen, false);
+                return;
+            }
+            for key in input.keys.iter().filter(|key| key.alt && !key.ctrl) {
+                let Key::Char(ch) = key.key else {
+                    continue;
+                };
+                if let Some(i) = self.menus.iter().position(|(title, _)| title.matches(ch)) {
+                    self.open_menu(i, screen, true);
+                    return;
+                }
+            }
+            return;
+        };
+
+        // Clicking the open title closes the menu, and moving over another
+        // title switches to its menu.
+        match self.hovered {
+            Some(i) if i == open && mouse.left.pressed => {
+                self.close();
+                return;
+            }
+            Some(i) if i != open => {
+                self.open_menu(i, screen, false);
+                return;
+            }
+            _ => {}
+        }
+
+        let count = self.menus.len();
+        match self.popup.handle_input(&self.menus[open].1, input) {
+            PopupResult::Nothing => {}
+            PopupResult::Activated(message) => {
+                messages.push(message);
+                self.close();
+            }
+            PopupResult::Closed => self.close(),
+            PopupResult::Left => self.open_menu((open + count - 1) % count, screen, true),
+            PopupResult::Right => self.open_menu((open + 1) % count, screen, true),
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        let bar = Rect::new(self.rect.x, self.rect.y, self.rect.width, 1);
+        image.draw_filled_rect(bar, Char::new(b' ', theme.ink, theme.paper));
+        for (i, ((label, _), rect)) in self.menus.iter().zip(self.title_rects()).enumerate() {
+            let (ink, paper) = theme.colours(self.hovered == Some(i), self.open == Some(i), false);
+            image.draw_filled_rect(rect, Char::new(b' ', ink, paper));
+            label.draw(
+                image,
+                Point::new(rect.x + 1, rect.y),
+                ink,
+                paper,
+                theme.accent,
+            );
+        }
+        if let Some(open) = self.open {
+            self.popup.draw(&self.menus[open].1, image, theme);
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.open.is_some()
+    }
+}
+
+/// A menu that pops up where the right mouse button is clicked within an
+/// area, or wherever the app opens it.
+///
+/// While the menu is open it is controlled with the same keys as the menus of
+/// a [`MenuBar`].
+///
+/// [`MenuBar`]: struct.MenuBar.html
+///
+pub struct ContextMenu<M> {
+    rect: Rect,
+    menu: Menu<M>,
+    popup: Popup,
+    screen: (u32, u32),
+}
+
+impl<M> ContextMenu<M> {
+    /// Creates a new closed context menu.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area in which a right click opens the menu.
+    /// * `menu` - The menu to show.
+    ///
+    pub fn new(rect: Rect, menu: Menu<M>) -> Self {
+        Self {
+            rect,
+            menu,
+            popup: Popup::default(),
+            screen: (0, 0),
+        }
+    }
+
+    /// Opens the menu with its top-left corner at the given point, moved to
+    /// fit on the screen.  The first item is highlighted.
+    pub fn open_at(&mut self, p: Point) {
+        self.popup.open(&self.menu, p, self.screen, true);
+    }
+
+    /// Returns true if the menu is open.
+    pub fn is_open(&self) -> bool {
+        self.popup.is_open()
+    }
+
+    /// Closes the menu.
+    pub fn close(&mut self) {
+        self.popup.close();
+    }
+}
+
+impl<M: 'static> Widget<M> for ContextMenu<M> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>) {
+        self.screen = (input.width, input.height);
+        let mouse = &input.mouse;
+        if !self.popup.is_open() {
+            if let (Some(p), true) = (mouse.position, mouse.right.pressed) {
+                if contains(self.rect, p) {
+                    self.popup.open(&self.menu, p, self.screen, false);
+                }
+            }
+            return;
+        }
+
+        if let PopupResult::Activated(message) = self.popup.handle_input(&self.menu, input) {
+            messages.push(message);
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        self.popup.draw(&self.menu, image, theme);
+    }
+
+    fn captures_input(&self) -> bool {
+        self.popup.is_open()
+    }
+}
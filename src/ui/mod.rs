@@ -0,0 +1,958 @@
+//! Retained mode widgets drawn with chars.
+//!
+//! A [`Ui`] owns a list of widgets, such as [`Button`]s and [`Slider`]s, each
+//! with a rectangle on the screen.  Every tick, [`Ui::update`] passes the
+//! engine's input to the widgets and returns the messages they produced, and
+//! every frame [`Ui::draw`] draws them on to an image.
+//!
+//! Widgets report events through callbacks that turn the event into a message
+//! of the app's own type, so the app can handle every event in one `match`:
+//!
+//! ```no_run
+//! use mage_core::{image::Rect, ui::{Button, Slider, Ui}};
+//!
+//! enum Message {
+//!     Quit,
+//!     Volume(f32),
+//! }
+//!
+//! let mut ui = Ui::new();
+//! ui.add(Button::new(Rect::new(2, 2, 10, 1), "Quit").on_click(|| Message::Quit));
+//! ui.add(Slider::new(Rect::new(2, 4, 20, 1), 0.0, 1.0).on_change(Message::Volume));
+//! ```
+//!
+//! UIs can be used without a mouse.  One widget at a time has the keyboard
+//! focus and is drawn in the theme's focus colours; tab and shift+tab move the
+//! focus in order, the arrow keys move it to the nearest widget in that
+//! direction, and enter or space presses the focused control.
+//!
+//! For quick tools, [`ImmediateUi`] draws controls and returns their input in
+//! a single call instead, such as `if frame.button("OK") { ... }`.
+//!
+//! [`Ui`]: struct.Ui.html
+//! [`Ui::update`]: struct.Ui.html#method.update
+//! [`Ui::draw`]: struct.Ui.html#method.draw
+//! [`Button`]: struct.Button.html
+//! [`Slider`]: struct.Slider.html
+//! [`ImmediateUi`]: struct.ImmediateUi.html
+
+mod command;
+mod console;
+mod dialog;
+mod immediate;
+mod layout;
+mod list;
+mod menu;
+mod panel;
+mod terminal;
+mod toast;
+mod tooltip;
+mod widgets;
+
+pub use command::*;
+pub use console::*;
+pub use dialog::*;
+pub use immediate::*;
+pub use layout::*;
+pub use list::*;
+pub use menu::*;
+pub use panel::*;
+pub use terminal::*;
+pub use toast::Severity;
+pub use tooltip::TooltipTarget;
+
+use toast::Toasts;
+use tooltip::Tooltips;
+pub use widgets::*;
+
+use std::any::Any;
+
+use chrono::Duration;
+
+use crate::{
+    app::TickInput,
+    colour::Colour,
+    image::{Image, Point, Rect},
+    input::{KeyEvent, MouseState},
+    lines::LineStyle,
+    table::Alignment,
+};
+
+/// A key used to control widgets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Enter,
+    Escape,
+    Tab,
+    Backspace,
+    Delete,
+
+    /// A key that types a character, including the space bar.
+    Char(char),
+}
+
+/// A key press along with the modifier keys held at the time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyPress {
+    /// The key that was pressed.
+    pub key: Key,
+
+    /// True if a shift key was held.
+    pub shift: bool,
+
+    /// True if a control key was held.
+    pub ctrl: bool,
+
+    /// True if an alt key was held.
+    pub alt: bool,
+}
+
+impl KeyPress {
+    /// Creates a key press with no modifier keys held.
+    pub fn new(key: Key) -> Self {
+        Self {
+            key,
+            shift: false,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    /// Returns the key press with shift held.
+    pub fn with_shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    /// Returns the key press with control held.
+    pub fn with_ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    /// Returns the key press with alt held.
+    pub fn with_alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Returns true if no modifier keys were held.
+    pub fn is_plain(&self) -> bool {
+        !self.shift && !self.ctrl && !self.alt
+    }
+}
+
+impl From<Key> for KeyPress {
+    fn from(key: Key) -> Self {
+        Self::new(key)
+    }
+}
+
+/// The input passed to the widgets of a [`Ui`] each tick.
+///
+/// [`Ui`]: struct.Ui.html
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UiInput {
+    /// The time since the last tick.
+    pub dt: Duration,
+
+    /// The state of the mouse.
+    pub mouse: MouseState,
+
+    /// The keys pressed since the last tick, in the order they were pressed.
+    pub keys: Vec<KeyPress>,
+
+    /// The width of the screen in characters.  Popups such as menus are kept
+    /// within the screen.  If zero, popups are not moved.
+    pub width: u32,
+
+    /// The height of the screen in characters.  Popups such as menus are kept
+    /// within the screen.  If zero, popups are not moved.
+    pub height: u32,
+}
+
+impl From<&TickInput<'_>> for UiInput {
+    /// Creates the UI input from the engine's input.  The keys pressed that
+    /// control widgets are filled in, followed by a key press for each char
+    /// of the text typed; see [`KeyEvent::to_key_press`].
+    ///
+    /// [`KeyEvent::to_key_press`]: ../input/struct.KeyEvent.html#method.to_key_press
+    ///
+    fn from(tick_input: &TickInput) -> Self {
+        // The space bar types a space, so it is only kept as a shortcut.
+        let keys = tick_input
+            .keys
+            .iter()
+            .filter_map(KeyEvent::to_key_press)
+            .filter(|key| !(key.key == Key::Char(' ') && !key.ctrl && !key.alt));
+        let text = tick_input
+            .text
+            .chars()
+            .map(|ch| KeyPress::new(Key::Char(ch)));
+        Self {
+            dt: tick_input.dt,
+            mouse: tick_input.mouse,
+            keys: keys.chain(text).collect(),
+            width: tick_input.width,
+            height: tick_input.height,
+        }
+    }
+}
+
+/// The colours, borders and spacing used to draw widgets.
+///
+/// Every built-in widget draws itself with the theme of the [`Ui`] it is in,
+/// so a whole UI can be reskinned by changing its theme.  With the `serde`
+/// feature, themes can be loaded from files.  Fields missing from a file keep
+/// their default values, and colours can be written as names or hex strings:
+///
+/// ```json
+/// { "paper": "#202040", "accent": "mage:light_cyan", "border": "Double" }
+/// ```
+///
+/// [`Ui`]: struct.Ui.html
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Theme {
+    /// The foreground colour of text and controls.
+    pub ink: Colour,
+
+    /// The background colour of text and controls.
+    pub paper: Colour,
+
+    /// The foreground colour of a control under the mouse.
+    pub hover_ink: Colour,
+
+    /// The background colour of a control under the mouse.
+    pub hover_paper: Colour,
+
+    /// The foreground colour of a control being pressed.
+    pub active_ink: Colour,
+
+    /// The background colour of a control being pressed.
+    pub active_paper: Colour,
+
+    /// The foreground colour of the control with the keyboard focus.
+    pub focus_ink: Colour,
+
+    /// The background colour of the control with the keyboard focus.
+    pub focus_paper: Colour,
+
+    /// The colour of marks such as ticks, selected options and slider thumbs.
+    pub accent: Colour,
+
+    /// The foreground colour of tooltips.
+    pub tooltip_ink: Colour,
+
+    /// The background colour of tooltips.
+    pub tooltip_paper: Colour,
+
+    /// The foreground colour of toasts.
+    pub toast_ink: Colour,
+
+    /// The background colour of information toasts.
+    pub info_paper: Colour,
+
+    /// The background colour of success toasts.
+    pub success_paper: Colour,
+
+    /// The background colour of warning toasts.
+    pub warning_paper: Colour,
+
+    /// The background colour of error toasts.
+    pub error_paper: Colour,
+
+    /// The lines drawn around menus and the panels behind the front panel.
+    pub border: LineStyle,
+
+    /// The lines drawn around dialogs and the front panel.
+    pub active_border: LineStyle,
+
+    /// The number of blank columns between the edges of tooltips and list
+    /// rows and their text.
+    pub padding: u32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            ink: Colour::LightGray,
+            paper: Colour::Black,
+            hover_ink: Colour::White,
+            hover_paper: Colour::DarkGray,
+            active_ink: Colour::Black,
+            active_paper: Colour::LightGray,
+            focus_ink: Colour::White,
+            focus_paper: Colour::Blue,
+            accent: Colour::Yellow,
+            tooltip_ink: Colour::Black,
+            tooltip_paper: Colour::LightGray,
+            toast_ink: Colour::White,
+            info_paper: Colour::Blue,
+            success_paper: Colour::Green,
+            warning_paper: Colour::Brown,
+            error_paper: Colour::Red,
+            border: LineStyle::Single,
+            active_border: LineStyle::Double,
+            padding: 1,
+        }
+    }
+}
+
+impl Theme {
+    /// Creates the default theme: light grey text on black.
+    pub fn dark() -> Self {
+        Self::default()
+    }
+
+    /// Creates a theme with black text on light grey.
+    pub fn light() -> Self {
+        Self {
+            ink: Colour::Black,
+            paper: Colour::LightGray,
+            hover_ink: Colour::Black,
+            hover_paper: Colour::White,
+            active_ink: Colour::White,
+            active_paper: Colour::DarkGray,
+            focus_ink: Colour::White,
+            focus_paper: Colour::Blue,
+            accent: Colour::Red,
+            tooltip_ink: Colour::LightGray,
+            tooltip_paper: Colour::Black,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a theme in the style of old text mode tools, with white text
+    /// on blue and double borders.
+    pub fn classic() -> Self {
+        Self {
+            ink: Colour::White,
+            paper: Colour::Blue,
+            hover_ink: Colour::Black,
+            hover_paper: Colour::Cyan,
+            active_ink: Colour::Black,
+            active_paper: Colour::LightGray,
+            focus_ink: Colour::Black,
+            focus_paper: Colour::Green,
+            accent: Colour::Yellow,
+            tooltip_ink: Colour::Black,
+            tooltip_paper: Colour::Cyan,
+            toast_ink: Colour::White,
+            info_paper: Colour::Magenta,
+            success_paper: Colour::Green,
+            warning_paper: Colour::Brown,
+            error_paper: Colour::Red,
+            border: LineStyle::Double,
+            active_border: LineStyle::Double,
+            padding: 1,
+        }
+    }
+
+    /// Returns the ink and paper colours for a control in the given state.
+    /// Being pressed takes priority over being under the mouse, which takes
+    /// priority over having the focus.
+    pub fn colours(&self, hovered: bool, active: bool, focused: bool) -> (Colour, Colour) {
+        if active {
+            (self.active_ink, self.active_paper)
+        } else if hovered {
+            (self.hover_ink, self.hover_paper)
+        } else if focused {
+            (self.focus_ink, self.focus_paper)
+        } else {
+            (self.ink, self.paper)
+        }
+    }
+}
+
+/// A control that can be added to a [`Ui`].
+///
+/// `M` is the type of the messages the widget produces when the user
+/// interacts with it.
+///
+/// [`Ui`]: struct.Ui.html
+///
+pub trait Widget<M>: Any {
+    /// Returns the area of the screen covered by the widget.
+    fn rect(&self) -> Rect;
+
+    /// Moves or resizes the widget.
+    fn set_rect(&mut self, rect: Rect);
+
+    /// Updates the widget's state from the input for this tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input for this tick.
+    /// * `messages` - Messages produced by the widget are pushed on to this.
+    ///
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>);
+
+    /// Draws the widget on to an image.
+    fn draw(&self, image: &mut Image, theme: &Theme);
+
+    /// Returns true if the widget should receive all input, such as an open
+    /// menu.  While a widget captures input, no other widgets are given any
+    /// input, and it is drawn on top of the other widgets.
+    fn captures_input(&self) -> bool {
+        false
+    }
+
+    /// Returns true if the widget can take the keyboard focus.
+    fn is_focusable(&self) -> bool {
+        false
+    }
+
+    /// Tells the widget whether it has the keyboard focus, so that it can be
+    /// drawn differently.
+    fn set_focused(&mut self, _focused: bool) {}
+
+    /// Returns true if the widget uses a key while it has the focus, rather
+    /// than the key moving the focus.  Only asked about tab and the arrow
+    /// keys.
+    fn wants_key(&self, _key: &KeyPress) -> bool {
+        false
+    }
+
+    /// Returns true if the widget uses a key even when it doesn't have the
+    /// focus, such as a key that opens it.
+    fn is_shortcut(&self, _key: &KeyPress) -> bool {
+        false
+    }
+}
+
+/// Identifies a widget within a [`Ui`].
+///
+/// [`Ui`]: struct.Ui.html
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WidgetId(usize);
+
+/// A collection of widgets that are updated and drawn together.
+pub struct Ui<M> {
+    /// The widgets in the order they were added.  Removed widgets leave a
+    /// `None` so that the IDs of the other widgets don't change.
+    widgets: Vec<Option<Box<dyn Widget<M>>>>,
+
+    /// The colours, borders and spacing used to draw the widgets.
+    theme: Theme,
+
+    /// The layout setting the areas of the widgets, and the screen size it
+    /// was last solved for.
+    layout: Option<(Layout<WidgetId>, (u32, u32))>,
+
+    /// The widget with the keyboard focus.
+    focus: Option<WidgetId>,
+
+    /// The order the focus moves between widgets with tab, or `None` for the
+    /// order the widgets were added.
+    tab_order: Option<Vec<WidgetId>>,
+
+    /// The text shown when the mouse rests over widgets and areas.
+    tooltips: Tooltips,
+
+    /// The notifications shown above everything else.
+    toasts: Toasts,
+}
+
+impl<M: 'static> Ui<M> {
+    /// Creates a new UI with no widgets and the default theme.
+    pub fn new() -> Self {
+        Self::with_theme(Theme::default())
+    }
+
+    /// Creates a new UI with no widgets and the given theme.
+    pub fn with_theme(theme: Theme) -> Self {
+        Self {
+            widgets: Vec::new(),
+            theme,
+            layout: None,
+            focus: None,
+            tab_order: None,
+            tooltips: Tooltips::new(),
+            toasts: Toasts::new(),
+        }
+    }
+
+    /// Returns the theme used to draw the widgets.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Changes the theme used to draw the widgets.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Adds a widget to the UI.
+    ///
+    /// # Returns
+    ///
+    /// The ID used to access the widget later with [`get`] and [`get_mut`].
+    ///
+    /// [`get`]: struct.Ui.html#method.get
+    /// [`get_mut`]: struct.Ui.html#method.get_mut
+    ///
+    pub fn add(&mut self, widget: impl Widget<M>) -> WidgetId {
+        self.widgets.push(Some(Box::new(widget)));
+        WidgetId(self.widgets.len() - 1)
+    }
+
+    /// Removes a widget from the UI, returning true if it was there.
+    pub fn remove(&mut self, id: WidgetId) -> bool {
+        if self.focus == Some(id) {
+            self.focus = None;
+        }
+        self.tooltips.remove(TooltipTarget::Widget(id));
+        self.widgets
+            .get_mut(id.0)
+            .and_then(|widget| widget.take())
+            .is_some()
+    }
+
+    /// Returns a widget, or `None` if there is no widget of type `W` with the
+    /// given ID.
+    pub fn get<W: Widget<M>>(&self, id: WidgetId) -> Option<&W> {
+        let widget: &dyn Any = self.widgets.get(id.0)?.as_deref()?;
+        widget.downcast_ref()
+    }
+
+    /// Returns a mutable widget, or `None` if there is no widget of type `W`
+    /// with the given ID.
+    pub fn get_mut<W: Widget<M>>(&mut self, id: WidgetId) -> Option<&mut W> {
+        let widget: &mut dyn Any = self.widgets.get_mut(id.0)?.as_deref_mut()?;
+        widget.downcast_mut()
+    }
+
+    /// Sets the layout used to compute the areas of the widgets.
+    ///
+    /// The layout is solved for the whole screen the next time the UI is
+    /// updated, and again whenever the size of the screen changes.  Widgets
+    /// not in the layout keep their areas.
+    ///
+    pub fn set_layout(&mut self, layout: Layout<WidgetId>) {
+        self.layout = Some((layout, (0, 0)));
+    }
+
+    /// Removes the layout, leaving the widgets where they are.
+    pub fn clear_layout(&mut self) {
+        self.layout = None;
+    }
+
+    /// Solves a layout for an area and moves the widgets in it to their new
+    /// areas.
+    pub fn apply_layout(&mut self, layout: &Layout<WidgetId>, area: Rect) {
+        for (id, rect) in layout.solve(area) {
+            if let Some(Some(widget)) = self.widgets.get_mut(id.0) {
+                widget.set_rect(rect);
+            }
+        }
+    }
+
+    /// Sets the text shown when the mouse rests over a widget or an area of
+    /// the screen, replacing any tooltip it already had.  The text is wrapped
+    /// to fit, and can contain newlines.
+    pub fn set_tooltip(&mut self, target: impl Into<TooltipTarget>, text: impl Into<String>) {
+        self.tooltips.set(target.into(), text.into());
+    }
+
+    /// Removes the tooltip of a widget or area, returning true if it had one.
+    pub fn remove_tooltip(&mut self, target: impl Into<TooltipTarget>) -> bool {
+        self.tooltips.remove(target.into())
+    }
+
+    /// Sets how long the mouse must rest over a widget or area before its
+    /// tooltip is shown.  The default is half a second.
+    pub fn set_tooltip_delay(&mut self, delay: Duration) {
+        self.tooltips.set_delay(delay);
+    }
+
+    /// Shows a short notification at the top-right of the screen, above
+    /// everything else.  Notifications stack below each other, slide in and
+    /// out, and disappear when clicked or after a few seconds.
+    pub fn notify(&mut self, severity: Severity, text: impl Into<String>) {
+        self.toasts.push(severity, text.into());
+    }
+
+    /// Removes every notification.
+    pub fn clear_notifications(&mut self) {
+        self.toasts.clear();
+    }
+
+    /// Sets how long new notifications are shown for, including sliding in
+    /// and out.  The default is 4 seconds.
+    pub fn set_notification_duration(&mut self, duration: Duration) {
+        self.toasts.set_duration(duration);
+    }
+
+    /// Returns the widget with the keyboard focus.
+    pub fn focused(&self) -> Option<WidgetId> {
+        self.focus
+    }
+
+    /// Gives the keyboard focus to a widget, or removes the focus from every
+    /// widget if `None`.  Widgets that can't take the focus are ignored.
+    pub fn set_focus(&mut self, id: Option<WidgetId>) {
+        if id.is_some_and(|id| !self.is_focusable(id)) || id == self.focus {
+            return;
+        }
+        for (id, focused) in [(self.focus, false), (id, true)] {
+            if let Some(Some(widget)) = id.and_then(|id| self.widgets.get_mut(id.0)) {
+                widget.set_focused(focused);
+            }
+        }
+        self.focus = id;
+    }
+
+    /// Sets the order the focus moves between widgets when tab is pressed.
+    /// Widgets not in the list can still be focused with the mouse or arrow
+    /// keys.
+    pub fn set_tab_order(&mut self, order: Vec<WidgetId>) {
+        self.tab_order = Some(order);
+    }
+
+    /// Moves the focus to the next widget in the tab order, wrapping around
+    /// at the end.
+    pub fn focus_next(&mut self) {
+        self.focus_step(true);
+    }
+
+    /// Moves the focus to the previous widget in the tab order, wrapping
+    /// around at the start.
+    pub fn focus_prev(&mut self) {
+        self.focus_step(false);
+    }
+
+    /// Moves the focus forwards or backwards through the tab order.
+    fn focus_step(&mut self, forward: bool) {
+        let order: Vec<WidgetId> = match &self.tab_order {
+            Some(order) => order.clone(),
+            None => (0..self.widgets.len()).map(WidgetId).collect(),
+        };
+        let order: Vec<WidgetId> = order
+            .into_iter()
+            .filter(|&id| self.is_focusable(id))
+            .collect();
+        let n = order.len();
+        if n == 0 {
+            return;
+        }
+        let current = self
+            .focus
+            .and_then(|focus| order.iter().position(|&id| id == focus));
+        let next = match (current, forward) {
+            (Some(i), true) => (i + 1) % n,
+            (Some(i), false) => (i + n - 1) % n,
+            (None, true) => 0,
+            (None, false) => n - 1,
+        };
+        self.set_focus(Some(order[next]));
+    }
+
+    /// Moves the focus to the nearest widget in the direction of an arrow
+    /// key.  If no widget has the focus, the first widget in the tab order is
+    /// focused.
+    fn focus_towards(&mut self, key: Key) {
+        let Some(from) = self.focus.and_then(|id| self.rect_of(id)) else {
+            self.focus_next();
+            return;
+        };
+
+        // Compare centres, doubled to keep them whole numbers.
+        let centre = |rect: Rect| {
+            (
+                rect.x as i64 * 2 + rect.width as i64,
+                rect.y as i64 * 2 + rect.height as i64,
+            )
+        };
+        let (fx, fy) = centre(from);
+        let nearest = (0..self.widgets.len())
+            .map(WidgetId)
+            .filter(|&id| Some(id) != self.focus && self.is_focusable(id))
+            .filter_map(|id| {
+                let (x, y) = centre(self.rect_of(id)?);
+                let (dx, dy) = (x - fx, y - fy);
+                let (ahead, across) = match key {
+                    Key::Left => (-dx, dy),
+                    Key::Right => (dx, dy),
+                    Key::Up => (-dy, dx),
+                    _ => (dy, dx),
+                };
+                (ahead > 0).then_some((ahead + across.abs() * 2, id))
+            })
+            .min_by_key(|&(score, _)| score);
+        if let Some((_, id)) = nearest {
+            self.set_focus(Some(id));
+        }
+    }
+
+    /// Returns true if the widget exists and can take the focus.
+    fn is_focusable(&self, id: WidgetId) -> bool {
+        self.widgets
+            .get(id.0)
+            .and_then(|widget| widget.as_ref())
+            .is_some_and(|widget| widget.is_focusable())
+    }
+
+    /// Returns the area of a widget.
+    fn rect_of(&self, id: WidgetId) -> Option<Rect> {
+        Some(self.widgets.get(id.0)?.as_ref()?.rect())
+    }
+
+    /// Passes the input for this tick to the widgets, or only to the last
+    /// added widget that captures input if there is one.
+    ///
+    /// Clicking a widget that can take the focus gives it the focus.  Tab and
+    /// shift+tab move the focus through the tab order, and the arrow keys
+    /// move it to the nearest widget in that direction, unless the focused
+    /// widget uses those keys itself.  Other keys are only given to the
+    /// focused widget, apart from shortcuts, which are given to every widget.
+    /// Keys pressed with alt or control are always shortcuts, and widgets can
+    /// claim other keys as shortcuts with [`Widget::is_shortcut`].
+    ///
+    /// # Returns
+    ///
+    /// The messages produced by the widgets, in the order the widgets were
+    /// added.
+    ///
+    /// [`Widget::is_shortcut`]: trait.Widget.html#method.is_shortcut
+    ///
+    pub fn update(&mut self, input: &UiInput) -> Vec<M> {
+        let screen = (input.width, input.height);
+        if let Some((layout, size)) = self.layout.take() {
+            if size != screen && screen != (0, 0) {
+                self.apply_layout(&layout, Rect::new(0, 0, screen.0, screen.1));
+            }
+            self.layout = Some((layout, screen));
+        }
+
+        // Toasts are on top of everything, so the widgets below a toast
+        // don't see the mouse.
+        let mut input = input.clone();
+        if self.toasts.update(&input, &self.theme) {
+            input.mouse.position = None;
+        }
+        let input = &input;
+
+        let capturing = self
+            .widgets
+            .iter()
+            .flatten()
+            .any(|widget| widget.captures_input());
+        let widgets = &self.widgets;
+        self.tooltips.update(input, !capturing, |id| {
+            Some(widgets.get(id.0)?.as_ref()?.rect())
+        });
+
+        let mut messages = Vec::new();
+        let capturing = self
+            .widgets
+            .iter_mut()
+            .flatten()
+            .rev()
+            .find(|widget| widget.captures_input());
+        if let Some(widget) = capturing {
+            widget.handle_input(input, &mut messages);
+            return messages;
+        }
+
+        let mouse = &input.mouse;
+        if let (Some(p), true) = (mouse.position, mouse.left.pressed) {
+            let clicked = (0..self.widgets.len()).rev().map(WidgetId).find(|&id| {
+                self.is_focusable(id) && self.rect_of(id).is_some_and(|r| contains(r, p))
+            });
+            if clicked.is_some() {
+                self.set_focus(clicked);
+            }
+        }
+
+        // Sort the keys into those that move the focus, those for the focused
+        // widget only, and shortcuts for every widget.
+        let mut keys = Vec::new();
+        for key in &input.keys {
+            let wanted = self
+                .focus
+                .and_then(|id| self.widgets.get(id.0)?.as_ref())
+                .is_some_and(|widget| widget.wants_key(key));
+            let shortcut = key.alt
+                || key.ctrl
+                || self
+                    .widgets
+                    .iter()
+                    .flatten()
+                    .any(|widget| widget.is_shortcut(key));
+            let navigation = matches!(key.key, Key::Up | Key::Down | Key::Left | Key::Right);
+            match key.key {
+                _ if wanted => keys.push((*key, false)),
+                _ if shortcut => keys.push((*key, true)),
+                Key::Tab if !key.ctrl && !key.alt => self.focus_step(!key.shift),
+                _ if navigation && key.is_plain() => self.focus_towards(key.key),
+                _ => keys.push((*key, false)),
+            }
+        }
+
+        let focus = self.focus;
+        for (i, widget) in self.widgets.iter_mut().enumerate() {
+            let Some(widget) = widget else {
+                continue;
+            };
+            let focused = focus == Some(WidgetId(i));
+            let input = UiInput {
+                keys: keys
+                    .iter()
+                    .filter(|(_, shortcut)| focused || *shortcut)
+                    .map(|(key, _)| *key)
+                    .collect(),
+                ..input.clone()
+            };
+            widget.handle_input(&input, &mut messages);
+        }
+        messages
+    }
+
+    /// Draws every widget on to an image, in the order they were added.
+    /// Widgets that capture input are drawn after all the others.
+    pub fn draw(&self, image: &mut Image) {
+        self.draw_with_theme(image, &self.theme);
+    }
+
+    /// Draws the widgets with a theme other than the UI's own, such as the
+    /// theme of the UI a panel is shown in.
+    fn draw_with_theme(&self, image: &mut Image, theme: &Theme) {
+        let (capturing, others): (Vec<_>, Vec<_>) = self
+            .widgets
+            .iter()
+            .flatten()
+            .partition(|widget| widget.captures_input());
+        for widget in others.into_iter().chain(capturing) {
+            widget.draw(image, theme);
+        }
+        self.tooltips.draw(image, theme);
+        self.toasts.draw(image, theme);
+    }
+}
+
+impl<M: 'static> Default for Ui<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tracks the mouse over a control that is clicked with the left button.
+///
+/// A click happens when the button is pressed and released over the control.
+/// Pressing over the control and releasing elsewhere cancels the click.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct ClickState {
+    /// True if the mouse is over the control.
+    hovered: bool,
+
+    /// True if the button was pressed over the control and is still down.
+    pressed: bool,
+}
+
+impl ClickState {
+    /// Updates the state from the mouse, returning true if the control was
+    /// clicked.
+    fn update(&mut self, rect: Rect, mouse: &MouseState) -> bool {
+        self.hovered = mouse.position.is_some_and(|p| contains(rect, p));
+        if mouse.left.pressed && self.hovered {
+            self.pressed = true;
+        }
+        if mouse.left.released || !mouse.left.down {
+            let clicked = self.pressed && mouse.left.released && self.hovered;
+            self.pressed = false;
+            clicked
+        } else {
+            false
+        }
+    }
+}
+
+/// Returns true if a key should press the focused control: enter or space.
+fn is_activate_key(key: &KeyPress) -> bool {
+    key.is_plain() && matches!(key.key, Key::Enter | Key::Char(' '))
+}
+
+/// Returns true if the point is inside the rectangle.
+fn contains(rect: Rect, p: Point) -> bool {
+    let (x, y) = (p.x as i64 - rect.x as i64, p.y as i64 - rect.y as i64);
+    (0..rect.width as i64).contains(&x) && (0..rect.height as i64).contains(&y)
+}
+
+/// Draws a line of text within a width, padding the rest of the width with
+/// the paper colour and clipping text that doesn't fit.
+///
+/// Characters are converted to code page 437 as [`Image::draw_string_unicode`].
+///
+/// [`Image::draw_string_unicode`]: ../image/struct.Image.html#method.draw_string_unicode
+///
+fn draw_text(
+    image: &mut Image,
+    p: Point,
+    width: u32,
+    text: &str,
+    alignment: Alignment,
+    ink: Colour,
+    paper: Colour,
+) {
+    let len = text.chars().count().min(width as usize);
+    let offset = match alignment {
+        Alignment::Left => 0,
+        Alignment::Centre => (width as usize - len) / 2,
+        Alignment::Right => width as usize - len,
+    };
+    let text: String = std::iter::repeat_n(' ', offset)
+        .chain(text.chars().take(len))
+        .chain(std::iter::repeat(' '))
+        .take(width as usize)
+        .collect();
+    image.draw_string_unicode(p, &text, ink, paper);
+}
+
+/// Splits text into lines no wider than a width, breaking at spaces where
+/// possible and at newlines.  Words wider than the width are split.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut len = 0;
+        for word in paragraph.split(' ') {
+            let mut word: Vec<char> = word.chars().collect();
+            loop {
+                let space = usize::from(len > 0);
+                if len + space + word.len() <= width {
+                    if space > 0 {
+                        line.push(' ');
+                    }
+                    line.extend(&word);
+                    len += space + word.len();
+                    break;
+                }
+                if len > 0 {
+                    lines.push(std::mem::take(&mut line));
+                    len = 0;
+                    continue;
+                }
+                let rest = word.split_off(width);
+                line.extend(&word);
+                lines.push(std::mem::take(&mut line));
+                word = rest;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
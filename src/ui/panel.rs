@@ -0,0 +1,499 @@
+//! Movable, resizable windows within the screen.
+//!
+//! A [`PanelStack`] holds a number of [`Panel`]s, each a titled frame around
+//! its own [`Ui`].  Panels are dragged by their title bars, resized by the
+//! grip in their bottom-right corners and closed with the button in their
+//! top-right corners.  Clicking a panel brings it to the front.
+//!
+//! ```no_run
+//! use mage_core::{image::Rect, ui::{Button, Panel, PanelStack}};
+//!
+//! enum Message {
+//!     Attack,
+//!     InventoryClosed,
+//! }
+//!
+//! let mut inventory = Panel::new(Rect::new(10, 5, 30, 12), "Inventory")
+//!     .on_close(|| Message::InventoryClosed);
+//! inventory
+//!     .ui_mut()
+//!     .add(Button::new(Rect::new(1, 1, 10, 1), "Attack").on_click(|| Message::Attack));
+//!
+//! let mut panels = PanelStack::new(Rect::new(0, 0, 80, 25));
+//! panels.add(inventory);
+//! ```
+//!
+//! [`PanelStack`]: struct.PanelStack.html
+//! [`Panel`]: struct.Panel.html
+//! [`Ui`]: struct.Ui.html
+
+use crate::{
+    image::{BlitOptions, Char, Image, Point, Rect},
+    table::Alignment,
+};
+
+use super::{contains, draw_text, KeyPress, Theme, Ui, UiInput, Widget};
+
+/// The glyph of the close button of a panel, `■`.
+const CLOSE_GLYPH: u8 = 0xfe;
+
+/// The glyph of the resize grip of a panel, `▓`.
+const GRIP_GLYPH: u8 = 0xb2;
+
+/// A titled window holding its own widgets, shown by a [`PanelStack`].
+///
+/// The widgets are added to the panel's [`Ui`], and their rectangles are
+/// relative to the inside of the panel's frame.  Widgets are clipped to the
+/// inside of the frame, and a layout set on the panel's UI is solved for the
+/// inside of the frame whenever the panel is resized.
+///
+/// [`PanelStack`]: struct.PanelStack.html
+/// [`Ui`]: struct.Ui.html
+///
+pub struct Panel<M> {
+    rect: Rect,
+    title: String,
+    min_width: u32,
+    min_height: u32,
+    resizable: bool,
+    closable: bool,
+    ui: Ui<M>,
+    on_close: Option<Box<dyn Fn() -> M>>,
+}
+
+impl<M: 'static> Panel<M> {
+    /// Creates a new empty panel that can be resized and closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area of the panel, including its frame.
+    /// * `title` - The title shown on the top of the frame.
+    ///
+    pub fn new(rect: Rect, title: impl Into<String>) -> Self {
+        Self {
+            rect,
+            title: title.into(),
+            min_width: 8,
+            min_height: 3,
+            resizable: true,
+            closable: true,
+            ui: Ui::new(),
+            on_close: None,
+        }
+    }
+
+    /// Sets the smallest size the panel can be resized to, including its
+    /// frame.  The default is 8 by 3.
+    pub fn with_min_size(mut self, width: u32, height: u32) -> Self {
+        self.min_width = width.max(3);
+        self.min_height = height.max(2);
+        self
+    }
+
+    /// Sets whether the panel can be resized with the mouse.
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Sets whether the panel has a close button.
+    pub fn with_closable(mut self, closable: bool) -> Self {
+        self.closable = closable;
+        self
+    }
+
+    /// Sets the function that creates the message produced when the panel is
+    /// closed with its close button.
+    pub fn on_close(mut self, f: impl Fn() -> M + 'static) -> Self {
+        self.on_close = Some(Box::new(f));
+        self
+    }
+
+    /// Returns the title of the panel.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Changes the title of the panel.
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = title.into();
+    }
+
+    /// Returns the area of the panel, including its frame.
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Moves or resizes the panel.  The panel is kept within its stack's area
+    /// the next time the stack is updated.
+    pub fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    /// Returns the area inside the panel's frame, where its widgets are drawn.
+    pub fn client_rect(&self) -> Rect {
+        Rect::new(
+            self.rect.x + 1,
+            self.rect.y + 1,
+            self.rect.width.saturating_sub(2),
+            self.rect.height.saturating_sub(2),
+        )
+    }
+
+    /// Returns the UI holding the panel's widgets.
+    pub fn ui(&self) -> &Ui<M> {
+        &self.ui
+    }
+
+    /// Returns the UI holding the panel's widgets, so widgets can be added
+    /// and changed.
+    pub fn ui_mut(&mut self) -> &mut Ui<M> {
+        &mut self.ui
+    }
+
+    /// Returns the position of the close button, if the panel has one.
+    fn close_button(&self) -> Option<Point> {
+        (self.closable && self.rect.width >= 4)
+            .then(|| Point::new(self.rect.x + self.rect.width as i32 - 2, self.rect.y))
+    }
+
+    /// Returns the position of the resize grip, if the panel has one.
+    fn grip(&self) -> Option<Point> {
+        (self.resizable && self.rect.width > 0 && self.rect.height > 1).then(|| {
+            Point::new(
+                self.rect.x + self.rect.width as i32 - 1,
+                self.rect.y + self.rect.height as i32 - 1,
+            )
+        })
+    }
+
+    /// Keeps the panel at least its minimum size and within an area, moving
+    /// it before shrinking it.
+    fn keep_within(&mut self, area: Rect) {
+        let width = self.rect.width.max(self.min_width).min(area.width);
+        let height = self.rect.height.max(self.min_height).min(area.height);
+        let right = area.x + (area.width - width) as i32;
+        let bottom = area.y + (area.height - height) as i32;
+        self.rect = Rect::new(
+            self.rect.x.clamp(area.x, right),
+            self.rect.y.clamp(area.y, bottom),
+            width,
+            height,
+        );
+    }
+
+    /// Draws the panel's frame and widgets.
+    fn draw(&self, image: &mut Image, theme: &Theme, front: bool) {
+        let rect = self.rect;
+        if rect.width == 0 || rect.height == 0 {
+            return;
+        }
+        image.draw_filled_rect(rect, Char::new(b' ', theme.ink, theme.paper));
+
+        // The panel at the front has the active border and a highlighted
+        // title.
+        let (line_style, title_ink) = if front {
+            (theme.active_border, theme.accent)
+        } else {
+            (theme.border, theme.ink)
+        };
+        image.draw_box(rect, line_style, theme.ink, theme.paper);
+        if !self.title.is_empty() {
+            let title = format!(" {} ", self.title);
+            let width = rect.width.saturating_sub(if self.closable { 5 } else { 2 });
+            let p = Point::new(rect.x + 1, rect.y);
+            let title_width = (title.chars().count() as u32).min(width);
+            draw_text(
+                image,
+                p,
+                title_width,
+                &title,
+                Alignment::Left,
+                title_ink,
+                theme.paper,
+            );
+        }
+        if let Some(p) = self.close_button() {
+            image.draw_char(p, Char::new(CLOSE_GLYPH, theme.accent, theme.paper));
+        }
+        if let Some(p) = self.grip() {
+            image.draw_char(p, Char::new(GRIP_GLYPH, theme.ink, theme.paper));
+        }
+
+        // Draw the widgets on their own image so that they are clipped to the
+        // inside of the frame.
+        let client = self.client_rect();
+        if client.width > 0 && client.height > 0 {
+            let mut inside = Image::new(client.width, client.height);
+            inside.clear(theme.ink, theme.paper);
+            self.ui.draw_with_theme(&mut inside, theme);
+            image.blit(
+                Point::new(client.x, client.y),
+                &inside,
+                inside.rect(),
+                BlitOptions::default(),
+            );
+        }
+    }
+}
+
+/// Identifies a panel in a [`PanelStack`].
+///
+/// [`PanelStack`]: struct.PanelStack.html
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PanelId(usize);
+
+/// What a panel is being dragged to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DragKind {
+    /// The panel is moved, keeping the mouse at the same place on the panel.
+    Move(Point),
+
+    /// The panel's bottom-right corner follows the mouse.
+    Resize,
+}
+
+/// A panel being dragged with the mouse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Drag {
+    id: PanelId,
+    kind: DragKind,
+}
+
+/// Overlapping panels within an area, drawn from back to front.
+///
+/// Only the panel at the front is given keys, and only the panel under the
+/// mouse is given the mouse.  While the mouse is over a panel, or a panel is
+/// being dragged, the stack captures input so that widgets beneath the panels
+/// don't respond to it.
+pub struct PanelStack<M> {
+    rect: Rect,
+    panels: Vec<Option<Panel<M>>>,
+    order: Vec<PanelId>,
+    drag: Option<Drag>,
+    hovered: bool,
+}
+
+impl<M: 'static> PanelStack<M> {
+    /// Creates a new stack with no panels.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - The area the panels are kept within, usually the whole
+    ///   screen.
+    ///
+    pub fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            panels: Vec::new(),
+            order: Vec::new(),
+            drag: None,
+            hovered: false,
+        }
+    }
+
+    /// Adds a panel at the front of the stack, returning its ID.
+    pub fn add(&mut self, panel: Panel<M>) -> PanelId {
+        let id = PanelId(self.panels.len());
+        self.panels.push(Some(panel));
+        self.order.push(id);
+        id
+    }
+
+    /// Removes a panel without producing a message, returning it if it was
+    /// there.
+    pub fn remove(&mut self, id: PanelId) -> Option<Panel<M>> {
+        self.order.retain(|&other| other != id);
+        if self.drag.is_some_and(|drag| drag.id == id) {
+            self.drag = None;
+        }
+        self.panels.get_mut(id.0)?.take()
+    }
+
+    /// Returns a panel.
+    pub fn get(&self, id: PanelId) -> Option<&Panel<M>> {
+        self.panels.get(id.0)?.as_ref()
+    }
+
+    /// Returns a panel so it can be changed.
+    pub fn get_mut(&mut self, id: PanelId) -> Option<&mut Panel<M>> {
+        self.panels.get_mut(id.0)?.as_mut()
+    }
+
+    /// Brings a panel to the front of the stack.
+    pub fn raise(&mut self, id: PanelId) {
+        if let Some(i) = self.order.iter().position(|&other| other == id) {
+            self.order.remove(i);
+            self.order.push(id);
+        }
+    }
+
+    /// Returns the panel at the front of the stack.
+    pub fn front(&self) -> Option<PanelId> {
+        self.order.last().copied()
+    }
+
+    /// Returns the panels from back to front.
+    pub fn order(&self) -> &[PanelId] {
+        &self.order
+    }
+
+    /// Returns the number of panels.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Returns true if there are no panels.
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Returns the front-most panel containing a point.
+    fn panel_at(&self, p: Point) -> Option<PanelId> {
+        self.order
+            .iter()
+            .rev()
+            .copied()
+            .find(|&id| self.get(id).is_some_and(|panel| contains(panel.rect, p)))
+    }
+
+    /// Handles pressing the mouse on a panel's frame, closing the panel or
+    /// starting to drag it.
+    fn press_frame(&mut self, id: PanelId, p: Point, messages: &mut Vec<M>) {
+        let Some(panel) = self.get(id) else {
+            return;
+        };
+        if panel.close_button() == Some(p) {
+            if let Some(panel) = self.remove(id) {
+                messages.extend(panel.on_close.as_ref().map(|f| f()));
+            }
+            return;
+        }
+        let rect = panel.rect;
+        let kind = if panel.grip() == Some(p) {
+            DragKind::Resize
+        } else if p.y == rect.y {
+            DragKind::Move(Point::new(p.x - rect.x, p.y - rect.y))
+        } else {
+            return;
+        };
+        self.drag = Some(Drag { id, kind });
+    }
+
+    /// Moves or resizes the dragged panel to follow the mouse.
+    fn update_drag(&mut self, p: Point) {
+        let Some(drag) = self.drag else {
+            return;
+        };
+        let area = self.rect;
+        let Some(panel) = self.get_mut(drag.id) else {
+            return;
+        };
+        let rect = panel.rect;
+        match drag.kind {
+            DragKind::Move(grab) => {
+                panel.rect = Rect::new(p.x - grab.x, p.y - grab.y, rect.width, rect.height);
+            }
+            DragKind::Resize => {
+                // Don't let the corner pass the edge of the area, as the
+                // panel would be moved rather than resized.
+                let right = (p.x + 1).min(area.x + area.width as i32);
+                let bottom = (p.y + 1).min(area.y + area.height as i32);
+                let width = (right - rect.x).max(0) as u32;
+                let height = (bottom - rect.y).max(0) as u32;
+                panel.rect = Rect::new(rect.x, rect.y, width, height);
+            }
+        }
+    }
+}
+
+impl<M: 'static> Widget<M> for PanelStack<M> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>) {
+        let mouse = &input.mouse;
+        if let (Some(p), true) = (mouse.position, mouse.left.pressed) {
+            if let Some(id) = self.panel_at(p) {
+                self.raise(id);
+                self.press_frame(id, p, messages);
+            }
+        }
+        if let Some(p) = mouse.position {
+            self.update_drag(p);
+        }
+        if !mouse.left.down {
+            self.drag = None;
+        }
+
+        let area = self.rect;
+        for panel in self.panels.iter_mut().flatten() {
+            panel.keep_within(area);
+        }
+
+        let hovered = mouse.position.and_then(|p| self.panel_at(p));
+        self.hovered = hovered.is_some();
+        let front = self.front();
+        let dragging = self.drag.is_some();
+        for &id in &self.order.clone() {
+            let Some(panel) = self.get_mut(id) else {
+                continue;
+            };
+            let client = panel.client_rect();
+            let mut inside = UiInput {
+                keys: Vec::new(),
+                width: client.width,
+                height: client.height,
+                ..input.clone()
+            };
+            if Some(id) == front {
+                inside.keys.clone_from(&input.keys);
+            }
+            inside.mouse.position = mouse
+                .position
+                .filter(|&p| !dragging && hovered == Some(id) && contains(client, p))
+                .map(|p| Point::new(p.x - client.x, p.y - client.y));
+            messages.extend(panel.ui.update(&inside));
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        let front = self.front();
+        for &id in &self.order {
+            if let Some(panel) = self.get(id) {
+                panel.draw(image, theme, Some(id) == front);
+            }
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.hovered || self.drag.is_some()
+    }
+
+    fn is_focusable(&self) -> bool {
+        !self.is_empty()
+    }
+
+    /// The panel at the front moves the focus between its own widgets, so
+    /// the stack keeps every key while it has panels.
+    fn wants_key(&self, _key: &KeyPress) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl<M: 'static> Default for PanelStack<M> {
+    /// Creates an empty panel stack with an empty area.  Set the area with
+    /// [`Widget::set_rect`] before adding panels.
+    ///
+    /// [`Widget::set_rect`]: trait.Widget.html#tymethod.set_rect
+    ///
+    fn default() -> Self {
+        Self::new(Rect::default())
+    }
+}
@@ -0,0 +1,789 @@
+//! A terminal emulator that draws text containing ANSI escape sequences.
+
+use std::collections::VecDeque;
+
+use crate::{
+    codepage::{cp437_to_unicode, unicode_to_cp437},
+    colour::Colour,
+    image::{Char, Image, Point, Rect, DEFAULT_FALLBACK_GLYPH},
+};
+
+use super::{contains, Key, KeyPress, Theme, UiInput, Widget};
+
+/// The number of rows scrolled for each notch of the mouse wheel.
+const WHEEL_ROWS: usize = 3;
+
+/// The columns tab stops are placed at multiples of.
+const TAB_WIDTH: usize = 8;
+
+/// The longest parameter string kept for a control sequence.
+const MAX_CSI_PARAMS_LEN: usize = 64;
+
+/// A foreground or background colour set by an escape sequence.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum TermColour {
+    /// The theme's colour.
+    #[default]
+    Default,
+
+    /// One of the 256 xterm colours.
+    Indexed(u8),
+
+    /// A 24-bit colour.
+    Direct(Colour),
+}
+
+/// The attributes set by SGR (`ESC[...m`) sequences.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Attrs {
+    ink: TermColour,
+    paper: TermColour,
+    bold: bool,
+    inverse: bool,
+}
+
+impl Attrs {
+    /// Returns a cell showing a glyph with these attributes.  Bold text in
+    /// one of the 8 basic colours is shown in the bright version of it.
+    fn cell(&self, ch: u8) -> Cell {
+        let resolve = |colour, bright: bool| match colour {
+            TermColour::Default => None,
+            TermColour::Indexed(index) if bright && index < 8 => {
+                Some(Colour::from_xterm(index + 8))
+            }
+            TermColour::Indexed(index) => Some(Colour::from_xterm(index)),
+            TermColour::Direct(colour) => Some(colour),
+        };
+        Cell {
+            ch,
+            ink: resolve(self.ink, self.bold),
+            paper: resolve(self.paper, false),
+            inverse: self.inverse,
+        }
+    }
+}
+
+/// A char on the terminal's screen.  Colours of `None` use the theme's ink
+/// and paper.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Cell {
+    ch: u8,
+    ink: Option<Colour>,
+    paper: Option<Colour>,
+    inverse: bool,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Attrs::default().cell(b' ')
+    }
+}
+
+/// Where the parser is within an escape sequence.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum Parse {
+    /// Printing text.
+    #[default]
+    Text,
+
+    /// After an `ESC`.
+    Escape,
+
+    /// Within a control sequence, `ESC[`, collecting its parameters.
+    Csi(String),
+
+    /// Within an operating system command, `ESC]`, which is ignored up to
+    /// its terminating `BEL` or `ESC\`.
+    Osc,
+
+    /// After an `ESC` within an operating system command.
+    OscEscape,
+}
+
+/// A terminal emulator showing text written to it, which can contain ANSI
+/// escape sequences for colours and cursor movement.
+///
+/// The terminal is useful for showing the output of other programs, talking
+/// to MUD servers or making computers inside a game.  Lines scrolled off the
+/// top are kept in a scrollback buffer, which can be scrolled through with
+/// the mouse wheel or shift with page up, page down, home and end.
+///
+/// While the terminal has the focus, the keys pressed are turned into the
+/// bytes a terminal would send, such as `ESC[A` for the up key, and passed to
+/// the function set with [`on_input`], so they can be sent on to a program or
+/// server.
+///
+/// The escape sequences understood are those for moving the cursor, erasing,
+/// inserting and deleting, scrolling, saving and restoring the cursor, and
+/// setting the 16 and 256 colour and 24-bit colours, bold and inverse text.
+/// Other sequences are ignored.
+///
+/// [`on_input`]: struct.Terminal.html#method.on_input
+///
+pub struct Terminal<M> {
+    rect: Rect,
+    screen: Vec<Vec<Cell>>,
+    scrollback: VecDeque<Vec<Cell>>,
+    capacity: usize,
+    offset: usize,
+    cursor: (usize, usize),
+    saved_cursor: ((usize, usize), Attrs),
+    attrs: Attrs,
+    cursor_visible: bool,
+    wrap_pending: bool,
+    newline_mode: bool,
+    parse: Parse,
+    utf8: Vec<u8>,
+    focused: bool,
+    on_input: Option<Box<dyn Fn(String) -> M>>,
+}
+
+impl<M> Terminal<M> {
+    /// Creates a new empty terminal that keeps 1000 lines of scrollback.
+    pub fn new(rect: Rect) -> Self {
+        let (width, height) = (rect.width as usize, rect.height as usize);
+        Self {
+            rect,
+            screen: vec![vec![Cell::default(); width]; height],
+            scrollback: VecDeque::new(),
+            capacity: 1000,
+            offset: 0,
+            cursor: (0, 0),
+            saved_cursor: ((0, 0), Attrs::default()),
+            attrs: Attrs::default(),
+            cursor_visible: true,
+            wrap_pending: false,
+            newline_mode: true,
+            parse: Parse::Text,
+            utf8: Vec::new(),
+            focused: false,
+            on_input: None,
+        }
+    }
+
+    /// Sets the number of lines kept after they scroll off the top.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self.trim_scrollback();
+        self
+    }
+
+    /// Sets whether a line feed also returns the cursor to the left edge.
+    /// This is on by default, as most text uses a line feed alone to end each
+    /// line.  Turn it off for programs that send a carriage return and line
+    /// feed.
+    pub fn with_newline_mode(mut self, newline_mode: bool) -> Self {
+        self.newline_mode = newline_mode;
+        self
+    }
+
+    /// Sets the function that creates the message produced when keys are
+    /// pressed while the terminal has the focus.  It is passed the text a
+    /// terminal would send for the keys.
+    pub fn on_input(mut self, f: impl Fn(String) -> M + 'static) -> Self {
+        self.on_input = Some(Box::new(f));
+        self
+    }
+
+    /// Writes text to the terminal at the cursor, following any escape
+    /// sequences in it.  Escape sequences can be split between writes.
+    pub fn write(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.feed(ch);
+        }
+    }
+
+    /// Writes UTF-8 encoded bytes to the terminal, such as the output of
+    /// another program.  Characters split between writes are joined, and
+    /// invalid bytes are shown as `?`.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.utf8.extend_from_slice(bytes);
+        let mut bytes = std::mem::take(&mut self.utf8);
+        let mut start = 0;
+        loop {
+            match std::str::from_utf8(&bytes[start..]) {
+                Ok(text) => {
+                    self.write(text);
+                    break;
+                }
+                Err(error) => {
+                    let valid = start + error.valid_up_to();
+                    let text = std::str::from_utf8(&bytes[start..valid]).unwrap_or_default();
+                    self.write(text);
+                    match error.error_len() {
+                        Some(len) => {
+                            self.feed(char::REPLACEMENT_CHARACTER);
+                            start = valid + len;
+                        }
+                        None => {
+                            // Keep the start of a character split between
+                            // writes.
+                            bytes.drain(..valid);
+                            self.utf8 = bytes;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Clears the screen and scrollback, and moves the cursor to the top-left
+    /// corner.
+    pub fn clear(&mut self) {
+        self.scrollback.clear();
+        self.offset = 0;
+        self.erase_rows(0, self.screen.len());
+        self.cursor = (0, 0);
+        self.wrap_pending = false;
+    }
+
+    /// Clears the terminal and resets the colours, cursor and any partly
+    /// written escape sequence.
+    pub fn reset(&mut self) {
+        self.attrs = Attrs::default();
+        self.saved_cursor = ((0, 0), Attrs::default());
+        self.cursor_visible = true;
+        self.parse = Parse::Text;
+        self.utf8.clear();
+        self.clear();
+    }
+
+    /// Returns the position of the cursor within the screen, from the
+    /// top-left corner.
+    pub fn cursor(&self) -> Point {
+        Point::new(self.cursor.0 as i32, self.cursor.1 as i32)
+    }
+
+    /// Returns the text on the screen, one line per row, without trailing
+    /// spaces.  The scrollback is not included.
+    pub fn screen_text(&self) -> String {
+        let rows: Vec<String> = self.screen.iter().map(|row| row_text(row)).collect();
+        rows.join("\n")
+    }
+
+    /// Returns the number of lines in the scrollback.
+    pub fn scrollback_len(&self) -> usize {
+        self.scrollback.len()
+    }
+
+    /// Returns the number of lines the view is scrolled back by, or 0 if it
+    /// shows the screen.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Scrolls the view back to show the screen.
+    pub fn scroll_to_bottom(&mut self) {
+        self.offset = 0;
+    }
+
+    /// Returns the width of the screen.
+    fn width(&self) -> usize {
+        self.rect.width as usize
+    }
+
+    /// Returns the height of the screen.
+    fn height(&self) -> usize {
+        self.screen.len()
+    }
+
+    /// Handles one character of text.
+    fn feed(&mut self, ch: char) {
+        match std::mem::take(&mut self.parse) {
+            Parse::Text => match ch {
+                '\x1b' => self.parse = Parse::Escape,
+                '\n' | '\x0b' | '\x0c' => {
+                    if self.newline_mode {
+                        self.cursor.0 = 0;
+                    }
+                    self.line_feed();
+                }
+                '\r' => self.move_to(0, self.cursor.1),
+                '\x08' => self.move_to(self.cursor.0.saturating_sub(1), self.cursor.1),
+                '\t' => {
+                    let x = (self.cursor.0 / TAB_WIDTH + 1) * TAB_WIDTH;
+                    self.move_to(x.min(self.width().saturating_sub(1)), self.cursor.1);
+                }
+                ch if ch.is_control() => {}
+                ch => self.print(unicode_to_cp437(ch).unwrap_or(DEFAULT_FALLBACK_GLYPH)),
+            },
+            Parse::Escape => match ch {
+                '[' => self.parse = Parse::Csi(String::new()),
+                ']' => self.parse = Parse::Osc,
+                '7' => self.saved_cursor = (self.cursor, self.attrs),
+                '8' => self.restore_cursor(),
+                'c' => self.reset(),
+                'D' => self.line_feed(),
+                'E' => {
+                    self.cursor.0 = 0;
+                    self.line_feed();
+                }
+                'M' => self.reverse_line_feed(),
+                _ => {}
+            },
+            Parse::Csi(mut params) => match ch {
+                '\x40'..='\x7e' => self.control_sequence(&params, ch),
+                _ => {
+                    // Drop parameters past the limit rather than letting an
+                    // unterminated sequence grow without bound.
+                    if params.len() < MAX_CSI_PARAMS_LEN {
+                        params.push(ch);
+                    }
+                    self.parse = Parse::Csi(params);
+                }
+            },
+            Parse::Osc => match ch {
+                '\x07' => {}
+                '\x1b' => self.parse = Parse::OscEscape,
+                _ => self.parse = Parse::Osc,
+            },
+            Parse::OscEscape => {
+                if ch != '\\' {
+                    self.parse = Parse::Osc;
+                }
+            }
+        }
+    }
+
+    /// Carries out a control sequence, `ESC[` followed by parameters and a
+    /// final character.
+    fn control_sequence(&mut self, params: &str, command: char) {
+        let private = params.starts_with('?');
+        let args: Vec<usize> = params
+            .trim_start_matches('?')
+            .split(';')
+            .map(|arg| arg.parse().unwrap_or(0))
+            .collect();
+        // Returns an argument, treating a missing or zero argument as 1.  The
+        // stream is untrusted, so counts are clamped to the screen size to
+        // keep the cursor arithmetic from overflowing.
+        let max_count = self.width().max(self.height()).max(1);
+        let count = |i: usize| args.get(i).copied().unwrap_or(0).clamp(1, max_count);
+        let arg = |i: usize| args.get(i).copied().unwrap_or(0);
+        let (x, y) = self.cursor;
+        match command {
+            'A' => self.move_to(x, y.saturating_sub(count(0))),
+            'B' | 'e' => self.move_to(x, y + count(0)),
+            'C' | 'a' => self.move_to(x + count(0), y),
+            'D' => self.move_to(x.saturating_sub(count(0)), y),
+            'E' => self.move_to(0, y + count(0)),
+            'F' => self.move_to(0, y.saturating_sub(count(0))),
+            'G' | '`' => self.move_to(count(0) - 1, y),
+            'd' => self.move_to(x, count(0) - 1),
+            'H' | 'f' => self.move_to(count(1) - 1, count(0) - 1),
+            'J' => match arg(0) {
+                0 => {
+                    self.erase_line(x, self.width());
+                    self.erase_rows(y + 1, self.height());
+                }
+                1 => {
+                    self.erase_rows(0, y);
+                    self.erase_line(0, x + 1);
+                }
+                2 => self.erase_rows(0, self.height()),
+                3 => {
+                    self.scrollback.clear();
+                    self.offset = 0;
+                }
+                _ => {}
+            },
+            'K' => match arg(0) {
+                0 => self.erase_line(x, self.width()),
+                1 => self.erase_line(0, x + 1),
+                2 => self.erase_line(0, self.width()),
+                _ => {}
+            },
+            'X' => self.erase_line(x, x + count(0)),
+            '@' => self.insert_chars(count(0)),
+            'P' => self.delete_chars(count(0)),
+            'L' => self.insert_lines(count(0)),
+            'M' => self.delete_lines(count(0)),
+            'S' => (0..count(0)).for_each(|_| self.scroll_up()),
+            'T' => (0..count(0)).for_each(|_| self.scroll_down()),
+            'm' => self.select_graphic_rendition(&args),
+            's' => self.saved_cursor = (self.cursor, self.attrs),
+            'u' => self.restore_cursor(),
+            'h' | 'l' if private && args.contains(&25) => self.cursor_visible = command == 'h',
+            _ => {}
+        }
+    }
+
+    /// Changes the attributes of the text written after it.
+    fn select_graphic_rendition(&mut self, args: &[usize]) {
+        let mut args = args.iter().copied();
+        while let Some(arg) = args.next() {
+            match arg {
+                0 => self.attrs = Attrs::default(),
+                1 => self.attrs.bold = true,
+                22 => self.attrs.bold = false,
+                7 => self.attrs.inverse = true,
+                27 => self.attrs.inverse = false,
+                30..=37 => self.attrs.ink = TermColour::Indexed((arg - 30) as u8),
+                39 => self.attrs.ink = TermColour::Default,
+                40..=47 => self.attrs.paper = TermColour::Indexed((arg - 40) as u8),
+                49 => self.attrs.paper = TermColour::Default,
+                90..=97 => self.attrs.ink = TermColour::Indexed((arg - 90 + 8) as u8),
+                100..=107 => self.attrs.paper = TermColour::Indexed((arg - 100 + 8) as u8),
+                38 | 48 => {
+                    let colour = match args.next() {
+                        Some(5) => args.next().map(|i| TermColour::Indexed(i.min(255) as u8)),
+                        Some(2) => {
+                            let mut channel = || args.next().unwrap_or(0).min(255) as u8;
+                            let (r, g, b) = (channel(), channel(), channel());
+                            Some(TermColour::Direct(Colour::Rgb(r, g, b)))
+                        }
+                        _ => None,
+                    };
+                    if let Some(colour) = colour {
+                        if arg == 38 {
+                            self.attrs.ink = colour;
+                        } else {
+                            self.attrs.paper = colour;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Draws a glyph at the cursor and moves the cursor on, wrapping to the
+    /// next line after the last column.
+    fn print(&mut self, ch: u8) {
+        if self.width() == 0 || self.height() == 0 {
+            return;
+        }
+        if self.wrap_pending {
+            self.cursor.0 = 0;
+            self.line_feed();
+        }
+        let (x, y) = self.cursor;
+        self.screen[y][x] = self.attrs.cell(ch);
+        if x + 1 < self.width() {
+            self.cursor.0 += 1;
+        } else {
+            self.wrap_pending = true;
+        }
+    }
+
+    /// Moves the cursor, keeping it on the screen.
+    fn move_to(&mut self, x: usize, y: usize) {
+        self.cursor = (
+            x.min(self.width().saturating_sub(1)),
+            y.min(self.height().saturating_sub(1)),
+        );
+        self.wrap_pending = false;
+    }
+
+    /// Moves the cursor back to where it was saved.
+    fn restore_cursor(&mut self) {
+        let ((x, y), attrs) = self.saved_cursor;
+        self.attrs = attrs;
+        self.move_to(x, y);
+    }
+
+    /// Moves the cursor down a row, scrolling the screen up at the bottom.
+    fn line_feed(&mut self) {
+        self.wrap_pending = false;
+        if self.cursor.1 + 1 < self.height() {
+            self.cursor.1 += 1;
+        } else {
+            self.scroll_up();
+        }
+    }
+
+    /// Moves the cursor up a row, scrolling the screen down at the top.
+    fn reverse_line_feed(&mut self) {
+        self.wrap_pending = false;
+        if self.cursor.1 > 0 {
+            self.cursor.1 -= 1;
+        } else {
+            self.scroll_down();
+        }
+    }
+
+    /// Scrolls the screen up a row, moving the top row into the scrollback.
+    fn scroll_up(&mut self) {
+        if self.screen.is_empty() {
+            return;
+        }
+        let row = self.screen.remove(0);
+        self.screen.push(self.blank_row());
+        self.scrollback.push_back(row);
+        // Keep showing the same lines if the view is scrolled back.
+        if self.offset > 0 {
+            self.offset += 1;
+        }
+        self.trim_scrollback();
+    }
+
+    /// Scrolls the screen down a row, losing the bottom row.
+    fn scroll_down(&mut self) {
+        if self.screen.is_empty() {
+            return;
+        }
+        self.screen.pop();
+        self.screen.insert(0, self.blank_row());
+    }
+
+    /// Drops the oldest lines of the scrollback beyond its capacity.
+    fn trim_scrollback(&mut self) {
+        while self.scrollback.len() > self.capacity {
+            self.scrollback.pop_front();
+        }
+        self.offset = self.offset.min(self.scrollback.len());
+    }
+
+    /// Returns a row of blank cells in the current background colour.
+    fn blank_row(&self) -> Vec<Cell> {
+        vec![self.blank(); self.width()]
+    }
+
+    /// Returns a blank cell in the current background colour.
+    fn blank(&self) -> Cell {
+        Attrs {
+            ink: TermColour::Default,
+            bold: false,
+            ..self.attrs
+        }
+        .cell(b' ')
+    }
+
+    /// Erases the columns from `start` up to `end` of the cursor's row.
+    fn erase_line(&mut self, start: usize, end: usize) {
+        let blank = self.blank();
+        let (width, y) = (self.width(), self.cursor.1);
+        if let Some(row) = self.screen.get_mut(y) {
+            row[start.min(width)..end.min(width)].fill(blank);
+        }
+        self.wrap_pending = false;
+    }
+
+    /// Erases the rows from `start` up to `end`.
+    fn erase_rows(&mut self, start: usize, end: usize) {
+        let blank = self.blank_row();
+        let end = end.min(self.height());
+        for row in self.screen[start.min(end)..end].iter_mut() {
+            row.clone_from(&blank);
+        }
+    }
+
+    /// Inserts blank cells at the cursor, moving the rest of the row right.
+    fn insert_chars(&mut self, n: usize) {
+        let blank = self.blank();
+        let (width, (x, y)) = (self.width(), self.cursor);
+        if let Some(row) = self.screen.get_mut(y) {
+            let n = n.min(width - x);
+            row.truncate(width - n);
+            row.splice(x..x, std::iter::repeat_n(blank, n));
+        }
+    }
+
+    /// Deletes cells at the cursor, moving the rest of the row left.
+    fn delete_chars(&mut self, n: usize) {
+        let blank = self.blank();
+        let (width, (x, y)) = (self.width(), self.cursor);
+        if let Some(row) = self.screen.get_mut(y) {
+            let n = n.min(width - x);
+            row.drain(x..x + n);
+            row.extend(std::iter::repeat_n(blank, n));
+        }
+    }
+
+    /// Inserts blank rows at the cursor, moving the rows below down.
+    fn insert_lines(&mut self, n: usize) {
+        let y = self.cursor.1;
+        for _ in 0..n.min(self.height() - y) {
+            self.screen.pop();
+            self.screen.insert(y, self.blank_row());
+        }
+        self.cursor.0 = 0;
+    }
+
+    /// Deletes rows at the cursor, moving the rows below up.
+    fn delete_lines(&mut self, n: usize) {
+        let y = self.cursor.1;
+        for _ in 0..n.min(self.height() - y) {
+            self.screen.remove(y);
+            self.screen.push(self.blank_row());
+        }
+        self.cursor.0 = 0;
+    }
+
+    /// Scrolls the view back through the scrollback by a number of rows, or
+    /// forward if negative.
+    fn scroll_view(&mut self, rows: i64) {
+        let offset = (self.offset as i64 + rows).max(0) as usize;
+        self.offset = offset.min(self.scrollback.len());
+    }
+}
+
+impl<M: 'static> Widget<M> for Terminal<M> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        let (width, height) = (rect.width as usize, rect.height as usize);
+        let blank = self.blank();
+        for row in &mut self.screen {
+            row.resize(width, blank);
+        }
+        // Move rows above the cursor into the scrollback, rather than losing
+        // the cursor's row, when the screen gets shorter.
+        while self.screen.len() > height {
+            if self.cursor.1 > 0 {
+                self.scrollback.push_back(self.screen.remove(0));
+                self.cursor.1 -= 1;
+            } else {
+                self.screen.pop();
+            }
+        }
+        while self.screen.len() < height {
+            self.screen.push(vec![blank; width]);
+        }
+        self.trim_scrollback();
+        let (x, y) = self.cursor;
+        self.move_to(x, y);
+    }
+
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>) {
+        let mouse = &input.mouse;
+        if mouse.position.is_some_and(|p| contains(self.rect, p)) && mouse.wheel != 0 {
+            self.scroll_view(mouse.wheel as i64 * WHEEL_ROWS as i64);
+        }
+
+        let page = self.height().saturating_sub(1).max(1) as i64;
+        let mut sent = String::new();
+        for key in &input.keys {
+            if key.shift && !key.ctrl && !key.alt {
+                match key.key {
+                    Key::PageUp => self.scroll_view(page),
+                    Key::PageDown => self.scroll_view(-page),
+                    Key::Home => self.offset = self.scrollback.len(),
+                    Key::End => self.offset = 0,
+                    _ => sent.push_str(&key_text(key)),
+                }
+            } else {
+                sent.push_str(&key_text(key));
+            }
+        }
+        if !sent.is_empty() {
+            self.offset = 0;
+            messages.extend(self.on_input.as_ref().map(|f| f(sent)));
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        let top = self.scrollback.len() - self.offset;
+        let rows = self.scrollback.iter().chain(&self.screen).skip(top);
+        for (y, row) in rows.take(self.height()).enumerate() {
+            for x in 0..self.width() {
+                let mut cell = row.get(x).copied().unwrap_or_default();
+                let at_cursor = (x, y) == self.cursor;
+                if at_cursor && self.focused && self.cursor_visible && self.offset == 0 {
+                    cell.inverse = !cell.inverse;
+                }
+                let ink = cell.ink.unwrap_or(theme.ink);
+                let paper = cell.paper.unwrap_or(theme.paper);
+                let (ink, paper) = if cell.inverse {
+                    (paper, ink)
+                } else {
+                    (ink, paper)
+                };
+                let p = Point::new(self.rect.x + x as i32, self.rect.y + y as i32);
+                image.draw_char(p, Char::new(cell.ch, ink, paper));
+            }
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn wants_key(&self, _key: &KeyPress) -> bool {
+        true
+    }
+}
+
+/// Returns the text of a row without trailing spaces.
+fn row_text(row: &[Cell]) -> String {
+    let text: String = row.iter().map(|cell| cp437_to_unicode(cell.ch)).collect();
+    text.trim_end().to_string()
+}
+
+/// Returns the text a terminal sends for a key.
+fn key_text(key: &KeyPress) -> String {
+    let text = match key.key {
+        Key::Up => "\x1b[A",
+        Key::Down => "\x1b[B",
+        Key::Right => "\x1b[C",
+        Key::Left => "\x1b[D",
+        Key::Home => "\x1b[H",
+        Key::End => "\x1b[F",
+        Key::PageUp => "\x1b[5~",
+        Key::PageDown => "\x1b[6~",
+        Key::Delete => "\x1b[3~",
+        Key::Enter => "\r",
+        Key::Escape => "\x1b",
+        Key::Tab if key.shift => "\x1b[Z",
+        Key::Tab => "\t",
+        Key::Backspace => "\x7f",
+        Key::Char(ch) => {
+            let ch = match ch {
+                // Control and a letter send the letter's control code.
+                'a'..='z' | 'A'..='Z' if key.ctrl => {
+                    char::from(ch.to_ascii_uppercase() as u8 - b'@')
+                }
+                ch => ch,
+            };
+            return if key.alt {
+                format!("\x1b{}", ch)
+            } else {
+                ch.to_string()
+            };
+        }
+    };
+    text.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terminal() -> Terminal<()> {
+        Terminal::new(Rect::new(0, 0, 10, 4))
+    }
+
+    #[test]
+    fn huge_counts_are_clamped_to_the_screen() {
+        let mut terminal = terminal();
+        terminal.write("ab\x1b[18446744073709551615C");
+        assert_eq!(terminal.cursor(), Point::new(9, 0));
+        terminal.write("\x1b[18446744073709551615B");
+        assert_eq!(terminal.cursor(), Point::new(9, 3));
+        terminal.write("\x1b[18446744073709551615E");
+        assert_eq!(terminal.cursor(), Point::new(0, 3));
+        terminal.write("\x1b[18446744073709551615;18446744073709551615H");
+        assert_eq!(terminal.cursor(), Point::new(9, 3));
+        terminal.write("\x1b[1;1H\x1b[18446744073709551615X\x1b[18446744073709551615S");
+        terminal.write("\x1b[18446744073709551615@\x1b[18446744073709551615P");
+        terminal.write("\x1b[18446744073709551615L\x1b[18446744073709551615M");
+        assert_eq!(terminal.screen_text(), "\n\n\n");
+    }
+
+    #[test]
+    fn long_control_sequences_are_bounded() {
+        let mut terminal = terminal();
+        terminal.write("\x1b[");
+        terminal.write(&"1;".repeat(10_000));
+        terminal.write("mok");
+        assert_eq!(terminal.screen_text(), "ok\n\n\n");
+    }
+}
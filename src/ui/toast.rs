@@ -0,0 +1,193 @@
+//! Short-lived notifications that slide in at the top-right of the screen.
+
+use chrono::Duration;
+
+use crate::{
+    colour::Colour,
+    image::{Char, Image, Point, Rect},
+    table::Alignment,
+};
+
+use super::{contains, draw_text, wrap_text, Theme, UiInput};
+
+/// The widest a line of a toast can be before it is wrapped.
+const MAX_TOAST_WIDTH: u32 = 30;
+
+/// The number of rows and columns between toasts and the edges of the screen,
+/// and the number of rows between toasts.
+const TOAST_MARGIN: u32 = 1;
+
+/// How long a toast takes to slide in and out, in milliseconds.
+const SLIDE_MILLISECONDS: i64 = 250;
+
+/// How important a toast is, which sets its colour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// Something the user may want to know.
+    #[default]
+    Info,
+
+    /// Something finished as the user wanted.
+    Success,
+
+    /// Something the user should look at.
+    Warning,
+
+    /// Something went wrong.
+    Error,
+}
+
+impl Severity {
+    /// Returns the background colour of a toast of this severity.
+    fn paper(self, theme: &Theme) -> Colour {
+        match self {
+            Self::Info => theme.info_paper,
+            Self::Success => theme.success_paper,
+            Self::Warning => theme.warning_paper,
+            Self::Error => theme.error_paper,
+        }
+    }
+}
+
+/// A toast being shown.
+struct Toast {
+    severity: Severity,
+    text: String,
+
+    /// How long the toast has been shown.
+    age: Duration,
+
+    /// How long the toast is shown for, including sliding in and out.
+    duration: Duration,
+}
+
+/// The toasts of a [`Ui`], which are drawn above everything else.
+///
+/// [`Ui`]: struct.Ui.html
+///
+pub(super) struct Toasts {
+    /// The toasts being shown, oldest first.
+    toasts: Vec<Toast>,
+
+    /// How long new toasts are shown for.
+    duration: Duration,
+}
+
+impl Toasts {
+    /// Creates an empty set of toasts shown for 4 seconds each.
+    pub(super) fn new() -> Self {
+        Self {
+            toasts: Vec::new(),
+            duration: Duration::seconds(4),
+        }
+    }
+
+    /// Shows a new toast below the others.
+    pub(super) fn push(&mut self, severity: Severity, text: String) {
+        self.toasts.push(Toast {
+            severity,
+            text,
+            age: Duration::zero(),
+            duration: self.duration,
+        });
+    }
+
+    /// Removes every toast.
+    pub(super) fn clear(&mut self) {
+        self.toasts.clear();
+    }
+
+    /// Sets how long new toasts are shown for.
+    pub(super) fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
+    /// Ages the toasts, removing those that have expired or were clicked.
+    ///
+    /// # Returns
+    ///
+    /// True if the mouse is over a toast, so the widgets below shouldn't see
+    /// it.
+    ///
+    pub(super) fn update(&mut self, input: &UiInput, theme: &Theme) -> bool {
+        let mouse = &input.mouse;
+        let hovered = (input.width > 0)
+            .then(|| self.layout(input.width, theme))
+            .and_then(|layout| {
+                let p = mouse.position?;
+                layout.into_iter().find(|(_, rect, _)| contains(*rect, p))
+            })
+            .map(|(index, _, _)| index);
+        if let (Some(index), true) = (hovered, mouse.left.pressed) {
+            self.toasts.remove(index);
+        }
+
+        for toast in &mut self.toasts {
+            toast.age += input.dt;
+        }
+        self.toasts.retain(|toast| toast.age < toast.duration);
+        hovered.is_some()
+    }
+
+    /// Draws the toasts stacked down the right of the image, sliding in from
+    /// the right edge when they appear and out again when they expire.
+    pub(super) fn draw(&self, image: &mut Image, theme: &Theme) {
+        let slide = Duration::milliseconds(SLIDE_MILLISECONDS);
+        let padding = padding(image.width, theme);
+        for (index, rect, lines) in self.layout(image.width, theme) {
+            let toast = &self.toasts[index];
+            let shown = toast.age.min(toast.duration - toast.age);
+            let hidden = if shown < slide {
+                let distance = (rect.width + TOAST_MARGIN) as i64;
+                distance - distance * shown.num_milliseconds() / SLIDE_MILLISECONDS
+            } else {
+                0
+            };
+            let rect = Rect::new(rect.x + hidden as i32, rect.y, rect.width, rect.height);
+
+            let (ink, paper) = (theme.toast_ink, toast.severity.paper(theme));
+            image.draw_filled_rect(rect, Char::new(b' ', ink, paper));
+            for (row, line) in lines.iter().enumerate() {
+                let p = Point::new(rect.x + padding as i32, rect.y + row as i32);
+                let width = rect.width - padding * 2;
+                draw_text(image, p, width, line, Alignment::Left, ink, paper);
+            }
+        }
+    }
+
+    /// Works out where each toast goes on a screen of the given width.
+    ///
+    /// # Returns
+    ///
+    /// The index, area and wrapped lines of each toast, in the order they are
+    /// stacked from the top.
+    ///
+    fn layout(&self, screen_width: u32, theme: &Theme) -> Vec<(usize, Rect, Vec<String>)> {
+        let padding = padding(screen_width, theme);
+        let available = screen_width.saturating_sub(TOAST_MARGIN * 2);
+        let text_width = MAX_TOAST_WIDTH.min(available.saturating_sub(padding * 2));
+        let mut y = TOAST_MARGIN as i32;
+        let mut layout = Vec::new();
+        for (index, toast) in self.toasts.iter().enumerate() {
+            let lines = wrap_text(&toast.text, text_width as usize);
+            let width = lines
+                .iter()
+                .map(|line| line.chars().count() as u32)
+                .max()
+                .unwrap_or(0)
+                + padding * 2;
+            let x = screen_width.saturating_sub(width + TOAST_MARGIN) as i32;
+            let rect = Rect::new(x, y, width, lines.len() as u32);
+            y += (rect.height + TOAST_MARGIN) as i32;
+            layout.push((index, rect, lines));
+        }
+        layout
+    }
+}
+
+/// Returns the number of blank columns either side of the text of toasts on a
+/// screen of the given width, leaving room for at least one column of text.
+fn padding(screen_width: u32, theme: &Theme) -> u32 {
+    let available = screen_width.saturating_sub(TOAST_MARGIN * 2);
+    theme.padding.min(available.saturating_sub(1) / 2)
+}
@@ -0,0 +1,184 @@
+//! Text shown when the mouse rests over a widget or area.
+
+use chrono::Duration;
+
+use crate::{
+    image::{Char, Image, Point, Rect},
+    table::Alignment,
+};
+
+use super::{contains, draw_text, wrap_text, Theme, UiInput, WidgetId};
+
+/// The widest a line of a tooltip can be before it is wrapped.
+const MAX_TOOLTIP_WIDTH: u32 = 40;
+
+/// What the mouse must be over to show a tooltip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TooltipTarget {
+    /// The area of a widget, which follows the widget as it moves.
+    Widget(WidgetId),
+
+    /// An area of the screen.
+    Area(Rect),
+}
+
+impl From<WidgetId> for TooltipTarget {
+    fn from(id: WidgetId) -> Self {
+        Self::Widget(id)
+    }
+}
+
+impl From<Rect> for TooltipTarget {
+    fn from(rect: Rect) -> Self {
+        Self::Area(rect)
+    }
+}
+
+/// The tooltips of a [`Ui`] and which one is shown.
+///
+/// [`Ui`]: struct.Ui.html
+///
+pub(super) struct Tooltips {
+    /// The tooltips, in the order they were set.
+    entries: Vec<(TooltipTarget, String)>,
+
+    /// How long the mouse must rest over a target before its tooltip is
+    /// shown.
+    delay: Duration,
+
+    /// The index of the tooltip whose target the mouse is over.
+    hovered: Option<usize>,
+
+    /// How long the mouse has been over the hovered target.
+    elapsed: Duration,
+
+    /// True if a mouse button was pressed over the hovered target, which
+    /// hides its tooltip until the mouse leaves it.
+    dismissed: bool,
+
+    /// The position of the mouse.
+    mouse: Option<Point>,
+}
+
+impl Tooltips {
+    /// Creates an empty set of tooltips with a half second delay.
+    pub(super) fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            delay: Duration::milliseconds(500),
+            hovered: None,
+            elapsed: Duration::zero(),
+            dismissed: false,
+            mouse: None,
+        }
+    }
+
+    /// Sets the text of the tooltip for a target, replacing any it had.
+    pub(super) fn set(&mut self, target: TooltipTarget, text: String) {
+        match self.entries.iter_mut().find(|(other, _)| *other == target) {
+            Some(entry) => entry.1 = text,
+            None => self.entries.push((target, text)),
+        }
+    }
+
+    /// Removes the tooltip for a target, returning true if it had one.
+    pub(super) fn remove(&mut self, target: TooltipTarget) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|(other, _)| *other != target);
+        if self.entries.len() != len {
+            self.hovered = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets how long the mouse must rest over a target before its tooltip is
+    /// shown.
+    pub(super) fn set_delay(&mut self, delay: Duration) {
+        self.delay = delay;
+    }
+
+    /// Works out which tooltip the mouse is over and for how long.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input for this tick.
+    /// * `enabled` - False if tooltips should be hidden, such as while a menu
+    ///   is open.
+    /// * `rect_of` - Returns the area of a widget, or `None` if it has been
+    ///   removed.
+    ///
+    pub(super) fn update(
+        &mut self,
+        input: &UiInput,
+        enabled: bool,
+        rect_of: impl Fn(WidgetId) -> Option<Rect>,
+    ) {
+        let mouse = &input.mouse;
+        self.mouse = mouse.position;
+
+        // Later tooltips are on top of earlier ones.
+        let hovered = mouse.position.filter(|_| enabled).and_then(|p| {
+            self.entries.iter().rposition(|(target, _)| {
+                let rect = match *target {
+                    TooltipTarget::Widget(id) => rect_of(id),
+                    TooltipTarget::Area(rect) => Some(rect),
+                };
+                rect.is_some_and(|rect| contains(rect, p))
+            })
+        });
+        if hovered != self.hovered {
+            self.hovered = hovered;
+            self.elapsed = Duration::zero();
+            self.dismissed = false;
+        } else {
+            self.elapsed += input.dt;
+        }
+        if mouse.left.pressed || mouse.right.pressed || mouse.middle.pressed {
+            self.dismissed = true;
+        }
+    }
+
+    /// Draws the tooltip the mouse has rested over for long enough, below and
+    /// to the right of the mouse, or above it if there's no room below.  The
+    /// tooltip is kept within the image.
+    pub(super) fn draw(&self, image: &mut Image, theme: &Theme) {
+        let (Some(index), Some(mouse)) = (self.hovered, self.mouse) else {
+            return;
+        };
+        if self.dismissed || self.elapsed < self.delay {
+            return;
+        }
+
+        let (screen_width, screen_height) = (image.width, image.height);
+        let padding = theme.padding.min(screen_width.saturating_sub(1) / 2);
+        let text_width = MAX_TOOLTIP_WIDTH.min(screen_width.saturating_sub(padding * 2));
+        let lines = wrap_text(&self.entries[index].1, text_width as usize);
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count() as u32)
+            .max()
+            .unwrap_or(0)
+            + padding * 2;
+        let height = (lines.len() as u32).min(screen_height);
+
+        let max_x = screen_width.saturating_sub(width) as i32;
+        let x = (mouse.x + 1).clamp(0, max_x.max(0));
+        let below = mouse.y + 1;
+        let y = if below + height as i32 <= screen_height as i32 {
+            below
+        } else {
+            (mouse.y - height as i32).max(0)
+        };
+
+        let (ink, paper) = (theme.tooltip_ink, theme.tooltip_paper);
+        let rect = Rect::new(x, y, width, height);
+        image.draw_filled_rect(rect, Char::new(b' ', ink, paper));
+        for (row, line) in lines.iter().take(height as usize).enumerate() {
+            let p = Point::new(x + padding as i32, y + row as i32);
+            let width = width - padding * 2;
+            draw_text(image, p, width, line, Alignment::Left, ink, paper);
+        }
+    }
+}
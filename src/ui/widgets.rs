@@ -0,0 +1,522 @@
+//! The basic widgets: labels, buttons, checkboxes, radio groups and sliders.
+
+use crate::{
+    image::{Char, Image, Point, Rect},
+    table::Alignment,
+};
+
+use super::{
+    contains, draw_text, is_activate_key, ClickState, Key, KeyPress, Theme, UiInput, Widget,
+};
+
+/// The glyph drawn in a ticked checkbox, `√`.
+pub(super) const TICK_GLYPH: u8 = 0xfb;
+
+/// The glyph drawn in a selected radio option, `•`.
+pub(super) const SELECTED_GLYPH: u8 = 0x07;
+
+/// The glyph of the track of a slider, `─`.
+pub(super) const TRACK_GLYPH: u8 = 0xc4;
+
+/// The glyph of the thumb of a slider, `█`.
+pub(super) const THUMB_GLYPH: u8 = 0xdb;
+
+/// A line of text that doesn't respond to input.
+pub struct Label {
+    rect: Rect,
+    text: String,
+    alignment: Alignment,
+}
+
+impl Label {
+    /// Creates a new left-aligned label.  Only the top row of the rectangle is
+    /// used, and text wider than the rectangle is clipped.
+    pub fn new(rect: Rect, text: impl Into<String>) -> Self {
+        Self {
+            rect,
+            text: text.into(),
+            alignment: Alignment::Left,
+        }
+    }
+
+    /// Sets how the text is positioned within the rectangle.
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Returns the text of the label.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Changes the text of the label.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+    }
+}
+
+impl<M> Widget<M> for Label {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn handle_input(&mut self, _input: &UiInput, _messages: &mut Vec<M>) {}
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        let p = Point::new(self.rect.x, self.rect.y);
+        draw_text(
+            image,
+            p,
+            self.rect.width,
+            &self.text,
+            self.alignment,
+            theme.ink,
+            theme.paper,
+        );
+    }
+}
+
+/// A button that produces a message when clicked, or when enter or space is
+/// pressed while it has the focus.
+pub struct Button<M> {
+    rect: Rect,
+    text: String,
+    state: ClickState,
+    focused: bool,
+    on_click: Option<Box<dyn Fn() -> M>>,
+}
+
+impl<M> Button<M> {
+    /// Creates a new button.  The text is centred vertically and horizontally
+    /// within the rectangle.
+    pub fn new(rect: Rect, text: impl Into<String>) -> Self {
+        Self {
+            rect,
+            text: text.into(),
+            state: ClickState::default(),
+            focused: false,
+            on_click: None,
+        }
+    }
+
+    /// Sets the function that creates the message produced when the button is
+    /// clicked.
+    pub fn on_click(mut self, f: impl Fn() -> M + 'static) -> Self {
+        self.on_click = Some(Box::new(f));
+        self
+    }
+
+    /// Returns the text of the button.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Changes the text of the button.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+    }
+}
+
+impl<M: 'static> Widget<M> for Button<M> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>) {
+        let clicks = usize::from(self.state.update(self.rect, &input.mouse))
+            + input.keys.iter().filter(|key| is_activate_key(key)).count();
+        for _ in 0..clicks {
+            messages.extend(self.on_click.as_ref().map(|f| f()));
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        let hovered = self.state.hovered;
+        let (ink, paper) = theme.colours(hovered, hovered && self.state.pressed, self.focused);
+        image.draw_filled_rect(self.rect, Char::new(b' ', ink, paper));
+        let p = Point::new(self.rect.x, self.rect.y + self.rect.height as i32 / 2);
+        draw_text(
+            image,
+            p,
+            self.rect.width,
+            &self.text,
+            Alignment::Centre,
+            ink,
+            paper,
+        );
+    }
+}
+
+/// A box that is ticked and unticked by clicking it, or by pressing enter or
+/// space while it has the focus, drawn as `[√] Text`.
+pub struct Checkbox<M> {
+    rect: Rect,
+    text: String,
+    checked: bool,
+    state: ClickState,
+    focused: bool,
+    on_toggle: Option<Box<dyn Fn(bool) -> M>>,
+}
+
+impl<M> Checkbox<M> {
+    /// Creates a new checkbox.  Only the top row of the rectangle is used.
+    pub fn new(rect: Rect, text: impl Into<String>, checked: bool) -> Self {
+        Self {
+            rect,
+            text: text.into(),
+            checked,
+            state: ClickState::default(),
+            focused: false,
+            on_toggle: None,
+        }
+    }
+
+    /// Sets the function that creates the message produced when the checkbox
+    /// is clicked.  It is passed the new state of the checkbox.
+    pub fn on_toggle(mut self, f: impl Fn(bool) -> M + 'static) -> Self {
+        self.on_toggle = Some(Box::new(f));
+        self
+    }
+
+    /// Returns true if the checkbox is ticked.
+    pub fn checked(&self) -> bool {
+        self.checked
+    }
+
+    /// Ticks or unticks the checkbox without producing a message.
+    pub fn set_checked(&mut self, checked: bool) {
+        self.checked = checked;
+    }
+}
+
+impl<M: 'static> Widget<M> for Checkbox<M> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>) {
+        let row = Rect::new(self.rect.x, self.rect.y, self.rect.width, 1);
+        let toggles = usize::from(self.state.update(row, &input.mouse))
+            + input.keys.iter().filter(|key| is_activate_key(key)).count();
+        for _ in 0..toggles {
+            self.checked = !self.checked;
+            messages.extend(self.on_toggle.as_ref().map(|f| f(self.checked)));
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        let hovered = self.state.hovered;
+        let (ink, paper) = theme.colours(hovered, hovered && self.state.pressed, self.focused);
+        let p = Point::new(self.rect.x, self.rect.y);
+        let text = format!("[ ] {}", self.text);
+        draw_text(
+            image,
+            p,
+            self.rect.width,
+            &text,
+            Alignment::Left,
+            ink,
+            paper,
+        );
+        if self.checked && self.rect.width > 1 {
+            let mark = Char::new(TICK_GLYPH, theme.accent, paper);
+            image.draw_char(Point::new(p.x + 1, p.y), mark);
+        }
+    }
+}
+
+/// A list of options, one per row, of which exactly one is selected.  Each
+/// option is drawn as `(•) Text`.  While the group has the focus, the up and
+/// down keys select the previous and next options.
+pub struct RadioGroup<M> {
+    rect: Rect,
+    options: Vec<String>,
+    selected: usize,
+    states: Vec<ClickState>,
+    focused: bool,
+    on_select: Option<Box<dyn Fn(usize) -> M>>,
+}
+
+impl<M> RadioGroup<M> {
+    /// Creates a new radio group with the first option selected.  Options
+    /// below the bottom of the rectangle are not shown.
+    pub fn new<S: Into<String>>(rect: Rect, options: impl IntoIterator<Item = S>) -> Self {
+        let options: Vec<String> = options.into_iter().map(Into::into).collect();
+        Self {
+            rect,
+            states: vec![ClickState::default(); options.len()],
+            options,
+            selected: 0,
+            focused: false,
+            on_select: None,
+        }
+    }
+
+    /// Sets the function that creates the message produced when an option is
+    /// clicked.  It is passed the index of the newly selected option.
+    pub fn on_select(mut self, f: impl Fn(usize) -> M + 'static) -> Self {
+        self.on_select = Some(Box::new(f));
+        self
+    }
+
+    /// Returns the index of the selected option.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Selects an option without producing a message.  Indices past the last
+    /// option are ignored.
+    pub fn set_selected(&mut self, index: usize) {
+        if index < self.options.len() {
+            self.selected = index;
+        }
+    }
+
+    /// Returns the text of each option.
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    /// Returns the rectangle of the option at the given index.
+    fn option_rect(&self, index: usize) -> Rect {
+        Rect::new(self.rect.x, self.rect.y + index as i32, self.rect.width, 1)
+    }
+
+    /// Returns the number of options that fit within the rectangle.
+    fn visible(&self) -> usize {
+        self.options.len().min(self.rect.height as usize)
+    }
+}
+
+impl<M: 'static> Widget<M> for RadioGroup<M> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn wants_key(&self, key: &KeyPress) -> bool {
+        matches!(key.key, Key::Up | Key::Down)
+    }
+
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>) {
+        for i in 0..self.visible() {
+            let rect = self.option_rect(i);
+            if self.states[i].update(rect, &input.mouse) && i != self.selected {
+                self.selected = i;
+                messages.extend(self.on_select.as_ref().map(|f| f(i)));
+            }
+        }
+        for key in &input.keys {
+            let i = match key.key {
+                Key::Up => self.selected.saturating_sub(1),
+                Key::Down => (self.selected + 1).min(self.visible().saturating_sub(1)),
+                _ => continue,
+            };
+            if i != self.selected {
+                self.selected = i;
+                messages.extend(self.on_select.as_ref().map(|f| f(i)));
+            }
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        for i in 0..self.visible() {
+            let state = self.states[i];
+            let focused = self.focused && i == self.selected;
+            let (ink, paper) =
+                theme.colours(state.hovered, state.hovered && state.pressed, focused);
+            let p = Point::new(self.rect.x, self.rect.y + i as i32);
+            let text = format!("( ) {}", self.options[i]);
+            draw_text(
+                image,
+                p,
+                self.rect.width,
+                &text,
+                Alignment::Left,
+                ink,
+                paper,
+            );
+            if i == self.selected && self.rect.width > 1 {
+                let mark = Char::new(SELECTED_GLYPH, theme.accent, paper);
+                image.draw_char(Point::new(p.x + 1, p.y), mark);
+            }
+        }
+    }
+}
+
+/// A horizontal bar for choosing a value within a range by dragging a thumb
+/// with the mouse or scrolling the mouse wheel over it.  While the slider has
+/// the focus, the left and right keys move it by a step, and home and end
+/// move it to either end.
+pub struct Slider<M> {
+    rect: Rect,
+    min: f32,
+    max: f32,
+    value: f32,
+    step: f32,
+    hovered: bool,
+    dragging: bool,
+    focused: bool,
+    on_change: Option<Box<dyn Fn(f32) -> M>>,
+}
+
+impl<M> Slider<M> {
+    /// Creates a new slider set to its minimum value.  Only the top row of the
+    /// rectangle is used.  By default, each notch of the mouse wheel moves the
+    /// slider by a twentieth of the range.
+    pub fn new(rect: Rect, min: f32, max: f32) -> Self {
+        Self {
+            rect,
+            min,
+            max,
+            value: min,
+            step: (max - min) / 20.0,
+            hovered: false,
+            dragging: false,
+            focused: false,
+            on_change: None,
+        }
+    }
+
+    /// Sets the amount the value changes for each notch of the mouse wheel or
+    /// press of an arrow key.
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Sets the function that creates the message produced when the value
+    /// changes.  It is passed the new value.
+    pub fn on_change(mut self, f: impl Fn(f32) -> M + 'static) -> Self {
+        self.on_change = Some(Box::new(f));
+        self
+    }
+
+    /// Returns the current value.
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Changes the value without producing a message.  The value is clamped to
+    /// the range of the slider.
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value.clamp(self.min, self.max);
+    }
+
+    /// Returns the column of the thumb, from 0 to one less than the width.
+    fn thumb(&self) -> u32 {
+        let range = self.max - self.min;
+        let t = if range > 0.0 {
+            (self.value - self.min) / range
+        } else {
+            0.0
+        };
+        (t * self.rect.width.saturating_sub(1) as f32).round() as u32
+    }
+}
+
+impl<M: 'static> Widget<M> for Slider<M> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn wants_key(&self, key: &KeyPress) -> bool {
+        matches!(key.key, Key::Left | Key::Right)
+    }
+
+    fn handle_input(&mut self, input: &UiInput, messages: &mut Vec<M>) {
+        let mouse = &input.mouse;
+        let row = Rect::new(self.rect.x, self.rect.y, self.rect.width, 1);
+        self.hovered = mouse.position.is_some_and(|p| contains(row, p));
+        if mouse.left.pressed && self.hovered {
+            self.dragging = true;
+        }
+
+        let old_value = self.value;
+        if let (true, Some(p)) = (self.dragging, mouse.position) {
+            let last = self.rect.width.saturating_sub(1).max(1) as f32;
+            let t = ((p.x - self.rect.x) as f32 / last).clamp(0.0, 1.0);
+            self.value = self.min + t * (self.max - self.min);
+        } else if self.hovered && mouse.wheel != 0 {
+            self.set_value(self.value + mouse.wheel as f32 * self.step);
+        }
+        if !mouse.left.down {
+            self.dragging = false;
+        }
+        for key in &input.keys {
+            match key.key {
+                Key::Left => self.set_value(self.value - self.step),
+                Key::Right => self.set_value(self.value + self.step),
+                Key::Home => self.value = self.min,
+                Key::End => self.value = self.max,
+                _ => {}
+            }
+        }
+
+        if self.value != old_value {
+            messages.extend(self.on_change.as_ref().map(|f| f(self.value)));
+        }
+    }
+
+    fn draw(&self, image: &mut Image, theme: &Theme) {
+        let (ink, paper) = theme.colours(self.hovered, self.dragging, self.focused);
+        let row = Rect::new(self.rect.x, self.rect.y, self.rect.width, 1);
+        image.draw_filled_rect(row, Char::new(TRACK_GLYPH, ink, paper));
+        if self.rect.width > 0 {
+            let p = Point::new(self.rect.x + self.thumb() as i32, self.rect.y);
+            image.draw_char(p, Char::new(THUMB_GLYPH, theme.accent, paper));
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! Cameras showing part of a world larger than the screen.
+
+use crate::image::{BlitOptions, Char, Image, Point, Rect};
+
+/// A camera looking at part of a world, such as a map held in an image that
+/// is larger than the screen, and showing it in an area of the screen.
+///
+/// The viewport converts between world and screen coordinates and draws the
+/// part of the world the camera can see.  By default, the camera is kept
+/// within the world so that nothing beyond its edges is shown.  A world
+/// smaller than the screen area is centred within it.
+///
+/// ```
+/// use mage_core::{image::{Point, Rect}, viewport::Viewport};
+///
+/// let mut viewport = Viewport::new(Rect::new(0, 0, 80, 40), 200, 100);
+/// viewport.centre_on(Point::new(150, 20));
+/// assert_eq!(viewport.camera(), Point::new(110, 0));
+/// assert_eq!(viewport.world_to_screen(Point::new(150, 20)), Some(Point::new(40, 20)));
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Viewport {
+    /// The area of the screen the world is shown in.
+    screen: Rect,
+
+    /// The width of the world in cells.
+    world_width: u32,
+
+    /// The height of the world in cells.
+    world_height: u32,
+
+    /// The world coordinates shown at the top-left of the screen area.
+    camera: Point,
+
+    /// True if the camera is kept within the world.
+    clamped: bool,
+}
+
+impl Viewport {
+    /// Creates a new viewport with the camera at the top-left of the world.
+    ///
+    /// # Arguments
+    ///
+    /// * `screen` - The area of the screen the world is shown in.
+    /// * `world_width` - The width of the world in cells.
+    /// * `world_height` - The height of the world in cells.
+    ///
+    pub fn new(screen: Rect, world_width: u32, world_height: u32) -> Self {
+        let mut viewport = Self {
+            screen,
+            world_width,
+            world_height,
+            camera: Point::new(0, 0),
+            clamped: true,
+        };
+        viewport.clamp();
+        viewport
+    }
+
+    /// Sets whether the camera is kept within the world.  If not, the camera
+    /// can move beyond the edges of the world, such as to keep the player in
+    /// the centre of the screen at all times.
+    pub fn with_clamping(mut self, clamped: bool) -> Self {
+        self.clamped = clamped;
+        self.clamp();
+        self
+    }
+
+    /// Returns the area of the screen the world is shown in.
+    pub fn screen_rect(&self) -> Rect {
+        self.screen
+    }
+
+    /// Changes the area of the screen the world is shown in, such as when the
+    /// window is resized.  The camera stays where it is, unless it must move
+    /// to stay within the world.
+    pub fn set_screen_rect(&mut self, screen: Rect) {
+        self.screen = screen;
+        self.clamp();
+    }
+
+    /// Returns the size of the world in cells.
+    pub fn world_size(&self) -> (u32, u32) {
+        (self.world_width, self.world_height)
+    }
+
+    /// Changes the size of the world, such as when a new level is loaded.
+    pub fn set_world_size(&mut self, width: u32, height: u32) {
+        self.world_width = width;
+        self.world_height = height;
+        self.clamp();
+    }
+
+    /// Returns the world coordinates shown at the top-left of the screen
+    /// area.
+    pub fn camera(&self) -> Point {
+        self.camera
+    }
+
+    /// Moves the camera so that the given world coordinates are shown at the
+    /// top-left of the screen area.
+    pub fn set_camera(&mut self, camera: Point) {
+        self.camera = camera;
+        self.clamp();
+    }
+
+    /// Moves the camera by a number of cells.
+    pub fn scroll(&mut self, dx: i32, dy: i32) {
+        self.set_camera(Point::new(self.camera.x + dx, self.camera.y + dy));
+    }
+
+    /// Moves the camera so that a point in the world, such as the player, is
+    /// in the centre of the screen area, or as near as it can be while the
+    /// camera stays within the world.
+    pub fn centre_on(&mut self, target: Point) {
+        self.set_camera(Point::new(
+            target.x - (self.screen.width / 2) as i32,
+            target.y - (self.screen.height / 2) as i32,
+        ));
+    }
+
+    /// Moves the camera only as far as needed to keep a point in the world at
+    /// least a margin away from the edges of the screen area.  This lets the
+    /// player move around the middle of the screen without it scrolling.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The world coordinates to keep on the screen.
+    /// * `margin` - The number of cells to keep between the target and the
+    ///   edges of the screen area.  It is reduced for small screen areas so
+    ///   that the target can always be shown.
+    ///
+    pub fn follow(&mut self, target: Point, margin: u32) {
+        let axis = |camera: i32, target: i32, size: u32| {
+            let margin = margin.min(size.saturating_sub(1) / 2) as i32;
+            let min = target - margin - camera;
+            let max = target + margin - camera - size as i32 + 1;
+            camera + min.min(0) + max.max(0)
+        };
+        self.set_camera(Point::new(
+            axis(self.camera.x, target.x, self.screen.width),
+            axis(self.camera.y, target.y, self.screen.height),
+        ));
+    }
+
+    /// Scrolls the camera when the mouse is near the edges of the screen
+    /// area, as in strategy games.  Call this each tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `mouse` - The position of the mouse on the screen, or `None` if it
+    ///   is outside the window.
+    /// * `margin` - How close to an edge, in cells, the mouse must be to
+    ///   scroll.
+    /// * `speed` - The number of cells to scroll by.
+    ///
+    /// # Returns
+    ///
+    /// True if the camera moved.
+    ///
+    pub fn edge_scroll(&mut self, mouse: Option<Point>, margin: u32, speed: u32) -> bool {
+        let Some(p) = mouse.filter(|&p| self.contains_screen(p)) else {
+            return false;
+        };
+        let axis = |p: i32, start: i32, size: u32| {
+            let offset = p - start;
+            if offset < margin as i32 {
+                -(speed as i32)
+            } else if offset >= size as i32 - margin as i32 {
+                speed as i32
+            } else {
+                0
+            }
+        };
+        let old_camera = self.camera;
+        self.scroll(
+            axis(p.x, self.screen.x, self.screen.width),
+            axis(p.y, self.screen.y, self.screen.height),
+        );
+        self.camera != old_camera
+    }
+
+    /// Returns the area of the world the camera can see, which may extend
+    /// beyond the edges of the world.
+    pub fn world_rect(&self) -> Rect {
+        Rect::from_point_and_size(self.camera, self.screen.width, self.screen.height)
+    }
+
+    /// Converts world coordinates to screen coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The screen coordinates, or `None` if the point isn't in the screen
+    /// area.
+    ///
+    pub fn world_to_screen(&self, p: Point) -> Option<Point> {
+        let p = Point::new(
+            p.x - self.camera.x + self.screen.x,
+            p.y - self.camera.y + self.screen.y,
+        );
+        self.contains_screen(p).then_some(p)
+    }
+
+    /// Converts screen coordinates, such as the position of the mouse, to
+    /// world coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The world coordinates, or `None` if the point isn't in the screen area
+    /// or is beyond the edges of the world.
+    ///
+    pub fn screen_to_world(&self, p: Point) -> Option<Point> {
+        if !self.contains_screen(p) {
+            return None;
+        }
+        let p = Point::new(
+            p.x - self.screen.x + self.camera.x,
+            p.y - self.screen.y + self.camera.y,
+        );
+        self.contains_world(p).then_some(p)
+    }
+
+    /// Returns true if a point in the world is shown in the screen area.
+    pub fn is_visible(&self, p: Point) -> bool {
+        self.world_to_screen(p).is_some()
+    }
+
+    /// Draws the part of a world image the camera can see into the screen
+    /// area.  Parts of the screen area beyond the edges of the world are left
+    /// untouched.
+    pub fn draw(&self, image: &mut Image, world: &Image) {
+        let visible = self.visible_world();
+        let dst = Point::new(
+            visible.x - self.camera.x + self.screen.x,
+            visible.y - self.camera.y + self.screen.y,
+        );
+        image.blit(dst, world, visible, BlitOptions::default());
+    }
+
+    /// Draws the part of the world the camera can see into the screen area,
+    /// asking a function for the char at each visible point in the world.
+    /// This suits worlds too large to keep in an image, or made up of layers
+    /// such as a map, items and monsters.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image to draw on.
+    /// * `f` - Returns the char at world coordinates, or `None` to leave the
+    ///   screen untouched.  It is only called for points within the world.
+    ///
+    pub fn draw_with(&self, image: &mut Image, mut f: impl FnMut(Point) -> Option<Char>) {
+        let visible = self.visible_world();
+        for world in visible.points() {
+            if let Some(ch) = f(world) {
+                let p = Point::new(
+                    world.x - self.camera.x + self.screen.x,
+                    world.y - self.camera.y + self.screen.y,
+                );
+                image.draw_char(p, ch);
+            }
+        }
+    }
+
+    /// Returns the part of the world the camera can see that is within the
+    /// edges of the world.
+    fn visible_world(&self) -> Rect {
+        let view = self.world_rect();
+        let x = view.x.max(0);
+        let y = view.y.max(0);
+        let right = (view.x + view.width as i32).min(self.world_width as i32);
+        let bottom = (view.y + view.height as i32).min(self.world_height as i32);
+        Rect::new(x, y, (right - x).max(0) as u32, (bottom - y).max(0) as u32)
+    }
+
+    /// Returns true if a point is in the screen area.
+    fn contains_screen(&self, p: Point) -> bool {
+        let (x, y) = (p.x - self.screen.x, p.y - self.screen.y);
+        x >= 0 && y >= 0 && (x as u32) < self.screen.width && (y as u32) < self.screen.height
+    }
+
+    /// Returns true if a point is within the edges of the world.
+    fn contains_world(&self, p: Point) -> bool {
+        p.x >= 0 && p.y >= 0 && (p.x as u32) < self.world_width && (p.y as u32) < self.world_height
+    }
+
+    /// Moves the camera back within the world if it is clamped.  On an axis
+    /// where the world is smaller than the screen area, the world is centred.
+    fn clamp(&mut self) {
+        if !self.clamped {
+            return;
+        }
+        let axis = |camera: i32, world: u32, screen: u32| {
+            if world <= screen {
+                -(((screen - world) / 2) as i32)
+            } else {
+                camera.clamp(0, (world - screen) as i32)
+            }
+        };
+        self.camera = Point::new(
+            axis(self.camera.x, self.world_width, self.screen.width),
+            axis(self.camera.y, self.world_height, self.screen.height),
+        );
+    }
+}
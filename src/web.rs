@@ -0,0 +1,87 @@
+//! Support for running in a web page.
+
+use wasm_bindgen::JsCast;
+use web_sys::{GamepadButton as WebGamepadButton, GamepadMappingType, HtmlCanvasElement};
+use winit::{platform::web::WindowBuilderExtWebSys, window::WindowBuilder};
+
+use crate::{
+    error::MageError,
+    gamepad::{Gamepad, GamepadAxis, GamepadButton},
+};
+
+/// Sets up a window builder to draw to a canvas in the web page.
+///
+/// # Arguments
+///
+/// * `builder` - The window builder to set up.
+/// * `canvas_id` - The id of the canvas element to draw to, or `None` to add
+///   a new canvas to the end of the page's body.
+///
+/// # Returns
+///
+/// The window builder, or an error if there is no canvas with the given id.
+///
+pub(crate) fn with_canvas(
+    builder: WindowBuilder,
+    canvas_id: Option<&str>,
+) -> Result<WindowBuilder, MageError> {
+    let Some(canvas_id) = canvas_id else {
+        return Ok(builder.with_append(true));
+    };
+    let canvas = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id(canvas_id))
+        .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok())
+        .ok_or_else(|| MageError::CanvasNotFound(canvas_id.to_string()))?;
+    Ok(builder.with_canvas(Some(canvas)))
+}
+
+/// Puts text on the clipboard.  Browsers only allow this while handling
+/// input, and may ask the player first, so it can fail without an error.
+pub(crate) fn copy_to_clipboard(text: &str) {
+    if let Some(window) = web_sys::window() {
+        let _ = window.navigator().clipboard().write_text(text);
+    }
+}
+
+/// Reads the controllers connected to the browser, keeping those already
+/// connected so their buttons' changes since the last tick are kept.  Only
+/// controllers the browser maps to its standard layout are read.
+pub(crate) fn poll_gamepads(gamepads: &mut Vec<Gamepad>) {
+    let Some(pads) = web_sys::window().and_then(|window| window.navigator().get_gamepads().ok())
+    else {
+        return;
+    };
+    let pads: Vec<web_sys::Gamepad> = pads
+        .iter()
+        .filter_map(|pad| pad.dyn_into::<web_sys::Gamepad>().ok())
+        .filter(|pad| pad.connected() && pad.mapping() == GamepadMappingType::Standard)
+        .collect();
+
+    gamepads.retain(|gamepad| pads.iter().any(|pad| pad.index() == gamepad.id()));
+    for pad in pads {
+        let gamepad = match gamepads
+            .iter()
+            .position(|gamepad| gamepad.id() == pad.index())
+        {
+            Some(i) => &mut gamepads[i],
+            None => {
+                gamepads.push(Gamepad::new(pad.index()));
+                gamepads.last_mut().unwrap()
+            }
+        };
+        let buttons = pad.buttons();
+        for (i, button) in GamepadButton::ALL.into_iter().enumerate() {
+            let down = buttons
+                .get(i as u32)
+                .dyn_into::<WebGamepadButton>()
+                .is_ok_and(|button| button.pressed());
+            gamepad.set_button(button, down);
+        }
+        let axes = pad.axes();
+        for (i, axis) in GamepadAxis::ALL.into_iter().enumerate() {
+            let value = axes.get(i as u32).as_f64().unwrap_or(0.0);
+            gamepad.set_axis(axis, value as f32);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Zooming the font at runtime, so that players can make the text bigger or
+//! smaller while the game runs.
+//!
+//! Zooming draws each pixel of the font as a larger square of screen pixels,
+//! so the window holds fewer chars.  The grid is recomputed straight away,
+//! and the app is told with [`App::zoom_changed`] and sees the new size in
+//! its next [`TickInput`].
+//!
+//! Players zoom with Ctrl+= and Ctrl+-, and go back to the starting zoom with
+//! Ctrl+0, unless [`Config::zoom_keys`] is switched off.  Apps can zoom with
+//! [`set_zoom`], such as from an options menu:
+//!
+//! ```
+//! use mage_core::{zoom, TickInput};
+//!
+//! fn zoom_in(tick_input: &TickInput) {
+//!     zoom::set_zoom(tick_input.zoom + 1);
+//! }
+//! ```
+//!
+//! [`App::zoom_changed`]: ../trait.App.html#method.zoom_changed
+//! [`TickInput`]: ../struct.TickInput.html
+//! [`Config::zoom_keys`]: ../struct.Config.html#structfield.zoom_keys
+//! [`set_zoom`]: fn.set_zoom.html
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// The largest zoom, which draws each pixel of the font as an 8x8 square.
+pub const MAX_ZOOM: u32 = 8;
+
+/// The zoom asked for with [`set_zoom`] and not yet applied, or 0 if none
+/// has been asked for.
+///
+/// [`set_zoom`]: fn.set_zoom.html
+///
+static REQUESTED_ZOOM: AtomicU32 = AtomicU32::new(0);
+
+/// Asks for the font to be zoomed, which happens before the app is next
+/// ticked.
+///
+/// # Arguments
+///
+/// * `zoom` - How many screen pixels wide and high to draw each pixel of the
+///   font, which is kept between 1 and [`MAX_ZOOM`].  On displays that are
+///   already scaled, such as Retina displays on iOS, this is on top of that
+///   scale.
+///
+/// [`MAX_ZOOM`]: constant.MAX_ZOOM.html
+///
+pub fn set_zoom(zoom: u32) {
+    REQUESTED_ZOOM.store(clamp_zoom(zoom), Ordering::Relaxed);
+}
+
+/// Returns the zoom asked for with [`set_zoom`] since this was last called.
+///
+/// [`set_zoom`]: fn.set_zoom.html
+///
+pub(crate) fn take_request() -> Option<u32> {
+    match REQUESTED_ZOOM.swap(0, Ordering::Relaxed) {
+        0 => None,
+        zoom => Some(zoom),
+    }
+}
+
+/// Keeps a zoom between 1 and [`MAX_ZOOM`].
+///
+/// [`MAX_ZOOM`]: constant.MAX_ZOOM.html
+///
+pub(crate) fn clamp_zoom(zoom: u32) -> u32 {
+    zoom.clamp(1, MAX_ZOOM)
+}